@@ -1,6 +1,7 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
 
 use db::{CommentRow, IssueRow, UserRow};
 
@@ -15,6 +16,81 @@ macro_rules! lazy_regex {
 pub struct ContributionStats {
     pub posts_last_24h: u32,
     pub dedupe_hits_last_48h: u32,
+    /// Author is a public member of the organization that owns the repository.
+    #[serde(default)]
+    pub org_member: bool,
+    /// Comment burst on the thread around this comment; see [`thread_bursts`].
+    #[serde(default)]
+    pub thread_burst: ThreadBurst,
+    /// The author's issues that maintainers closed as `not_planned`.
+    #[serde(default)]
+    pub not_planned_closures: u32,
+    /// The author's issues seen closed by someone else shortly after creation.
+    #[serde(default)]
+    pub rapid_closures: u32,
+    /// Other recent issues in the repo sharing this issue's normalized title.
+    #[serde(default)]
+    pub same_title_issues: u32,
+    /// Edits GitHub recorded for this issue/comment, when its history was fetched.
+    #[serde(default)]
+    pub edit_count: u32,
+    #[serde(default)]
+    pub last_edited_at: Option<DateTime<Utc>>,
+    /// Distinct accounts that reacted, when this issue/comment's reactions were fetched.
+    #[serde(default)]
+    pub reactor_count: u32,
+    /// Share of those accounts created within the new-account window.
+    #[serde(default)]
+    pub new_reactor_frac: f32,
+    /// The post is in a fork rather than an upstream repository.
+    #[serde(default)]
+    pub repo_is_fork: bool,
+}
+
+/// Rolling window used by [`thread_bursts`].
+pub const BURST_WINDOW_SECS: i64 = 600;
+
+/// The busiest [`BURST_WINDOW_SECS`] window of a thread that contains a given
+/// comment, and the share of that window's comments written by new accounts.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+pub struct ThreadBurst {
+    pub comments_in_window: u32,
+    pub new_account_frac: f32,
+}
+
+/// One [`ThreadBurst`] per `(created_at, author_is_new)` entry, in input order.
+pub fn thread_bursts(comments: &[(DateTime<Utc>, bool)]) -> Vec<ThreadBurst> {
+    let window = chrono::Duration::seconds(BURST_WINDOW_SECS);
+    let mut order: Vec<usize> = (0..comments.len()).collect();
+    order.sort_by_key(|&i| comments[i].0);
+
+    let mut bursts = vec![ThreadBurst::default(); comments.len()];
+    let mut end = 0;
+    for start in 0..order.len() {
+        end = end.max(start);
+        while end < order.len() && comments[order[end]].0 - comments[order[start]].0 <= window {
+            end += 1;
+        }
+        let in_window = &order[start..end];
+        let new_accounts = in_window.iter().filter(|&&i| comments[i].1).count();
+        let burst = ThreadBurst {
+            comments_in_window: in_window.len() as u32,
+            new_account_frac: new_accounts as f32 / in_window.len() as f32,
+        };
+        for &i in in_window {
+            if burst.comments_in_window > bursts[i].comments_in_window {
+                bursts[i] = burst;
+            }
+        }
+    }
+    bursts
+}
+
+/// The issue a comment replies to, supplied only for the first comment in a thread.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FirstReply {
+    pub issue_created_at: DateTime<Utc>,
+    pub issue_author_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
@@ -22,14 +98,48 @@ pub struct FeatureSet {
     pub body_length: usize,
     pub url_count: usize,
     pub email_count: usize,
+    /// Emails at a [`DISPOSABLE_EMAIL_DOMAINS`] domain or one of its subdomains.
+    pub disposable_email_count: usize,
     pub mention_count: usize,
+    /// Distinct `@handles`, case-insensitive, ignoring the domain part of emails.
+    pub distinct_mention_count: usize,
     pub emoji_count: usize,
+    pub zero_width_count: usize,
     pub repeated_char_ratio: f32,
+    /// Base64-like or percent-encoded run outside code blocks and images.
+    pub encoded_blob_detected: bool,
+    /// Share of the body's prose inside such runs.
+    pub encoded_ratio: f32,
     pub token_entropy: f32,
     pub title_body_similarity: Option<f32>,
     pub account_age_days: Option<f32>,
     pub posts_last_24h: u32,
     pub default_template_hit: bool,
+    /// Issue body is an unfilled or stripped template: only headings and/or
+    /// checkboxes, with nothing written under any of them.
+    pub empty_template_hit: bool,
+    pub first_reply_secs: Option<f32>,
+    pub first_reply_by_author: bool,
+    pub org_member: bool,
+    pub burst_comment_count: u32,
+    pub burst_new_account_frac: f32,
+    pub author_not_planned_closures: u32,
+    #[serde(default)]
+    pub author_rapid_closures: u32,
+    pub repo_same_title_issues: u32,
+    pub edit_count: u32,
+    /// Seconds from posting to the most recent edit.
+    pub last_edit_delay_secs: Option<f32>,
+    /// Links to a [`URL_SHORTENER_DOMAINS`] host in the author's bio or website.
+    pub profile_shortener_links: usize,
+    pub reactor_count: u32,
+    pub reactor_new_account_frac: f32,
+    /// GitHub's `author_association` for the post, e.g. `MEMBER` or `NONE`.
+    #[serde(default)]
+    pub author_association: Option<String>,
+    /// The post is in a fork rather than an upstream repository.
+    #[serde(default)]
+    pub repo_is_fork: bool,
 }
 
 pub fn features_for_issue(
@@ -44,6 +154,18 @@ pub fn features_for_issue(
         account_age_days: account_age_days(user),
         posts_last_24h: stats.posts_last_24h,
         default_template_hit: default_template_hit(body),
+        empty_template_hit: empty_template_hit(body),
+        org_member: stats.org_member,
+        author_not_planned_closures: stats.not_planned_closures,
+        author_rapid_closures: stats.rapid_closures,
+        repo_same_title_issues: stats.same_title_issues,
+        edit_count: stats.edit_count,
+        last_edit_delay_secs: edit_delay_secs(issue.created_at, stats.last_edited_at),
+        profile_shortener_links: profile_shortener_links(user),
+        reactor_count: stats.reactor_count,
+        reactor_new_account_frac: stats.new_reactor_frac,
+        author_association: issue.author_association.clone(),
+        repo_is_fork: stats.repo_is_fork,
         ..base
     }
 }
@@ -52,6 +174,7 @@ pub fn features_for_comment(
     comment: &CommentRow,
     user: Option<&UserRow>,
     stats: ContributionStats,
+    first_reply: Option<&FirstReply>,
 ) -> FeatureSet {
     let base = base_features(&comment.body);
     FeatureSet {
@@ -59,23 +182,57 @@ pub fn features_for_comment(
         account_age_days: account_age_days(user),
         posts_last_24h: stats.posts_last_24h,
         default_template_hit: default_template_hit(&comment.body),
+        first_reply_secs: first_reply.map(|first| first_reply_secs(comment, first)),
+        first_reply_by_author: first_reply.is_some_and(|first| is_self_reply(comment, first)),
+        org_member: stats.org_member,
+        burst_comment_count: stats.thread_burst.comments_in_window,
+        burst_new_account_frac: stats.thread_burst.new_account_frac,
+        edit_count: stats.edit_count,
+        last_edit_delay_secs: edit_delay_secs(comment.created_at, stats.last_edited_at),
+        profile_shortener_links: profile_shortener_links(user),
+        reactor_count: stats.reactor_count,
+        reactor_new_account_frac: stats.new_reactor_frac,
+        author_association: comment.author_association.clone(),
+        repo_is_fork: stats.repo_is_fork,
         ..base
     }
 }
 
 fn base_features(body: &str) -> FeatureSet {
+    let encoded = common::text::encoded_blobs(body);
     FeatureSet {
         body_length: body.chars().count(),
         url_count: count_urls(body),
         email_count: count_emails(body),
+        disposable_email_count: count_disposable_emails(body),
         mention_count: count_mentions(body),
+        distinct_mention_count: count_distinct_mentions(body),
         emoji_count: count_emojis(body),
+        zero_width_count: count_zero_width(body),
         repeated_char_ratio: repeated_char_ratio(body),
+        encoded_blob_detected: encoded.runs > 0,
+        encoded_ratio: encoded.ratio,
         token_entropy: token_entropy(body),
         title_body_similarity: None,
         account_age_days: None,
         posts_last_24h: 0,
         default_template_hit: default_template_hit(body),
+        empty_template_hit: false,
+        first_reply_secs: None,
+        first_reply_by_author: false,
+        org_member: false,
+        burst_comment_count: 0,
+        burst_new_account_frac: 0.0,
+        author_not_planned_closures: 0,
+        author_rapid_closures: 0,
+        repo_same_title_issues: 0,
+        edit_count: 0,
+        last_edit_delay_secs: None,
+        profile_shortener_links: 0,
+        reactor_count: 0,
+        reactor_new_account_frac: 0.0,
+        author_association: None,
+        repo_is_fork: false,
     }
 }
 
@@ -84,9 +241,101 @@ fn count_urls(text: &str) -> usize {
     URL_RE.find_iter(text).count()
 }
 
-fn count_emails(text: &str) -> usize {
+/// Throwaway-inbox providers; an address at one of these is a spam tell.
+pub const DISPOSABLE_EMAIL_DOMAINS: &[&str] = &[
+    "10minutemail.com",
+    "dispostable.com",
+    "guerrillamail.com",
+    "guerrillamail.net",
+    "maildrop.cc",
+    "mailinator.com",
+    "mailnesia.com",
+    "sharklasers.com",
+    "temp-mail.org",
+    "tempmail.com",
+    "throwawaymail.com",
+    "trashmail.com",
+    "yopmail.com",
+];
+
+/// Link shorteners, which hide where a profile link actually points.
+pub const URL_SHORTENER_DOMAINS: &[&str] = &[
+    "bit.ly",
+    "buff.ly",
+    "cutt.ly",
+    "goo.gl",
+    "is.gd",
+    "ow.ly",
+    "rb.gy",
+    "rebrand.ly",
+    "shorturl.at",
+    "t.co",
+    "t.ly",
+    "tinyurl.com",
+];
+
+/// The author's bio and website, newline-separated; empty without a profile.
+pub fn profile_text(user: Option<&UserRow>) -> String {
+    user.map(|user| {
+        [user.bio.as_deref(), user.blog.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+    .unwrap_or_default()
+}
+
+fn profile_shortener_links(user: Option<&UserRow>) -> usize {
+    profile_text(user)
+        .split_whitespace()
+        .filter(|token| is_shortener_link(token))
+        .count()
+}
+
+// Website fields often omit the scheme ("bit.ly/abc"), so match on the host
+// of any token rather than on full URLs.
+fn is_shortener_link(token: &str) -> bool {
+    let rest = token
+        .trim_start_matches(|c: char| !c.is_ascii_alphanumeric())
+        .to_ascii_lowercase();
+    let rest = rest
+        .strip_prefix("https://")
+        .or_else(|| rest.strip_prefix("http://"))
+        .unwrap_or(&rest);
+    let rest = rest.strip_prefix("www.").unwrap_or(rest);
+    let host = rest
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(|c: char| !c.is_ascii_alphanumeric());
+    URL_SHORTENER_DOMAINS.contains(&host)
+}
+
+fn emails(text: &str) -> impl Iterator<Item = &str> {
     lazy_regex!(EMAIL_RE = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}");
-    EMAIL_RE.find_iter(text).count()
+    EMAIL_RE.find_iter(text).map(|m| m.as_str())
+}
+
+fn count_emails(text: &str) -> usize {
+    emails(text).count()
+}
+
+fn count_disposable_emails(text: &str) -> usize {
+    emails(text)
+        .filter_map(|email| email.rsplit_once('@'))
+        .filter(|(_, domain)| is_disposable_domain(domain))
+        .count()
+}
+
+fn is_disposable_domain(domain: &str) -> bool {
+    let domain = domain.to_ascii_lowercase();
+    DISPOSABLE_EMAIL_DOMAINS.iter().any(|listed| {
+        domain == *listed
+            || domain
+                .strip_suffix(listed)
+                .is_some_and(|sub| sub.ends_with('.'))
+    })
 }
 
 fn count_mentions(text: &str) -> usize {
@@ -94,12 +343,49 @@ fn count_mentions(text: &str) -> usize {
     MENTION_RE.find_iter(text).count()
 }
 
+fn count_distinct_mentions(text: &str) -> usize {
+    lazy_regex!(HANDLE_RE = r"@[A-Za-z0-9][A-Za-z0-9\-]{0,38}");
+    HANDLE_RE
+        .find_iter(text)
+        // `user@example.com` is an email, not a mention of `@example`.
+        .filter(|m| {
+            !text[..m.start()]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || matches!(c, '.' | '_' | '-' | '+'))
+        })
+        .map(|m| m.as_str()[1..].to_ascii_lowercase())
+        .collect::<std::collections::HashSet<_>>()
+        .len()
+}
+
+// Counts grapheme clusters so ZWJ sequences, skin-tone modifiers and flags
+// each count as a single emoji.
 fn count_emojis(text: &str) -> usize {
-    text.chars()
-        .filter(|c| !c.is_ascii_punctuation() && emojis::is_emoji(*c))
+    text.graphemes(true).filter(|g| is_emoji(g)).count()
+}
+
+fn is_emoji(grapheme: &str) -> bool {
+    !grapheme.is_ascii() && emojis::get(grapheme).is_some()
+}
+
+// Zero-width characters used to split keywords (e.g. "v\u{200B}i\u{200B}a").
+// Joiners inside emoji sequences are legitimate and not counted.
+fn count_zero_width(text: &str) -> usize {
+    text.graphemes(true)
+        .filter(|g| !is_emoji(g))
+        .flat_map(str::chars)
+        .filter(|c| is_zero_width(*c))
         .count()
 }
 
+fn is_zero_width(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{180E}' | '\u{FEFF}'
+    )
+}
+
 fn repeated_char_ratio(text: &str) -> f32 {
     if text.is_empty() {
         return 0.0;
@@ -181,6 +467,29 @@ fn default_template_hit(body: &str) -> bool {
     PHRASES.iter().any(|p| lower.contains(p))
 }
 
+// We never fetch the repo's template, so approximate: every heading section is
+// empty (GitHub issue forms render skipped fields as "_No response_"), or the
+// body is nothing but checkboxes.
+fn empty_template_hit(body: &str) -> bool {
+    lazy_regex!(HTML_COMMENT_RE = r"(?s)<!--.*?-->");
+    lazy_regex!(HEADING_RE = r"^#{1,6}(\s|$)");
+    lazy_regex!(CHECKBOX_RE = r"^[-*+]\s+\[[ xX]\]");
+
+    let stripped = HTML_COMMENT_RE.replace_all(body, "");
+    let mut headings = 0usize;
+    let mut checkboxes = 0usize;
+    for line in stripped.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if HEADING_RE.is_match(line) {
+            headings += 1;
+        } else if CHECKBOX_RE.is_match(line) {
+            checkboxes += 1;
+        } else if !line.eq_ignore_ascii_case("_no response_") {
+            return false;
+        }
+    }
+    headings >= 2 || checkboxes > 0
+}
+
 fn account_age_days(user: Option<&UserRow>) -> Option<f32> {
     let user = user?;
     let created_at = user.created_at?;
@@ -188,15 +497,20 @@ fn account_age_days(user: Option<&UserRow>) -> Option<f32> {
     Some(age.num_seconds().max(0) as f32 / 86_400.0)
 }
 
-mod emojis {
-    pub fn is_emoji(ch: char) -> bool {
-        matches!(ch as u32,
-            0x1F300..=0x1F6FF |
-            0x1F900..=0x1F9FF |
-            0x1FA70..=0x1FAFF |
-            0x2600..=0x26FF |
-            0x2700..=0x27BF)
-    }
+fn first_reply_secs(comment: &CommentRow, first: &FirstReply) -> f32 {
+    let gap = comment.created_at - first.issue_created_at;
+    gap.num_milliseconds().max(0) as f32 / 1_000.0
+}
+
+fn edit_delay_secs(
+    created_at: DateTime<Utc>,
+    last_edited_at: Option<DateTime<Utc>>,
+) -> Option<f32> {
+    last_edited_at.map(|edited| (edited - created_at).num_milliseconds().max(0) as f32 / 1_000.0)
+}
+
+fn is_self_reply(comment: &CommentRow, first: &FirstReply) -> bool {
+    matches!((comment.user_id, first.issue_author_id), (Some(a), Some(b)) if a == b)
 }
 
 #[cfg(test)]
@@ -214,8 +528,117 @@ mod tests {
         assert!(low < high);
     }
 
+    #[test]
+    fn zwj_family_counts_as_one_emoji() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}";
+        assert_eq!(count_emojis(family), 1);
+        assert_eq!(
+            count_emojis("thumbs \u{1F44D}\u{1F3FD} rocket \u{1F680}"),
+            2
+        );
+        assert_eq!(count_zero_width(family), 0);
+    }
+
+    #[test]
+    fn plain_punctuation_is_not_emoji() {
+        assert_eq!(count_emojis("#1 * 2 = 3!"), 0);
+    }
+
+    #[test]
+    fn counts_injected_zero_width_chars() {
+        let body = "buy v\u{200B}i\u{200B}a\u{200B}g\u{200B}r\u{200B}a now\u{FEFF}";
+        assert_eq!(count_zero_width(body), 6);
+        assert_eq!(count_zero_width("plain text"), 0);
+    }
+
+    #[test]
+    fn distinct_mentions_ignore_repeats_and_emails() {
+        assert_eq!(
+            count_distinct_mentions("@alice @Alice @bob mail me at carol@example.com"),
+            2
+        );
+        assert_eq!(count_distinct_mentions("no mentions here"), 0);
+    }
+
+    #[test]
+    fn disposable_emails_are_counted_by_domain() {
+        let body = "Write to jo@mailinator.com, Jo@YOPMAIL.com or x@eu.guerrillamail.net \
+                    (not dev@example.com, me@notmailinator.com or team@mailinator.company.org)";
+        assert_eq!(count_emails(body), 6);
+        assert_eq!(count_disposable_emails(body), 3);
+        assert_eq!(count_disposable_emails("no addresses here"), 0);
+    }
+
+    #[test]
+    fn shortener_links_are_found_with_or_without_scheme() {
+        assert!(is_shortener_link("https://bit.ly/3xYz"));
+        assert!(is_shortener_link("(tinyurl.com/deal)"));
+        assert!(is_shortener_link("WWW.T.CO/abc,"));
+        assert!(!is_shortener_link("https://example.com/bit.ly"));
+        assert!(!is_shortener_link("t.company"));
+    }
+
     #[test]
     fn template_detects_phrase() {
         assert!(default_template_hit("Thanks for submitting the bug report"));
     }
+
+    #[test]
+    fn empty_template_detects_stripped_sections() {
+        let stripped = "### Describe the bug\n\n_No response_\n\n### Steps to reproduce\n\n<!-- list the steps -->\n\n### Expected behavior\n";
+        assert!(empty_template_hit(stripped));
+        assert!(empty_template_hit(
+            "- [ ] I searched existing issues\n- [x] I read the docs"
+        ));
+
+        let filled = "### Describe the bug\n\nThe CLI panics when the config file is missing.\n\n### Steps to reproduce\n\n1. Delete config.toml\n2. Run `app start`\n";
+        assert!(!empty_template_hit(filled));
+        assert!(!empty_template_hit("Plain report without any headings."));
+        assert!(!empty_template_hit(""));
+    }
+
+    #[test]
+    fn first_reply_measures_gap_and_author() {
+        let issue_created_at = Utc::now();
+        let comment = CommentRow {
+            id: 2,
+            issue_id: 1,
+            user_id: Some(42),
+            body: "same here".into(),
+            created_at: issue_created_at + chrono::Duration::seconds(3),
+            updated_at: None,
+            author_association: None,
+            dedupe_hash: String::new(),
+            raw: serde_json::Value::Null,
+            found: true,
+        };
+        let first = FirstReply {
+            issue_created_at,
+            issue_author_id: Some(42),
+        };
+        let features =
+            features_for_comment(&comment, None, ContributionStats::default(), Some(&first));
+        assert_eq!(features.first_reply_secs, Some(3.0));
+        assert!(features.first_reply_by_author);
+
+        let later = features_for_comment(&comment, None, ContributionStats::default(), None);
+        assert_eq!(later.first_reply_secs, None);
+        assert!(!later.first_reply_by_author);
+    }
+
+    #[test]
+    fn thread_bursts_report_busiest_window_per_comment() {
+        let start = Utc::now();
+        let at = |mins: i64, new: bool| (start + chrono::Duration::minutes(mins), new);
+        // Three quick replies (two from new accounts), then one an hour later.
+        let bursts = thread_bursts(&[at(60, false), at(2, true), at(0, false), at(4, true)]);
+
+        assert_eq!(bursts[0].comments_in_window, 1);
+        assert_eq!(bursts[0].new_account_frac, 0.0);
+        for burst in &bursts[1..] {
+            assert_eq!(burst.comments_in_window, 3);
+            assert!((burst.new_account_frac - 2.0 / 3.0).abs() < 1e-6);
+        }
+        assert!(thread_bursts(&[]).is_empty());
+    }
 }
@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+
+use regex::RegexSet;
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum KeywordRulesError {
+    #[error("failed to read keyword patterns from {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to parse keyword patterns: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("invalid pattern in category '{category}': {source}")]
+    Pattern {
+        category: String,
+        #[source]
+        source: regex::Error,
+    },
+    #[error("category '{0}' has no patterns")]
+    EmptyCategory(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct KeywordFile {
+    #[serde(default, rename = "category")]
+    categories: Vec<CategorySpec>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CategorySpec {
+    name: String,
+    #[serde(default = "CategorySpec::default_weight")]
+    weight: f32,
+    patterns: Vec<String>,
+}
+
+impl CategorySpec {
+    const fn default_weight() -> f32 {
+        1.0
+    }
+}
+
+#[derive(Debug)]
+struct KeywordCategory {
+    name: String,
+    weight: f32,
+    patterns: RegexSet,
+}
+
+/// Weighted keyword categories loaded from a TOML file so patterns can be
+/// updated without a release. Matching is case-insensitive.
+///
+/// ```toml
+/// [[category]]
+/// name = "gift_card"
+/// weight = 1.5
+/// patterns = ["free gift card", "claim your (reward|prize)"]
+/// ```
+#[derive(Debug, Default)]
+pub struct KeywordRules {
+    categories: Vec<KeywordCategory>,
+}
+
+impl KeywordRules {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, KeywordRulesError> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path).map_err(|source| KeywordRulesError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_toml_str(&raw)
+    }
+
+    pub fn from_toml_str(raw: &str) -> Result<Self, KeywordRulesError> {
+        let file: KeywordFile = toml::from_str(raw)?;
+        let mut categories = Vec::with_capacity(file.categories.len());
+        for spec in file.categories {
+            if spec.patterns.is_empty() {
+                return Err(KeywordRulesError::EmptyCategory(spec.name));
+            }
+            let patterns = RegexSet::new(spec.patterns.iter().map(|p| format!("(?i){}", p)))
+                .map_err(|source| KeywordRulesError::Pattern {
+                    category: spec.name.clone(),
+                    source,
+                })?;
+            categories.push(KeywordCategory {
+                name: spec.name,
+                weight: spec.weight,
+                patterns,
+            });
+        }
+        Ok(Self { categories })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty()
+    }
+
+    /// Returns `(category, weight)` for every category with at least one match.
+    pub fn matches(&self, body: &str) -> Vec<(&str, f32)> {
+        self.categories
+            .iter()
+            .filter(|category| category.patterns.is_match(body))
+            .map(|category| (category.name.as_str(), category.weight))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERNS: &str = r#"
+[[category]]
+name = "gift_card"
+weight = 1.5
+patterns = ["free gift card", "claim your (reward|prize)"]
+
+[[category]]
+name = "crypto_airdrop"
+weight = 2.0
+patterns = ["airdrop", "connect your wallet"]
+"#;
+
+    #[test]
+    fn matches_weighted_categories_case_insensitively() {
+        let rules = KeywordRules::from_toml_str(PATTERNS).expect("patterns load");
+        let hits = rules.matches("CLAIM YOUR REWARD now, free gift card inside");
+        assert_eq!(hits, vec![("gift_card", 1.5)]);
+        assert!(rules.matches("a regular bug report").is_empty());
+    }
+
+    #[test]
+    fn invalid_pattern_names_category() {
+        let raw = r#"
+[[category]]
+name = "broken"
+patterns = ["(unclosed"]
+"#;
+        let err = KeywordRules::from_toml_str(raw).unwrap_err();
+        assert!(matches!(err, KeywordRulesError::Pattern { .. }));
+        assert!(err.to_string().contains("'broken'"));
+    }
+}
@@ -1,7 +1,9 @@
 pub mod features;
+pub mod keywords;
 pub mod rules;
 pub mod scorer;
 
-pub use features::{ContributionStats, FeatureSet};
+pub use features::{thread_bursts, ContributionStats, FeatureSet, FirstReply, ThreadBurst};
+pub use keywords::{KeywordRules, KeywordRulesError};
 pub use rules::{RuleEngine, RuleOutcome};
-pub use scorer::{score_comment, score_issue};
+pub use scorer::{score_comment, score_issue, Scorer};
@@ -1,24 +1,126 @@
+use async_trait::async_trait;
 use db::{CommentRow, IssueRow, UserRow};
 
-use crate::features::{features_for_comment, features_for_issue, ContributionStats};
+use crate::features::{
+    features_for_comment, features_for_issue, profile_text, ContributionStats, FirstReply,
+};
 use crate::rules::{RuleContext, RuleEngine, RuleOutcome};
 
+/// Assigns spam scores to issues and comments. [`RuleEngine`] is the default;
+/// other implementations (a remote model, an ensemble) can be handed to the
+/// collector instead. Async so implementations may call out over the network.
+#[async_trait]
+pub trait Scorer: Send + Sync {
+    /// Stored as `spam_flags.version`, so flags from different scorers stay apart.
+    fn version(&self) -> &str;
+
+    async fn score_issue(
+        &self,
+        issue: &IssueRow,
+        user: Option<&UserRow>,
+        stats: ContributionStats,
+        dedupe_hits_last_48h: u32,
+    ) -> anyhow::Result<RuleOutcome>;
+
+    async fn score_comment(
+        &self,
+        comment: &CommentRow,
+        user: Option<&UserRow>,
+        stats: ContributionStats,
+        dedupe_hits_last_48h: u32,
+        first_reply: Option<&FirstReply>,
+    ) -> anyhow::Result<RuleOutcome>;
+}
+
+#[async_trait]
+impl Scorer for RuleEngine {
+    fn version(&self) -> &str {
+        RuleEngine::version(self)
+    }
+
+    async fn score_issue(
+        &self,
+        issue: &IssueRow,
+        user: Option<&UserRow>,
+        stats: ContributionStats,
+        dedupe_hits_last_48h: u32,
+    ) -> anyhow::Result<RuleOutcome> {
+        Ok(RuleEngine::score_issue(
+            self,
+            issue,
+            user,
+            stats,
+            dedupe_hits_last_48h,
+        ))
+    }
+
+    async fn score_comment(
+        &self,
+        comment: &CommentRow,
+        user: Option<&UserRow>,
+        stats: ContributionStats,
+        dedupe_hits_last_48h: u32,
+        first_reply: Option<&FirstReply>,
+    ) -> anyhow::Result<RuleOutcome> {
+        Ok(RuleEngine::score_comment(
+            self,
+            comment,
+            user,
+            stats,
+            dedupe_hits_last_48h,
+            first_reply,
+        ))
+    }
+}
+
+impl RuleEngine {
+    pub fn score_issue(
+        &self,
+        issue: &IssueRow,
+        user: Option<&UserRow>,
+        stats: ContributionStats,
+        dedupe_hits_last_48h: u32,
+    ) -> RuleOutcome {
+        let features = features_for_issue(issue, user, stats.clone());
+        self.evaluate(
+            &features,
+            RuleContext {
+                body: issue.body.as_deref().unwrap_or(""),
+                profile: &profile_text(user),
+                stats: &stats,
+                dedupe_hits_last_48h,
+            },
+        )
+    }
+
+    pub fn score_comment(
+        &self,
+        comment: &CommentRow,
+        user: Option<&UserRow>,
+        stats: ContributionStats,
+        dedupe_hits_last_48h: u32,
+        first_reply: Option<&FirstReply>,
+    ) -> RuleOutcome {
+        let features = features_for_comment(comment, user, stats.clone(), first_reply);
+        self.evaluate(
+            &features,
+            RuleContext {
+                body: &comment.body,
+                profile: &profile_text(user),
+                stats: &stats,
+                dedupe_hits_last_48h,
+            },
+        )
+    }
+}
+
 pub fn score_issue(
     issue: &IssueRow,
     user: Option<&UserRow>,
     stats: ContributionStats,
     dedupe_hits_last_48h: u32,
 ) -> RuleOutcome {
-    let engine = RuleEngine::default();
-    let features = features_for_issue(issue, user, stats.clone());
-    engine.evaluate(
-        &features,
-        RuleContext {
-            body: issue.body.as_deref().unwrap_or(""),
-            stats: &stats,
-            dedupe_hits_last_48h,
-        },
-    )
+    RuleEngine::default().score_issue(issue, user, stats, dedupe_hits_last_48h)
 }
 
 pub fn score_comment(
@@ -26,15 +128,7 @@ pub fn score_comment(
     user: Option<&UserRow>,
     stats: ContributionStats,
     dedupe_hits_last_48h: u32,
+    first_reply: Option<&FirstReply>,
 ) -> RuleOutcome {
-    let engine = RuleEngine::default();
-    let features = features_for_comment(comment, user, stats.clone());
-    engine.evaluate(
-        &features,
-        RuleContext {
-            body: &comment.body,
-            stats: &stats,
-            dedupe_hits_last_48h,
-        },
-    )
+    RuleEngine::default().score_comment(comment, user, stats, dedupe_hits_last_48h, first_reply)
 }
@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use analysis::{KeywordRules, KeywordRulesError, RuleEngine};
+use chrono::Utc;
+use db::CommentRow;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn comment(body: &str) -> CommentRow {
+    CommentRow {
+        id: 1,
+        issue_id: 1,
+        user_id: None,
+        body: body.to_string(),
+        created_at: Utc::now(),
+        updated_at: None,
+        author_association: None,
+        dedupe_hash: String::new(),
+        raw: serde_json::Value::Null,
+        found: true,
+    }
+}
+
+#[test]
+fn keyword_file_scores_matching_body() {
+    let keywords = KeywordRules::from_path(fixture("keywords.toml")).expect("patterns load");
+    let engine = RuleEngine::default().with_keywords(Arc::new(keywords));
+    let body = "Huge airdrop this week! Connect your wallet and claim your reward before it ends.";
+
+    let baseline =
+        RuleEngine::default().score_comment(&comment(body), None, Default::default(), 0, None);
+    let outcome = engine.score_comment(&comment(body), None, Default::default(), 0, None);
+
+    assert!(outcome.reasons.contains(&"keyword:gift_card".to_string()));
+    assert!(outcome
+        .reasons
+        .contains(&"keyword:crypto_airdrop".to_string()));
+    assert!((outcome.score - baseline.score - 4.0).abs() < f32::EPSILON);
+}
+
+#[test]
+fn missing_keyword_file_is_reported_with_path() {
+    let err = KeywordRules::from_path(fixture("does_not_exist.toml")).unwrap_err();
+    assert!(matches!(err, KeywordRulesError::Io { .. }));
+    assert!(err.to_string().contains("does_not_exist.toml"));
+}
+
+#[test]
+fn shipped_keyword_file_is_valid() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../config/keywords.toml");
+    let keywords = KeywordRules::from_path(path).expect("config/keywords.toml loads");
+    assert!(!keywords.is_empty());
+}
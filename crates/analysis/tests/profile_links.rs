@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use analysis::{KeywordRules, RuleEngine};
+use chrono::{Duration, Utc};
+use db::{IssueRow, UserRow};
+use serde_json::json;
+
+fn issue() -> IssueRow {
+    IssueRow {
+        id: 1,
+        repo_id: 1,
+        number: 1,
+        is_pull_request: false,
+        state: "open".into(),
+        title: "Build fails on Windows".into(),
+        body: Some("Running cargo build on Windows 11 fails with a linker error.".into()),
+        user_id: Some(9),
+        comments_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: String::new(),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn user(age_days: i64, bio: &str, blog: Option<&str>) -> UserRow {
+    UserRow {
+        id: 9,
+        login: "fresh".into(),
+        user_type: "User".into(),
+        site_admin: false,
+        created_at: Some(Utc::now() - Duration::days(age_days)),
+        followers: Some(0),
+        following: Some(0),
+        public_repos: Some(0),
+        bio: Some(bio.into()),
+        blog: blog.map(str::to_string),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn flagged(engine: &RuleEngine, user: &UserRow) -> bool {
+    engine
+        .score_issue(&issue(), Some(user), Default::default(), 0)
+        .reasons
+        .contains(&"promotional_profile".to_string())
+}
+
+#[test]
+fn new_account_with_shortener_in_bio_is_flagged() {
+    let engine = RuleEngine::default();
+    let bio = "Best prices on followers, DM me or visit https://bit.ly/3cheapf0llow";
+    assert!(flagged(&engine, &user(3, bio, None)));
+    assert!(flagged(
+        &engine,
+        &user(3, "Growth hacker", Some("tinyurl.com/grow-now"))
+    ));
+    // The same profile on an established account is left alone.
+    assert!(!flagged(&engine, &user(900, bio, None)));
+    // A plain personal site is not promotional.
+    assert!(!flagged(
+        &engine,
+        &user(3, "Rust and embedded", Some("https://example.dev"))
+    ));
+}
+
+#[test]
+fn new_account_with_keyword_profile_is_flagged() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/keywords.toml");
+    let keywords = KeywordRules::from_path(path).expect("patterns load");
+    let engine = RuleEngine::default().with_keywords(Arc::new(keywords));
+    let bio = "Weekly airdrop alerts, connect your wallet to join";
+    assert!(flagged(&engine, &user(5, bio, None)));
+    assert!(!flagged(&RuleEngine::default(), &user(5, bio, None)));
+}
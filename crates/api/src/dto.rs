@@ -1,7 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use serde_json::Value;
 
-use db::models::{ActorSpamSummary, IssueRow, RepositoryRow, SpamFlagRow, UserRow};
+use db::models::{
+    ActorSpamSummary, ClusterMember, DedupeCluster, IssueRow, RecentFlagRow, RepoDeletion,
+    RepoSummary, RepositoryRow, ReviewStatus, ScoreBucket, SpamFlagRow, SpammyUserCursor,
+    UserActivitySummary, UserRow, UserSpamSummary,
+};
 
 #[derive(Debug, Serialize)]
 pub struct RepoDto {
@@ -40,6 +45,8 @@ pub struct IssueDto {
     pub closed_at: Option<DateTime<Utc>>,
     pub spam_score: Option<f32>,
     pub spam_reasons: Vec<String>,
+    /// Features behind the highest-scoring flag, when they were stored.
+    pub spam_features: Option<Value>,
     pub found: bool,
 }
 
@@ -60,9 +67,15 @@ impl IssueDto {
             closed_at: row.closed_at,
             spam_score: spam,
             spam_reasons: reasons,
+            spam_features: None,
             found: row.found,
         }
     }
+
+    pub fn with_spam_features(mut self, features: Option<Value>) -> Self {
+        self.spam_features = features;
+        self
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -76,6 +89,22 @@ pub struct UserDto {
     pub following: Option<i64>,
     pub public_repos: Option<i64>,
     pub found: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activity: Option<UserActivityDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spam: Option<UserSpamSummaryDto>,
+}
+
+impl UserDto {
+    pub fn with_activity(mut self, activity: UserActivitySummary) -> Self {
+        self.activity = Some(activity.into());
+        self
+    }
+
+    pub fn with_spam_summary(mut self, spam: UserSpamSummary) -> Self {
+        self.spam = Some(spam.into());
+        self
+    }
 }
 
 impl From<UserRow> for UserDto {
@@ -90,6 +119,119 @@ impl From<UserRow> for UserDto {
             following: row.following,
             public_repos: row.public_repos,
             found: row.found,
+            activity: None,
+            spam: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserActivityDto {
+    pub issue_count: i64,
+    pub comment_count: i64,
+    pub flagged_issue_count: i64,
+    pub flagged_comment_count: i64,
+    pub first_activity_at: Option<DateTime<Utc>>,
+    pub last_activity_at: Option<DateTime<Utc>>,
+}
+
+impl From<UserActivitySummary> for UserActivityDto {
+    fn from(summary: UserActivitySummary) -> Self {
+        Self {
+            issue_count: summary.issue_count,
+            comment_count: summary.comment_count,
+            flagged_issue_count: summary.flagged_issue_count,
+            flagged_comment_count: summary.flagged_comment_count,
+            first_activity_at: summary.first_activity_at,
+            last_activity_at: summary.last_activity_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserSpamSummaryDto {
+    pub flag_count: i64,
+    pub total_score: f32,
+    pub avg_score: f32,
+    pub reasons: Vec<String>,
+    pub first_flagged_at: Option<DateTime<Utc>>,
+    pub last_flagged_at: Option<DateTime<Utc>>,
+}
+
+impl From<UserSpamSummary> for UserSpamSummaryDto {
+    fn from(summary: UserSpamSummary) -> Self {
+        Self {
+            flag_count: summary.flag_count,
+            total_score: summary.total_score,
+            avg_score: summary.avg_score,
+            reasons: summary.reasons,
+            first_flagged_at: summary.first_flagged_at,
+            last_flagged_at: summary.last_flagged_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepoSummaryDto {
+    pub full_name: String,
+    pub issue_count: i64,
+    pub comment_count: i64,
+    pub distinct_authors: i64,
+    pub flagged_issue_count: i64,
+    pub flagged_comment_count: i64,
+}
+
+impl RepoSummaryDto {
+    pub fn new(full_name: String, summary: RepoSummary) -> Self {
+        Self {
+            full_name,
+            issue_count: summary.issue_count,
+            comment_count: summary.comment_count,
+            distinct_authors: summary.distinct_authors,
+            flagged_issue_count: summary.flagged_issue_count,
+            flagged_comment_count: summary.flagged_comment_count,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepoDeletionDto {
+    pub full_name: String,
+    pub repository: bool,
+    pub issues: u64,
+    pub comments: u64,
+    pub spam_flags: u64,
+    pub watermark: bool,
+    pub collection_job: bool,
+}
+
+impl RepoDeletionDto {
+    pub fn new(full_name: String, deletion: RepoDeletion) -> Self {
+        Self {
+            full_name,
+            repository: deletion.repository,
+            issues: deletion.issues,
+            comments: deletion.comments,
+            spam_flags: deletion.spam_flags,
+            watermark: deletion.watermark,
+            collection_job: deletion.collection_job,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreBucketDto {
+    pub bucket_start: DateTime<Utc>,
+    pub flag_count: i64,
+    pub total_score: f32,
+}
+
+impl From<ScoreBucket> for ScoreBucketDto {
+    fn from(bucket: ScoreBucket) -> Self {
+        Self {
+            bucket_start: bucket.bucket_start,
+            flag_count: bucket.flag_count,
+            total_score: bucket.total_score,
         }
     }
 }
@@ -101,11 +243,15 @@ pub struct SpammyUserDto {
     pub total_score: f32,
     pub flag_count: i64,
     pub reasons: Vec<String>,
+    /// Pass as `?cursor=` to list the users ranked after this one; not
+    /// accepted with `decay`, whose scores change between requests.
+    pub cursor: String,
 }
 
 impl From<ActorSpamSummary> for SpammyUserDto {
     fn from(summary: ActorSpamSummary) -> Self {
         Self {
+            cursor: encode_spammy_cursor(&summary.cursor()),
             login: summary.login,
             avg_score: summary.avg_score,
             total_score: summary.total_score,
@@ -115,6 +261,68 @@ impl From<ActorSpamSummary> for SpammyUserDto {
     }
 }
 
+// `total_score:flag_count:login`; logins never contain `:`.
+pub fn encode_spammy_cursor(cursor: &SpammyUserCursor) -> String {
+    format!(
+        "{}:{}:{}",
+        cursor.total_score, cursor.flag_count, cursor.login
+    )
+}
+
+pub fn decode_spammy_cursor(value: &str) -> Option<SpammyUserCursor> {
+    let mut parts = value.splitn(3, ':');
+    let total_score = parts.next()?.parse::<f32>().ok()?;
+    let flag_count = parts.next()?.parse().ok()?;
+    let login = parts.next().filter(|login| !login.is_empty())?;
+    Some(SpammyUserCursor {
+        total_score,
+        flag_count,
+        login: login.to_string(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpamFlagDto {
+    pub id: i64,
+    pub subject_type: String,
+    pub subject_id: i64,
+    pub score: f32,
+    pub reasons: Vec<String>,
+    pub version: String,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_status: ReviewStatus,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub reviewed_by: Option<String>,
+    pub features: Option<Value>,
+}
+
+impl From<SpamFlagRow> for SpamFlagDto {
+    fn from(row: SpamFlagRow) -> Self {
+        Self {
+            id: row.id,
+            subject_type: row.subject_type,
+            subject_id: row.subject_id,
+            score: row.score,
+            reasons: row.reasons,
+            version: row.version,
+            created_at: row.created_at,
+            reviewed_status: row.reviewed_status,
+            reviewed_at: row.reviewed_at,
+            reviewed_by: row.reviewed_by,
+            features: row.features,
+        }
+    }
+}
+
+/// Stored features of the highest-scoring flag that has them.
+pub fn top_flag_features(flags: &[SpamFlagRow]) -> Option<Value> {
+    flags
+        .iter()
+        .filter(|flag| flag.features.is_some())
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+        .and_then(|flag| flag.features.clone())
+}
+
 pub fn summarise_flags(flags: &[SpamFlagRow]) -> (Option<f32>, Vec<String>) {
     if flags.is_empty() {
         return (None, Vec::new());
@@ -131,3 +339,69 @@ pub fn summarise_flags(flags: &[SpamFlagRow]) -> (Option<f32>, Vec<String>) {
     }
     (max_score, reasons)
 }
+
+#[derive(Debug, Serialize)]
+pub struct RecentFlagDto {
+    #[serde(flatten)]
+    pub flag: SpamFlagDto,
+    pub repo_full_name: String,
+    pub issue_number: i64,
+    pub title: String,
+    pub author_login: Option<String>,
+    pub snippet: String,
+}
+
+impl From<RecentFlagRow> for RecentFlagDto {
+    fn from(row: RecentFlagRow) -> Self {
+        Self {
+            flag: SpamFlagDto::from(row.flag),
+            repo_full_name: row.repo_full_name,
+            issue_number: row.issue_number,
+            title: row.title,
+            author_login: row.author_login,
+            snippet: row.snippet,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClusterDto {
+    pub dedupe_hash: String,
+    pub size: i64,
+    pub distinct_authors: i64,
+    pub members: Vec<ClusterMemberDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClusterMemberDto {
+    pub subject_type: String,
+    pub subject_id: i64,
+    pub author_login: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<DedupeCluster> for ClusterDto {
+    fn from(cluster: DedupeCluster) -> Self {
+        Self {
+            dedupe_hash: cluster.dedupe_hash,
+            size: cluster.size,
+            distinct_authors: cluster.distinct_authors,
+            members: cluster
+                .members
+                .into_iter()
+                .map(ClusterMemberDto::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<ClusterMember> for ClusterMemberDto {
+    fn from(member: ClusterMember) -> Self {
+        Self {
+            subject_type: member.subject_type,
+            subject_id: member.subject_id,
+            author_login: member.author_login,
+            created_at: member.created_at,
+        }
+    }
+}
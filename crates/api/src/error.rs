@@ -1,13 +1,20 @@
-use axum::http::StatusCode;
+use std::time::Duration;
+
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
+use gh_broker::{GraphqlRateLimitedError, TokensExhaustedError};
 use serde::Serialize;
 
 #[derive(Debug)]
 pub enum ApiError {
     NotFound(String),
     BadRequest(String),
+    Unauthorized(String),
     Database(String),
+    /// An on-demand GitHub operation hit the rate limit; answered with 429
+    /// and, when known, a `Retry-After` header.
+    RateLimited(Option<Duration>),
     Internal(String),
 }
 
@@ -19,6 +26,10 @@ impl ApiError {
     pub fn bad_request(msg: impl Into<String>) -> Self {
         Self::BadRequest(msg.into())
     }
+
+    pub fn unauthorized(msg: impl Into<String>) -> Self {
+        Self::Unauthorized(msg.into())
+    }
 }
 
 impl From<db::DbError> for ApiError {
@@ -27,9 +38,39 @@ impl From<db::DbError> for ApiError {
     }
 }
 
+impl From<common::AppError> for ApiError {
+    fn from(err: common::AppError) -> Self {
+        match err {
+            common::AppError::RateLimited { retry_after } => Self::RateLimited(retry_after),
+            common::AppError::NotFound(what) => Self::NotFound(what.to_string()),
+            common::AppError::Database(err) => Self::Database(err.to_string()),
+            other => Self::Internal(other.to_string()),
+        }
+    }
+}
+
 impl From<anyhow::Error> for ApiError {
     fn from(err: anyhow::Error) -> Self {
-        Self::Internal(err.to_string())
+        // On-demand fetches go through the broker, which fails with its own
+        // typed errors rather than `AppError`.
+        let limited = err.chain().find_map(|cause| {
+            if let Some(exhausted) = cause.downcast_ref::<TokensExhaustedError>() {
+                return Some(common::AppError::from(exhausted.clone()));
+            }
+            if let Some(limited) = cause.downcast_ref::<GraphqlRateLimitedError>() {
+                return Some(common::AppError::from(limited.clone()));
+            }
+            match cause.downcast_ref::<common::AppError>() {
+                Some(common::AppError::RateLimited { retry_after }) => {
+                    Some(common::AppError::rate_limited(*retry_after))
+                }
+                _ => None,
+            }
+        });
+        match limited {
+            Some(limited) => limited.into(),
+            None => Self::Internal(err.to_string()),
+        }
     }
 }
 
@@ -43,7 +84,24 @@ impl IntoResponse for ApiError {
         let (status, message) = match self {
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
             ApiError::Database(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            ApiError::RateLimited(retry_after) => {
+                let message = common::AppError::rate_limited(retry_after).to_string();
+                let mut response = (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    Json(ErrorBody { error: message }),
+                )
+                    .into_response();
+                if let Some(wait) = retry_after {
+                    // Retry-After is whole seconds; round up so clients never retry early.
+                    let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+                    response
+                        .headers_mut()
+                        .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+                }
+                return response;
+            }
             ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
         let body = Json(ErrorBody { error: message });
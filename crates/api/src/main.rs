@@ -3,8 +3,8 @@ use std::sync::Arc;
 use anyhow::Result;
 use api::{build_router, ApiState};
 use axum::Router;
-use common::{config::AppConfig, logging};
-use db::pg::PgDatabase;
+use common::{config::AppConfig, logging, shutdown::ShutdownSignal};
+use db::pg::{ConnectRetry, PgDatabase};
 use db::Repositories;
 use tracing::info;
 
@@ -12,7 +12,11 @@ use tracing::info;
 async fn main() -> Result<()> {
     logging::init_tracing("api", "info");
     let config = AppConfig::load()?;
-    let database = Arc::new(PgDatabase::connect(&config.database.url).await?);
+    let shutdown = ShutdownSignal::install()?;
+    let database = Arc::new(
+        PgDatabase::connect_with(&config.database.url, ConnectRetry::from(&config.database))
+            .await?,
+    );
     let repositories: Arc<dyn Repositories> = database.clone();
     let metrics_path: &'static str =
         Box::leak(config.observability.metrics_path.clone().into_boxed_str());
@@ -21,13 +25,16 @@ async fn main() -> Result<()> {
         metrics_path,
         pool: Arc::new(database.pool().clone()),
         config: config.clone(),
+        entity_counts: Default::default(),
     });
     let app: Router = build_router(state);
 
     let addr: std::net::SocketAddr = config.api.bind.parse()?;
     info!("api listening on {}", addr);
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown.recv())
+        .await?;
     // Ensure any remaining spans are flushed on shutdown (no-op if otel disabled)
     common::logging::shutdown_tracer_provider();
     Ok(())
@@ -1,21 +1,31 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::IntoResponse;
-use axum::routing::get;
+use axum::routing::{delete, get, post};
 use axum::{Json, Router};
 use chrono::{DateTime, NaiveDate, Utc};
 use common::config::AppConfig;
-use db::models::{CollectionJobCreate, IssueQuery, SpamFilter};
+use common::labels::fold_top_repos;
+use common::text::truncate_chars;
+use db::models::{
+    CollectionJobCreate, CollectionStatus, IssueOrder, IssueQuery, ReviewStatus, SpamFilter,
+    SpamFlagReview, TrendBucket,
+};
 use db::Repositories;
 use once_cell::sync::Lazy;
-use prometheus::{register_int_gauge_vec, Encoder, IntGaugeVec};
+use prometheus::{register_int_counter, register_int_gauge_vec, Encoder, IntCounter, IntGaugeVec};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sqlx::{PgPool, Row};
 use tracing::instrument;
 
-use crate::dto::{summarise_flags, IssueDto, RepoDto, SpammyUserDto, UserDto};
+use crate::dto::{
+    decode_spammy_cursor, summarise_flags, top_flag_features, ClusterDto, IssueDto, RecentFlagDto,
+    RepoDeletionDto, RepoDto, RepoSummaryDto, ScoreBucketDto, SpamFlagDto, SpammyUserDto, UserDto,
+};
 use crate::error::{ApiError, ApiResult};
 
 #[derive(Clone)]
@@ -24,6 +34,7 @@ pub struct ApiState {
     pub metrics_path: &'static str,
     pub pool: Arc<PgPool>,
     pub config: AppConfig,
+    pub entity_counts: Arc<EntityCountsCache>,
 }
 
 pub fn build_router(state: Arc<ApiState>) -> Router {
@@ -32,11 +43,24 @@ pub fn build_router(state: Arc<ApiState>) -> Router {
         .route("/healthz", get(healthz))
         .route("/config/status", get(config_status))
         .route("/repos", get(list_repos).post(register_repo))
+        .route("/repos/:owner/:name", delete(delete_repo))
+        .route("/repos/:owner/:name/summary", get(repo_summary))
+        .route("/repos/:owner/:name/trend", get(repo_trend))
+        .route("/repos/:owner/:name/watermark", delete(reset_watermark))
         .route("/collection-jobs", get(list_collection_jobs))
         .route("/issues", get(list_issues))
         .route("/actors/:login", get(get_actor))
+        .route(
+            "/spam-flags/:subject_type/:subject_id",
+            get(list_spam_flags),
+        )
+        .route("/raw/:subject_type/:subject_id", get(raw_payload))
+        .route("/flags/recent", get(recent_flags))
+        .route("/flags/:id/review", post(review_spam_flag))
         .route("/top/spammy-users", get(top_spammy_users))
+        .route("/clusters", get(dedupe_clusters))
         .route(metrics_path, get(metrics))
+        .route("/metrics.json", get(metrics_json))
         .with_state(state)
 }
 
@@ -67,7 +91,46 @@ static USERS_BY_REPO: Lazy<IntGaugeVec> = Lazy::new(|| {
     .expect("users_by_repo gauge")
 });
 
-async fn refresh_repo_entity_counts(pool: &PgPool) -> Result<(), String> {
+static ENTITY_COUNTS_REFRESHES: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "api_entity_counts_refreshes_total",
+        "Times the per-repository issue/comment/user counts were recomputed"
+    )
+    .expect("entity_counts_refreshes counter")
+});
+
+/// When the per-repository count gauges were last recomputed. The gauges keep
+/// their values between refreshes, so scrapes inside the TTL reuse them.
+#[derive(Default)]
+pub struct EntityCountsCache {
+    refreshed_at: tokio::sync::Mutex<Option<Instant>>,
+}
+
+impl EntityCountsCache {
+    /// Recomputes the gauges unless they are younger than `ttl`. Concurrent
+    /// scrapes wait on the same refresh instead of each running the queries.
+    async fn refresh(&self, pool: &PgPool, limit: usize, ttl: Duration) -> Result<(), String> {
+        let mut refreshed_at = self.refreshed_at.lock().await;
+        if refreshed_at.is_some_and(|at| at.elapsed() < ttl) {
+            return Ok(());
+        }
+        refresh_repo_entity_counts(pool, limit).await?;
+        ENTITY_COUNTS_REFRESHES.inc();
+        *refreshed_at = Some(Instant::now());
+        Ok(())
+    }
+}
+
+/// Replaces every series of `gauge` with `rows`, keeping the `limit` largest
+/// repositories and summing the rest into `repo="other"`.
+fn set_repo_gauge(gauge: &IntGaugeVec, rows: Vec<(String, i64)>, limit: usize) {
+    gauge.reset();
+    for (repo, cnt) in fold_top_repos(rows, limit) {
+        gauge.with_label_values(&[&repo]).set(cnt);
+    }
+}
+
+async fn refresh_repo_entity_counts(pool: &PgPool, limit: usize) -> Result<(), String> {
     // Issues per repo
     let issues_rows = sqlx::query(
         r#"
@@ -80,14 +143,18 @@ async fn refresh_repo_entity_counts(pool: &PgPool) -> Result<(), String> {
     .fetch_all(pool)
     .await
     .map_err(|e| e.to_string())?;
-    for row in &issues_rows {
-        let repo: &str = row.try_get::<&str, _>("repo").unwrap_or("");
-        let cnt: i64 = row
-            .try_get::<Option<i64>, _>("count")
-            .unwrap_or(Some(0))
-            .unwrap_or(0);
-        ISSUES_BY_REPO.with_label_values(&[repo]).set(cnt);
-    }
+    let issues: Vec<(String, i64)> = issues_rows
+        .iter()
+        .map(|row| {
+            let repo: &str = row.try_get::<&str, _>("repo").unwrap_or("");
+            let cnt: i64 = row
+                .try_get::<Option<i64>, _>("count")
+                .unwrap_or(Some(0))
+                .unwrap_or(0);
+            (repo.to_string(), cnt)
+        })
+        .collect();
+    set_repo_gauge(&ISSUES_BY_REPO, issues, limit);
 
     // Comments per repo
     let comments_rows = sqlx::query(
@@ -102,14 +169,18 @@ async fn refresh_repo_entity_counts(pool: &PgPool) -> Result<(), String> {
     .fetch_all(pool)
     .await
     .map_err(|e| e.to_string())?;
-    for row in &comments_rows {
-        let repo: &str = row.try_get::<&str, _>("repo").unwrap_or("");
-        let cnt: i64 = row
-            .try_get::<Option<i64>, _>("count")
-            .unwrap_or(Some(0))
-            .unwrap_or(0);
-        COMMENTS_BY_REPO.with_label_values(&[repo]).set(cnt);
-    }
+    let comments: Vec<(String, i64)> = comments_rows
+        .iter()
+        .map(|row| {
+            let repo: &str = row.try_get::<&str, _>("repo").unwrap_or("");
+            let cnt: i64 = row
+                .try_get::<Option<i64>, _>("count")
+                .unwrap_or(Some(0))
+                .unwrap_or(0);
+            (repo.to_string(), cnt)
+        })
+        .collect();
+    set_repo_gauge(&COMMENTS_BY_REPO, comments, limit);
 
     // Distinct users (issue authors + comment authors) per repo
     let users_rows = sqlx::query(
@@ -140,17 +211,21 @@ async fn refresh_repo_entity_counts(pool: &PgPool) -> Result<(), String> {
     .fetch_all(pool)
     .await
     .map_err(|e| e.to_string())?;
-    for row in &users_rows {
-        let repo: &str = row
-            .try_get::<Option<&str>, _>("repo")
-            .unwrap_or(Some(""))
-            .unwrap_or("");
-        let cnt: i64 = row
-            .try_get::<Option<i64>, _>("count")
-            .unwrap_or(Some(0))
-            .unwrap_or(0);
-        USERS_BY_REPO.with_label_values(&[repo]).set(cnt);
-    }
+    let users: Vec<(String, i64)> = users_rows
+        .iter()
+        .map(|row| {
+            let repo: &str = row
+                .try_get::<Option<&str>, _>("repo")
+                .unwrap_or(Some(""))
+                .unwrap_or("");
+            let cnt: i64 = row
+                .try_get::<Option<i64>, _>("count")
+                .unwrap_or(Some(0))
+                .unwrap_or(0);
+            (repo.to_string(), cnt)
+        })
+        .collect();
+    set_repo_gauge(&USERS_BY_REPO, users, limit);
 
     Ok(())
 }
@@ -198,6 +273,7 @@ async fn config_status(
         broker: _,
         api: _,
         observability: _,
+        analysis: _,
     } = &state.config;
 
     let github_status = GithubStatus {
@@ -239,8 +315,10 @@ async fn list_repos(
 struct IssuesQuery {
     repo: Option<String>,
     spam: Option<String>,
+    reviewed: Option<String>,
     since: Option<String>,
     limit: Option<i64>,
+    order: Option<String>,
 }
 
 #[instrument(skip(state))]
@@ -252,10 +330,16 @@ async fn list_issues(
         repo_full_name: query.repo,
         limit: query.limit.map(|l| l.clamp(1, 200)),
         spam: query.spam.as_deref().map(parse_spam_filter).transpose()?,
+        reviewed: query
+            .reviewed
+            .as_deref()
+            .map(parse_review_status)
+            .transpose()?,
         since: match query.since {
             Some(ref value) => Some(parse_since(value)?),
             None => None,
         },
+        order_by: query.order.as_deref().map(parse_issue_order).transpose()?,
     };
 
     let rows = state.repositories.issues().query(issue_query).await?;
@@ -267,7 +351,9 @@ async fn list_issues(
             .list_for_subject("issue", issue.id)
             .await?;
         let (score, reasons) = summarise_flags(&flags);
-        issues.push(IssueDto::from_row(issue, score, reasons));
+        issues.push(
+            IssueDto::from_row(issue, score, reasons).with_spam_features(top_flag_features(&flags)),
+        );
     }
     Ok(Json(issues))
 }
@@ -283,13 +369,245 @@ async fn get_actor(
         .get_by_login(&login)
         .await?
         .ok_or_else(|| ApiError::not_found(format!("user {} not found", login)))?;
-    Ok(Json(UserDto::from(user)))
+    let activity = state.repositories.users().activity_summary(&login).await?;
+    let spam = state.repositories.users().spam_summary(&login).await?;
+    Ok(Json(
+        UserDto::from(user)
+            .with_activity(activity)
+            .with_spam_summary(spam),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct SpamFlagsQuery {
+    #[serde(default)]
+    history: bool,
+}
+
+#[instrument(skip(state))]
+async fn list_spam_flags(
+    State(state): State<Arc<ApiState>>,
+    Path((subject_type, subject_id)): Path<(String, i64)>,
+    Query(query): Query<SpamFlagsQuery>,
+) -> ApiResult<Json<Vec<SpamFlagDto>>> {
+    if !matches!(subject_type.as_str(), "issue" | "comment") {
+        return Err(ApiError::bad_request(format!(
+            "invalid subject type: {}",
+            subject_type
+        )));
+    }
+    let spam_flags = state.repositories.spam_flags();
+    let rows = if query.history {
+        spam_flags
+            .list_history_for_subject(&subject_type, subject_id)
+            .await?
+    } else {
+        spam_flags
+            .list_for_subject(&subject_type, subject_id)
+            .await?
+    };
+    Ok(Json(rows.into_iter().map(SpamFlagDto::from).collect()))
+}
+
+/// Rejects the request unless it carries `Authorization: Bearer <api.api_key>`.
+fn require_api_key(config: &AppConfig, headers: &HeaderMap) -> ApiResult<()> {
+    let Some(expected) = config.api.api_key.as_deref().filter(|key| !key.is_empty()) else {
+        return Err(ApiError::unauthorized("api.api_key is not configured"));
+    };
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if presented != Some(expected) {
+        return Err(ApiError::unauthorized("missing or invalid API key"));
+    }
+    Ok(())
+}
+
+/// The GitHub payload stored for a subject, returned exactly as received.
+/// Payloads can carry PII (emails, names), so this requires the API key.
+#[instrument(skip(state, headers))]
+async fn raw_payload(
+    State(state): State<Arc<ApiState>>,
+    Path((subject_type, subject_id)): Path<(String, i64)>,
+    headers: HeaderMap,
+) -> ApiResult<Json<serde_json::Value>> {
+    require_api_key(&state.config, &headers)?;
+    let repositories = &state.repositories;
+    let raw = match subject_type.as_str() {
+        "issue" => repositories.issues().raw(subject_id).await?,
+        "comment" => repositories.comments().raw(subject_id).await?,
+        "user" => repositories
+            .users()
+            .get_by_id(subject_id)
+            .await?
+            .map(|user| user.raw),
+        "repo" => repositories
+            .repos()
+            .get_by_id(subject_id)
+            .await?
+            .map(|repo| repo.raw),
+        other => {
+            return Err(ApiError::bad_request(format!(
+                "invalid subject type: {}",
+                other
+            )))
+        }
+    };
+    raw.map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("{} {} not found", subject_type, subject_id)))
+}
+
+/// Stored issue/comment/author/flag counts for one repository.
+#[instrument(skip(state))]
+async fn repo_summary(
+    State(state): State<Arc<ApiState>>,
+    Path((owner, name)): Path<(String, String)>,
+) -> ApiResult<Json<RepoSummaryDto>> {
+    let full_name = format!("{}/{}", owner, name);
+    let summary = state
+        .repositories
+        .repos()
+        .summary(&full_name)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("repository {} not found", full_name)))?;
+    Ok(Json(RepoSummaryDto::new(full_name, summary)))
+}
+
+#[derive(Debug, Deserialize)]
+struct TrendQuery {
+    /// `day` (default) or `hour`.
+    bucket: Option<String>,
+    since: Option<String>,
+}
+
+/// Flag counts and summed scores of one repository per day or hour.
+#[instrument(skip(state))]
+async fn repo_trend(
+    State(state): State<Arc<ApiState>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(query): Query<TrendQuery>,
+) -> ApiResult<Json<Vec<ScoreBucketDto>>> {
+    let full_name = format!("{}/{}", owner, name);
+    let bucket = query
+        .bucket
+        .as_deref()
+        .map(parse_trend_bucket)
+        .transpose()?
+        .unwrap_or_default();
+    let since = match query.since {
+        Some(ref value) => Some(parse_since(value)?),
+        None => None,
+    };
+    if state
+        .repositories
+        .repos()
+        .get_by_full_name(&full_name)
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::not_found(format!(
+            "repository {} not found",
+            full_name
+        )));
+    }
+    let buckets = state
+        .repositories
+        .spam_flags()
+        .score_timeseries(&full_name, bucket, since)
+        .await?;
+    Ok(Json(
+        buckets.into_iter().map(ScoreBucketDto::from).collect(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct DeleteRepoQuery {
+    /// Also remove the collection job, so the repo is not collected again.
+    #[serde(default)]
+    purge: bool,
+}
+
+/// Removes everything stored for a repository we stopped tracking; authors
+/// stay, as they may have posted elsewhere.
+#[instrument(skip(state, headers))]
+async fn delete_repo(
+    State(state): State<Arc<ApiState>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(query): Query<DeleteRepoQuery>,
+    headers: HeaderMap,
+) -> ApiResult<Json<RepoDeletionDto>> {
+    require_api_key(&state.config, &headers)?;
+    let full_name = format!("{}/{}", owner, name);
+    let deletion = state
+        .repositories
+        .repos()
+        .delete_repo(&full_name, query.purge)
+        .await?;
+    if deletion.is_empty() {
+        return Err(ApiError::not_found(format!(
+            "repository {} not found",
+            full_name
+        )));
+    }
+    Ok(Json(RepoDeletionDto::new(full_name, deletion)))
+}
+
+/// Drops a repo's watermark and any half-finished pagination, so the next
+/// collection re-pages it from scratch (e.g. after a normalization change).
+#[instrument(skip(state, headers))]
+async fn reset_watermark(
+    State(state): State<Arc<ApiState>>,
+    Path((owner, name)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> ApiResult<StatusCode> {
+    require_api_key(&state.config, &headers)?;
+    let full_name = format!("{}/{}", owner, name);
+    let watermarks = state.repositories.watermarks();
+    if !watermarks.delete(&full_name).await? {
+        return Err(ApiError::not_found(format!(
+            "no watermark for {}",
+            full_name
+        )));
+    }
+    watermarks.clear_progress(&full_name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewRequest {
+    status: String,
+    reviewed_by: Option<String>,
+}
+
+#[instrument(skip(state))]
+async fn review_spam_flag(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<i64>,
+    Json(request): Json<ReviewRequest>,
+) -> ApiResult<Json<SpamFlagDto>> {
+    let review = SpamFlagReview {
+        id,
+        status: parse_review_status(&request.status)?,
+        reviewed_by: request.reviewed_by,
+    };
+    let row = state
+        .repositories
+        .spam_flags()
+        .review(review)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("spam flag {} not found", id)))?;
+    Ok(Json(SpamFlagDto::from(row)))
 }
 
 #[derive(Debug, Deserialize)]
 struct SpammyUsersQuery {
     since: Option<String>,
     limit: Option<i64>,
+    /// Half-life in days for recency weighting; omitted means a plain sum.
+    decay: Option<f64>,
+    /// `cursor` of the last user on the previous page.
+    cursor: Option<String>,
 }
 
 #[instrument(skip(state))]
@@ -302,20 +620,101 @@ async fn top_spammy_users(
         None => None,
     };
     let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    if query
+        .decay
+        .is_some_and(|half_life| !(half_life.is_finite() && half_life > 0.0))
+    {
+        return Err(ApiError::bad_request(
+            "decay must be a positive half-life in days",
+        ));
+    }
+    // Decayed scores move with `now()`, so a cursor from one page cannot
+    // place the next one.
+    if query.decay.is_some() && query.cursor.is_some() {
+        return Err(ApiError::bad_request(
+            "cursor cannot be combined with decay",
+        ));
+    }
+    let after = match query.cursor.as_deref() {
+        Some(value) => Some(
+            decode_spammy_cursor(value)
+                .ok_or_else(|| ApiError::bad_request(format!("invalid cursor: {}", value)))?,
+        ),
+        None => None,
+    };
     let rows = state
         .repositories
         .spam_flags()
-        .top_spammy_users(since, limit)
+        .top_spammy_users(since, limit, query.decay, after)
         .await?;
     Ok(Json(rows.into_iter().map(SpammyUserDto::from).collect()))
 }
 
+#[derive(Debug, Deserialize)]
+struct ClustersQuery {
+    min_size: Option<i64>,
+    limit: Option<i64>,
+}
+
 #[instrument(skip(state))]
-async fn metrics(State(state): State<Arc<ApiState>>) -> ApiResult<impl IntoResponse> {
+async fn dedupe_clusters(
+    State(state): State<Arc<ApiState>>,
+    Query(query): Query<ClustersQuery>,
+) -> ApiResult<Json<Vec<ClusterDto>>> {
+    // A "cluster" of one is just a post.
+    let min_size = query.min_size.unwrap_or(3).max(2);
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let clusters = state
+        .repositories
+        .spam_flags()
+        .dedupe_clusters(min_size, limit)
+        .await?;
+    Ok(Json(clusters.into_iter().map(ClusterDto::from).collect()))
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentFlagsQuery {
+    since: Option<String>,
+    limit: Option<i64>,
+}
+
+#[instrument(skip(state))]
+async fn recent_flags(
+    State(state): State<Arc<ApiState>>,
+    Query(query): Query<RecentFlagsQuery>,
+) -> ApiResult<Json<Vec<RecentFlagDto>>> {
+    let since = match query.since {
+        Some(ref value) => Some(parse_since(value)?),
+        None => None,
+    };
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let rows = state
+        .repositories
+        .spam_flags()
+        .recent_flags(since, limit)
+        .await?;
+    Ok(Json(rows.into_iter().map(RecentFlagDto::from).collect()))
+}
+
+async fn refresh_entity_counts(state: &ApiState) {
     // Best-effort: refresh counts before scraping metrics
-    if let Err(err) = refresh_repo_entity_counts(&state.pool).await {
+    let observability = &state.config.observability;
+    if let Err(err) = state
+        .entity_counts
+        .refresh(
+            &state.pool,
+            observability.max_repo_labels,
+            Duration::from_secs(observability.entity_counts_ttl_secs),
+        )
+        .await
+    {
         tracing::warn!(error = %err, "failed to refresh repo entity counts");
     }
+}
+
+#[instrument(skip(state))]
+async fn metrics(State(state): State<Arc<ApiState>>) -> ApiResult<impl IntoResponse> {
+    refresh_entity_counts(&state).await;
     let encoder = prometheus::TextEncoder::new();
     let metric_families = prometheus::gather();
     let mut buffer = Vec::new();
@@ -330,6 +729,15 @@ async fn metrics(State(state): State<Arc<ApiState>>) -> ApiResult<impl IntoRespo
     ))
 }
 
+/// The same families as the text endpoint, for tooling that wants JSON.
+#[instrument(skip(state))]
+async fn metrics_json(
+    State(state): State<Arc<ApiState>>,
+) -> Json<Vec<common::metrics_json::MetricFamilyJson>> {
+    refresh_entity_counts(&state).await;
+    Json(common::metrics_json::gather())
+}
+
 fn parse_spam_filter(value: &str) -> ApiResult<SpamFilter> {
     match value.to_ascii_lowercase().as_str() {
         "likely" => Ok(SpamFilter::Likely),
@@ -342,6 +750,41 @@ fn parse_spam_filter(value: &str) -> ApiResult<SpamFilter> {
     }
 }
 
+fn parse_issue_order(value: &str) -> ApiResult<IssueOrder> {
+    match value.to_ascii_lowercase().as_str() {
+        "updated_desc" => Ok(IssueOrder::UpdatedDesc),
+        "created_desc" => Ok(IssueOrder::CreatedDesc),
+        "score_desc" => Ok(IssueOrder::ScoreDesc),
+        other => Err(ApiError::bad_request(format!(
+            "invalid issue order: {}",
+            other
+        ))),
+    }
+}
+
+fn parse_trend_bucket(value: &str) -> ApiResult<TrendBucket> {
+    match value.to_ascii_lowercase().as_str() {
+        "hour" => Ok(TrendBucket::Hour),
+        "day" => Ok(TrendBucket::Day),
+        other => Err(ApiError::bad_request(format!(
+            "invalid trend bucket: {}",
+            other
+        ))),
+    }
+}
+
+fn parse_review_status(value: &str) -> ApiResult<ReviewStatus> {
+    match value.to_ascii_lowercase().as_str() {
+        "unreviewed" => Ok(ReviewStatus::Unreviewed),
+        "confirmed" => Ok(ReviewStatus::Confirmed),
+        "false_positive" => Ok(ReviewStatus::FalsePositive),
+        other => Err(ApiError::bad_request(format!(
+            "invalid review status: {}",
+            other
+        ))),
+    }
+}
+
 fn parse_since(value: &str) -> ApiResult<DateTime<Utc>> {
     if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
         return Ok(dt.with_timezone(&Utc));
@@ -412,6 +855,7 @@ async fn register_repo(
 #[derive(Debug, Deserialize)]
 struct CollectionJobsQuery {
     limit: Option<i32>,
+    status: Option<String>,
 }
 
 #[instrument(skip(state))]
@@ -420,7 +864,15 @@ async fn list_collection_jobs(
     Query(query): Query<CollectionJobsQuery>,
 ) -> ApiResult<Json<Vec<CollectionJobResponse>>> {
     let limit = query.limit.unwrap_or(50).clamp(1, 500);
-    let jobs = state.repositories.collection_jobs().list(limit).await?;
+    let statuses = match query.status.as_deref() {
+        Some(value) => parse_job_statuses(value)?,
+        None => Vec::new(),
+    };
+    let jobs = state
+        .repositories
+        .collection_jobs()
+        .list(limit, &statuses)
+        .await?;
 
     let response = jobs
         .into_iter()
@@ -443,22 +895,32 @@ async fn list_collection_jobs(
     Ok(Json(response))
 }
 
+fn parse_job_statuses(value: &str) -> ApiResult<Vec<CollectionStatus>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| match s.to_ascii_lowercase().as_str() {
+            "pending" => Ok(CollectionStatus::Pending),
+            "in_progress" => Ok(CollectionStatus::InProgress),
+            "completed" => Ok(CollectionStatus::Completed),
+            "failed" => Ok(CollectionStatus::Failed),
+            "error" => Ok(CollectionStatus::Error),
+            other => Err(ApiError::bad_request(format!(
+                "invalid job status: {}",
+                other
+            ))),
+        })
+        .collect()
+}
+
 fn displayable_error(
     status: &db::models::CollectionStatus,
     message: Option<&str>,
 ) -> Option<String> {
     use db::models::CollectionStatus as S;
     match status {
-        S::Failed | S::Error => message.map(|m| truncate(m, 512)),
+        S::Failed | S::Error => message.map(|m| truncate_chars(m, 512)),
         _ => None,
     }
 }
-
-fn truncate(msg: &str, max: usize) -> String {
-    if msg.len() <= max {
-        return msg.to_string();
-    }
-    let mut s: String = msg.chars().take(max).collect();
-    s.push('…');
-    s
-}
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use chrono::{Duration, Utc};
+use common::config::AppConfig;
+use db::models::{CommentRow, IssueRow, RepositoryRow, UserRow};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+
+use api::{build_router, routes::ApiState};
+
+const SPAM_HASH: &str = "hash-spam";
+
+fn issue(id: i64, user_id: i64, dedupe_hash: &str) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id: 1,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: "Cheap followers".into(),
+        body: Some("buy followers at example.com".into()),
+        user_id: Some(user_id),
+        comments_count: 0,
+        created_at: Utc::now() - Duration::hours(10 - id),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: dedupe_hash.into(),
+        raw: json!({ "id": id }),
+        found: true,
+    }
+}
+
+fn comment(id: i64, user_id: i64, dedupe_hash: &str) -> CommentRow {
+    CommentRow {
+        id,
+        issue_id: 1,
+        user_id: Some(user_id),
+        body: "buy followers at example.com".into(),
+        created_at: Utc::now(),
+        updated_at: None,
+        author_association: None,
+        dedupe_hash: dedupe_hash.into(),
+        raw: json!({ "id": id }),
+        found: true,
+    }
+}
+
+// Four posts of the spam body by three accounts, plus a pair and a one-off.
+async fn seed(database: &PgDatabase) -> anyhow::Result<()> {
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({ "id": 1 }),
+        })
+        .await?;
+    for (id, login) in [(7, "alice"), (8, "bob"), (9, "carol")] {
+        database
+            .users()
+            .upsert(UserRow {
+                id,
+                login: login.into(),
+                user_type: "User".into(),
+                site_admin: false,
+                created_at: None,
+                followers: None,
+                following: None,
+                public_repos: None,
+                bio: None,
+                blog: None,
+                raw: json!({ "id": id, "login": login }),
+                found: true,
+            })
+            .await?;
+    }
+    database.issues().upsert(issue(1, 7, SPAM_HASH)).await?;
+    database.issues().upsert(issue(2, 8, SPAM_HASH)).await?;
+    database.issues().upsert(issue(3, 9, "hash-pair")).await?;
+    database.issues().upsert(issue(4, 9, "hash-single")).await?;
+    database
+        .comments()
+        .upsert(comment(10, 7, SPAM_HASH))
+        .await?;
+    database
+        .comments()
+        .upsert(comment(11, 9, SPAM_HASH))
+        .await?;
+    database
+        .comments()
+        .upsert(comment(12, 8, "hash-pair"))
+        .await?;
+    Ok(())
+}
+
+fn app(database: Arc<PgDatabase>) -> anyhow::Result<Router> {
+    let config = AppConfig::load_from_path("../..")?;
+    let state = Arc::new(ApiState {
+        repositories: database.clone(),
+        metrics_path: "/metrics",
+        pool: Arc::new(database.pool().clone()),
+        config,
+        entity_counts: Default::default(),
+    });
+    Ok(build_router(state))
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+    let res = app
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn shared_dedupe_hashes_form_clusters() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping shared_dedupe_hashes_form_clusters: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("api_clusters").await?;
+    let database = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    seed(&database).await?;
+    let app = app(database.clone())?;
+
+    let (status, body) = get(&app, "/clusters?min_size=3").await;
+    assert_eq!(status, StatusCode::OK);
+    let clusters = body.as_array().expect("array");
+    assert_eq!(clusters.len(), 1, "{body}");
+    let cluster = &clusters[0];
+    assert_eq!(cluster["dedupe_hash"], SPAM_HASH);
+    assert_eq!(cluster["size"], 4);
+    assert_eq!(cluster["distinct_authors"], 3);
+    let members: Vec<(String, i64)> = cluster["members"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| {
+            (
+                m["subject_type"].as_str().unwrap().to_string(),
+                m["subject_id"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        members,
+        vec![
+            ("issue".to_string(), 1),
+            ("issue".to_string(), 2),
+            ("comment".to_string(), 10),
+            ("comment".to_string(), 11),
+        ]
+    );
+    assert_eq!(cluster["members"][0]["author_login"], "alice");
+
+    let (_, body) = get(&app, "/clusters?min_size=2").await;
+    let sizes: Vec<i64> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["size"].as_i64().unwrap())
+        .collect();
+    assert_eq!(sizes, vec![4, 2]);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use common::config::AppConfig;
+use db::pg::PgDatabase;
+use db_test_fixture::DbFixture;
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+
+use api::{build_router, routes::ApiState};
+
+fn app(database: &PgDatabase, ttl_secs: u64) -> anyhow::Result<Router> {
+    let mut config = AppConfig::load_from_path("../..")?;
+    config.observability.entity_counts_ttl_secs = ttl_secs;
+    let state = Arc::new(ApiState {
+        repositories: Arc::new(database.clone()),
+        metrics_path: "/metrics",
+        pool: Arc::new(database.pool().clone()),
+        config,
+        entity_counts: Default::default(),
+    });
+    Ok(build_router(state))
+}
+
+/// Scrapes `/metrics` and returns `api_entity_counts_refreshes_total`.
+async fn scrape_refreshes(app: &Router) -> u64 {
+    let res = app
+        .clone()
+        .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .find_map(|line| line.strip_prefix("api_entity_counts_refreshes_total "))
+        .expect("refresh counter exported")
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn scrapes_within_ttl_reuse_cached_entity_counts() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping scrapes_within_ttl_reuse_cached_entity_counts: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("metrics_cache").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    let cached = app(&database, 300)?;
+    let first = scrape_refreshes(&cached).await;
+    assert_eq!(first, 1, "first scrape computes the counts");
+    for _ in 0..3 {
+        assert_eq!(scrape_refreshes(&cached).await, first);
+    }
+
+    // The JSON snapshot shares the cache and reports the same counter.
+    let res = cached
+        .clone()
+        .oneshot(Request::get("/metrics.json").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let families: Value =
+        serde_json::from_slice(&to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+    let refreshes = families
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|family| family["name"] == "api_entity_counts_refreshes_total")
+        .expect("refresh counter in JSON snapshot");
+    assert_eq!(refreshes["type"], "counter");
+    assert_eq!(refreshes["samples"][0]["value"], json!(first as f64));
+
+    let uncached = app(&database, 0)?;
+    let before = scrape_refreshes(&uncached).await;
+    assert_eq!(scrape_refreshes(&uncached).await, before + 1);
+
+    handle.cleanup().await?;
+    Ok(())
+}
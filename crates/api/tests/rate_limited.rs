@@ -0,0 +1,154 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::extract::{Path, State};
+use axum::http::{header, Request, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use chrono::Utc;
+use common::AppError;
+use gh_broker::broker::HttpExec;
+use gh_broker::{GithubBroker, GithubBrokerBuilder, GithubToken, Priority};
+use serde_json::Value;
+use tower::util::ServiceExt;
+
+use api::error::{ApiError, ApiResult};
+
+/// Answers every call but reports the token's budget as spent until an hour
+/// from now; GraphQL calls get GitHub's `RATE_LIMITED` body.
+struct SpentExec;
+
+#[async_trait]
+impl HttpExec for SpentExec {
+    async fn execute(
+        &self,
+        req: http::Request<Vec<u8>>,
+    ) -> anyhow::Result<http::Response<Vec<u8>>> {
+        let reset = Utc::now() + chrono::Duration::hours(1);
+        let (resource, body) = if req.uri().path() == "/graphql" {
+            (
+                "graphql",
+                serde_json::json!({
+                    "data": { "rateLimit": { "resetAt": reset.to_rfc3339() } },
+                    "errors": [{ "type": "RATE_LIMITED", "message": "API rate limit exceeded" }],
+                }),
+            )
+        } else {
+            ("core", serde_json::json!({ "login": "octocat" }))
+        };
+        Ok(http::Response::builder()
+            .status(200)
+            .header("x-ratelimit-limit", "5000")
+            .header("x-ratelimit-remaining", "0")
+            .header("x-ratelimit-reset", reset.timestamp().to_string())
+            .header("x-ratelimit-resource", resource)
+            .body(serde_json::to_vec(&body)?)?)
+    }
+}
+
+fn github_request(method: http::Method, path: &str) -> http::Request<Vec<u8>> {
+    http::Request::builder()
+        .method(method)
+        .uri(format!("https://api.github.com/{path}"))
+        .header(http::header::USER_AGENT, "test-agent")
+        .body(b"{}".to_vec())
+        .unwrap()
+}
+
+/// An on-demand user lookup, as a handler would run it.
+async fn fetch_user(
+    State(broker): State<Arc<dyn GithubBroker>>,
+    Path(login): Path<String>,
+) -> ApiResult<()> {
+    broker
+        .enqueue(
+            github_request(http::Method::GET, &format!("users/{login}")),
+            Priority::Critical,
+        )
+        .await
+        .with_context(|| format!("fetching {login} on demand"))?;
+    Ok(())
+}
+
+async fn graphql_query(State(broker): State<Arc<dyn GithubBroker>>) -> ApiResult<()> {
+    broker
+        .enqueue(
+            github_request(http::Method::POST, "graphql"),
+            Priority::Critical,
+        )
+        .await
+        .context("querying GitHub on demand")?;
+    Ok(())
+}
+
+fn router() -> Router {
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(Arc::new(SpentExec))
+    .max_rate_limit_wait(Duration::from_secs(60))
+    .build();
+    Router::new()
+        .route("/users/:login", get(fetch_user))
+        .route("/graphql", get(graphql_query))
+        .route(
+            "/without-wait",
+            get(|| async { Err::<(), _>(ApiError::from(AppError::rate_limited(None))) }),
+        )
+        .with_state(broker)
+}
+
+async fn call(router: &Router, uri: &str) -> axum::response::Response {
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        router
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap()),
+    )
+    .await
+    .expect("fails fast instead of waiting for the reset")
+    .unwrap()
+}
+
+#[tokio::test]
+async fn exhausted_tokens_during_an_on_demand_fetch_return_429_with_retry_after() {
+    let router = router();
+    // Spends the token's last call.
+    assert_eq!(call(&router, "/users/hubot").await.status(), StatusCode::OK);
+
+    let res = call(&router, "/users/octocat").await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = res.headers()[header::RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((3500..=3600).contains(&retry_after), "{retry_after}");
+    let body: Value =
+        serde_json::from_slice(&to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("rate limited by GitHub"));
+}
+
+#[tokio::test]
+async fn graphql_rate_limited_on_demand_query_returns_429() {
+    let router = router();
+
+    let res = call(&router, "/graphql").await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().get(header::RETRY_AFTER).is_some());
+}
+
+#[tokio::test]
+async fn rate_limited_without_known_wait_omits_retry_after() {
+    let res = call(&router(), "/without-wait").await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().get(header::RETRY_AFTER).is_none());
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use chrono::Utc;
+use common::config::AppConfig;
+use db::models::{CommentRow, IssueRow, RepositoryRow, UserRow};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+
+use api::{build_router, routes::ApiState};
+
+const KEY: &str = "test-key";
+
+async fn seed(database: &PgDatabase) -> anyhow::Result<()> {
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({ "id": 1, "full_name": "o/r", "private": false }),
+        })
+        .await?;
+    database
+        .users()
+        .upsert(UserRow {
+            id: 7,
+            login: "octocat".into(),
+            user_type: "User".into(),
+            site_admin: false,
+            created_at: None,
+            followers: None,
+            following: None,
+            public_repos: None,
+            bio: None,
+            blog: None,
+            raw: json!({ "id": 7, "login": "octocat", "email": "octo@example.com" }),
+            found: true,
+        })
+        .await?;
+    database
+        .issues()
+        .upsert(IssueRow {
+            id: 10,
+            repo_id: 1,
+            number: 1,
+            is_pull_request: false,
+            state: "open".into(),
+            title: "title".into(),
+            body: None,
+            user_id: Some(7),
+            comments_count: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: "issue-10".into(),
+            raw: json!({ "id": 10, "labels": [{ "name": "bug" }] }),
+            found: true,
+        })
+        .await?;
+    database
+        .comments()
+        .upsert(CommentRow {
+            id: 20,
+            issue_id: 10,
+            user_id: Some(7),
+            body: "body".into(),
+            created_at: Utc::now(),
+            updated_at: None,
+            author_association: None,
+            dedupe_hash: "comment-20".into(),
+            raw: json!({ "id": 20, "author_association": "NONE" }),
+            found: true,
+        })
+        .await?;
+    Ok(())
+}
+
+fn app(database: Arc<PgDatabase>) -> anyhow::Result<Router> {
+    let mut config = AppConfig::load_from_path("../..")?;
+    config.api.api_key = Some(KEY.to_string());
+    let state = Arc::new(ApiState {
+        repositories: database.clone(),
+        metrics_path: "/metrics",
+        pool: Arc::new(database.pool().clone()),
+        config,
+        entity_counts: Default::default(),
+    });
+    Ok(build_router(state))
+}
+
+async fn get(app: &Router, uri: &str, key: Option<&str>) -> (StatusCode, Value) {
+    let mut request = Request::get(uri);
+    if let Some(key) = key {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {key}"));
+    }
+    let res = app
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn raw_payload_returns_stored_json_behind_api_key() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping raw_payload_returns_stored_json_behind_api_key: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("api_raw_payload").await?;
+    let database = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    seed(&database).await?;
+    let app = app(database)?;
+
+    for (uri, expected) in [
+        (
+            "/raw/repo/1",
+            json!({ "id": 1, "full_name": "o/r", "private": false }),
+        ),
+        (
+            "/raw/user/7",
+            json!({ "id": 7, "login": "octocat", "email": "octo@example.com" }),
+        ),
+        (
+            "/raw/issue/10",
+            json!({ "id": 10, "labels": [{ "name": "bug" }] }),
+        ),
+        (
+            "/raw/comment/20",
+            json!({ "id": 20, "author_association": "NONE" }),
+        ),
+    ] {
+        assert_eq!(get(&app, uri, Some(KEY)).await, (StatusCode::OK, expected));
+    }
+
+    for uri in [
+        "/raw/issue/11",
+        "/raw/comment/21",
+        "/raw/user/8",
+        "/raw/repo/2",
+    ] {
+        assert_eq!(get(&app, uri, Some(KEY)).await.0, StatusCode::NOT_FOUND);
+    }
+    assert_eq!(
+        get(&app, "/raw/label/1", Some(KEY)).await.0,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        get(&app, "/raw/issue/10", None).await.0,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        get(&app, "/raw/issue/10", Some("wrong")).await.0,
+        StatusCode::UNAUTHORIZED
+    );
+
+    handle.cleanup().await?;
+    Ok(())
+}
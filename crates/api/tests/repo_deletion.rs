@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use chrono::Utc;
+use common::config::AppConfig;
+use db::models::{CollectionJobCreate, IssueRow, RepositoryRow, WatermarkUpdate};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+
+use api::{build_router, routes::ApiState};
+
+const KEY: &str = "test-key";
+
+fn app(database: Arc<PgDatabase>) -> anyhow::Result<Router> {
+    let mut config = AppConfig::load_from_path("../..")?;
+    config.api.api_key = Some(KEY.to_string());
+    let state = Arc::new(ApiState {
+        repositories: database.clone(),
+        metrics_path: "/metrics",
+        pool: Arc::new(database.pool().clone()),
+        config,
+        entity_counts: Default::default(),
+    });
+    Ok(build_router(state))
+}
+
+async fn delete(app: &Router, uri: &str, key: Option<&str>) -> (StatusCode, Value) {
+    let mut request = Request::delete(uri);
+    if let Some(key) = key {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {key}"));
+    }
+    let res = app
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn deleting_repo_reports_what_was_removed() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping deleting_repo_reports_what_was_removed: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("api_repo_deletion").await?;
+    let database = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "acme/widgets".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    database
+        .issues()
+        .upsert(IssueRow {
+            id: 10,
+            repo_id: 1,
+            number: 1,
+            is_pull_request: false,
+            state: "open".into(),
+            title: "Crash on start".into(),
+            body: None,
+            user_id: None,
+            comments_count: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: "issue-10".into(),
+            raw: json!({}),
+            found: true,
+        })
+        .await?;
+    database
+        .watermarks()
+        .set(WatermarkUpdate {
+            repo_full_name: "acme/widgets".into(),
+            last_updated: Utc::now(),
+        })
+        .await?;
+    database
+        .collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "widgets".into(),
+            priority: 0,
+        })
+        .await?;
+    let app = app(database.clone())?;
+
+    let uri = "/repos/acme/widgets?purge=true";
+    assert_eq!(delete(&app, uri, None).await.0, StatusCode::UNAUTHORIZED);
+    assert!(database
+        .repos()
+        .get_by_full_name("acme/widgets")
+        .await?
+        .is_some());
+
+    let (status, body) = delete(&app, uri, Some(KEY)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({
+            "full_name": "acme/widgets",
+            "repository": true,
+            "issues": 1,
+            "comments": 0,
+            "spam_flags": 0,
+            "watermark": true,
+            "collection_job": true,
+        })
+    );
+
+    assert_eq!(delete(&app, uri, Some(KEY)).await.0, StatusCode::NOT_FOUND);
+
+    handle.cleanup().await?;
+    Ok(())
+}
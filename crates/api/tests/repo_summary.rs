@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use chrono::Utc;
+use common::config::AppConfig;
+use db::models::{
+    CommentRow, IssueRow, RepositoryRow, ReviewStatus, SpamFlagReview, SpamFlagUpsert, UserRow,
+};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+
+use api::{build_router, routes::ApiState};
+
+fn issue(id: i64, repo_id: i64, user_id: i64) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("Issue {id}"),
+        body: Some("Steps to reproduce attached.".into()),
+        user_id: Some(user_id),
+        comments_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("issue-{id}"),
+        raw: json!({ "id": id }),
+        found: true,
+    }
+}
+
+fn comment(id: i64, issue_id: i64, user_id: i64) -> CommentRow {
+    CommentRow {
+        id,
+        issue_id,
+        user_id: Some(user_id),
+        body: "Same here.".into(),
+        created_at: Utc::now(),
+        updated_at: None,
+        author_association: None,
+        dedupe_hash: format!("comment-{id}"),
+        raw: json!({ "id": id }),
+        found: true,
+    }
+}
+
+fn flag(subject_type: &str, subject_id: i64) -> SpamFlagUpsert {
+    SpamFlagUpsert {
+        subject_type: subject_type.into(),
+        subject_id,
+        score: 3.0,
+        reasons: vec!["contact_only".into()],
+        version: "rules_v1".into(),
+        features: None,
+    }
+}
+
+// `o/r`: issues 1 and 2, comments 10 and 11, three authors, issue 1 and
+// comment 10 flagged, comment 11 flagged but reviewed as a false positive.
+// `o/other`: one issue with one comment, which must not leak into `o/r`.
+async fn seed(database: &PgDatabase) -> anyhow::Result<()> {
+    for (id, full_name) in [(1, "o/r"), (2, "o/other")] {
+        database
+            .repos()
+            .upsert(RepositoryRow {
+                id,
+                full_name: full_name.into(),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": id }),
+            })
+            .await?;
+    }
+    for (id, login) in [(7, "alice"), (8, "bob"), (9, "carol")] {
+        database
+            .users()
+            .upsert(UserRow {
+                id,
+                login: login.into(),
+                user_type: "User".into(),
+                site_admin: false,
+                created_at: None,
+                followers: None,
+                following: None,
+                public_repos: None,
+                bio: None,
+                blog: None,
+                raw: json!({ "id": id, "login": login }),
+                found: true,
+            })
+            .await?;
+    }
+    database.issues().upsert(issue(1, 1, 7)).await?;
+    database.issues().upsert(issue(2, 1, 8)).await?;
+    database.issues().upsert(issue(3, 2, 9)).await?;
+    database.comments().upsert(comment(10, 1, 9)).await?;
+    database.comments().upsert(comment(11, 2, 7)).await?;
+    database.comments().upsert(comment(12, 3, 8)).await?;
+
+    let flags = database.spam_flags();
+    flags.upsert(flag("issue", 1)).await?;
+    flags.upsert(flag("comment", 10)).await?;
+    flags.upsert(flag("comment", 11)).await?;
+    let dismissed = flags.list_for_subject("comment", 11).await?.remove(0);
+    flags
+        .review(SpamFlagReview {
+            id: dismissed.id,
+            status: ReviewStatus::FalsePositive,
+            reviewed_by: Some("maintainer".into()),
+        })
+        .await?;
+    Ok(())
+}
+
+fn app(database: Arc<PgDatabase>) -> anyhow::Result<Router> {
+    let config = AppConfig::load_from_path("../..")?;
+    let state = Arc::new(ApiState {
+        repositories: database.clone(),
+        metrics_path: "/metrics",
+        pool: Arc::new(database.pool().clone()),
+        config,
+        entity_counts: Default::default(),
+    });
+    Ok(build_router(state))
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+    let res = app
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn repo_summary_counts_only_that_repo() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping repo_summary_counts_only_that_repo: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("api_repo_summary").await?;
+    let database = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    seed(&database).await?;
+
+    assert_eq!(database.comments().count_by_repo("o/r").await?, 2);
+    assert_eq!(database.comments().count_by_repo("o/other").await?, 1);
+    assert_eq!(database.comments().count_by_repo("o/missing").await?, 0);
+
+    let app = app(database.clone())?;
+    let (status, body) = get(&app, "/repos/o/r/summary").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({
+            "full_name": "o/r",
+            "issue_count": 2,
+            "comment_count": 2,
+            "distinct_authors": 3,
+            "flagged_issue_count": 1,
+            "flagged_comment_count": 1
+        })
+    );
+
+    let (status, _) = get(&app, "/repos/o/missing/summary").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    handle.cleanup().await?;
+    Ok(())
+}
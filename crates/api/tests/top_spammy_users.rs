@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use common::config::AppConfig;
+use db::pg::PgDatabase;
+use sqlx::PgPool;
+use tower::util::ServiceExt;
+
+use api::{build_router, routes::ApiState};
+
+#[tokio::test]
+async fn cursor_is_rejected_with_decay() -> anyhow::Result<()> {
+    // Validation fails before any query, so the pool never connects.
+    let pool = PgPool::connect_lazy("postgres://localhost:1/unused")?;
+    let state = Arc::new(ApiState {
+        repositories: Arc::new(PgDatabase::from_pool(pool.clone())),
+        metrics_path: "/metrics",
+        pool: Arc::new(pool),
+        config: AppConfig::load_from_path("../..")?,
+        entity_counts: Default::default(),
+    });
+    let response = build_router(state)
+        .oneshot(
+            Request::get("/top/spammy-users?decay=7&cursor=4.5:2:alice")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
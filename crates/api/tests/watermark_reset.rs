@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use chrono::Utc;
+use common::config::AppConfig;
+use db::models::{ProgressUpdate, WatermarkUpdate};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use tower::util::ServiceExt;
+
+use api::{build_router, routes::ApiState};
+
+const KEY: &str = "test-key";
+
+fn app(database: Arc<PgDatabase>) -> anyhow::Result<Router> {
+    let mut config = AppConfig::load_from_path("../..")?;
+    config.api.api_key = Some(KEY.to_string());
+    let state = Arc::new(ApiState {
+        repositories: database.clone(),
+        metrics_path: "/metrics",
+        pool: Arc::new(database.pool().clone()),
+        config,
+        entity_counts: Default::default(),
+    });
+    Ok(build_router(state))
+}
+
+async fn delete(app: &Router, uri: &str, key: Option<&str>) -> StatusCode {
+    let mut request = Request::delete(uri);
+    if let Some(key) = key {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {key}"));
+    }
+    app.clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn deleting_watermark_resets_repo_progress() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping deleting_watermark_resets_repo_progress: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("api_watermark_reset").await?;
+    let database = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let watermarks = database.watermarks();
+    watermarks
+        .set(WatermarkUpdate {
+            repo_full_name: "acme/widgets".into(),
+            last_updated: Utc::now(),
+        })
+        .await?;
+    watermarks
+        .set_progress(ProgressUpdate {
+            repo_full_name: "acme/widgets".into(),
+            cursor: "page-3".into(),
+            newest_ts: None,
+        })
+        .await?;
+    watermarks
+        .set(WatermarkUpdate {
+            repo_full_name: "acme/gadgets".into(),
+            last_updated: Utc::now(),
+        })
+        .await?;
+    let app = app(database.clone())?;
+
+    let uri = "/repos/acme/widgets/watermark";
+    assert_eq!(delete(&app, uri, None).await, StatusCode::UNAUTHORIZED);
+    assert!(watermarks.get("acme/widgets").await?.is_some());
+
+    assert_eq!(delete(&app, uri, Some(KEY)).await, StatusCode::NO_CONTENT);
+    assert!(watermarks.get("acme/widgets").await?.is_none());
+    assert!(watermarks.get_progress("acme/widgets").await?.is_none());
+    assert!(watermarks.get("acme/gadgets").await?.is_some());
+
+    assert_eq!(delete(&app, uri, Some(KEY)).await, StatusCode::NOT_FOUND);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use gh_broker::{GithubBroker, HttpStatusError, Priority};
 use http::{header, Request, StatusCode};
 use serde_json::Value;
@@ -10,6 +10,8 @@ use thiserror::Error;
 use tracing::{debug, instrument};
 use url::Url;
 
+use crate::priority::job_priority_or;
+
 #[derive(Debug, Error)]
 pub enum GithubApiError {
     #[error("github api error: {status} for {endpoint}")]
@@ -17,6 +19,14 @@ pub enum GithubApiError {
         status: StatusCode,
         endpoint: String,
     },
+    /// A 422: GitHub rejected parameters we sent, which is a bug on our side.
+    #[error("github rejected {} for {endpoint}: {message}", if fields.is_empty() { "the request".to_string() } else { fields.join(", ") })]
+    Unprocessable {
+        endpoint: String,
+        /// `field` of each entry of the response's `errors`.
+        fields: Vec<String>,
+        message: String,
+    },
 }
 
 impl GithubApiError {
@@ -27,19 +37,59 @@ impl GithubApiError {
         }
     }
 
+    /// A 422 response, naming the parameters its body's `errors` point at.
+    pub fn unprocessable(endpoint: impl Into<String>, body: &[u8]) -> Self {
+        let body: Value = serde_json::from_slice(body).unwrap_or(Value::Null);
+        let fields = body
+            .get("errors")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|error| error.get("field").and_then(Value::as_str))
+            .map(str::to_string)
+            .collect();
+        let message = body
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("Validation Failed")
+            .to_string();
+        Self::Unprocessable {
+            endpoint: endpoint.into(),
+            fields,
+            message,
+        }
+    }
+
     pub fn status_code(&self) -> StatusCode {
         match *self {
             GithubApiError::Http { status, .. } => status,
+            GithubApiError::Unprocessable { .. } => StatusCode::UNPROCESSABLE_ENTITY,
         }
     }
 
     pub fn endpoint(&self) -> &str {
         match self {
-            GithubApiError::Http { endpoint, .. } => endpoint.as_str(),
+            GithubApiError::Http { endpoint, .. }
+            | GithubApiError::Unprocessable { endpoint, .. } => endpoint.as_str(),
         }
     }
 }
 
+/// Largest `per_page` GitHub's REST API accepts.
+pub const MAX_PER_PAGE: u32 = 100;
+
+/// `page` and `per_page` moved into the range GitHub accepts (`page >= 1`,
+/// `1 <= per_page <= 100`) instead of earning a 422.
+pub fn clamp_paging(page: u32, per_page: u32) -> (u32, u32) {
+    (page.max(1), per_page.clamp(1, MAX_PER_PAGE))
+}
+
+/// `since` as the `YYYY-MM-DDTHH:MM:SSZ` timestamp GitHub documents. Rounds
+/// down to whole seconds, so nothing at or after `since` is skipped.
+pub fn since_param(since: DateTime<Utc>) -> String {
+    since.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
 #[async_trait]
 pub trait GithubClient: Send + Sync {
     async fn get_repo(&self, owner: &str, repo: &str) -> Result<Value>;
@@ -51,6 +101,8 @@ pub trait GithubClient: Send + Sync {
         page: u32,
         per_page: u32,
     ) -> Result<Vec<Value>>;
+    /// A single issue or pull request by number.
+    async fn get_issue(&self, owner: &str, repo: &str, issue_number: u64) -> Result<Value>;
     async fn list_issue_comments(
         &self,
         owner: &str,
@@ -60,12 +112,18 @@ pub trait GithubClient: Send + Sync {
         per_page: u32,
     ) -> Result<Vec<Value>>;
     async fn get_user(&self, login: &str) -> Result<Value>;
+    /// Whether `login` publicly belongs to `org`. GitHub answers 404 for
+    /// non-members (and for owners that are not orgs), which is `Ok(false)`.
+    async fn get_org_membership(&self, org: &str, login: &str) -> Result<bool>;
 }
 
+pub use common::redact::{redact_url, REDACTED_QUERY_PARAMS};
+
 pub struct BrokerGithubClient {
     broker: Arc<dyn GithubBroker>,
     base: Url,
     user_agent: String,
+    redact_params: Vec<String>,
 }
 
 impl BrokerGithubClient {
@@ -74,22 +132,34 @@ impl BrokerGithubClient {
             broker,
             base: Url::parse("https://api.github.com/").expect("valid base url"),
             user_agent,
+            redact_params: Vec::new(),
         }
     }
 
+    /// Masks these query parameters in logged URLs, besides
+    /// [`REDACTED_QUERY_PARAMS`].
+    pub fn with_redacted_params(mut self, params: Vec<String>) -> Self {
+        self.redact_params = params;
+        self
+    }
+
     async fn get_json(&self, url: Url, priority: Priority) -> Result<Value> {
         let endpoint = url.path().trim_start_matches('/').to_string();
-        let full_url = url.as_str().to_string();
         debug!(
             endpoint = %endpoint,
-            url = %full_url,
-            priority = %priority.as_str(),
+            url = %redact_url(&url, &self.redact_params),
+            priority = %job_priority_or(priority).as_str(),
             "Dispatching GitHub request"
         );
         let response = match self.execute(url, priority).await {
             Ok(resp) => resp,
             Err(err) => {
                 if let Some(status_err) = err.downcast_ref::<HttpStatusError>() {
+                    if status_err.status == StatusCode::UNPROCESSABLE_ENTITY {
+                        return Err(
+                            GithubApiError::unprocessable(endpoint, &status_err.body).into()
+                        );
+                    }
                     return Err(GithubApiError::status(status_err.status, endpoint).into());
                 }
                 return Err(err);
@@ -116,7 +186,7 @@ impl BrokerGithubClient {
         }
     }
 
-    #[instrument(skip(self), fields(url = %url))]
+    #[instrument(skip(self, url), fields(url = %redact_url(&url, &self.redact_params)))]
     async fn execute(&self, url: Url, priority: Priority) -> Result<http::Response<Vec<u8>>> {
         let uri: http::Uri = url.as_str().parse()?;
         let request = Request::builder()
@@ -127,6 +197,8 @@ impl BrokerGithubClient {
             .header("X-GitHub-Api-Version", "2022-11-28")
             .body(Vec::new())?;
 
+        // A job's own priority wins over the per-endpoint default.
+        let priority = job_priority_or(priority);
         let response = self.broker.enqueue(request, priority).await?;
         Ok(response)
     }
@@ -168,6 +240,7 @@ impl GithubClient for BrokerGithubClient {
         page: u32,
         per_page: u32,
     ) -> Result<Vec<Value>> {
+        let (page, per_page) = clamp_paging(page, per_page);
         let mut url = self
             .join_segments(&["repos", owner, repo, "issues"])?
             .clone();
@@ -179,12 +252,18 @@ impl GithubClient for BrokerGithubClient {
             ("per_page", per_page.to_string()),
         ];
         if let Some(since) = since {
-            params.push(("since", since.to_rfc3339()));
+            params.push(("since", since_param(since)));
         }
         Self::with_query(&mut url, &params);
         self.get_json_array(url, Priority::Normal).await
     }
 
+    async fn get_issue(&self, owner: &str, repo: &str, issue_number: u64) -> Result<Value> {
+        let issue_num = issue_number.to_string();
+        let url = self.join_segments(&["repos", owner, repo, "issues", &issue_num])?;
+        self.get_json(url, Priority::Normal).await
+    }
+
     async fn list_issue_comments(
         &self,
         owner: &str,
@@ -193,6 +272,7 @@ impl GithubClient for BrokerGithubClient {
         page: u32,
         per_page: u32,
     ) -> Result<Vec<Value>> {
+        let (page, per_page) = clamp_paging(page, per_page);
         let issue_num = issue_number.to_string();
         let mut url = self
             .join_segments(&["repos", owner, repo, "issues", &issue_num, "comments"])?
@@ -209,4 +289,20 @@ impl GithubClient for BrokerGithubClient {
         let url = self.join_segments(&["users", login])?;
         self.get_json(url, Priority::Normal).await
     }
+
+    async fn get_org_membership(&self, org: &str, login: &str) -> Result<bool> {
+        // The public endpoint answers the same for every token; `/members`
+        // redirects when the token itself is not in the org.
+        let url = self.join_segments(&["orgs", org, "public_members", login])?;
+        let endpoint = url.path().trim_start_matches('/').to_string();
+        match self.execute(url, Priority::Normal).await {
+            Ok(response) if response.status() == StatusCode::NO_CONTENT => Ok(true),
+            Ok(response) => Err(GithubApiError::status(response.status(), endpoint).into()),
+            Err(err) => match err.downcast_ref::<HttpStatusError>() {
+                Some(status_err) if status_err.status == StatusCode::NOT_FOUND => Ok(false),
+                Some(status_err) => Err(GithubApiError::status(status_err.status, endpoint).into()),
+                None => Err(err),
+            },
+        }
+    }
 }
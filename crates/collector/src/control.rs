@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::service::Collector;
+
+/// Operational endpoints served next to the collector's metrics.
+pub fn router(collector: Arc<Collector>) -> Router {
+    Router::new()
+        .route("/run-now", post(run_now))
+        .route("/maintenance/repair-users", post(repair_users))
+        .with_state(collector)
+}
+
+// Starts a collection cycle without waiting for `interval_secs`; 409 while one is running.
+async fn run_now(State(collector): State<Arc<Collector>>) -> (StatusCode, Json<Value>) {
+    let Some(run) = collector.trigger_run() else {
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({"error": "a collector run is already in progress"})),
+        );
+    };
+    info!("collector run triggered via /run-now");
+    tokio::spawn(async move {
+        match run.await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => warn!(error = ?err, "triggered collector run failed"),
+            Err(err) => warn!(error = ?err, "triggered collector run panicked"),
+        }
+    });
+    (StatusCode::ACCEPTED, Json(json!({"status": "started"})))
+}
+
+// Re-fetches authors of issues/comments that point at a missing `users` row.
+async fn repair_users(State(collector): State<Arc<Collector>>) -> (StatusCode, Json<Value>) {
+    match collector.repair_users().await {
+        Ok(repair) => (
+            StatusCode::OK,
+            Json(json!({
+                "orphaned": repair.orphaned,
+                "repaired": repair.repaired,
+                "failed": repair.failed,
+            })),
+        ),
+        Err(err) => {
+            warn!(error = ?err, "user repair pass failed");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": err.to_string()})),
+            )
+        }
+    }
+}
@@ -5,20 +5,23 @@ use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64_ENGINE, Engine as _};
 use chrono::{DateTime, Utc};
-use gh_broker::{GithubBroker, Priority};
+use common::config::{DedupeMode, Leniency};
+use gh_broker::{GithubBroker, GraphqlRateLimitedError, Priority};
 use http::{header, Request, StatusCode};
 use normalizer::models::NormalizedUser;
 use normalizer::payloads::{CommentPayload, IssuePayload, RepoPayload, UserPayload, UserRef};
+use normalizer::PayloadStrategy;
 use serde_json::{json, Value};
 use std::time::Instant;
 use tokio::sync::Mutex;
 
 use crate::client::{GithubApiError, GithubClient};
 use crate::fetcher::{
-    CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, MissingUser, RepoSnapshot,
-    UserFetch,
+    updated_since, CommentPage, CommentRecord, ContentEdits, DataFetcher, IssuePage, IssueRecord,
+    MissingUser, Reaction, Reactions, RepoSnapshot, UserFetch,
 };
 use crate::metrics;
+use crate::priority::job_priority_or;
 
 const GRAPHQL_ENDPOINT: &str = "https://api.github.com/graphql";
 
@@ -59,6 +62,7 @@ query RepoIssues(
         endCursor
       }
       nodes {
+        id
         databaseId
         number
         title
@@ -67,12 +71,17 @@ query RepoIssues(
         createdAt
         updatedAt
         closedAt
+        locked
+        authorAssociation
+        stateReason
         author {
           __typename
           login
           ... on User {
             databaseId
             isSiteAdmin
+            bio
+            websiteUrl
             createdAt
             followers { totalCount }
             following { totalCount }
@@ -97,16 +106,20 @@ query RepoIssues(
             endCursor
           }
           nodes {
+            id
             databaseId
             body
             createdAt
             updatedAt
+            authorAssociation
             author {
               __typename
               login
               ... on User {
                 databaseId
                 isSiteAdmin
+                bio
+                websiteUrl
                 createdAt
                 followers { totalCount }
                 following { totalCount }
@@ -148,6 +161,7 @@ query RepoPulls(
     ) {
       pageInfo { hasNextPage endCursor }
       nodes {
+        id
         databaseId
         number
         title
@@ -156,12 +170,16 @@ query RepoPulls(
         createdAt
         updatedAt
         closedAt
+        locked
+        authorAssociation
         author {
           __typename
           login
           ... on User {
             databaseId
             isSiteAdmin
+            bio
+            websiteUrl
             createdAt
             followers { totalCount }
             following { totalCount }
@@ -174,16 +192,20 @@ query RepoPulls(
           totalCount
           pageInfo { hasNextPage endCursor }
           nodes {
+            id
             databaseId
             body
             createdAt
             updatedAt
+            authorAssociation
             author {
               __typename
               login
               ... on User {
                 databaseId
                 isSiteAdmin
+                bio
+                websiteUrl
                 createdAt
                 followers { totalCount }
                 following { totalCount }
@@ -200,6 +222,105 @@ query RepoPulls(
 }
 "#;
 
+const ISSUE_QUERY: &str = r#"
+query RepoIssue($owner: String!, $name: String!, $number: Int!) {
+  rateLimit { limit remaining resetAt used cost }
+  repository(owner: $owner, name: $name) {
+    issueOrPullRequest(number: $number) {
+      __typename
+      ... on Issue {
+        id
+        databaseId
+        number
+        title
+        body
+        state
+        createdAt
+        updatedAt
+        closedAt
+        locked
+        authorAssociation
+        stateReason
+        comments { totalCount }
+        author { ...IssueAuthor }
+      }
+      ... on PullRequest {
+        id
+        databaseId
+        number
+        title
+        body
+        state
+        createdAt
+        updatedAt
+        closedAt
+        locked
+        authorAssociation
+        comments { totalCount }
+        author { ...IssueAuthor }
+      }
+    }
+  }
+}
+
+fragment IssueAuthor on Actor {
+  __typename
+  login
+  ... on User {
+    databaseId
+    isSiteAdmin
+    bio
+    websiteUrl
+    createdAt
+    followers { totalCount }
+    following { totalCount }
+    repositories(privacy: PUBLIC) { totalCount }
+  }
+  ... on Bot { databaseId createdAt }
+  ... on Organization { databaseId createdAt }
+}
+"#;
+
+const CONTENT_EDITS_QUERY: &str = r#"
+query ContentEdits($id: ID!, $first: Int!) {
+  rateLimit { limit remaining resetAt used cost }
+  node(id: $id) {
+    ... on UserContentEditable {
+      userContentEdits(first: $first) {
+        totalCount
+        nodes {
+          editedAt
+          editor { login }
+        }
+      }
+    }
+  }
+}
+"#;
+
+// Edits inspected per subject; `totalCount` still reports the full history.
+const CONTENT_EDITS_PER_FETCH: u32 = 20;
+
+const REACTIONS_QUERY: &str = r#"
+query Reactions($id: ID!, $first: Int!) {
+  rateLimit { limit remaining resetAt used cost }
+  node(id: $id) {
+    ... on Reactable {
+      reactions(first: $first) {
+        totalCount
+        nodes {
+          content
+          user { login createdAt }
+        }
+      }
+    }
+  }
+}
+"#;
+
+// Reactions inspected per subject; enough to see who dominates them.
+const REACTIONS_PER_FETCH: u32 = 100;
+
 const ISSUE_COMMENTS_QUERY: &str = r#"
 query IssueComments(
   $owner: String!,
@@ -221,16 +342,20 @@ query IssueComments(
           endCursor
         }
         nodes {
+          id
           databaseId
           body
           createdAt
           updatedAt
+          authorAssociation
           author {
             __typename
             login
             ... on User {
               databaseId
               isSiteAdmin
+              bio
+              websiteUrl
               createdAt
               followers { totalCount }
               following { totalCount }
@@ -270,16 +395,20 @@ query PRComments(
       ) {
         pageInfo { hasNextPage endCursor }
         nodes {
+          id
           databaseId
           body
           createdAt
           updatedAt
+          authorAssociation
           author {
             __typename
             login
             ... on User {
               databaseId
               isSiteAdmin
+              bio
+              websiteUrl
               createdAt
               followers { totalCount }
               following { totalCount }
@@ -326,6 +455,10 @@ pub struct GraphqlDataFetcher {
     broker: Arc<dyn GithubBroker>,
     rest_client: Arc<dyn GithubClient>,
     user_agent: String,
+    // Comments embedded per issue in listing queries; defaults to the issue page size.
+    comment_page_size: Option<u32>,
+    dedupe_mode: DedupeMode,
+    payloads: PayloadStrategy,
     initial_comments: Mutex<HashMap<IssueKey, CommentCacheEntry>>,
     user_cache: Mutex<HashMap<String, NormalizedUser>>,
 }
@@ -340,11 +473,29 @@ impl GraphqlDataFetcher {
             broker,
             rest_client,
             user_agent,
+            comment_page_size: None,
+            dedupe_mode: DedupeMode::default(),
+            payloads: PayloadStrategy::default(),
             initial_comments: Mutex::new(HashMap::new()),
             user_cache: Mutex::new(HashMap::new()),
         }
     }
 
+    pub fn with_comment_page_size(mut self, size: u32) -> Self {
+        self.comment_page_size = Some(size);
+        self
+    }
+
+    pub fn with_dedupe_mode(mut self, mode: DedupeMode) -> Self {
+        self.dedupe_mode = mode;
+        self
+    }
+
+    pub fn with_leniency(mut self, leniency: Leniency) -> Self {
+        self.payloads = PayloadStrategy::new(leniency);
+        self
+    }
+
     async fn execute_graphql(&self, op: &str, query: &str, variables: Value) -> Result<Value> {
         let payload = json!({
             "query": query,
@@ -361,7 +512,11 @@ impl GraphqlDataFetcher {
             .body(serde_json::to_vec(&payload)?)?;
 
         // Capture broker errors and annotate with endpoint for better context
-        let response = match self.broker.enqueue(request, Priority::Normal).await {
+        let response = match self
+            .broker
+            .enqueue(request, job_priority_or(Priority::Normal))
+            .await
+        {
             Ok(resp) => resp,
             Err(err) => {
                 if let Some(http) = err.downcast_ref::<gh_broker::HttpStatusError>() {
@@ -401,12 +556,26 @@ impl GraphqlDataFetcher {
         }
 
         if let Some(errors) = value.get("errors").and_then(Value::as_array) {
-            return Err(map_graphql_errors(errors));
+            return Err(map_graphql_errors(errors, &value));
         }
 
         Ok(value)
     }
 
+    // Runs one listing query, recording request count and latency under `op`.
+    async fn fetch_listing(&self, op: &str, query: &str, variables: Value) -> Result<Value> {
+        let start = Instant::now();
+        let result = self.execute_graphql(op, query, variables).await;
+        let status = if result.is_ok() { "success" } else { "error" };
+        metrics::FETCH_REQUESTS_TOTAL
+            .with_label_values(&["graphql", op, status])
+            .inc();
+        metrics::FETCH_LATENCY_SECONDS
+            .with_label_values(&["graphql", op])
+            .observe(start.elapsed().as_secs_f64());
+        result
+    }
+
     fn extract_repository<'a>(
         &self,
         root: &'a Value,
@@ -482,6 +651,8 @@ impl GraphqlDataFetcher {
             .get("repositories")
             .and_then(|r| r.get("totalCount"))
             .and_then(Value::as_i64);
+        let bio = actor.get("bio").and_then(Value::as_str);
+        let blog = actor.get("websiteUrl").and_then(Value::as_str);
 
         let user_json = json!({
             "id": id,
@@ -492,8 +663,10 @@ impl GraphqlDataFetcher {
             "followers": followers,
             "following": following,
             "public_repos": public_repos,
+            "bio": bio,
+            "blog": blog,
         });
-        let payload: UserPayload = serde_json::from_value(user_json.clone())?;
+        let payload: UserPayload = self.payloads.parse("user", &user_json)?;
         let normalized = normalizer::normalize_user(&payload, user_json);
 
         Ok(ActorInfo {
@@ -535,14 +708,20 @@ impl GraphqlDataFetcher {
                 let user_value = actor_info.user_ref.as_ref().map(user_ref_to_value);
                 let comment_value = json!({
                     "id": comment_id,
+                    "node_id": node.get("id").and_then(Value::as_str),
                     "user": user_value,
                     "body": body,
                     "created_at": created_at,
                     "updated_at": updated_at,
+                    "author_association": node.get("authorAssociation").and_then(Value::as_str),
                 });
-                let payload: CommentPayload = serde_json::from_value(comment_value.clone())?;
-                let normalized =
-                    normalizer::normalize_comment(&payload, issue_id, comment_value.clone());
+                let payload: CommentPayload = self.payloads.parse("comment", &comment_value)?;
+                let normalized = normalizer::normalize_comment_with(
+                    &payload,
+                    issue_id,
+                    comment_value.clone(),
+                    self.dedupe_mode,
+                );
                 records.push(CommentRecord {
                     comment: normalized,
                     author: actor_info.user_ref.clone(),
@@ -636,7 +815,7 @@ impl DataFetcher for GraphqlDataFetcher {
             "created_at": created_at,
             "pushed_at": pushed_at,
         });
-        let payload: RepoPayload = serde_json::from_value(repo_value.clone())?;
+        let payload: RepoPayload = self.payloads.parse("repo", &repo_value)?;
         let repository = normalizer::normalize_repo(&payload, repo_value);
         Ok(RepoSnapshot { repository })
     }
@@ -652,153 +831,74 @@ impl DataFetcher for GraphqlDataFetcher {
     ) -> Result<IssuePage> {
         let op = "issues";
         let per_page = per_page.min(100);
-        let comments_per_page = per_page;
-        // Combined cursor format: "i:<issueCursor>|p:<prCursor>" (either may be empty)
-        let (issue_cur, pr_cur) = match cursor.as_deref() {
-            Some(cur) if cur.starts_with("i:") || cur.contains("|p:") => {
-                let mut ic: Option<String> = None;
-                let mut pc: Option<String> = None;
-                for part in cur.split('|') {
-                    if let Some(rest) = part.strip_prefix("i:") {
-                        ic = if rest.is_empty() {
-                            None
-                        } else {
-                            Some(rest.to_string())
-                        };
-                    }
-                    if let Some(rest) = part.strip_prefix("p:") {
-                        pc = if rest.is_empty() {
-                            None
-                        } else {
-                            Some(rest.to_string())
-                        };
-                    }
-                }
-                (ic, pc)
-            }
-            other => (other.map(|s| s.to_string()), None),
-        };
-
-        // Fetch issues page
-        let start = Instant::now();
-        let issues_resp = self
-            .execute_graphql(
-                "issues",
-                ISSUES_QUERY,
-                json!({
-                    "owner": owner,
-                    "name": name,
-                    "perPage": per_page as i64,
-                    "commentsPerPage": comments_per_page as i64,
-                    "cursor": issue_cur,
-                    "since": since.map(|dt| dt.to_rfc3339()),
-                }),
-            )
-            .await;
-        let issues_elapsed = start.elapsed().as_secs_f64();
-        let issues_resp = match issues_resp {
-            Ok(v) => {
-                metrics::FETCH_REQUESTS_TOTAL
-                    .with_label_values(&["graphql", "issues", "success"])
-                    .inc();
-                metrics::FETCH_LATENCY_SECONDS
-                    .with_label_values(&["graphql", "issues"])
-                    .observe(issues_elapsed);
-                v
-            }
-            Err(e) => {
-                metrics::FETCH_REQUESTS_TOTAL
-                    .with_label_values(&["graphql", "issues", "error"])
-                    .inc();
-                metrics::FETCH_LATENCY_SECONDS
-                    .with_label_values(&["graphql", "issues"])
-                    .observe(issues_elapsed);
-                return Err(e);
-            }
+        let comments_per_page = self.comment_page_size.unwrap_or(per_page).min(100);
+        // Combined cursor format: "i:<issueCursor>|p:<prCursor>". An empty side starts
+        // from the first page; "-" marks a side with nothing left to fetch.
+        let (issue_cur, pr_cur) = parse_combined_cursor(cursor.as_deref());
+
+        let issues_resp = match &issue_cur {
+            SideCursor::Done => None,
+            side => Some(
+                self.fetch_listing(
+                    "issues",
+                    ISSUES_QUERY,
+                    json!({
+                        "owner": owner,
+                        "name": name,
+                        "perPage": per_page as i64,
+                        "commentsPerPage": comments_per_page as i64,
+                        "cursor": side.after(),
+                        "since": since.map(|dt| dt.to_rfc3339()),
+                    }),
+                )
+                .await?,
+            ),
         };
-
-        // Fetch PRs page
-        let start2 = Instant::now();
-        let pulls_resp = self
-            .execute_graphql(
-                "pulls",
-                PULLS_QUERY,
-                json!({
-                    "owner": owner,
-                    "name": name,
-                    "perPage": per_page as i64,
-                    "commentsPerPage": comments_per_page as i64,
-                    "cursor": pr_cur,
-                }),
-            )
-            .await;
-        let pulls_elapsed = start2.elapsed().as_secs_f64();
-        let pulls_resp = match pulls_resp {
-            Ok(v) => {
-                metrics::FETCH_REQUESTS_TOTAL
-                    .with_label_values(&["graphql", "pulls", "success"])
-                    .inc();
-                metrics::FETCH_LATENCY_SECONDS
-                    .with_label_values(&["graphql", "pulls"])
-                    .observe(pulls_elapsed);
-                v
-            }
-            Err(e) => {
-                metrics::FETCH_REQUESTS_TOTAL
-                    .with_label_values(&["graphql", "pulls", "error"])
-                    .inc();
-                metrics::FETCH_LATENCY_SECONDS
-                    .with_label_values(&["graphql", "pulls"])
-                    .observe(pulls_elapsed);
-                return Err(e);
-            }
+        // pullRequests has no `since` filter; older PRs are dropped below instead.
+        let pulls_resp = match &pr_cur {
+            SideCursor::Done => None,
+            side => Some(
+                self.fetch_listing(
+                    "pulls",
+                    PULLS_QUERY,
+                    json!({
+                        "owner": owner,
+                        "name": name,
+                        "perPage": per_page as i64,
+                        "commentsPerPage": comments_per_page as i64,
+                        "cursor": side.after(),
+                    }),
+                )
+                .await?,
+            ),
         };
 
-        let repo_issues = self.extract_repository(&issues_resp, owner, name)?;
-        let repo_pulls = self.extract_repository(&pulls_resp, owner, name)?;
-
-        let issues_conn = repo_issues
-            .get("issues")
-            .and_then(Value::as_object)
-            .ok_or_else(|| anyhow!("missing issues connection"))?;
-
-        let has_next = issues_conn
-            .get("pageInfo")
-            .and_then(|p| p.get("hasNextPage"))
-            .and_then(Value::as_bool)
-            .unwrap_or(false);
-        let next_cursor = if has_next {
-            issues_conn
-                .get("pageInfo")
-                .and_then(|p| p.get("endCursor"))
-                .and_then(Value::as_str)
-                .map(|s| s.to_string())
-        } else {
-            None
+        let issues_conn = match &issues_resp {
+            Some(resp) => Some(
+                self.extract_repository(resp, owner, name)?
+                    .get("issues")
+                    .and_then(Value::as_object)
+                    .ok_or_else(|| anyhow!("missing issues connection"))?,
+            ),
+            None => None,
         };
-
-        let pulls_conn = repo_pulls
-            .get("pullRequests")
-            .and_then(Value::as_object)
-            .ok_or_else(|| anyhow!("missing pullRequests connection"))?;
-
-        let pulls_has_next = pulls_conn
-            .get("pageInfo")
-            .and_then(|p| p.get("hasNextPage"))
-            .and_then(Value::as_bool)
-            .unwrap_or(false);
-        let pulls_cursor = if pulls_has_next {
-            pulls_conn
-                .get("pageInfo")
-                .and_then(|p| p.get("endCursor"))
-                .and_then(Value::as_str)
-                .map(|s| s.to_string())
-        } else {
-            None
+        let pulls_conn = match &pulls_resp {
+            Some(resp) => Some(
+                self.extract_repository(resp, owner, name)?
+                    .get("pullRequests")
+                    .and_then(Value::as_object)
+                    .ok_or_else(|| anyhow!("missing pullRequests connection"))?,
+            ),
+            None => None,
         };
+        let next_cursor = issues_conn.and_then(end_cursor);
+        let mut pulls_cursor = pulls_conn.and_then(end_cursor);
 
         let mut items = Vec::new();
-        if let Some(nodes) = issues_conn.get("nodes").and_then(Value::as_array) {
+        if let Some(nodes) = issues_conn
+            .and_then(|conn| conn.get("nodes"))
+            .and_then(Value::as_array)
+        {
             for node in nodes.iter().filter(|node| !node.is_null()) {
                 let actor_info = self.parse_actor(node.get("author").unwrap_or(&Value::Null))?;
                 if let Some(user) = actor_info.normalized_user.clone() {
@@ -835,6 +935,10 @@ impl DataFetcher for GraphqlDataFetcher {
                 let closed_at =
                     node.get("closedAt")
                         .and_then(|v| if v.is_null() { None } else { v.as_str() });
+                let state_reason = node
+                    .get("stateReason")
+                    .and_then(Value::as_str)
+                    .map(str::to_lowercase);
 
                 let comments_conn = node
                     .get("comments")
@@ -850,6 +954,7 @@ impl DataFetcher for GraphqlDataFetcher {
                 let user_value = actor_info.user_ref.as_ref().map(user_ref_to_value);
                 let issue_value = json!({
                     "id": issue_id,
+                    "node_id": node.get("id").and_then(Value::as_str),
                     "number": issue_number,
                     "pull_request": pull_request_value,
                     "state": state,
@@ -860,10 +965,21 @@ impl DataFetcher for GraphqlDataFetcher {
                     "created_at": created_at,
                     "updated_at": updated_at,
                     "closed_at": closed_at,
+                    "locked": node.get("locked").and_then(Value::as_bool).unwrap_or(false),
+                    "state_reason": state_reason,
+                    "author_association": node.get("authorAssociation").and_then(Value::as_str),
                 });
-                let payload: IssuePayload = serde_json::from_value(issue_value.clone())?;
-                let normalized =
-                    normalizer::normalize_issue(&payload, repo_id, issue_value.clone());
+                let payload: IssuePayload = self.payloads.parse("issue", &issue_value)?;
+                let normalized = normalizer::normalize_issue_with(
+                    &payload,
+                    repo_id,
+                    issue_value.clone(),
+                    self.dedupe_mode,
+                );
+                // filterBy.since is applied server-side; re-check for sub-second boundaries
+                if !updated_since(&normalized, since) {
+                    continue;
+                }
 
                 let (comment_records, comment_cursor) = self
                     .collect_comment_records(comments_conn, normalized.id)
@@ -887,7 +1003,10 @@ impl DataFetcher for GraphqlDataFetcher {
             }
         }
 
-        if let Some(nodes) = pulls_conn.get("nodes").and_then(Value::as_array) {
+        if let Some(nodes) = pulls_conn
+            .and_then(|conn| conn.get("nodes"))
+            .and_then(Value::as_array)
+        {
             for node in nodes.iter().filter(|node| !node.is_null()) {
                 let actor_info = self.parse_actor(node.get("author").unwrap_or(&Value::Null))?;
                 if let Some(user) = actor_info.normalized_user.clone() {
@@ -935,6 +1054,7 @@ impl DataFetcher for GraphqlDataFetcher {
                 let user_value = actor_info.user_ref.as_ref().map(user_ref_to_value);
                 let issue_value = json!({
                     "id": pr_id,
+                    "node_id": node.get("id").and_then(Value::as_str),
                     "number": pr_number,
                     "pull_request": pull_request_value,
                     "state": state,
@@ -945,10 +1065,22 @@ impl DataFetcher for GraphqlDataFetcher {
                     "created_at": created_at,
                     "updated_at": updated_at,
                     "closed_at": closed_at,
+                    "locked": node.get("locked").and_then(Value::as_bool).unwrap_or(false),
+                    "author_association": node.get("authorAssociation").and_then(Value::as_str),
                 });
-                let payload: IssuePayload = serde_json::from_value(issue_value.clone())?;
-                let normalized =
-                    normalizer::normalize_issue(&payload, repo_id, issue_value.clone());
+                let payload: IssuePayload = self.payloads.parse("issue", &issue_value)?;
+                let normalized = normalizer::normalize_issue_with(
+                    &payload,
+                    repo_id,
+                    issue_value.clone(),
+                    self.dedupe_mode,
+                );
+                // PRs arrive newest-updated first, so once one predates `since`
+                // every later page does too.
+                if !updated_since(&normalized, since) {
+                    pulls_cursor = None;
+                    continue;
+                }
 
                 let (comment_records, comment_cursor) = self
                     .collect_comment_records(comments_conn, normalized.id)
@@ -978,8 +1110,8 @@ impl DataFetcher for GraphqlDataFetcher {
         let combined_cursor = if next_cursor.is_some() || pulls_cursor.is_some() {
             Some(format!(
                 "i:{}|p:{}",
-                next_cursor.clone().unwrap_or_default(),
-                pulls_cursor.clone().unwrap_or_default()
+                next_cursor.as_deref().unwrap_or("-"),
+                pulls_cursor.as_deref().unwrap_or("-")
             ))
         } else {
             None
@@ -994,6 +1126,101 @@ impl DataFetcher for GraphqlDataFetcher {
         })
     }
 
+    async fn fetch_issue(
+        &self,
+        owner: &str,
+        name: &str,
+        repo_id: i64,
+        number: i64,
+    ) -> Result<Option<IssueRecord>> {
+        let op = "issue";
+        let start = Instant::now();
+        let result = self
+            .execute_graphql(
+                op,
+                ISSUE_QUERY,
+                json!({ "owner": owner, "name": name, "number": number }),
+            )
+            .await;
+        let not_found = |err: &anyhow::Error| {
+            err.downcast_ref::<GithubApiError>()
+                .is_some_and(|api_err| api_err.status_code() == StatusCode::NOT_FOUND)
+        };
+        let outcome = match &result {
+            Err(err) if !not_found(err) => "error",
+            _ => "success",
+        };
+        metrics::FETCH_REQUESTS_TOTAL
+            .with_label_values(&["graphql", op, outcome])
+            .inc();
+        metrics::FETCH_LATENCY_SECONDS
+            .with_label_values(&["graphql", op])
+            .observe(start.elapsed().as_secs_f64());
+        let response = match result {
+            Ok(response) => response,
+            Err(err) if not_found(&err) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let node = match self
+            .extract_repository(&response, owner, name)?
+            .get("issueOrPullRequest")
+        {
+            Some(node) if !node.is_null() => node,
+            _ => return Ok(None),
+        };
+        let actor_info = self.parse_actor(node.get("author").unwrap_or(&Value::Null))?;
+        if let Some(user) = actor_info.normalized_user.clone() {
+            self.cache_user(user).await;
+        }
+        let is_pull_request = node.get("__typename").and_then(Value::as_str) == Some("PullRequest");
+        // REST spelling: lowercase, merged pull requests are closed.
+        let state = match node.get("state").and_then(Value::as_str).unwrap_or("OPEN") {
+            "MERGED" => "closed".to_string(),
+            other => other.to_lowercase(),
+        };
+        let nullable_str = |field: &str| node.get(field).and_then(Value::as_str);
+        let issue_value = json!({
+            "id": node
+                .get("databaseId")
+                .and_then(Value::as_i64)
+                .ok_or_else(|| anyhow!("missing issue databaseId"))?,
+            "node_id": nullable_str("id"),
+            "number": node
+                .get("number")
+                .and_then(Value::as_i64)
+                .ok_or_else(|| anyhow!("missing issue number"))?,
+            "pull_request": is_pull_request.then(|| json!({"present": true})),
+            "state": state,
+            "title": nullable_str("title").unwrap_or(""),
+            "body": nullable_str("body"),
+            "user": actor_info.user_ref.as_ref().map(user_ref_to_value),
+            "comments": node
+                .get("comments")
+                .and_then(|c| c.get("totalCount"))
+                .and_then(Value::as_i64)
+                .unwrap_or(0),
+            "created_at": nullable_str("createdAt").ok_or_else(|| anyhow!("missing issue createdAt"))?,
+            "updated_at": nullable_str("updatedAt").ok_or_else(|| anyhow!("missing issue updatedAt"))?,
+            "closed_at": nullable_str("closedAt"),
+            "locked": node.get("locked").and_then(Value::as_bool).unwrap_or(false),
+            // Issues only; GitHub's enum is upper-case, REST's lower-case.
+            "state_reason": nullable_str("stateReason").map(str::to_lowercase),
+            // Same upper-case values as REST (`OWNER`, `FIRST_TIME_CONTRIBUTOR`, ...).
+            "author_association": nullable_str("authorAssociation"),
+        });
+        let payload: IssuePayload = self.payloads.parse("issue", &issue_value)?;
+        let issue =
+            normalizer::normalize_issue_with(&payload, repo_id, issue_value, self.dedupe_mode);
+        metrics::FETCH_ITEMS_TOTAL
+            .with_label_values(&["graphql", op])
+            .inc();
+        Ok(Some(IssueRecord {
+            issue,
+            author: payload.user,
+        }))
+    }
+
     async fn fetch_issue_comments(
         &self,
         owner: &str,
@@ -1127,7 +1354,7 @@ impl DataFetcher for GraphqlDataFetcher {
         let elapsed = start.elapsed().as_secs_f64();
         match result {
             Ok(value) => {
-                let payload: UserPayload = serde_json::from_value(value.clone())?;
+                let payload: UserPayload = self.payloads.parse("user", &value)?;
                 let normalized = normalizer::normalize_user(&payload, value);
                 self.cache_user(normalized.clone()).await;
                 metrics::FETCH_REQUESTS_TOTAL
@@ -1180,6 +1407,190 @@ impl DataFetcher for GraphqlDataFetcher {
             }
         }
     }
+
+    async fn inlined_user(&self, user: &UserRef) -> Option<NormalizedUser> {
+        self.user_cache.lock().await.get(&user.login).cloned()
+    }
+
+    async fn fetch_org_membership(&self, org: &str, login: &str) -> Result<Option<bool>> {
+        let op = "org_membership";
+        let start = Instant::now();
+        let result = self.rest_client.get_org_membership(org, login).await;
+        let outcome = if result.is_ok() { "success" } else { "error" };
+        metrics::FETCH_REQUESTS_TOTAL
+            .with_label_values(&["graphql", op, outcome])
+            .inc();
+        metrics::FETCH_LATENCY_SECONDS
+            .with_label_values(&["graphql", op])
+            .observe(start.elapsed().as_secs_f64());
+        result.map(Some)
+    }
+
+    async fn fetch_content_edits(&self, node_id: &str) -> Result<Option<ContentEdits>> {
+        let op = "content_edits";
+        let start = Instant::now();
+        let result = self
+            .execute_graphql(
+                op,
+                CONTENT_EDITS_QUERY,
+                json!({ "id": node_id, "first": CONTENT_EDITS_PER_FETCH }),
+            )
+            .await;
+        let outcome = if result.is_ok() { "success" } else { "error" };
+        metrics::FETCH_REQUESTS_TOTAL
+            .with_label_values(&["graphql", op, outcome])
+            .inc();
+        metrics::FETCH_LATENCY_SECONDS
+            .with_label_values(&["graphql", op])
+            .observe(start.elapsed().as_secs_f64());
+        let response = result?;
+        Ok(response
+            .get("data")
+            .and_then(|data| data.get("node"))
+            .and_then(|node| node.get("userContentEdits"))
+            .filter(|conn| !conn.is_null())
+            .map(parse_content_edits))
+    }
+
+    async fn fetch_reactions(&self, node_id: &str) -> Result<Option<Reactions>> {
+        let op = "reactions";
+        let start = Instant::now();
+        let result = self
+            .execute_graphql(
+                op,
+                REACTIONS_QUERY,
+                json!({ "id": node_id, "first": REACTIONS_PER_FETCH }),
+            )
+            .await;
+        let outcome = if result.is_ok() { "success" } else { "error" };
+        metrics::FETCH_REQUESTS_TOTAL
+            .with_label_values(&["graphql", op, outcome])
+            .inc();
+        metrics::FETCH_LATENCY_SECONDS
+            .with_label_values(&["graphql", op])
+            .observe(start.elapsed().as_secs_f64());
+        let response = result?;
+        Ok(response
+            .get("data")
+            .and_then(|data| data.get("node"))
+            .and_then(|node| node.get("reactions"))
+            .filter(|conn| !conn.is_null())
+            .map(parse_reactions))
+    }
+}
+
+/// Reads a `reactions` connection, skipping reactions of deleted accounts.
+fn parse_reactions(conn: &Value) -> Reactions {
+    let total_count = conn.get("totalCount").and_then(Value::as_u64).unwrap_or(0) as u32;
+    let reactions = conn
+        .get("nodes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|node| {
+            let user = node.get("user")?;
+            Some(Reaction {
+                login: user.get("login").and_then(Value::as_str)?.to_string(),
+                content: node.get("content").and_then(Value::as_str)?.to_string(),
+                account_created_at: user
+                    .get("createdAt")
+                    .and_then(Value::as_str)
+                    .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                    .map(|at| at.with_timezone(&Utc)),
+            })
+        })
+        .collect();
+    Reactions {
+        total_count,
+        reactions,
+    }
+}
+
+/// Summarises a `userContentEdits` connection: the total, plus when and by
+/// whom the most recent of the returned edits was made.
+fn parse_content_edits(conn: &Value) -> ContentEdits {
+    let total_count = conn.get("totalCount").and_then(Value::as_u64).unwrap_or(0) as u32;
+    let latest = conn
+        .get("nodes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|node| {
+            let edited_at = node.get("editedAt").and_then(Value::as_str)?;
+            let edited_at = DateTime::parse_from_rfc3339(edited_at).ok()?;
+            Some((edited_at.with_timezone(&Utc), node))
+        })
+        .max_by_key(|(edited_at, _)| *edited_at);
+    ContentEdits {
+        total_count,
+        last_edited_at: latest.as_ref().map(|(edited_at, _)| *edited_at),
+        last_editor: latest.and_then(|(_, node)| {
+            node.get("editor")
+                .and_then(|editor| editor.get("login"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        }),
+    }
+}
+
+// One half of the combined issues/pulls cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SideCursor {
+    Start,
+    After(String),
+    Done,
+}
+
+impl SideCursor {
+    fn parse(raw: &str) -> Self {
+        match raw {
+            "" => SideCursor::Start,
+            "-" => SideCursor::Done,
+            cursor => SideCursor::After(cursor.to_string()),
+        }
+    }
+
+    fn after(&self) -> Option<&str> {
+        match self {
+            SideCursor::After(cursor) => Some(cursor),
+            SideCursor::Start | SideCursor::Done => None,
+        }
+    }
+}
+
+fn parse_combined_cursor(cursor: Option<&str>) -> (SideCursor, SideCursor) {
+    match cursor {
+        Some(cur) if cur.starts_with("i:") || cur.contains("|p:") => {
+            let mut issues = SideCursor::Start;
+            let mut pulls = SideCursor::Start;
+            for part in cur.split('|') {
+                if let Some(rest) = part.strip_prefix("i:") {
+                    issues = SideCursor::parse(rest);
+                }
+                if let Some(rest) = part.strip_prefix("p:") {
+                    pulls = SideCursor::parse(rest);
+                }
+            }
+            (issues, pulls)
+        }
+        Some(cur) => (SideCursor::After(cur.to_string()), SideCursor::Start),
+        None => (SideCursor::Start, SideCursor::Start),
+    }
+}
+
+fn end_cursor(conn: &serde_json::Map<String, Value>) -> Option<String> {
+    let page_info = conn.get("pageInfo")?;
+    if !page_info
+        .get("hasNextPage")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+    {
+        return None;
+    }
+    page_info
+        .get("endCursor")
+        .and_then(Value::as_str)
+        .map(|s| s.to_string())
 }
 
 fn user_ref_to_value(user: &UserRef) -> Value {
@@ -1221,7 +1632,12 @@ fn decode_global_id(value: &str) -> Option<i64> {
 #[error("graphql resource limit exceeded")]
 pub struct GraphqlResourceLimitError;
 
-fn map_graphql_errors(errors: &[Value]) -> anyhow::Error {
+fn map_graphql_errors(errors: &[Value], body: &Value) -> anyhow::Error {
+    // The broker normally parks the token and retries these; one can still
+    // surface here once its retries are exhausted or behind a plain executor.
+    if let Some(limited) = GraphqlRateLimitedError::from_body(body) {
+        return limited.into();
+    }
     if let Some(first) = errors.first() {
         let message = first
             .get("message")
@@ -1246,3 +1662,92 @@ fn map_graphql_errors(errors: &[Value]) -> anyhow::Error {
     }
     anyhow!("unknown GraphQL error")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limited_errors_are_typed_with_reset() {
+        let body = serde_json::json!({
+            "data": {"rateLimit": {"remaining": 0, "resetAt": "2030-01-01T00:00:00Z"}},
+            "errors": [{"type": "RATE_LIMITED", "message": "API rate limit exceeded"}]
+        });
+        let errors = body["errors"].as_array().unwrap();
+        let err = map_graphql_errors(errors, &body);
+        let limited = err
+            .downcast_ref::<GraphqlRateLimitedError>()
+            .expect("typed rate limit error");
+        assert_eq!(
+            limited.reset_at.map(|reset| reset.to_rfc3339()),
+            Some("2030-01-01T00:00:00+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn content_edits_report_latest_editor() {
+        let conn = serde_json::json!({
+            "totalCount": 3,
+            "nodes": [
+                {"editedAt": "2024-05-01T10:00:00Z", "editor": {"login": "author"}},
+                {"editedAt": "2024-05-03T08:30:00Z", "editor": {"login": "link-swapper"}},
+                {"editedAt": "2024-05-02T12:00:00Z", "editor": null}
+            ]
+        });
+        let edits = parse_content_edits(&conn);
+        assert_eq!(edits.total_count, 3);
+        assert_eq!(
+            edits.last_edited_at.map(|at| at.to_rfc3339()),
+            Some("2024-05-03T08:30:00+00:00".to_string())
+        );
+        assert_eq!(edits.last_editor.as_deref(), Some("link-swapper"));
+
+        let untouched = parse_content_edits(&serde_json::json!({"totalCount": 0, "nodes": []}));
+        assert_eq!(untouched, ContentEdits::default());
+    }
+
+    #[test]
+    fn reactions_keep_reactor_accounts() {
+        let conn = serde_json::json!({
+            "totalCount": 3,
+            "nodes": [
+                {"content": "THUMBS_UP", "user": {"login": "fresh1", "createdAt": "2024-05-01T10:00:00Z"}},
+                {"content": "HEART", "user": {"login": "veteran", "createdAt": "2012-01-01T00:00:00Z"}},
+                {"content": "ROCKET", "user": null}
+            ]
+        });
+        let reactions = parse_reactions(&conn);
+        assert_eq!(reactions.total_count, 3);
+        assert_eq!(
+            reactions.reactions,
+            vec![
+                Reaction {
+                    login: "fresh1".into(),
+                    content: "THUMBS_UP".into(),
+                    account_created_at: Some("2024-05-01T10:00:00Z".parse().unwrap()),
+                },
+                Reaction {
+                    login: "veteran".into(),
+                    content: "HEART".into(),
+                    account_created_at: Some("2012-01-01T00:00:00Z".parse().unwrap()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn combined_cursor_marks_exhausted_sides() {
+        assert_eq!(
+            parse_combined_cursor(Some("i:abc|p:-")),
+            (SideCursor::After("abc".into()), SideCursor::Done)
+        );
+        assert_eq!(
+            parse_combined_cursor(Some("i:|p:xyz")),
+            (SideCursor::Start, SideCursor::After("xyz".into()))
+        );
+        assert_eq!(
+            parse_combined_cursor(None),
+            (SideCursor::Start, SideCursor::Start)
+        );
+    }
+}
@@ -3,19 +3,22 @@ use std::sync::Arc;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use common::config::{DedupeMode, Leniency};
 
 use crate::client::GithubClient;
 use crate::fetcher::{
-    CommentPage, DataFetcher, GraphqlDataFetcher, IssuePage, RepoSnapshot, RestDataFetcher,
-    UserFetch,
+    CommentPage, ContentEdits, DataFetcher, GraphqlDataFetcher, IssuePage, IssueRecord, Reactions,
+    RepoSnapshot, RestDataFetcher, UserFetch,
 };
 use normalizer::payloads::UserRef;
 
 /// Hybrid fetcher:
 /// - Repo metadata via GraphQL
-/// - Issues listing via REST (ensures PRs included)
+/// - Issues listing and single issues via REST (ensures PRs included)
 /// - Comments via REST (works for both issues and PRs)
 /// - Users via REST (GraphQL user caching is less critical here)
+/// - Edit histories via GraphQL (REST has no equivalent)
+/// - Reactions via GraphQL (one query instead of paging REST per subject)
 pub struct HybridDataFetcher {
     graphql: GraphqlDataFetcher,
     rest: RestDataFetcher,
@@ -31,11 +34,35 @@ impl HybridDataFetcher {
         let rest = RestDataFetcher::new(rest_client);
         Self { graphql, rest }
     }
+
+    pub fn with_dedupe_mode(self, mode: DedupeMode) -> Self {
+        Self {
+            graphql: self.graphql.with_dedupe_mode(mode),
+            rest: self.rest.with_dedupe_mode(mode),
+        }
+    }
+
+    pub fn with_leniency(self, leniency: Leniency) -> Self {
+        Self {
+            graphql: self.graphql.with_leniency(leniency),
+            rest: self.rest.with_leniency(leniency),
+        }
+    }
+
+    /// See [`RestDataFetcher::with_prefetch`]; issues and comments are
+    /// listed over REST here.
+    pub fn with_prefetch(self, prefetch: bool) -> Self {
+        Self {
+            rest: self.rest.with_prefetch(prefetch),
+            ..self
+        }
+    }
 }
 
 #[async_trait]
 impl DataFetcher for HybridDataFetcher {
     async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        self.rest.discard_prefetches(owner, name);
         self.graphql.fetch_repo(owner, name).await
     }
 
@@ -53,6 +80,16 @@ impl DataFetcher for HybridDataFetcher {
             .await
     }
 
+    async fn fetch_issue(
+        &self,
+        owner: &str,
+        name: &str,
+        repo_id: i64,
+        number: i64,
+    ) -> Result<Option<IssueRecord>> {
+        self.rest.fetch_issue(owner, name, repo_id, number).await
+    }
+
     async fn fetch_issue_comments(
         &self,
         owner: &str,
@@ -70,4 +107,16 @@ impl DataFetcher for HybridDataFetcher {
     async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
         self.rest.fetch_user(user).await
     }
+
+    async fn fetch_org_membership(&self, org: &str, login: &str) -> Result<Option<bool>> {
+        self.rest.fetch_org_membership(org, login).await
+    }
+
+    async fn fetch_content_edits(&self, node_id: &str) -> Result<Option<ContentEdits>> {
+        self.graphql.fetch_content_edits(node_id).await
+    }
+
+    async fn fetch_reactions(&self, node_id: &str) -> Result<Option<Reactions>> {
+        self.graphql.fetch_reactions(node_id).await
+    }
 }
@@ -21,6 +21,9 @@ pub use rest::RestDataFetcher;
 pub trait DataFetcher: Send + Sync {
     async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot>;
 
+    /// Lists issues and pull requests, newest update first. `since` is inclusive:
+    /// every fetcher returns exactly the items with `updated_at >= since`, so REST,
+    /// GraphQL and hybrid modes agree on the watermark boundary.
     async fn fetch_issues(
         &self,
         owner: &str,
@@ -31,6 +34,18 @@ pub trait DataFetcher: Send + Sync {
         per_page: u32,
     ) -> Result<IssuePage>;
 
+    /// A single issue or pull request by number, for targeted refreshes.
+    /// `Ok(None)` when GitHub no longer serves it.
+    async fn fetch_issue(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _repo_id: i64,
+        _number: i64,
+    ) -> Result<Option<IssueRecord>> {
+        Err(anyhow::anyhow!("this fetcher cannot fetch single issues"))
+    }
+
     async fn fetch_issue_comments(
         &self,
         owner: &str,
@@ -42,6 +57,36 @@ pub trait DataFetcher: Send + Sync {
     ) -> Result<CommentPage>;
 
     async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch>;
+
+    /// Author details this fetcher already received inline with an issue or
+    /// comment, so no separate lookup is needed.
+    async fn inlined_user(&self, _user: &UserRef) -> Option<NormalizedUser> {
+        None
+    }
+
+    /// Whether `login` is a public member of `org`; `None` when this fetcher
+    /// cannot tell, so nothing is cached.
+    async fn fetch_org_membership(&self, _org: &str, _login: &str) -> Result<Option<bool>> {
+        Ok(None)
+    }
+
+    /// Edit history summary of the issue/comment with GraphQL node id `node_id`;
+    /// `None` when this fetcher cannot tell or the node is gone.
+    async fn fetch_content_edits(&self, _node_id: &str) -> Result<Option<ContentEdits>> {
+        Ok(None)
+    }
+
+    /// Accounts that reacted to the issue/comment with GraphQL node id
+    /// `node_id`; `None` when this fetcher cannot tell or the node is gone.
+    async fn fetch_reactions(&self, _node_id: &str) -> Result<Option<Reactions>> {
+        Ok(None)
+    }
+}
+
+// Shared `since` semantics for `fetch_issues`. GitHub's server-side filters are
+// not applied everywhere (GraphQL pullRequests has none), so fetchers re-check locally.
+pub(crate) fn updated_since(issue: &NormalizedIssue, since: Option<DateTime<Utc>>) -> bool {
+    since.is_none_or(|since| issue.updated_at >= since)
 }
 
 #[derive(Debug, Clone)]
@@ -73,6 +118,28 @@ pub struct CommentPage {
     pub next_cursor: Option<String>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContentEdits {
+    pub total_count: u32,
+    pub last_edited_at: Option<DateTime<Utc>>,
+    pub last_editor: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Reactions {
+    pub total_count: u32,
+    /// The fetched reactions whose account still exists.
+    pub reactions: Vec<Reaction>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reaction {
+    pub login: String,
+    /// GraphQL `ReactionContent`, e.g. `THUMBS_UP`.
+    pub content: String,
+    pub account_created_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct MissingUser {
     pub id: i64,
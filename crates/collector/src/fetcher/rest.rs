@@ -1,41 +1,175 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use common::config::{DedupeMode, Leniency};
 use http::StatusCode;
 use normalizer::models::{
     NormalizedComment, NormalizedIssue, NormalizedRepository, NormalizedUser,
 };
 use normalizer::payloads::{CommentPayload, IssuePayload, RepoPayload, UserPayload, UserRef};
+use normalizer::PayloadStrategy;
 use serde_json::Value;
 use std::time::Instant;
+use tokio::task::JoinHandle;
 
-use crate::client::{GithubApiError, GithubClient};
+use crate::client::{clamp_paging, GithubApiError, GithubClient};
 use crate::fetcher::{
-    CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, MissingUser, RepoSnapshot,
-    UserFetch,
+    updated_since, CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, MissingUser,
+    RepoSnapshot, UserFetch,
 };
 use crate::metrics;
 
+/// Listing a prefetched page belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Listing {
+    Issues {
+        owner: String,
+        name: String,
+    },
+    Comments {
+        owner: String,
+        name: String,
+        issue_number: i64,
+    },
+}
+
+impl Listing {
+    fn op(&self) -> &'static str {
+        match self {
+            Self::Issues { .. } => "issues",
+            Self::Comments { .. } => "comments",
+        }
+    }
+
+    fn of_repo(&self, repo_owner: &str, repo_name: &str) -> bool {
+        let (Self::Issues { owner, name } | Self::Comments { owner, name, .. }) = self;
+        owner == repo_owner && name == repo_name
+    }
+}
+
+/// The next page of a listing, requested while the caller processes the
+/// current one.
+struct Prefetch {
+    page: u32,
+    per_page: u32,
+    since: Option<DateTime<Utc>>,
+    response: JoinHandle<Result<Vec<Value>>>,
+}
+
 pub struct RestDataFetcher {
     client: Arc<dyn GithubClient>,
+    dedupe_mode: DedupeMode,
+    payloads: PayloadStrategy,
+    prefetch: bool,
+    prefetched: Mutex<HashMap<Listing, Prefetch>>,
 }
 
 impl RestDataFetcher {
     pub fn new(client: Arc<dyn GithubClient>) -> Self {
-        Self { client }
+        Self {
+            client,
+            dedupe_mode: DedupeMode::default(),
+            payloads: PayloadStrategy::default(),
+            prefetch: false,
+            prefetched: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Requests the next issue/comment page as soon as a full page is
+    /// returned, so it downloads while the caller stores and scores the
+    /// current one. Issue listings stop prefetching once a page reaches past
+    /// `since`.
+    pub fn with_prefetch(mut self, prefetch: bool) -> Self {
+        self.prefetch = prefetch;
+        self
+    }
+
+    /// Drops pages prefetched for `owner/name` that were never asked for,
+    /// e.g. because an earlier collection stopped at its page cap.
+    pub fn discard_prefetches(&self, owner: &str, name: &str) {
+        let mut prefetched = self.prefetched.lock().unwrap();
+        prefetched.retain(|listing, prefetch| {
+            if !listing.of_repo(owner, name) {
+                return true;
+            }
+            discard(prefetch, listing);
+            false
+        });
+    }
+
+    /// The response to `page` of `listing` if it was prefetched; any other
+    /// page prefetched for the listing is discarded.
+    async fn prefetched(
+        &self,
+        listing: &Listing,
+        page: u32,
+        per_page: u32,
+        since: Option<DateTime<Utc>>,
+    ) -> Option<Result<Vec<Value>>> {
+        let prefetch = self.prefetched.lock().unwrap().remove(listing)?;
+        if (prefetch.page, prefetch.per_page, prefetch.since) != (page, per_page, since) {
+            discard(&prefetch, listing);
+            return None;
+        }
+        metrics::REST_PREFETCH_TOTAL
+            .with_label_values(&[listing.op(), "used"])
+            .inc();
+        Some(match prefetch.response.await {
+            Ok(response) => response,
+            Err(err) => Err(err.into()),
+        })
+    }
+
+    fn spawn_prefetch<F>(
+        &self,
+        listing: Listing,
+        page: u32,
+        per_page: u32,
+        since: Option<DateTime<Utc>>,
+        request: F,
+    ) where
+        F: Future<Output = Result<Vec<Value>>> + Send + 'static,
+    {
+        let prefetch = Prefetch {
+            page,
+            per_page,
+            since,
+            response: tokio::spawn(request),
+        };
+        if let Some(stale) = self
+            .prefetched
+            .lock()
+            .unwrap()
+            .insert(listing.clone(), prefetch)
+        {
+            discard(&stale, &listing);
+        }
+    }
+
+    pub fn with_dedupe_mode(mut self, mode: DedupeMode) -> Self {
+        self.dedupe_mode = mode;
+        self
+    }
+
+    pub fn with_leniency(mut self, leniency: Leniency) -> Self {
+        self.payloads = PayloadStrategy::new(leniency);
+        self
     }
 }
 
 #[async_trait]
 impl DataFetcher for RestDataFetcher {
     async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        self.discard_prefetches(owner, name);
         let op = "repo";
         let start = Instant::now();
         let result = async {
             let repo_value = self.client.get_repo(owner, name).await?;
-            let repo_payload: RepoPayload = serde_json::from_value(repo_value.clone())?;
+            let repo_payload: RepoPayload = self.payloads.parse("repo", &repo_value)?;
             let repository = normalize_repo(&repo_payload, repo_value);
             Ok::<_, anyhow::Error>(RepoSnapshot { repository })
         }
@@ -73,16 +207,20 @@ impl DataFetcher for RestDataFetcher {
     ) -> Result<IssuePage> {
         let op = "issues";
         let start = Instant::now();
-        let page = cursor
-            .as_deref()
-            .and_then(|value| value.parse::<u32>().ok())
-            .filter(|page| *page > 0)
-            .unwrap_or(1);
-
-        let issues_result = self
-            .client
-            .list_repo_issues(owner, name, since, page, per_page)
-            .await;
+        let (page, per_page) = listing_page(cursor.as_deref(), per_page);
+
+        let listing = Listing::Issues {
+            owner: owner.to_string(),
+            name: name.to_string(),
+        };
+        let issues_result = match self.prefetched(&listing, page, per_page, since).await {
+            Some(response) => response,
+            None => {
+                self.client
+                    .list_repo_issues(owner, name, since, page, per_page)
+                    .await
+            }
+        };
         let elapsed = start.elapsed().as_secs_f64();
         let issues = match issues_result {
             Ok(v) => {
@@ -108,8 +246,9 @@ impl DataFetcher for RestDataFetcher {
         let mut items = Vec::with_capacity(issues.len());
 
         for issue_value in issues {
-            let issue_payload: IssuePayload = serde_json::from_value(issue_value.clone())?;
-            let normalized = normalize_issue(&issue_payload, repo_id, issue_value);
+            let issue_payload: IssuePayload = self.payloads.parse("issue", &issue_value)?;
+            let normalized =
+                normalize_issue(&issue_payload, repo_id, issue_value, self.dedupe_mode);
             items.push(IssueRecord {
                 issue: normalized,
                 author: issue_payload.user,
@@ -121,6 +260,21 @@ impl DataFetcher for RestDataFetcher {
         } else {
             None
         };
+        // Pin the inclusive boundary locally instead of trusting server-side `since`
+        let reaches_since =
+            since.is_some_and(|since| items.iter().any(|record| record.issue.updated_at <= since));
+        items.retain(|record| updated_since(&record.issue, since));
+        // Issues come newest update first, so once a page reaches the
+        // watermark later pages hold nothing new and the collector stops.
+        if self.prefetch && next_cursor.is_some() && !reaches_since {
+            let client = self.client.clone();
+            let (owner, name) = (owner.to_string(), name.to_string());
+            self.spawn_prefetch(listing, page + 1, per_page, since, async move {
+                client
+                    .list_repo_issues(&owner, &name, since, page + 1, per_page)
+                    .await
+            });
+        }
 
         metrics::FETCH_ITEMS_TOTAL
             .with_label_values(&["rest", op])
@@ -128,6 +282,43 @@ impl DataFetcher for RestDataFetcher {
         Ok(IssuePage { items, next_cursor })
     }
 
+    async fn fetch_issue(
+        &self,
+        owner: &str,
+        name: &str,
+        repo_id: i64,
+        number: i64,
+    ) -> Result<Option<IssueRecord>> {
+        let op = "issue";
+        let start = Instant::now();
+        let result = self.client.get_issue(owner, name, number as u64).await;
+        let outcome = match &result {
+            Err(err) if !is_not_found(err) => "error",
+            _ => "success",
+        };
+        metrics::FETCH_REQUESTS_TOTAL
+            .with_label_values(&["rest", op, outcome])
+            .inc();
+        metrics::FETCH_LATENCY_SECONDS
+            .with_label_values(&["rest", op])
+            .observe(start.elapsed().as_secs_f64());
+        let issue_value = match result {
+            Ok(value) => value,
+            Err(err) if is_not_found(&err) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let issue_payload: IssuePayload = self.payloads.parse("issue", &issue_value)?;
+        let issue = normalize_issue(&issue_payload, repo_id, issue_value, self.dedupe_mode);
+        metrics::FETCH_ITEMS_TOTAL
+            .with_label_values(&["rest", op])
+            .inc();
+        Ok(Some(IssueRecord {
+            issue,
+            author: issue_payload.user,
+        }))
+    }
+
     async fn fetch_issue_comments(
         &self,
         owner: &str,
@@ -139,16 +330,21 @@ impl DataFetcher for RestDataFetcher {
     ) -> Result<CommentPage> {
         let op = "comments";
         let start = Instant::now();
-        let page = cursor
-            .as_deref()
-            .and_then(|value| value.parse::<u32>().ok())
-            .filter(|page| *page > 0)
-            .unwrap_or(1);
-
-        let comments_result = self
-            .client
-            .list_issue_comments(owner, name, issue_number as u64, page, per_page)
-            .await;
+        let (page, per_page) = listing_page(cursor.as_deref(), per_page);
+
+        let listing = Listing::Comments {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            issue_number,
+        };
+        let comments_result = match self.prefetched(&listing, page, per_page, None).await {
+            Some(response) => response,
+            None => {
+                self.client
+                    .list_issue_comments(owner, name, issue_number as u64, page, per_page)
+                    .await
+            }
+        };
         let elapsed = start.elapsed().as_secs_f64();
         let comments = match comments_result {
             Ok(v) => {
@@ -194,8 +390,9 @@ impl DataFetcher for RestDataFetcher {
         let mut items = Vec::with_capacity(comments.len());
 
         for comment_value in comments {
-            let comment_payload: CommentPayload = serde_json::from_value(comment_value.clone())?;
-            let normalized = normalize_comment(&comment_payload, issue_id, comment_value);
+            let comment_payload: CommentPayload = self.payloads.parse("comment", &comment_value)?;
+            let normalized =
+                normalize_comment(&comment_payload, issue_id, comment_value, self.dedupe_mode);
             items.push(CommentRecord {
                 comment: normalized,
                 author: comment_payload.user,
@@ -207,6 +404,15 @@ impl DataFetcher for RestDataFetcher {
         } else {
             None
         };
+        if self.prefetch && next_cursor.is_some() {
+            let client = self.client.clone();
+            let (owner, name) = (owner.to_string(), name.to_string());
+            self.spawn_prefetch(listing, page + 1, per_page, None, async move {
+                client
+                    .list_issue_comments(&owner, &name, issue_number as u64, page + 1, per_page)
+                    .await
+            });
+        }
 
         metrics::FETCH_ITEMS_TOTAL
             .with_label_values(&["rest", op])
@@ -221,7 +427,7 @@ impl DataFetcher for RestDataFetcher {
         let elapsed = start.elapsed().as_secs_f64();
         match result {
             Ok(user_value) => {
-                let payload: UserPayload = serde_json::from_value(user_value.clone())?;
+                let payload: UserPayload = self.payloads.parse("user", &user_value)?;
                 let normalized = normalize_user(&payload, user_value);
                 metrics::FETCH_REQUESTS_TOTAL
                     .with_label_values(&["rest", op, "success"])
@@ -289,18 +495,71 @@ impl DataFetcher for RestDataFetcher {
             }
         }
     }
+
+    async fn fetch_org_membership(&self, org: &str, login: &str) -> Result<Option<bool>> {
+        let op = "org_membership";
+        let start = Instant::now();
+        let result = self.client.get_org_membership(org, login).await;
+        let outcome = if result.is_ok() { "success" } else { "error" };
+        metrics::FETCH_REQUESTS_TOTAL
+            .with_label_values(&["rest", op, outcome])
+            .inc();
+        metrics::FETCH_LATENCY_SECONDS
+            .with_label_values(&["rest", op])
+            .observe(start.elapsed().as_secs_f64());
+        result.map(Some)
+    }
+}
+
+/// The page a listing cursor points at and the `per_page` to request it
+/// with. The client clamps too; clamping here as well means a full page is
+/// measured against what GitHub actually serves.
+fn listing_page(cursor: Option<&str>, per_page: u32) -> (u32, u32) {
+    let page = cursor
+        .and_then(|value| value.parse::<u32>().ok())
+        .filter(|page| *page > 0)
+        .unwrap_or(1);
+    clamp_paging(page, per_page)
+}
+
+/// Cancels a prefetched page nobody asked for.
+fn discard(prefetch: &Prefetch, listing: &Listing) {
+    prefetch.response.abort();
+    metrics::REST_PREFETCH_TOTAL
+        .with_label_values(&[listing.op(), "discarded"])
+        .inc();
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    if let Some(api_err) = err.downcast_ref::<GithubApiError>() {
+        api_err.status_code() == StatusCode::NOT_FOUND
+    } else if let Some(status_err) = err.downcast_ref::<gh_broker::HttpStatusError>() {
+        status_err.status == StatusCode::NOT_FOUND
+    } else {
+        false
+    }
 }
 
 fn normalize_repo(payload: &RepoPayload, raw: Value) -> NormalizedRepository {
     normalizer::normalize_repo(payload, raw)
 }
 
-fn normalize_issue(payload: &IssuePayload, repo_id: i64, raw: Value) -> NormalizedIssue {
-    normalizer::normalize_issue(payload, repo_id, raw)
+fn normalize_issue(
+    payload: &IssuePayload,
+    repo_id: i64,
+    raw: Value,
+    dedupe_mode: DedupeMode,
+) -> NormalizedIssue {
+    normalizer::normalize_issue_with(payload, repo_id, raw, dedupe_mode)
 }
 
-fn normalize_comment(payload: &CommentPayload, issue_id: i64, raw: Value) -> NormalizedComment {
-    normalizer::normalize_comment(payload, issue_id, raw)
+fn normalize_comment(
+    payload: &CommentPayload,
+    issue_id: i64,
+    raw: Value,
+    dedupe_mode: DedupeMode,
+) -> NormalizedComment {
+    normalizer::normalize_comment_with(payload, issue_id, raw, dedupe_mode)
 }
 
 fn normalize_user(payload: &UserPayload, raw: Value) -> NormalizedUser {
@@ -1,7 +1,11 @@
 pub mod client;
+pub mod control;
 pub mod fetcher;
 pub mod metrics;
+pub mod priority;
 pub mod service;
+pub mod sink;
 
 pub use client::{BrokerGithubClient, GithubClient};
 pub use service::Collector;
+pub use sink::RecordSink;
@@ -1,58 +1,106 @@
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::{anyhow, Result};
+use analysis::{KeywordRules, RuleEngine};
+use anyhow::{anyhow, Context, Result};
+use axum::extract::State;
 use axum::http::header;
 use axum::http::StatusCode;
 use axum::response::Response;
 use axum::routing::get;
 use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use clap::Parser;
 use collector::{
     fetcher::{DataFetcher, GraphqlDataFetcher, RestDataFetcher},
-    BrokerGithubClient, Collector, GithubClient,
+    metrics, BrokerGithubClient, Collector, GithubClient,
 };
 use common::{
-    config::{AppConfig, FetchMode, GithubToken},
+    config::{AppConfig, FetchMode, GithubConfig, GithubToken},
     logging,
+    shutdown::ShutdownSignal,
+    text::truncate_chars,
 };
-use db::pg::PgDatabase;
+use db::pg::{ConnectRetry, PgDatabase};
 use db::Repositories;
-use gh_broker::{Budget, GithubBrokerBuilder, GithubToken as BrokerToken, Priority};
+use gh_broker::broker::{HttpExec, ReqwestExecutor};
+use gh_broker::{
+    AppInstallationToken, Budget, GithubBroker, GithubBrokerBuilder, GithubToken as BrokerToken,
+    GraphqlCostModel, Priority, RetryingHttpExec,
+};
 use prometheus::Encoder;
 use serde::Serialize;
 use serde_json::Value;
 use tracing::{info, warn};
 
+#[derive(Debug, Parser)]
+#[command(
+    name = "collector",
+    about = "Collect GitHub issues and comments for spam analysis"
+)]
+struct CliArgs {
+    /// Run a single collection pass and exit
+    #[arg(long)]
+    once: bool,
+    /// Additional config file layered over config/default and config/local
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Only process the pending job for this repository
+    #[arg(long, value_name = "OWNER/NAME", value_parser = parse_repo_arg)]
+    repo: Option<String>,
+    /// Log the jobs that would be collected without fetching or writing
+    #[arg(long)]
+    dry_run: bool,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args = CliArgs::parse();
     logging::init_tracing("collector", "info");
-    let config = AppConfig::load()?;
+    let config =
+        AppConfig::load_with_overrides(".", args.config.as_deref(), &config_overrides(&args))?;
+    let shutdown = ShutdownSignal::install()?;
     let tokens = config.github.resolved_tokens()?;
-    if tokens.is_empty() {
+    if tokens.is_empty() && config.github.app.is_none() {
         return Err(anyhow!("no GitHub tokens configured"));
     }
 
     info!(
         token_ids = ?tokens.iter().map(|t| t.id.clone()).collect::<Vec<_>>(),
+        app_id = ?config.github.app.as_ref().map(|app| app.app_id.clone()),
         user_agent = %config.github.user_agent,
         "GitHub tokens configured for collector"
     );
 
-    verify_github_tokens(&config, &tokens).await?;
+    if !tokens.is_empty() {
+        verify_github_tokens(&config, &tokens).await?;
+    }
 
-    let broker_tokens: Vec<BrokerToken> = tokens
+    let broker_tokens = tokens
         .into_iter()
-        .map(|token| BrokerToken {
-            id: token.id,
-            secret: token.secret,
+        .map(|token| {
+            let budgets = token
+                .budgets
+                .as_deref()
+                .map(map_token_budgets)
+                .transpose()?;
+            Ok(BrokerToken {
+                id: token.id,
+                secret: token.secret,
+                budgets,
+            })
         })
-        .collect();
+        .collect::<Result<Vec<_>>>()?;
 
     let mut builder = GithubBrokerBuilder::new(broker_tokens)
+        .http_exec(Arc::new(github_http_exec(&config.github)?))
         .max_inflight(config.broker.max_inflight)
         .per_repo_inflight(config.broker.per_repo_inflight)
+        .critical_reserve(config.broker.critical_reserve)
+        .work_conserving(config.broker.work_conserving)
         .cache(
             config.broker.cache_capacity,
             Duration::from_secs(config.broker.cache_ttl_secs),
@@ -61,87 +109,122 @@ async fn main() -> Result<()> {
             Duration::from_millis(config.broker.backoff_base_ms),
             Duration::from_millis(config.broker.backoff_max_ms),
             config.broker.jitter_frac,
-        );
-
+        )
+        .graphql_cost_model(GraphqlCostModel {
+            page_size_variables: config.broker.graphql_cost_page_variables.clone(),
+            requests_per_point: config.broker.graphql_cost_requests_per_point,
+        })
+        .redact_query_params(config.github.redact_query_params.clone());
+
+    if let Some(app) = &config.github.app {
+        let pem = std::fs::read_to_string(&app.private_key_path)
+            .with_context(|| format!("reading GitHub App private key {}", app.private_key_path))?;
+        let provider = AppInstallationToken::new(
+            &app.app_id,
+            app.installation_id,
+            &pem,
+            Arc::new(github_http_exec(&config.github)?),
+        )?;
+        builder =
+            builder.token_provider(format!("app-{}", app.installation_id), Arc::new(provider));
+    }
     if !config.broker.queue_bounds.is_empty() {
         builder = builder.queue_bounds(map_queue_bounds(&config.broker.queue_bounds));
     }
     if !config.broker.weights.is_empty() {
         builder = builder.weights(map_weights(&config.broker.weights));
     }
+    if let Some(secs) = config.broker.max_rate_limit_wait_secs {
+        builder = builder.max_rate_limit_wait(Duration::from_secs(secs));
+    }
+
+    let broker = builder.build();
 
     let metrics_path: &'static str =
         Box::leak(config.observability.metrics_path.clone().into_boxed_str());
-    let metrics_addr: SocketAddr = config.observability.metrics_bind.parse()?;
-    tokio::spawn(async move {
-        if let Err(err) = serve_metrics(metrics_addr, metrics_path).await {
-            warn!(error = ?err, "collector metrics server exited");
-        }
-    });
-
-    let broker = builder.build();
-    let client: Arc<dyn GithubClient> = Arc::new(BrokerGithubClient::new(
-        broker.clone(),
-        config.github.user_agent.clone(),
-    ));
+    metrics::REPO_LABELS.set_limit(config.observability.max_repo_labels);
+    let client: Arc<dyn GithubClient> = Arc::new(
+        BrokerGithubClient::new(broker.clone(), config.github.user_agent.clone())
+            .with_redacted_params(config.github.redact_query_params.clone()),
+    );
     let fetcher: Arc<dyn DataFetcher> = match config.collector.fetch_mode {
-        FetchMode::Rest => Arc::new(RestDataFetcher::new(client.clone())),
-        FetchMode::Graphql => Arc::new(GraphqlDataFetcher::new(
-            broker.clone(),
-            client.clone(),
-            config.github.user_agent.clone(),
-        )),
-        FetchMode::Hybrid => Arc::new(collector::fetcher::HybridDataFetcher::new(
-            broker.clone(),
-            client.clone(),
-            config.github.user_agent.clone(),
-        )),
+        FetchMode::Rest => Arc::new(
+            RestDataFetcher::new(client.clone())
+                .with_dedupe_mode(config.collector.dedupe_mode)
+                .with_leniency(config.collector.normalization_leniency)
+                .with_prefetch(config.collector.rest_prefetch),
+        ),
+        FetchMode::Graphql => Arc::new(
+            GraphqlDataFetcher::new(
+                broker.clone(),
+                client.clone(),
+                config.github.user_agent.clone(),
+            )
+            .with_comment_page_size(config.collector.comment_page_size())
+            .with_dedupe_mode(config.collector.dedupe_mode)
+            .with_leniency(config.collector.normalization_leniency),
+        ),
+        FetchMode::Hybrid => Arc::new(
+            collector::fetcher::HybridDataFetcher::new(
+                broker.clone(),
+                client.clone(),
+                config.github.user_agent.clone(),
+            )
+            .with_dedupe_mode(config.collector.dedupe_mode)
+            .with_leniency(config.collector.normalization_leniency)
+            .with_prefetch(config.collector.rest_prefetch),
+        ),
     };
     info!(fetch_mode = ?config.collector.fetch_mode, "collector fetch mode selected");
 
-    let database = Arc::new(PgDatabase::connect(&config.database.url).await?);
+    let database = Arc::new(
+        PgDatabase::connect_with(&config.database.url, ConnectRetry::from(&config.database))
+            .await?,
+    );
     let repositories: Arc<dyn Repositories> = database.clone() as Arc<dyn Repositories>;
 
-    let max_repos = std::env::var("COLLECTOR__MAX_CONCURRENT_REPOS")
-        .ok()
-        .and_then(|v| v.parse::<usize>().ok())
-        .unwrap_or(4);
-    let collector = Collector::new(config.collector.clone(), fetcher, repositories, max_repos);
+    let max_repos = config.collector.max_concurrent_repos;
+    let mut rule_engine = RuleEngine::default()
+        .with_reason_caps(config.analysis.reason_caps.clone())
+        .with_fork_adjustment(config.analysis.fork_adjustment);
+    if let Some(path) = config.analysis.keyword_patterns_path.as_deref() {
+        let keywords = KeywordRules::from_path(path)?;
+        info!(path, "loaded spam keyword patterns");
+        rule_engine = rule_engine.with_keywords(Arc::new(keywords));
+    }
+    let sink = collector::sink::from_config(&config.collector.sink).await?;
+    let collector = Arc::new(
+        Collector::new(config.collector.clone(), fetcher, repositories, max_repos)
+            .with_rule_engine(rule_engine)
+            .with_sink(sink),
+    );
+
+    let metrics_addr: SocketAddr = config.observability.metrics_bind.parse()?;
+    let metrics_broker = broker.clone();
+    let control = collector::control::router(collector.clone());
+    tokio::spawn(async move {
+        if let Err(err) = serve_metrics(metrics_addr, metrics_path, metrics_broker, control).await {
+            warn!(error = ?err, "collector metrics server exited");
+        }
+    });
     info!(
         interval = config.collector.interval_secs,
         "collector started"
     );
-    collector.run().await?;
+    collector.run_until(shutdown.recv()).await?;
     // Ensure any remaining spans are flushed on shutdown (no-op if otel disabled)
     common::logging::shutdown_tracer_provider();
     Ok(())
 }
 
 async fn verify_github_tokens(config: &AppConfig, tokens: &[GithubToken]) -> Result<()> {
-    use http::header;
     use http::StatusCode;
 
-    let mut builder = reqwest::Client::builder().user_agent(config.github.user_agent.clone());
-
-    if let Ok(proxy) = std::env::var("HTTPS_PROXY")
-        .or_else(|_| std::env::var("https_proxy"))
-        .or_else(|_| std::env::var("HTTP_PROXY"))
-        .or_else(|_| std::env::var("http_proxy"))
-    {
-        if let Ok(p) = reqwest::Proxy::all(&proxy) {
-            builder = builder.proxy(p);
-        }
-    }
-
-    let client = builder.build()?;
+    let exec = github_probe_exec(config)?;
 
     let mut valid_count = 0usize;
     for token in tokens {
-        let resp = client
-            .get("https://api.github.com/rate_limit")
-            .header(header::AUTHORIZATION, format!("token {}", token.secret))
-            .send()
-            .await?;
+        let resp = exec.execute(rate_limit_request(token)?).await?;
 
         let status = resp.status();
         if status.is_success() {
@@ -154,13 +237,13 @@ async fn verify_github_tokens(config: &AppConfig, tokens: &[GithubToken]) -> Res
             continue;
         }
 
-        let body = resp.text().await.unwrap_or_default();
+        let body = String::from_utf8_lossy(resp.body());
 
         if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
             warn!(
                 token_id = %token.id,
                 status = %status,
-                body_preview = %body.chars().take(256).collect::<String>(),
+                body_preview = %truncate_chars(&body, 256),
                 "GitHub token failed verification with auth error"
             );
             return Err(anyhow!(
@@ -173,7 +256,7 @@ async fn verify_github_tokens(config: &AppConfig, tokens: &[GithubToken]) -> Res
         warn!(
             token_id = %token.id,
             status = %status,
-            body_preview = %body.chars().take(256).collect::<String>(),
+            body_preview = %truncate_chars(&body, 256),
             "GitHub token verification returned non-success status"
         );
     }
@@ -187,11 +270,46 @@ async fn verify_github_tokens(config: &AppConfig, tokens: &[GithubToken]) -> Res
     Ok(())
 }
 
-async fn serve_metrics(addr: SocketAddr, metrics_path: &'static str) -> Result<()> {
+/// Standalone `/rate_limit` calls bypass the broker queue but share its retry/backoff.
+fn github_probe_exec(config: &AppConfig) -> Result<RetryingHttpExec> {
+    Ok(RetryingHttpExec::new(Arc::new(github_http_exec(
+        &config.github,
+    )?)))
+}
+
+/// HTTP client for GitHub, also trusting `github.ca_bundle_path` when set.
+fn github_http_exec(github: &GithubConfig) -> Result<ReqwestExecutor> {
+    let Some(path) = github.ca_bundle_path.as_deref() else {
+        return Ok(ReqwestExecutor::new(&github.user_agent));
+    };
+    let pem = std::fs::read(path).with_context(|| format!("reading CA bundle {path}"))?;
+    ReqwestExecutor::with_ca_bundle(&github.user_agent, &pem)
+        .with_context(|| format!("loading CA bundle {path}"))
+}
+
+fn rate_limit_request(token: &GithubToken) -> Result<http::Request<Vec<u8>>> {
+    Ok(http::Request::get("https://api.github.com/rate_limit")
+        .header(
+            http::header::AUTHORIZATION,
+            format!("token {}", token.secret),
+        )
+        .body(Vec::new())?)
+}
+
+async fn serve_metrics(
+    addr: SocketAddr,
+    metrics_path: &'static str,
+    broker: Arc<dyn GithubBroker>,
+    control: Router,
+) -> Result<()> {
     let app = Router::new()
         .route("/healthz", get(healthz))
         .route(metrics_path, get(export_metrics))
-        .route("/rate_limits", get(rate_limits));
+        .route("/metrics.json", get(export_metrics_json))
+        .route("/rate_limits", get(rate_limits))
+        .route("/rate_limits/internal", get(internal_rate_limits))
+        .with_state(broker)
+        .merge(control);
     let listener = tokio::net::TcpListener::bind(addr).await?;
     info!(
         address = %addr,
@@ -228,6 +346,10 @@ async fn export_metrics() -> Response {
     res
 }
 
+async fn export_metrics_json() -> Json<Vec<common::metrics_json::MetricFamilyJson>> {
+    Json(common::metrics_json::gather())
+}
+
 #[derive(Debug, Serialize)]
 struct TokenRateLimit {
     token: String,
@@ -270,40 +392,29 @@ async fn rate_limits() -> Json<RateLimitsResponse> {
         }
     };
 
-    let mut builder = reqwest::Client::builder().user_agent(config.github.user_agent.clone());
-
-    if let Ok(proxy) = std::env::var("HTTPS_PROXY")
-        .or_else(|_| std::env::var("https_proxy"))
-        .or_else(|_| std::env::var("HTTP_PROXY"))
-        .or_else(|_| std::env::var("http_proxy"))
-    {
-        if let Ok(p) = reqwest::Proxy::all(&proxy) {
-            builder = builder.proxy(p);
-        }
-    }
-
-    let client = match builder.build() {
-        Ok(c) => c,
-        Err(_) => {
-            return Json(RateLimitsResponse { tokens: Vec::new() });
-        }
+    let Ok(exec) = github_probe_exec(&config) else {
+        return Json(RateLimitsResponse {
+            tokens: vec![TokenRateLimit {
+                token: "<config-error>".to_string(),
+                budget: "n/a".to_string(),
+                limit: 0,
+                remaining: 0,
+            }],
+        });
     };
 
     let mut out = Vec::new();
 
     for token in tokens {
-        let resp = match client
-            .get("https://api.github.com/rate_limit")
-            .header("Authorization", format!("token {}", token.secret))
-            .send()
-            .await
-        {
+        let Ok(request) = rate_limit_request(&token) else {
+            continue;
+        };
+        let resp = match exec.execute(request).await {
             Ok(r) => r,
             Err(_err) => continue,
         };
 
         let status = resp.status();
-        let body_text = resp.text().await.unwrap_or_default();
 
         if !status.is_success() {
             // Surface non-success statuses as synthetic entries per token.
@@ -316,7 +427,7 @@ async fn rate_limits() -> Json<RateLimitsResponse> {
             continue;
         }
 
-        let parsed: Value = match serde_json::from_str(&body_text) {
+        let parsed: Value = match serde_json::from_slice(resp.body()) {
             Ok(v) => v,
             Err(_) => continue,
         };
@@ -340,6 +451,62 @@ async fn rate_limits() -> Json<RateLimitsResponse> {
     Json(RateLimitsResponse { tokens: out })
 }
 
+#[derive(Debug, Serialize)]
+struct InternalRateLimit {
+    token: String,
+    budget: &'static str,
+    limit: i64,
+    remaining: i64,
+    reset_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct InternalRateLimitsResponse {
+    tokens: Vec<InternalRateLimit>,
+}
+
+// Reports the broker's token pool (what scheduling actually uses) without calling GitHub.
+async fn internal_rate_limits(
+    State(broker): State<Arc<dyn GithubBroker>>,
+) -> Json<InternalRateLimitsResponse> {
+    let tokens = broker
+        .rate_limit_snapshot()
+        .await
+        .into_iter()
+        .map(|entry| InternalRateLimit {
+            token: entry.token_id,
+            budget: entry.budget.as_str(),
+            limit: entry.limit,
+            remaining: entry.remaining,
+            reset_at: entry.reset_at,
+        })
+        .collect();
+    Json(InternalRateLimitsResponse { tokens })
+}
+
+fn config_overrides(args: &CliArgs) -> Vec<(&'static str, String)> {
+    let mut overrides = Vec::new();
+    if args.once {
+        overrides.push(("collector.run_once", "true".to_string()));
+    }
+    if let Some(repo) = &args.repo {
+        overrides.push(("collector.repo", repo.clone()));
+    }
+    if args.dry_run {
+        overrides.push(("collector.dry_run", "true".to_string()));
+    }
+    overrides
+}
+
+fn parse_repo_arg(input: &str) -> Result<String, String> {
+    match input.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+            Ok(input.to_string())
+        }
+        _ => Err(format!("expected owner/name, got '{}'", input)),
+    }
+}
+
 fn map_queue_bounds(bounds: &HashMap<String, usize>) -> HashMap<(Budget, Priority), usize> {
     let mut mapped = HashMap::new();
     for (key, value) in bounds {
@@ -360,6 +527,13 @@ fn map_weights(weights: &HashMap<String, [u32; 3]>) -> HashMap<Budget, [u32; 3]>
     mapped
 }
 
+fn map_token_budgets(names: &[String]) -> Result<Vec<Budget>> {
+    names
+        .iter()
+        .map(|name| parse_budget(name).ok_or_else(|| anyhow!("unknown token budget `{name}`")))
+        .collect()
+}
+
 fn parse_queue_key(key: &str) -> Option<(Budget, Priority)> {
     let mut parts = key.split('.');
     let budget = parse_budget(parts.next()?)?;
@@ -384,3 +558,44 @@ fn parse_priority(input: &str) -> Option<Priority> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_flags_produce_no_overrides() {
+        let args = CliArgs::parse_from(["collector"]);
+        assert!(config_overrides(&args).is_empty());
+        assert!(args.config.is_none());
+    }
+
+    #[test]
+    fn flags_map_onto_collector_overrides() {
+        let args = CliArgs::parse_from([
+            "collector",
+            "--once",
+            "--repo",
+            "rust-lang/rust",
+            "--dry-run",
+            "--config",
+            "config/cron.toml",
+        ]);
+        assert_eq!(
+            config_overrides(&args),
+            vec![
+                ("collector.run_once", "true".to_string()),
+                ("collector.repo", "rust-lang/rust".to_string()),
+                ("collector.dry_run", "true".to_string()),
+            ]
+        );
+        assert_eq!(args.config, Some(PathBuf::from("config/cron.toml")));
+    }
+
+    #[test]
+    fn repo_flag_requires_owner_and_name() {
+        assert!(CliArgs::try_parse_from(["collector", "--repo", "rust"]).is_err());
+        assert!(CliArgs::try_parse_from(["collector", "--repo", "a/b/c"]).is_err());
+        assert!(CliArgs::try_parse_from(["collector", "--repo", "/rust"]).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use common::labels::RepoLabels;
 use once_cell::sync::Lazy;
 use prometheus::{
     register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
@@ -5,6 +6,14 @@ use prometheus::{
     IntGauge, IntGaugeVec,
 };
 
+/// Caps the `repo` label values used by the per-repository metrics below.
+pub static REPO_LABELS: Lazy<RepoLabels> = Lazy::new(RepoLabels::default);
+
+/// `repo` label value for `full_name`, folded into `other` past the cap.
+pub fn repo_label(full_name: &str) -> String {
+    REPO_LABELS.label(full_name)
+}
+
 pub static RUNS_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
         "collector_runs_total",
@@ -69,6 +78,14 @@ pub static ACTIVE_REPOS: Lazy<IntGauge> = Lazy::new(|| {
     .expect("collector active repositories gauge")
 });
 
+pub static DB_UNAVAILABLE: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "collector_db_unavailable",
+        "1 while repeated database connection failures keep the collector backing off"
+    )
+    .expect("collector db unavailable gauge")
+});
+
 pub static REPOS_PROCESSED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "collector_repositories_processed_total",
@@ -87,6 +104,15 @@ pub static ISSUES_PROCESSED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     .expect("collector issues processed")
 });
 
+pub static ISSUES_UNCHANGED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "collector_issues_unchanged_total",
+        "Total number of issues skipped because their stored content hash and state were unchanged",
+        &["repo"]
+    )
+    .expect("collector issues unchanged")
+});
+
 pub static COMMENTS_PROCESSED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "collector_comments_processed_total",
@@ -96,6 +122,24 @@ pub static COMMENTS_PROCESSED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     .expect("collector comments processed")
 });
 
+pub static COMMENT_FETCHES_SKIPPED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "collector_comment_fetches_skipped_total",
+        "Issues whose comments were not re-paged because count and updated_at were unchanged",
+        &["repo"]
+    )
+    .expect("collector comment fetches skipped")
+});
+
+pub static FEATURE_SAMPLES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "collector_feature_samples_total",
+        "Unflagged issues/comments whose features were stored as clean training samples",
+        &["subject_type"]
+    )
+    .expect("collector feature samples")
+});
+
 pub static USERS_FETCHED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
         "collector_users_fetched_total",
@@ -104,6 +148,15 @@ pub static USERS_FETCHED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
     .expect("collector users fetched")
 });
 
+pub static USER_FETCHES_SKIPPED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "collector_user_fetches_skipped_total",
+        "Author lookups answered without a users/{login} call, by reason",
+        &["reason"]
+    )
+    .expect("collector user fetches skipped")
+});
+
 pub static RUN_DURATION: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!(
         "collector_run_duration_seconds",
@@ -168,6 +221,57 @@ pub static REPO_LAST_SUCCESS_TIMESTAMP: Lazy<IntGaugeVec> = Lazy::new(|| {
     .expect("collector repository last success timestamp")
 });
 
+pub static NEW_ACCOUNTS_SEEN: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "collector_new_accounts_seen",
+        "Distinct recently created accounts that authored content in a repository during the last run",
+        &["repo"]
+    )
+    .expect("collector new accounts seen")
+});
+
+pub static REPO_RENAMED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "collector_repo_renamed_total",
+        "Seeds whose repository was renamed upstream and followed to its new name"
+    )
+    .expect("collector repo renamed")
+});
+
+pub static REPO_DEADLINE_EXCEEDED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "collector_repo_deadline_exceeded_total",
+        "Repository jobs abandoned after exceeding collector.repo_deadline_secs"
+    )
+    .expect("collector repo deadline exceeded")
+});
+
+pub static REPO_PAGE_CAP_REACHED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "collector_repo_page_cap_reached_total",
+        "Repository jobs left pending after walking collector.max_pages_per_repo issue pages"
+    )
+    .expect("collector repo page cap reached")
+});
+
+pub static EDIT_HISTORY_FETCHES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "collector_edit_history_fetches_total",
+        "Edit histories fetched for subjects over collector.edit_history_min_score",
+        &["subject_type"]
+    )
+    .expect("collector edit history fetches")
+});
+
+pub static REACTIONS_FETCHES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "collector_reactions_fetches_total",
+        "Reactions fetched for subjects over collector.reactions_min_score",
+        &["subject_type"]
+    )
+    .expect("collector reactions fetches")
+});
+
 // Per-fetcher metrics (REST vs GraphQL)
 pub static FETCH_REQUESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -197,6 +301,15 @@ pub static FETCH_LATENCY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     .expect("collector fetch latency seconds")
 });
 
+pub static REST_PREFETCH_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "collector_rest_prefetch_total",
+        "REST pages requested ahead of the caller, by operation and whether they were used or discarded",
+        &["op", "outcome"]
+    )
+    .expect("collector rest prefetch total")
+});
+
 // Skips: track benign 404s that are intentionally treated as non-fatal
 pub static COMMENTS_404_SKIPS_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
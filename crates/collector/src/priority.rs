@@ -0,0 +1,51 @@
+use std::future::Future;
+
+use gh_broker::Priority;
+
+tokio::task_local! {
+    static JOB_PRIORITY: Priority;
+}
+
+/// Broker priority for a `collection_jobs.priority` value: positive jobs are
+/// urgent, zero is the default, negative marks backfill work.
+pub fn broker_priority(job_priority: i32) -> Priority {
+    match job_priority {
+        p if p > 0 => Priority::Critical,
+        0 => Priority::Normal,
+        _ => Priority::Backfill,
+    }
+}
+
+/// Runs `fut` with every GitHub request it issues enqueued at `priority`.
+pub async fn with_job_priority<F: Future>(priority: Priority, fut: F) -> F::Output {
+    JOB_PRIORITY.scope(priority, fut).await
+}
+
+/// The priority of the job being collected, or `default` outside a job (e.g.
+/// ad-hoc fetches from tests or tooling).
+pub fn job_priority_or(default: Priority) -> Priority {
+    JOB_PRIORITY.try_with(|p| *p).unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_priorities_map_to_broker_priorities() {
+        assert_eq!(broker_priority(10), Priority::Critical);
+        assert_eq!(broker_priority(1), Priority::Critical);
+        assert_eq!(broker_priority(0), Priority::Normal);
+        assert_eq!(broker_priority(-1), Priority::Backfill);
+    }
+
+    #[tokio::test]
+    async fn scope_overrides_the_default() {
+        assert_eq!(job_priority_or(Priority::Critical), Priority::Critical);
+        let inside = with_job_priority(Priority::Backfill, async {
+            job_priority_or(Priority::Critical)
+        })
+        .await;
+        assert_eq!(inside, Priority::Backfill);
+    }
+}
@@ -1,13 +1,18 @@
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use analysis::{score_comment, score_issue, ContributionStats, RuleEngine};
+use analysis::{
+    thread_bursts, ContributionStats, FirstReply, RuleEngine, RuleOutcome, Scorer, ThreadBurst,
+};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use db::models::{
-    CollectionJobUpdate, CollectionStatus, CommentRow, IssueRow, RepositoryRow, SpamFlagUpsert,
-    UserRow, WatermarkUpdate,
+    CollectionJobRow, CollectionJobUpdate, CollectionStatus, CommentRow, ContentEditsUpsert,
+    FeatureSampleUpsert, IssueFingerprint, IssueRow, IssueStateTransition, ProgressUpdate,
+    ReactionRow, RepositoryRow, SpamFlagUpsert, UserRow, WatermarkUpdate,
 };
 use db::Repositories;
 use http::StatusCode;
@@ -15,17 +20,24 @@ use normalizer::models::{
     NormalizedComment, NormalizedIssue, NormalizedRepository, NormalizedUser,
 };
 use normalizer::payloads::UserRef;
+use normalizer::NormalizationError;
 use serde::Deserialize;
+use serde_json::Value;
 use thiserror::Error;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
-use tracing::{info, instrument, warn};
+use tracing::{debug, info, instrument, warn};
 
 use crate::client::GithubApiError;
 use crate::fetcher::graphql::GraphqlResourceLimitError;
-use crate::fetcher::{DataFetcher, UserFetch};
+use crate::fetcher::{ContentEdits, DataFetcher, IssueRecord, Reactions, UserFetch};
 use crate::metrics::{self, ActiveRepoGuard};
+use crate::priority;
+use crate::sink::{NoopSink, RecordSink};
 use common::config::CollectorConfig;
-use gh_broker::HttpStatusError;
+use common::text::{truncate_body, truncate_chars};
+use gh_broker::{HttpStatusError, Priority};
 
 #[derive(Debug, Deserialize)]
 pub struct SeedRepo {
@@ -38,6 +50,38 @@ pub struct Collector {
     fetcher: Arc<dyn DataFetcher>,
     repos: Arc<dyn Repositories>,
     max_concurrent_repos: usize,
+    scorer: Arc<dyn Scorer>,
+    sink: Arc<dyn RecordSink>,
+    user_fetches: Arc<Semaphore>,
+    /// Held for the duration of a run so scheduled and triggered runs never overlap.
+    cycle: Arc<Mutex<()>>,
+    /// Database connection failures in a row, across repo jobs and runs.
+    db_failures: Arc<AtomicU32>,
+}
+
+// Upper bound on jobs claimed in one scheduling run.
+const MAX_JOBS_PER_RUN: usize = 100;
+
+// Issues created this close together with the same normalized title count
+// toward `templated_title`.
+const SAME_TITLE_WINDOW_DAYS: i64 = 7;
+
+// Upper bound on previously missing issues re-checked per repository run.
+const MISSING_ISSUE_RECHECK_LIMIT: i64 = 50;
+
+// Upper bound on orphaned issues/comments looked at per user repair pass.
+const USER_REPAIR_LIMIT: i64 = 500;
+
+/// Outcome of [`Collector::repair_users`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UserRepair {
+    /// Issues and comments found pointing at a missing `users` row.
+    pub orphaned: usize,
+    /// Distinct authors stored again (found or marked missing).
+    pub repaired: usize,
+    /// Distinct authors that could not be stored, e.g. because the stored
+    /// payload has no login to look them up by.
+    pub failed: usize,
 }
 
 struct ProcessContext<'a> {
@@ -45,7 +89,12 @@ struct ProcessContext<'a> {
     user_cache: &'a mut HashSet<String>,
     session_counts: &'a mut HashMap<String, u32>,
     dedupe_counts: &'a mut HashMap<String, u32>,
+    new_accounts: &'a mut HashSet<String>,
+    org_members: &'a mut HashMap<i64, bool>,
     repo_full_name: &'a str,
+    repo_is_fork: bool,
+    /// Page comments even when the stored issue says they are current.
+    refetch_comments: bool,
 }
 
 #[derive(Debug, Error)]
@@ -55,6 +104,20 @@ struct SeedMismatchError {
     actual: String,
 }
 
+#[derive(Debug, Error)]
+#[error("repository job exceeded its {secs}s deadline")]
+struct RepoDeadlineExceeded {
+    secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RepoOutcome {
+    /// Pagination ran to the watermark or the last page.
+    Finished,
+    /// Stopped at `max_pages_per_repo`; progress is saved for the next run.
+    Paused,
+}
+
 impl Collector {
     async fn retry_graphql<T, Fut, F>(&self, mut op: F, label: &str) -> Result<T>
     where
@@ -102,85 +165,214 @@ impl Collector {
         repos: Arc<dyn Repositories>,
         max_concurrent_repos: usize,
     ) -> Self {
+        let user_fetches = Arc::new(Semaphore::new(config.max_concurrent_user_fetches.max(1)));
         Self {
             config,
             fetcher,
             repos,
             max_concurrent_repos,
+            scorer: Arc::new(RuleEngine::default()),
+            sink: Arc::new(NoopSink),
+            user_fetches,
+            cycle: Arc::new(Mutex::new(())),
+            db_failures: Arc::new(AtomicU32::new(0)),
         }
     }
 
+    pub fn with_rule_engine(self, rule_engine: RuleEngine) -> Self {
+        self.with_scorer(Arc::new(rule_engine))
+    }
+
+    /// Replaces the rule engine with another scorer for issues and comments.
+    pub fn with_scorer(mut self, scorer: Arc<dyn Scorer>) -> Self {
+        self.scorer = scorer;
+        self
+    }
+
+    pub fn with_sink(mut self, sink: Arc<dyn RecordSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+
     pub async fn run(&self) -> Result<()> {
+        self.run_until(std::future::pending()).await
+    }
+
+    /// Like [`Collector::run`], but stops once `shutdown` resolves. An in-flight
+    /// cycle is allowed to finish; the signal is checked between cycles.
+    pub async fn run_until<F>(&self, shutdown: F) -> Result<()>
+    where
+        F: Future<Output = ()>,
+    {
+        tokio::pin!(shutdown);
         loop {
-            self.run_once().await?;
+            match self.run_once().await {
+                Ok(()) => {}
+                // Counted by the run; back off below instead of exiting.
+                Err(err) if is_db_unavailable(&err) && !self.config.run_once => {
+                    warn!(error = ?err, "database unavailable; collection run aborted");
+                }
+                Err(err) => return Err(err),
+            }
             if self.config.run_once {
                 break;
             }
-            sleep(Duration::from_secs(self.config.interval_secs)).await;
+            let failures = self.db_failures.load(Ordering::Relaxed);
+            let pause = match self.config.db_backoff(failures) {
+                Some(backoff) => {
+                    warn!(
+                        failures,
+                        wait_ms = backoff.as_millis() as u64,
+                        "database unavailable; backing off before the next run"
+                    );
+                    backoff
+                }
+                None => Duration::from_secs(self.config.interval_secs),
+            };
+            tokio::select! {
+                _ = &mut shutdown => {
+                    info!("collector stopping after shutdown signal");
+                    break;
+                }
+                _ = sleep(pause) => {}
+            }
         }
         Ok(())
     }
 
-    #[instrument(skip(self))]
+    /// Runs one collection cycle, waiting for any run already in progress.
     pub async fn run_once(&self) -> Result<()> {
+        let _cycle = self.cycle.lock().await;
+        self.run_cycle().await
+    }
+
+    /// Starts a cycle in the background unless one is already running, in
+    /// which case `None` is returned and nothing is started.
+    pub fn trigger_run(self: &Arc<Self>) -> Option<JoinHandle<Result<()>>> {
+        let cycle = self.cycle.clone().try_lock_owned().ok()?;
+        let collector = self.clone();
+        Some(tokio::spawn(async move {
+            let _cycle = cycle;
+            collector.run_cycle().await
+        }))
+    }
+
+    #[instrument(skip(self))]
+    async fn run_cycle(&self) -> Result<()> {
         let run_started = Utc::now();
         metrics::RUNS_TOTAL.inc();
         metrics::LAST_RUN_TIMESTAMP.set(run_started.timestamp());
         let _timer = metrics::RUN_DURATION.start_timer();
 
-        let pending_jobs = match self
-            .repos
-            .collection_jobs()
-            .get_pending(100)
+        let mut wave = match self
+            .next_wave(self.inflight_repo_limit(), &HashSet::new())
             .await
-            .context("loading pending collection jobs")
         {
-            Ok(jobs) => jobs,
+            Ok(jobs) => {
+                self.db_reachable();
+                jobs
+            }
             Err(err) => {
                 metrics::RUN_FAILURES_TOTAL.inc();
                 metrics::RUN_ERRORS_TOTAL.inc();
+                self.note_db_error(&err);
                 return Err(err);
             }
         };
-        metrics::SEED_REPOS.set(pending_jobs.len() as i64);
-        info!(count = pending_jobs.len(), "loaded pending collection jobs");
-        let rule_version = RuleEngine::default().version().to_string();
+        if wave.is_empty() {
+            if let Some(full_name) = self.config.repo.as_deref() {
+                warn!(repo = %full_name, "no pending collection job for requested repo");
+            }
+        }
+        if self.config.dry_run {
+            // Nothing gets claimed, so show the whole run's worth of jobs up front.
+            let jobs = self.load_pending(MAX_JOBS_PER_RUN).await?;
+            metrics::SEED_REPOS.set(jobs.len() as i64);
+            for job in &jobs {
+                info!(job_id = job.id, repo = %job.full_name, priority = job.priority, "dry run: would collect");
+            }
+            return Ok(());
+        }
+        let rule_version = self.scorer.version().to_string();
         let repo_errors = Arc::new(std::sync::atomic::AtomicU64::new(0));
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(
+        // Repos take a slot per page rather than for their whole run. The semaphore is
+        // FIFO, so a large repo re-queues behind waiting repos after every page and
+        // small repos are interleaved instead of waiting for it to drain.
+        let page_slots = Arc::new(tokio::sync::Semaphore::new(
             self.max_concurrent_repos.max(1),
         ));
         let mut join_set = tokio::task::JoinSet::new();
+        // Jobs are claimed in waves sized to the free in-flight capacity rather
+        // than all at once, so a burst of pending repos cannot pile up tasks.
+        let inflight_limit = self.inflight_repo_limit();
+        let mut claimed: HashSet<i64> = HashSet::new();
+        let mut exhausted = false;
 
-        for job in pending_jobs {
+        loop {
+            while join_set.len() >= inflight_limit {
+                join_set.join_next().await;
+            }
+            if wave.is_empty() && !exhausted {
+                let room = (inflight_limit - join_set.len()).min(MAX_JOBS_PER_RUN - claimed.len());
+                match self.next_wave(room, &claimed).await {
+                    Ok(jobs) => wave = jobs,
+                    Err(err) => {
+                        warn!(error = ?err, "failed to load next wave of collection jobs");
+                        metrics::RUN_ERRORS_TOTAL.inc();
+                        repo_errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        self.note_db_error(&err);
+                    }
+                }
+                exhausted = wave.is_empty();
+            }
+            if self.db_unavailable() {
+                warn!("database unavailable; not claiming further jobs this run");
+                repo_errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                break;
+            }
+            let Some(job) = wave.pop() else {
+                break;
+            };
+            if !claimed.insert(job.id) {
+                continue;
+            }
+            metrics::SEED_REPOS.set(claimed.len() as i64);
             // Mark job as in progress
             if let Err(err) = self.repos.collection_jobs().mark_in_progress(job.id).await {
                 warn!(job_id = job.id, error = ?err, "failed to mark job as in_progress");
+                if err.is_unavailable() {
+                    self.db_failed();
+                }
                 continue;
             }
 
             // Update metrics for in_progress status
+            let repo_label = metrics::repo_label(&job.full_name);
             metrics::REPO_JOB_STATUS
-                .with_label_values(&[&job.full_name, "in_progress"])
+                .with_label_values(&[&repo_label, "in_progress"])
                 .set(1);
             metrics::REPO_JOB_STATUS
-                .with_label_values(&[&job.full_name, "pending"])
+                .with_label_values(&[&repo_label, "pending"])
                 .set(0);
             metrics::REPO_JOB_PRIORITY
-                .with_label_values(&[&job.full_name])
+                .with_label_values(&[&repo_label])
                 .set(job.priority as i64);
             metrics::REPO_LAST_ATTEMPT_TIMESTAMP
-                .with_label_values(&[&job.full_name])
+                .with_label_values(&[&repo_label])
                 .set(Utc::now().timestamp());
-            let semaphore = semaphore.clone();
+            let page_slots = page_slots.clone();
             let fetcher = self.fetcher.clone();
             let repos = self.repos.clone();
             let rule_version = rule_version.clone();
             let repo_errors = repo_errors.clone();
             let config_clone = self.config.clone();
             let max_concurrent_repos = self.max_concurrent_repos;
+            let scorer = self.scorer.clone();
+            let sink = self.sink.clone();
+            let user_fetches = self.user_fetches.clone();
+            let cycle = self.cycle.clone();
+            let db_failures = self.db_failures.clone();
             join_set.spawn(async move {
-                let _permit = semaphore.acquire_owned().await.unwrap();
-
                 let repo_started = Instant::now();
                 let seed = SeedRepo {
                     owner: job.owner.clone(),
@@ -191,13 +383,43 @@ impl Collector {
                 let mut session_counts = HashMap::new();
                 let mut dedupe_counts = HashMap::new();
                 // The nested Collector is only used to call process_repo; concurrency not used there.
-                let c = Collector { config: config_clone, fetcher: fetcher.clone(), repos: repos.clone(), max_concurrent_repos };
-                let result = c
-                    .process_repo(&seed, &rule_version, &mut session_counts, &mut dedupe_counts)
-                    .await;
+                let c = Collector { config: config_clone, fetcher: fetcher.clone(), repos: repos.clone(), max_concurrent_repos, scorer, sink, user_fetches, cycle, db_failures };
+                let work = priority::with_job_priority(
+                    priority::broker_priority(job.priority),
+                    c.process_repo(&seed, &rule_version, &page_slots, &mut session_counts, &mut dedupe_counts),
+                );
+                // Dropping the timed-out future drops its broker calls, which cancels them.
+                let result = match c.config.repo_deadline() {
+                    Some(deadline) => match tokio::time::timeout(deadline, work).await {
+                        Ok(result) => result,
+                        Err(_) => {
+                            metrics::REPO_DEADLINE_EXCEEDED_TOTAL.inc();
+                            Err(RepoDeadlineExceeded { secs: deadline.as_secs() }.into())
+                        }
+                    },
+                    None => work.await,
+                };
 
                 match result {
-                    Ok(_) => {
+                    Ok(RepoOutcome::Paused) => {
+                        c.db_reachable();
+                        metrics::REPO_PAGE_CAP_REACHED_TOTAL.inc();
+                        metrics::REPO_DURATION
+                            .with_label_values(&["paused"])
+                            .observe(repo_started.elapsed().as_secs_f64());
+                        if let Err(err) = repos
+                            .collection_jobs()
+                            .update(CollectionJobUpdate { id: job.id, status: CollectionStatus::Pending, error_message: None })
+                            .await
+                        {
+                            warn!(job_id = job.id, error = ?err, "failed to return paused job to pending");
+                        } else {
+                            metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "pending"]).set(1);
+                            metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "in_progress"]).set(0);
+                        }
+                    }
+                    Ok(RepoOutcome::Finished) => {
+                        c.db_reachable();
                         metrics::REPOS_PROCESSED_TOTAL
                             .with_label_values(&["success"])
                             .inc();
@@ -211,16 +433,17 @@ impl Collector {
                         {
                             warn!(job_id = job.id, error = ?err, "failed to mark job as completed");
                         } else {
-                            metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "completed"]).set(1);
-                            metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "pending"]).set(0);
-                            metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "in_progress"]).set(0);
-                            metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "failed"]).set(0);
-                            metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "error"]).set(0);
-                            metrics::REPO_JOB_FAILURE_COUNT.with_label_values(&[&job.full_name]).set(0);
-                            metrics::REPO_LAST_SUCCESS_TIMESTAMP.with_label_values(&[&job.full_name]).set(Utc::now().timestamp());
+                            metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "completed"]).set(1);
+                            metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "pending"]).set(0);
+                            metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "in_progress"]).set(0);
+                            metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "failed"]).set(0);
+                            metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "error"]).set(0);
+                            metrics::REPO_JOB_FAILURE_COUNT.with_label_values(&[&repo_label]).set(0);
+                            metrics::REPO_LAST_SUCCESS_TIMESTAMP.with_label_values(&[&repo_label]).set(Utc::now().timestamp());
                         }
                     }
                     Err(err) => {
+                        c.note_db_error(&err);
                         let error_details = extract_error_details(&err);
 
                         // Soft-skip: user 404 should not fail the job
@@ -245,13 +468,13 @@ impl Collector {
                             {
                                 warn!(job_id = job.id, error = ?update_err, "failed to mark job as completed (user 404)");
                             } else {
-                                metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "completed"]).set(1);
-                                metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "pending"]).set(0);
-                                metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "in_progress"]).set(0);
-                                metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "failed"]).set(0);
-                                metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "error"]).set(0);
-                                metrics::REPO_JOB_FAILURE_COUNT.with_label_values(&[&job.full_name]).set(0);
-                                metrics::REPO_LAST_SUCCESS_TIMESTAMP.with_label_values(&[&job.full_name]).set(Utc::now().timestamp());
+                                metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "completed"]).set(1);
+                                metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "pending"]).set(0);
+                                metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "in_progress"]).set(0);
+                                metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "failed"]).set(0);
+                                metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "error"]).set(0);
+                                metrics::REPO_JOB_FAILURE_COUNT.with_label_values(&[&repo_label]).set(0);
+                                metrics::REPO_LAST_SUCCESS_TIMESTAMP.with_label_values(&[&repo_label]).set(Utc::now().timestamp());
                             }
                             return;
                         }
@@ -271,20 +494,20 @@ impl Collector {
                         } else {
                             match status {
                                 CollectionStatus::Error => {
-                                    metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "error"]).set(1);
-                                    metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "pending"]).set(0);
-                                    metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "completed"]).set(0);
-                                    metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "in_progress"]).set(0);
-                                    metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "failed"]).set(0);
-                                    metrics::REPO_JOB_FAILURE_COUNT.with_label_values(&[&job.full_name]).set((job.failure_count + 1) as i64);
+                                    metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "error"]).set(1);
+                                    metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "pending"]).set(0);
+                                    metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "completed"]).set(0);
+                                    metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "in_progress"]).set(0);
+                                    metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "failed"]).set(0);
+                                    metrics::REPO_JOB_FAILURE_COUNT.with_label_values(&[&repo_label]).set((job.failure_count + 1) as i64);
                                 }
                                 CollectionStatus::Failed => {
-                                    metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "pending"]).set(1);
-                                    metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "completed"]).set(0);
-                                    metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "in_progress"]).set(0);
-                                    metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "failed"]).set(1);
-                                    metrics::REPO_JOB_STATUS.with_label_values(&[&job.full_name, "error"]).set(0);
-                                    metrics::REPO_JOB_FAILURE_COUNT.with_label_values(&[&job.full_name]).set((job.failure_count + 1) as i64);
+                                    metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "pending"]).set(1);
+                                    metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "completed"]).set(0);
+                                    metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "in_progress"]).set(0);
+                                    metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "failed"]).set(1);
+                                    metrics::REPO_JOB_STATUS.with_label_values(&[&repo_label, "error"]).set(0);
+                                    metrics::REPO_JOB_FAILURE_COUNT.with_label_values(&[&repo_label]).set((job.failure_count + 1) as i64);
                                 }
                                 _ => {}
                             }
@@ -304,6 +527,20 @@ impl Collector {
             });
         }
         while join_set.join_next().await.is_some() {}
+        info!(count = claimed.len(), "processed pending collection jobs");
+
+        // The all-time spammy-user ranking is read from an aggregate; bring it
+        // up to date here, after this run's flag writes, rather than on reads.
+        if !claimed.is_empty() {
+            if let Err(err) = self.repos.spam_flags().refresh_spammy_user_totals().await {
+                warn!(error = ?err, "failed to refresh spammy user totals");
+                metrics::RUN_ERRORS_TOTAL.inc();
+                repo_errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                if err.is_unavailable() {
+                    self.db_failed();
+                }
+            }
+        }
 
         if repo_errors.load(std::sync::atomic::Ordering::Relaxed) == 0 {
             metrics::RUN_SUCCESSES_TOTAL.inc();
@@ -314,17 +551,201 @@ impl Collector {
         Ok(())
     }
 
+    /// Counts `err` toward the database backoff if it is a connection failure.
+    fn note_db_error(&self, err: &anyhow::Error) {
+        if is_db_unavailable(err) {
+            self.db_failed();
+        }
+    }
+
+    fn db_failed(&self) {
+        let failures = self.db_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.config.db_backoff(failures).is_some() {
+            metrics::DB_UNAVAILABLE.set(1);
+        }
+    }
+
+    /// A database round trip succeeded, ending any backoff.
+    fn db_reachable(&self) {
+        if self.db_failures.swap(0, Ordering::Relaxed) > 0 {
+            metrics::DB_UNAVAILABLE.set(0);
+        }
+    }
+
+    /// Enough connection failures in a row that claiming more jobs would only
+    /// fail them too.
+    fn db_unavailable(&self) -> bool {
+        self.config
+            .db_backoff(self.db_failures.load(Ordering::Relaxed))
+            .is_some()
+    }
+
+    /// Repo jobs claimed, and tasks spawned, at once. Defaults to one per page
+    /// slot so no spawned task sits holding its state while it waits for one.
+    fn inflight_repo_limit(&self) -> usize {
+        match self.config.max_inflight_repos {
+            0 => self.max_concurrent_repos.max(1),
+            limit => limit,
+        }
+    }
+
+    /// Pending jobs in priority order, restricted to the `repo` filter when set.
+    /// The filter runs in the query, so a match is found however low it ranks.
+    /// Backfill jobs are left pending outside the configured backfill window.
+    async fn load_pending(&self, limit: usize) -> Result<Vec<CollectionJobRow>> {
+        let jobs = self
+            .repos
+            .collection_jobs()
+            .get_pending(limit as i32, self.config.repo.as_deref())
+            .await
+            .context("loading pending collection jobs")?;
+        let jobs = if self.config.backfill_allowed_at(Utc::now()) {
+            jobs
+        } else {
+            let (jobs, deferred): (Vec<_>, Vec<_>) = jobs
+                .into_iter()
+                .partition(|job| priority::broker_priority(job.priority) != Priority::Backfill);
+            if !deferred.is_empty() {
+                debug!(
+                    deferred = deferred.len(),
+                    "outside the backfill window; leaving backfill jobs pending"
+                );
+            }
+            jobs
+        };
+        Ok(jobs)
+    }
+
+    /// Up to `limit` pending jobs not yet claimed this run, highest priority
+    /// last so they can be popped off the end. Empty once nothing new is pending.
+    async fn next_wave(
+        &self,
+        limit: usize,
+        claimed: &HashSet<i64>,
+    ) -> Result<Vec<CollectionJobRow>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut jobs: Vec<_> = self
+            .load_pending(limit)
+            .await?
+            .into_iter()
+            .filter(|job| !claimed.contains(&job.id))
+            .collect();
+        jobs.reverse();
+        Ok(jobs)
+    }
+
+    /// Re-fetches one issue and its comments and processes them as a listing
+    /// would, without paging the repository (e.g. to re-verify a flag on
+    /// demand). Watermarks are left alone. Returns `false` when GitHub no
+    /// longer serves the issue.
+    pub async fn refresh_issue(&self, owner: &str, name: &str, number: i64) -> Result<bool> {
+        let repo_full_name = format!("{}/{}", owner, name);
+        let repo_row = match self.repos.repos().get_by_full_name(&repo_full_name).await? {
+            Some(row) => row,
+            None => {
+                let snapshot = self
+                    .retry_graphql(|| self.fetcher.fetch_repo(owner, name), "fetch_repo")
+                    .await
+                    .with_context(|| format!("fetching repo {}", repo_full_name))?;
+                let row = to_repo_row(&snapshot.repository);
+                self.repos.repos().upsert(row.clone()).await?;
+                row
+            }
+        };
+        let record = self
+            .retry_graphql(
+                || self.fetcher.fetch_issue(owner, name, repo_row.id, number),
+                "fetch_issue",
+            )
+            .await
+            .with_context(|| format!("fetching issue {}#{}", repo_full_name, number))?;
+        let Some(record) = record else {
+            warn!(repo = %repo_full_name, issue_number = number, "issue not found for refresh");
+            return Ok(false);
+        };
+
+        let rule_version = self.scorer.version().to_string();
+        let mut ctx = ProcessContext {
+            rule_version: &rule_version,
+            user_cache: &mut HashSet::new(),
+            session_counts: &mut HashMap::new(),
+            dedupe_counts: &mut HashMap::new(),
+            new_accounts: &mut HashSet::new(),
+            org_members: &mut HashMap::new(),
+            repo_full_name: &repo_full_name,
+            repo_is_fork: repo_row.is_fork,
+            refetch_comments: true,
+        };
+        self.process_issue(record, owner, name, &mut ctx).await?;
+        Ok(true)
+    }
+
+    /// Re-fetches the authors of issues and comments whose `user_id` has no
+    /// `users` row, e.g. because storing the author failed after the
+    /// subject was written. Authors GitHub no longer serves get a
+    /// `found = false` placeholder, as during collection.
+    pub async fn repair_users(&self) -> Result<UserRepair> {
+        let orphans = self
+            .repos
+            .users()
+            .list_orphaned_authors(USER_REPAIR_LIMIT)
+            .await?;
+        let mut repair = UserRepair {
+            orphaned: orphans.len(),
+            ..UserRepair::default()
+        };
+        let mut authors: HashMap<i64, Option<String>> = HashMap::new();
+        for orphan in orphans {
+            let login = authors.entry(orphan.user_id).or_default();
+            if login.is_none() {
+                *login = orphan.login;
+            }
+        }
+
+        for (user_id, login) in authors {
+            let Some(login) = login else {
+                warn!(
+                    user_id,
+                    "orphaned author has no stored login; cannot re-fetch"
+                );
+                repair.failed += 1;
+                continue;
+            };
+            let user_ref = UserRef { id: user_id, login };
+            // Looked up unconditionally: a settled account with the same login
+            // would not cover a row missing under this id.
+            match self.fetch_and_store_user(&user_ref).await {
+                Ok(()) => repair.repaired += 1,
+                Err(err) => {
+                    warn!(user_id, login = %user_ref.login, error = ?err, "failed to repair orphaned author");
+                    repair.failed += 1;
+                }
+            }
+        }
+        info!(
+            orphaned = repair.orphaned,
+            repaired = repair.repaired,
+            failed = repair.failed,
+            "user repair pass finished"
+        );
+        Ok(repair)
+    }
+
     #[instrument(
-        skip(self, session_counts, dedupe_counts),
-        fields(owner = %seed.owner, repo = %seed.name, page_size = self.config.page_size)
+        skip(self, page_slots, session_counts, dedupe_counts),
+        fields(owner = %seed.owner, repo = %seed.name, page_size = self.config.issue_page_size())
     )]
     async fn process_repo(
         &self,
         seed: &SeedRepo,
         rule_version: &str,
+        page_slots: &Semaphore,
         session_counts: &mut HashMap<String, u32>,
         dedupe_counts: &mut HashMap<String, u32>,
-    ) -> Result<()> {
+    ) -> Result<RepoOutcome> {
+        let slot = page_slots.acquire().await?;
         let _active_repo = ActiveRepoGuard::new();
         let repo_full_name = format!("{}/{}", seed.owner, seed.name);
         let repo_snapshot = self
@@ -341,11 +762,24 @@ impl Collector {
         let expected_full_name = repo_full_name.to_lowercase();
         let actual_full_name = repo_snapshot.repository.full_name.to_lowercase();
         if expected_full_name != actual_full_name {
-            return Err(SeedMismatchError {
-                expected: repo_full_name,
-                actual: repo_snapshot.repository.full_name.clone(),
+            // GitHub follows renames, so the same repository id under a new name is
+            // the seed we asked for; only a different id is a genuine mismatch.
+            let known = self
+                .repos
+                .repos()
+                .get_by_id(repo_snapshot.repository.id)
+                .await?;
+            let renamed =
+                known.is_some_and(|row| row.full_name.to_lowercase() == expected_full_name);
+            if !renamed {
+                return Err(SeedMismatchError {
+                    expected: repo_full_name,
+                    actual: repo_snapshot.repository.full_name.clone(),
+                }
+                .into());
             }
-            .into());
+            self.follow_rename(seed, &repo_full_name, &repo_snapshot.repository.full_name)
+                .await?;
         }
         let repo_row = to_repo_row(&repo_snapshot.repository);
         self.repos.repos().upsert(repo_row.clone()).await?;
@@ -360,11 +794,31 @@ impl Collector {
 
         let mut cursor: Option<String> = None;
         let mut newest_ts: Option<DateTime<Utc>> = watermark;
+        // Resume from the last completed page if a previous run stopped mid-repo.
+        if let Some(progress) = self
+            .repos
+            .watermarks()
+            .get_progress(&repo_row.full_name)
+            .await?
+        {
+            info!(
+                full_name = %repo_row.full_name,
+                cursor = %progress.cursor,
+                "resuming repository pagination"
+            );
+            cursor = Some(progress.cursor);
+            newest_ts = newest_ts.max(progress.newest_ts);
+        }
         let mut user_cache = HashSet::new();
+        let mut new_accounts = HashSet::new();
+        let mut org_members = HashMap::new();
         let mut seen_existing = false;
+        let mut pages: usize = 0;
+        drop(slot);
 
         loop {
-            let mut issues_page_size: u32 = self.config.page_size.clamp(20, 100);
+            let _slot = page_slots.acquire().await?;
+            let mut issues_page_size: u32 = self.config.issue_page_size().clamp(20, 100);
             let page = {
                 let mut attempt: u32 = 0;
                 let max_attempts: u32 = 15;
@@ -415,62 +869,30 @@ impl Collector {
             }
 
             for record in page.items {
-                let issue = record.issue;
+                let updated_at = record.issue.updated_at;
                 if let Some(since) = watermark {
-                    if issue.updated_at <= since {
+                    if updated_at <= since {
                         seen_existing = true;
                         break;
                     }
                 }
-
-                let issue_row = to_issue_row(&issue);
-                let (posts_before, user_row) = if let Some(user_ref) = &record.author {
-                    let posts = record_post(session_counts, &user_ref.login);
-                    self.ensure_user(user_ref, &mut user_cache).await?;
-                    let user_row = self.repos.users().get_by_login(&user_ref.login).await?;
-                    (posts, user_row)
-                } else {
-                    (0, None)
-                };
-                let dedupe_hits = record_dedupe(dedupe_counts, &issue.dedupe_hash);
-
-                self.repos.issues().upsert(issue_row.clone()).await?;
-                metrics::ISSUES_PROCESSED_TOTAL
-                    .with_label_values(&[&repo_full_name])
-                    .inc();
-                let stats = ContributionStats {
-                    posts_last_24h: posts_before,
-                    dedupe_hits_last_48h: dedupe_hits,
-                };
-                let outcome = score_issue(&issue_row, user_row.as_ref(), stats, dedupe_hits);
-                if outcome.score > 0.0 {
-                    self.repos
-                        .spam_flags()
-                        .upsert(SpamFlagUpsert {
-                            subject_type: "issue".into(),
-                            subject_id: issue_row.id,
-                            score: outcome.score,
-                            reasons: outcome.reasons.clone(),
-                            version: rule_version.to_string(),
-                        })
-                        .await?;
-                }
                 newest_ts = Some(match newest_ts {
-                    Some(existing) if existing > issue_row.updated_at => existing,
-                    _ => issue_row.updated_at,
+                    Some(existing) if existing > updated_at => existing,
+                    _ => updated_at,
                 });
-
-                if issue_row.comments_count > 0 {
-                    let mut ctx = ProcessContext {
-                        rule_version,
-                        user_cache: &mut user_cache,
-                        session_counts,
-                        dedupe_counts,
-                        repo_full_name: &repo_full_name,
-                    };
-                    self.process_comments(&issue_row, &seed.owner, &seed.name, &mut ctx)
-                        .await?;
-                }
+                let mut ctx = ProcessContext {
+                    rule_version,
+                    user_cache: &mut user_cache,
+                    session_counts,
+                    dedupe_counts,
+                    new_accounts: &mut new_accounts,
+                    org_members: &mut org_members,
+                    repo_full_name: &repo_full_name,
+                    repo_is_fork: repo_row.is_fork,
+                    refetch_comments: false,
+                };
+                self.process_issue(record, &seed.owner, &seed.name, &mut ctx)
+                    .await?;
             }
 
             if seen_existing {
@@ -478,8 +900,55 @@ impl Collector {
             }
 
             cursor = page.next_cursor;
-            if cursor.is_none() {
-                break;
+            match &cursor {
+                Some(next) => {
+                    self.repos
+                        .watermarks()
+                        .set_progress(ProgressUpdate {
+                            repo_full_name: repo_row.full_name.clone(),
+                            cursor: next.clone(),
+                            newest_ts,
+                        })
+                        .await?;
+                }
+                None => break,
+            }
+            pages += 1;
+            let max_pages = self.config.max_pages_per_repo;
+            if max_pages > 0 && pages >= max_pages {
+                // The cursor is saved, so the next run picks up from here.
+                info!(
+                    full_name = %repo_row.full_name,
+                    pages,
+                    "page cap reached; leaving repository pending"
+                );
+                return Ok(RepoOutcome::Paused);
+            }
+        }
+
+        // Issues that 404'd earlier are skipped by the watermark once their
+        // updated_at falls behind it, so re-check them explicitly.
+        {
+            let _slot = page_slots.acquire().await?;
+            let missing = self
+                .repos
+                .issues()
+                .list_missing(repo_row.id, MISSING_ISSUE_RECHECK_LIMIT)
+                .await?;
+            let mut ctx = ProcessContext {
+                rule_version,
+                user_cache: &mut user_cache,
+                session_counts,
+                dedupe_counts,
+                new_accounts: &mut new_accounts,
+                org_members: &mut org_members,
+                repo_full_name: &repo_full_name,
+                repo_is_fork: repo_row.is_fork,
+                refetch_comments: false,
+            };
+            for issue in missing {
+                self.process_comments(&issue, &seed.owner, &seed.name, &mut ctx)
+                    .await?;
             }
         }
 
@@ -492,7 +961,179 @@ impl Collector {
                 })
                 .await?;
         }
+        self.repos
+            .watermarks()
+            .clear_progress(&repo_row.full_name)
+            .await?;
+        metrics::NEW_ACCOUNTS_SEEN
+            .with_label_values(&[&metrics::repo_label(&repo_full_name)])
+            .set(new_accounts.len() as i64);
+
+        Ok(RepoOutcome::Finished)
+    }
 
+    /// Stores, scores and walks the comments of one listed issue.
+    async fn process_issue(
+        &self,
+        record: IssueRecord,
+        owner: &str,
+        name: &str,
+        ctx: &mut ProcessContext<'_>,
+    ) -> Result<()> {
+        let issue = record.issue;
+        let pr_mode = self.config.pull_request_mode;
+        if issue.is_pull_request && !pr_mode.stores() {
+            return Ok(());
+        }
+
+        let issue_row = to_issue_row(&issue);
+        let (posts_before, user_row) = if let Some(user_ref) = &record.author {
+            let posts = record_post(ctx.session_counts, &user_ref.login);
+            self.ensure_user(user_ref, ctx.user_cache).await?;
+            let user_row = self.repos.users().get_by_login(&user_ref.login).await?;
+            self.note_new_account(user_row.as_ref(), ctx.new_accounts);
+            (posts, user_row)
+        } else {
+            (0, None)
+        };
+        let dedupe_hits = record_dedupe(ctx.dedupe_counts, &issue.dedupe_hash);
+
+        let stored_issue = cap_issue_body(issue_row.clone(), self.config.max_body_bytes);
+        let fingerprint = self.repos.issues().fingerprint(issue_row.id).await?;
+        let change = classify_issue_change(fingerprint.as_ref(), &issue_row);
+        let comments_current =
+            !ctx.refetch_comments && comments_unchanged(fingerprint.as_ref(), &issue_row);
+        if change == IssueChange::Unchanged {
+            metrics::ISSUES_UNCHANGED_TOTAL
+                .with_label_values(&[&metrics::repo_label(ctx.repo_full_name)])
+                .inc();
+        } else {
+            self.repos.issues().upsert(stored_issue.clone()).await?;
+            // Only after the upsert: a failure in between must not record the
+            // same transition again on the next run.
+            if let Some(transition) = self.state_transition(fingerprint.as_ref(), &issue_row) {
+                self.repos.issues().record_transition(transition).await?;
+            }
+            self.sink.write_issue(&issue).await?;
+            metrics::ISSUES_PROCESSED_TOTAL
+                .with_label_values(&[&metrics::repo_label(ctx.repo_full_name)])
+                .inc();
+        }
+        if change == IssueChange::ContentChanged
+            && (!issue_row.is_pull_request || pr_mode.scores())
+            && self.scores_repo(ctx)
+        {
+            let stats = ContributionStats {
+                posts_last_24h: posts_before,
+                dedupe_hits_last_48h: dedupe_hits,
+                org_member: self
+                    .is_org_member(owner, user_row.as_ref(), ctx.org_members)
+                    .await?,
+                thread_burst: ThreadBurst::default(),
+                not_planned_closures: match issue_row.user_id {
+                    Some(user_id) => {
+                        self.repos
+                            .issues()
+                            .count_closed_as(user_id, "not_planned")
+                            .await? as u32
+                    }
+                    None => 0,
+                },
+                rapid_closures: match issue_row.user_id {
+                    Some(user_id) if self.config.rapid_closure_window_mins > 0 => {
+                        self.repos.issues().count_rapid_closures(user_id).await? as u32
+                    }
+                    _ => 0,
+                },
+                same_title_issues: self
+                    .repos
+                    .issues()
+                    .count_same_title(&issue_row, chrono::Duration::days(SAME_TITLE_WINDOW_DAYS))
+                    .await? as u32,
+                repo_is_fork: ctx.repo_is_fork,
+                ..Default::default()
+            };
+            let mut outcome = self
+                .scorer
+                .score_issue(&issue_row, user_row.as_ref(), stats.clone(), dedupe_hits)
+                .await?;
+            outcome.suppress(self.config.suppressed_reasons_for(ctx.repo_full_name));
+            if let Some(stats) = self
+                .follow_up_stats("issue", issue_row.id, &issue_row.raw, outcome.score, &stats)
+                .await?
+            {
+                outcome = self
+                    .scorer
+                    .score_issue(&issue_row, user_row.as_ref(), stats, dedupe_hits)
+                    .await?;
+                outcome.suppress(self.config.suppressed_reasons_for(ctx.repo_full_name));
+            }
+            if outcome.score > 0.0 {
+                self.repos
+                    .spam_flags()
+                    .upsert(SpamFlagUpsert {
+                        subject_type: "issue".into(),
+                        subject_id: issue_row.id,
+                        score: outcome.score,
+                        reasons: outcome.reasons.clone(),
+                        version: ctx.rule_version.to_string(),
+                        features: self.flag_features(&outcome)?,
+                    })
+                    .await?;
+            } else {
+                self.sample_clean("issue", issue_row.id, &outcome, ctx.rule_version)
+                    .await?;
+            }
+        }
+
+        if issue_row.comments_count > 0 {
+            if comments_current {
+                metrics::COMMENT_FETCHES_SKIPPED_TOTAL
+                    .with_label_values(&[&metrics::repo_label(ctx.repo_full_name)])
+                    .inc();
+            } else {
+                self.process_comments(&stored_issue, owner, name, ctx)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn follow_rename(
+        &self,
+        seed: &SeedRepo,
+        old_full_name: &str,
+        new_full_name: &str,
+    ) -> Result<()> {
+        let Some((new_owner, new_name)) = new_full_name.split_once('/') else {
+            return Err(SeedMismatchError {
+                expected: old_full_name.to_string(),
+                actual: new_full_name.to_string(),
+            }
+            .into());
+        };
+        info!(
+            from = old_full_name,
+            to = new_full_name,
+            "repository was renamed upstream; following the new name"
+        );
+        metrics::REPO_RENAMED_TOTAL.inc();
+        if !self
+            .repos
+            .collection_jobs()
+            .rename(&seed.owner, &seed.name, new_owner, new_name)
+            .await?
+        {
+            warn!(
+                from = old_full_name,
+                to = new_full_name,
+                "collection job not renamed; a job for the new name may already exist"
+            );
+        }
+        self.repos
+            .watermarks()
+            .rename(old_full_name, new_full_name)
+            .await?;
         Ok(())
     }
 
@@ -501,7 +1142,7 @@ impl Collector {
         fields(
             repo = ctx.repo_full_name,
             issue_number = issue.number,
-            page_size = self.config.page_size
+            page_size = self.config.comment_page_size()
         )
     )]
     async fn process_comments(
@@ -511,7 +1152,15 @@ impl Collector {
         name: &str,
         ctx: &mut ProcessContext<'_>,
     ) -> Result<()> {
+        if issue.is_pull_request && self.config.skip_pull_request_comments {
+            return Ok(());
+        }
         let mut cursor: Option<String> = None;
+        // Comments arrive oldest first, so only the very first record replies to the issue itself
+        let mut first_reply = Some(FirstReply {
+            issue_created_at: issue.created_at,
+            issue_author_id: issue.user_id,
+        });
         loop {
             let page = match self
                 .retry_graphql(
@@ -523,7 +1172,7 @@ impl Collector {
                                 issue.number,
                                 issue.id,
                                 cursor.clone(),
-                                self.config.page_size,
+                                self.config.comment_page_size(),
                             )
                             .await
                     },
@@ -556,12 +1205,17 @@ impl Collector {
                 break;
             }
 
+            // Store the whole page first: scoring needs the thread's burst shape.
+            let scores_repo = self.scores_repo(ctx);
+            let mut scored = Vec::with_capacity(page.items.len());
+            let mut comments = Vec::with_capacity(page.items.len());
             for record in page.items {
                 let comment = record.comment;
                 let (posts_before, user_row) = if let Some(user_ref) = &record.author {
                     let posts = record_post(ctx.session_counts, &user_ref.login);
                     self.ensure_user(user_ref, ctx.user_cache).await?;
                     let user_row = self.repos.users().get_by_login(&user_ref.login).await?;
+                    self.note_new_account(user_row.as_ref(), ctx.new_accounts);
                     (posts, user_row)
                 } else {
                     (0, None)
@@ -569,42 +1223,431 @@ impl Collector {
                 let dedupe_hits = record_dedupe(ctx.dedupe_counts, &comment.dedupe_hash);
 
                 let comment_row = to_comment_row(&comment);
-                self.repos.comments().upsert(comment_row.clone()).await?;
-                metrics::COMMENTS_PROCESSED_TOTAL
-                    .with_label_values(&[ctx.repo_full_name])
-                    .inc();
+                // Unscored forks skip the membership lookup.
+                let org_member = scores_repo
+                    && self
+                        .is_org_member(owner, user_row.as_ref(), ctx.org_members)
+                        .await?;
                 let stats = ContributionStats {
                     posts_last_24h: posts_before,
                     dedupe_hits_last_48h: dedupe_hits,
+                    org_member,
+                    thread_burst: ThreadBurst::default(),
+                    repo_is_fork: ctx.repo_is_fork,
+                    ..Default::default()
                 };
-                let outcome = score_comment(&comment_row, user_row.as_ref(), stats, dedupe_hits);
+                scored.push((comment_row, user_row, stats, first_reply.take()));
+                comments.push(comment);
+            }
+            let rows = scored
+                .iter()
+                .map(|(comment_row, ..)| {
+                    cap_comment_body(comment_row.clone(), self.config.max_body_bytes)
+                })
+                .collect();
+            self.repos.comments().upsert_many(rows).await?;
+            for comment in &comments {
+                self.sink.write_comment(comment).await?;
+            }
+            metrics::COMMENTS_PROCESSED_TOTAL
+                .with_label_values(&[&metrics::repo_label(ctx.repo_full_name)])
+                .inc_by(comments.len() as u64);
+
+            if !scores_repo {
+                scored.clear();
+            }
+            let timeline: Vec<_> = scored
+                .iter()
+                .map(|(comment_row, user_row, _, _)| {
+                    (
+                        comment_row.created_at,
+                        user_row
+                            .as_ref()
+                            .is_some_and(|user| self.is_new_account(user)),
+                    )
+                })
+                .collect();
+            let mut flags = Vec::new();
+            for ((comment_row, user_row, mut stats, first_reply), burst) in
+                scored.into_iter().zip(thread_bursts(&timeline))
+            {
+                stats.thread_burst = burst;
+                let dedupe_hits = stats.dedupe_hits_last_48h;
+                let mut outcome = self
+                    .scorer
+                    .score_comment(
+                        &comment_row,
+                        user_row.as_ref(),
+                        stats.clone(),
+                        dedupe_hits,
+                        first_reply.as_ref(),
+                    )
+                    .await?;
+                outcome.suppress(self.config.suppressed_reasons_for(ctx.repo_full_name));
+                if let Some(stats) = self
+                    .follow_up_stats(
+                        "comment",
+                        comment_row.id,
+                        &comment_row.raw,
+                        outcome.score,
+                        &stats,
+                    )
+                    .await?
+                {
+                    outcome = self
+                        .scorer
+                        .score_comment(
+                            &comment_row,
+                            user_row.as_ref(),
+                            stats,
+                            dedupe_hits,
+                            first_reply.as_ref(),
+                        )
+                        .await?;
+                    outcome.suppress(self.config.suppressed_reasons_for(ctx.repo_full_name));
+                }
                 if outcome.score > 0.0 {
-                    self.repos
-                        .spam_flags()
-                        .upsert(SpamFlagUpsert {
-                            subject_type: "comment".into(),
-                            subject_id: comment_row.id,
-                            score: outcome.score,
-                            reasons: outcome.reasons.clone(),
-                            version: ctx.rule_version.to_string(),
-                        })
+                    flags.push(SpamFlagUpsert {
+                        subject_type: "comment".into(),
+                        subject_id: comment_row.id,
+                        score: outcome.score,
+                        reasons: outcome.reasons.clone(),
+                        version: ctx.rule_version.to_string(),
+                        features: self.flag_features(&outcome)?,
+                    });
+                } else {
+                    self.sample_clean("comment", comment_row.id, &outcome, ctx.rule_version)
                         .await?;
                 }
             }
+            self.repos.spam_flags().upsert_many(flags).await?;
 
             cursor = page.next_cursor;
             if cursor.is_none() {
                 break;
             }
         }
+        if !issue.found {
+            info!(
+                repo = ctx.repo_full_name,
+                issue_number = issue.number,
+                "previously missing issue is reachable again"
+            );
+            let mut restored = issue.clone();
+            restored.found = true;
+            self.repos.issues().upsert(restored).await?;
+            // The author may have been caught by the same outage; the issue is not
+            // re-listed, so nothing else would re-fetch them.
+            if let Some(author_id) = issue.user_id {
+                if let Some(author) = self.repos.users().get_by_id(author_id).await? {
+                    if !author.found {
+                        let user_ref = UserRef {
+                            id: author.id,
+                            login: author.login,
+                        };
+                        self.ensure_user(&user_ref, ctx.user_cache).await?;
+                    }
+                }
+            }
+        }
         Ok(())
     }
 
+    /// The state/lock change from the stored to the incoming issue, when
+    /// `rapid_closure_window_mins` enables tracking them.
+    fn state_transition(
+        &self,
+        stored: Option<&IssueFingerprint>,
+        incoming: &IssueRow,
+    ) -> Option<IssueStateTransition> {
+        let window = self.config.rapid_closure_window_mins;
+        let stored = stored.filter(|_| window > 0)?;
+        // GraphQL issue listings keep GitHub's upper-case states.
+        if stored.state.eq_ignore_ascii_case(&incoming.state) && stored.locked == incoming.locked {
+            return None;
+        }
+        let rapid_maintainer_closure = stored.state.eq_ignore_ascii_case("open")
+            && incoming.state.eq_ignore_ascii_case("closed")
+            && incoming.closed_at.is_some_and(|closed_at| {
+                closed_at - incoming.created_at <= chrono::Duration::minutes(window as i64)
+            })
+            && closed_by_maintainer(incoming);
+        Some(IssueStateTransition {
+            issue_id: incoming.id,
+            user_id: incoming.user_id,
+            from_state: stored.state.clone(),
+            to_state: incoming.state.clone(),
+            from_locked: stored.locked,
+            to_locked: incoming.locked,
+            rapid_maintainer_closure,
+        })
+    }
+
+    /// Whether issues/comments of the repository being processed are scored.
+    fn scores_repo(&self, ctx: &ProcessContext<'_>) -> bool {
+        !(ctx.repo_is_fork && self.config.skip_fork_scoring)
+    }
+
+    fn note_new_account(&self, user: Option<&UserRow>, new_accounts: &mut HashSet<String>) {
+        if let Some(user) = user.filter(|user| self.is_new_account(user)) {
+            new_accounts.insert(user.login.clone());
+        }
+    }
+
+    /// The features to store with a flag, when `store_flag_features` is on.
+    fn flag_features(&self, outcome: &RuleOutcome) -> Result<Option<serde_json::Value>> {
+        if !self.config.store_flag_features {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::to_value(&outcome.features)?))
+    }
+
+    /// Keeps the features of an unflagged subject in `feature_samples` when it
+    /// falls in the `clean_sample_rate` sample.
+    async fn sample_clean(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+        outcome: &RuleOutcome,
+        rule_version: &str,
+    ) -> Result<()> {
+        if !in_clean_sample(subject_id, self.config.clean_sample_rate) {
+            return Ok(());
+        }
+        self.repos
+            .spam_flags()
+            .upsert_feature_sample(FeatureSampleUpsert {
+                subject_type: subject_type.into(),
+                subject_id,
+                features: serde_json::to_value(&outcome.features)?,
+                version: rule_version.to_string(),
+            })
+            .await?;
+        metrics::FEATURE_SAMPLES_TOTAL
+            .with_label_values(&[subject_type])
+            .inc();
+        Ok(())
+    }
+
+    /// `stats` completed with the edit history and reactions fetched for a subject
+    /// whose preliminary `score` warranted them; `None` when neither was fetched.
+    async fn follow_up_stats(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+        raw: &Value,
+        score: f32,
+        stats: &ContributionStats,
+    ) -> Result<Option<ContributionStats>> {
+        let edits = self
+            .edit_history(subject_type, subject_id, raw, score)
+            .await?;
+        let reactions = self.reactions(subject_type, subject_id, raw, score).await?;
+        if edits.is_none() && reactions.is_none() {
+            return Ok(None);
+        }
+        let mut stats = stats.clone();
+        if let Some(edits) = edits {
+            stats.edit_count = edits.total_count;
+            stats.last_edited_at = edits.last_edited_at;
+        }
+        if let Some(reactions) = reactions {
+            let mut reactors: HashMap<&str, bool> = HashMap::new();
+            for reaction in &reactions.reactions {
+                reactors.insert(
+                    &reaction.login,
+                    self.created_recently(reaction.account_created_at),
+                );
+            }
+            stats.reactor_count = reactors.len() as u32;
+            if !reactors.is_empty() {
+                let new = reactors.values().filter(|is_new| **is_new).count();
+                stats.new_reactor_frac = new as f32 / reactors.len() as f32;
+            }
+        }
+        Ok(Some(stats))
+    }
+
+    /// Fetches and stores who reacted to a subject whose preliminary score
+    /// reached `reactions_min_score`, so only likely spam pays for the lookup.
+    async fn reactions(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+        raw: &Value,
+        score: f32,
+    ) -> Result<Option<Reactions>> {
+        let min_score = self.config.reactions_min_score;
+        if min_score <= 0.0 || score < min_score {
+            return Ok(None);
+        }
+        let Some(node_id) = raw.get("node_id").and_then(Value::as_str) else {
+            return Ok(None);
+        };
+        let Some(reactions) = self.fetcher.fetch_reactions(node_id).await? else {
+            return Ok(None);
+        };
+        let rows = reactions
+            .reactions
+            .iter()
+            .map(|reaction| ReactionRow {
+                user_login: reaction.login.clone(),
+                content: reaction.content.clone(),
+                user_created_at: reaction.account_created_at,
+            })
+            .collect();
+        self.repos
+            .spam_flags()
+            .replace_reactions(subject_type, subject_id, rows)
+            .await?;
+        metrics::REACTIONS_FETCHES_TOTAL
+            .with_label_values(&[subject_type])
+            .inc();
+        Ok(Some(reactions))
+    }
+
+    /// Fetches and stores the edit history of a subject whose preliminary score
+    /// reached `edit_history_min_score`, so only likely spam pays for the lookup.
+    async fn edit_history(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+        raw: &Value,
+        score: f32,
+    ) -> Result<Option<ContentEdits>> {
+        let min_score = self.config.edit_history_min_score;
+        if min_score <= 0.0 || score < min_score {
+            return Ok(None);
+        }
+        let Some(node_id) = raw.get("node_id").and_then(Value::as_str) else {
+            return Ok(None);
+        };
+        let Some(edits) = self.fetcher.fetch_content_edits(node_id).await? else {
+            return Ok(None);
+        };
+        self.repos
+            .spam_flags()
+            .upsert_content_edits(ContentEditsUpsert {
+                subject_type: subject_type.into(),
+                subject_id,
+                edit_count: edits.total_count as i32,
+                last_edited_at: edits.last_edited_at,
+                last_editor_login: edits.last_editor.clone(),
+            })
+            .await?;
+        metrics::EDIT_HISTORY_FETCHES_TOTAL
+            .with_label_values(&[subject_type])
+            .inc();
+        Ok(Some(edits))
+    }
+
+    /// Stored, found, and older than `skip_user_fetch_min_age_days`: its age can
+    /// only grow, so a fresh lookup would not change how it scores as new.
+    async fn is_settled_account(&self, login: &str) -> Result<bool> {
+        let min_age_days = self.config.skip_user_fetch_min_age_days;
+        if min_age_days == 0 {
+            return Ok(false);
+        }
+        let min_age = chrono::Duration::days(i64::from(min_age_days));
+        Ok(self
+            .repos
+            .users()
+            .get_by_login(login)
+            .await?
+            .filter(|user| user.found)
+            .and_then(|user| user.created_at)
+            .is_some_and(|created_at| Utc::now() - created_at >= min_age))
+    }
+
+    /// Created within `new_account_window_days`.
+    fn is_new_account(&self, user: &UserRow) -> bool {
+        self.created_recently(user.created_at)
+    }
+
+    fn created_recently(&self, created_at: Option<DateTime<Utc>>) -> bool {
+        let window = chrono::Duration::days(i64::from(self.config.new_account_window_days));
+        created_at.is_some_and(|created_at| Utc::now() - created_at <= window)
+    }
+
+    /// Public membership of `user` in the repo owner's org. Answers are stored
+    /// in `org_memberships` for `org_membership_ttl_days` and reused per run via
+    /// `known`. A failed check counts as "not a member" rather than failing the repo.
+    async fn is_org_member(
+        &self,
+        org: &str,
+        user: Option<&UserRow>,
+        known: &mut HashMap<i64, bool>,
+    ) -> Result<bool> {
+        let Some(user) = user.filter(|user| user.found) else {
+            return Ok(false);
+        };
+        if let Some(member) = known.get(&user.id) {
+            return Ok(*member);
+        }
+        let ttl = chrono::Duration::days(self.config.org_membership_ttl_days.into());
+        let cached = self
+            .repos
+            .users()
+            .org_membership(org, user.id)
+            .await?
+            .filter(|row| Utc::now() - row.checked_at < ttl);
+        let member = match cached {
+            Some(row) => row.is_member,
+            None => match self.fetcher.fetch_org_membership(org, &user.login).await {
+                Ok(Some(member)) => {
+                    self.repos
+                        .users()
+                        .set_org_membership(org, user.id, member)
+                        .await?;
+                    member
+                }
+                Ok(None) => false,
+                Err(err) => {
+                    warn!(org, login = %user.login, error = %err, "org membership check failed");
+                    false
+                }
+            },
+        };
+        known.insert(user.id, member);
+        Ok(member)
+    }
+
     async fn ensure_user(&self, user_ref: &UserRef, cache: &mut HashSet<String>) -> Result<()> {
         if !cache.insert(user_ref.login.clone()) {
             return Ok(());
         }
-        match self.fetcher.fetch_user(user_ref).await? {
+        // Listings that embed the author (GraphQL) already carry its account age.
+        if let Some(user) = self
+            .fetcher
+            .inlined_user(user_ref)
+            .await
+            .filter(|user| user.created_at.is_some())
+        {
+            metrics::USER_FETCHES_SKIPPED_TOTAL
+                .with_label_values(&["inlined"])
+                .inc();
+            let mut user_row = to_user_row(&user);
+            user_row.found = true;
+            self.repos.users().upsert(user_row).await?;
+            return Ok(());
+        }
+        if self.is_settled_account(&user_ref.login).await? {
+            metrics::USER_FETCHES_SKIPPED_TOTAL
+                .with_label_values(&["old_account"])
+                .inc();
+            return Ok(());
+        }
+        self.fetch_and_store_user(user_ref).await
+    }
+
+    /// Looks `user_ref` up on GitHub and stores it, or a `found = false`
+    /// placeholder when GitHub no longer serves it.
+    async fn fetch_and_store_user(&self, user_ref: &UserRef) -> Result<()> {
+        let fetched = {
+            let _permit = self.user_fetches.acquire().await?;
+            self.fetcher.fetch_user(user_ref).await?
+        };
+        match fetched {
             UserFetch::Found(normalized_user) => {
                 metrics::USERS_FETCHED_TOTAL.inc();
                 let mut user_row = to_user_row(&normalized_user);
@@ -628,6 +1671,8 @@ impl Collector {
                     followers: None,
                     following: None,
                     public_repos: None,
+                    bio: None,
+                    blog: None,
                     raw: serde_json::json!({"not_found": true}),
                     found: false,
                 };
@@ -661,6 +1706,8 @@ fn to_user_row(normalized: &NormalizedUser) -> UserRow {
         followers: normalized.followers,
         following: normalized.following,
         public_repos: normalized.public_repos,
+        bio: normalized.bio.clone(),
+        blog: normalized.blog.clone(),
         raw: normalized.raw.clone(),
         found: true,
     }
@@ -680,6 +1727,9 @@ fn to_issue_row(normalized: &NormalizedIssue) -> IssueRow {
         created_at: normalized.created_at,
         updated_at: normalized.updated_at,
         closed_at: normalized.closed_at,
+        state_reason: normalized.state_reason.clone(),
+        locked: normalized.locked,
+        author_association: normalized.author_association.clone(),
         dedupe_hash: normalized.dedupe_hash.clone(),
         raw: normalized.raw.clone(),
         found: true,
@@ -694,12 +1744,94 @@ fn to_comment_row(normalized: &NormalizedComment) -> CommentRow {
         body: normalized.body.clone(),
         created_at: normalized.created_at,
         updated_at: normalized.updated_at,
+        author_association: normalized.author_association.clone(),
         dedupe_hash: normalized.dedupe_hash.clone(),
         raw: normalized.raw.clone(),
         found: true,
     }
 }
 
+/// Scoring runs on the full row; only the stored copy is cut to `max_body_bytes`.
+fn cap_issue_body(mut row: IssueRow, max_bytes: usize) -> IssueRow {
+    if let Some(capped) = row
+        .body
+        .as_deref()
+        .and_then(|body| truncate_body(body, max_bytes))
+    {
+        cap_raw_body(&mut row.raw, &capped);
+        row.body = Some(capped);
+    }
+    row
+}
+
+fn cap_comment_body(mut row: CommentRow, max_bytes: usize) -> CommentRow {
+    if let Some(capped) = truncate_body(&row.body, max_bytes) {
+        cap_raw_body(&mut row.raw, &capped);
+        row.body = capped;
+    }
+    row
+}
+
+fn cap_raw_body(raw: &mut Value, capped: &str) {
+    if let Some(body) = raw.get_mut("body").filter(|body| body.is_string()) {
+        *body = Value::String(capped.to_string());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IssueChange {
+    /// New issue or different content hash: upsert and re-score.
+    ContentChanged,
+    /// Same content but state/comments moved (e.g. closed): upsert without re-scoring.
+    MetadataChanged,
+    /// Only `updated_at` moved (label toggles, reactions): nothing to write.
+    Unchanged,
+}
+
+fn classify_issue_change(stored: Option<&IssueFingerprint>, incoming: &IssueRow) -> IssueChange {
+    let Some(stored) = stored else {
+        return IssueChange::ContentChanged;
+    };
+    if stored.dedupe_hash != incoming.dedupe_hash {
+        return IssueChange::ContentChanged;
+    }
+    if stored.found == incoming.found
+        && stored.state == incoming.state
+        && stored.comments_count == incoming.comments_count
+        && stored.closed_at == incoming.closed_at
+        && stored.locked == incoming.locked
+    {
+        IssueChange::Unchanged
+    } else {
+        IssueChange::MetadataChanged
+    }
+}
+
+/// Whether someone other than the author closed the issue. Only single-issue
+/// REST payloads name the closer (`closed_by`); for listings, a lock or a
+/// `not_planned` closure stands in, since spammers rarely close their own posts.
+fn closed_by_maintainer(issue: &IssueRow) -> bool {
+    match issue
+        .raw
+        .get("closed_by")
+        .and_then(|closer| closer.get("id"))
+        .and_then(Value::as_i64)
+    {
+        Some(closer) => issue.user_id != Some(closer),
+        None => issue.locked || issue.state_reason.as_deref() == Some("not_planned"),
+    }
+}
+
+/// The stored issue already has every comment GitHub reports: same count, and
+/// the issue has not been updated since it was stored. New comments bump both.
+fn comments_unchanged(stored: Option<&IssueFingerprint>, incoming: &IssueRow) -> bool {
+    stored.is_some_and(|stored| {
+        stored.found
+            && stored.comments_count == incoming.comments_count
+            && incoming.updated_at <= stored.updated_at
+    })
+}
+
 fn record_post(counts: &mut HashMap<String, u32>, login: &str) -> u32 {
     let entry = counts.entry(login.to_string()).or_insert(0);
     let current = *entry;
@@ -714,6 +1846,33 @@ fn record_dedupe(counts: &mut HashMap<String, u32>, hash: &str) -> u32 {
     current
 }
 
+/// Whether `subject_id` falls in a `rate` sample. Decided by a hash of the id
+/// rather than at random, so re-ingesting a subject keeps the same decision.
+fn in_clean_sample(subject_id: i64, rate: f64) -> bool {
+    if rate <= 0.0 {
+        return false;
+    }
+    if rate >= 1.0 {
+        return true;
+    }
+    // splitmix64 finalizer: spreads sequential ids evenly over the u64 range.
+    let mut x = (subject_id as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^= x >> 31;
+    (x as f64) < rate * u64::MAX as f64
+}
+
+/// Whether `err` comes from failing to reach the database, rather than from
+/// a query it rejected.
+fn is_db_unavailable(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<db::DbError>()
+            .is_some_and(db::DbError::is_unavailable)
+    })
+}
+
 /// Determines if an error is permanent (will not retry) or transient (will retry)
 ///
 /// Permanent errors include:
@@ -776,10 +1935,17 @@ struct ErrorDetails {
 fn extract_error_details(err: &anyhow::Error) -> ErrorDetails {
     let status = status_from_error(err);
     let endpoint = endpoint_from_error(err);
+    // A payload that failed to normalize says which subject and field broke;
+    // that beats whatever context the failure was wrapped in.
     let context = err
         .chain()
-        .map(|cause| cause.to_string())
-        .find(|value| !value.trim().is_empty())
+        .find_map(|cause| cause.downcast_ref::<NormalizationError>())
+        .map(|normalization| normalization.to_string())
+        .or_else(|| {
+            err.chain()
+                .map(|cause| cause.to_string())
+                .find(|value| !value.trim().is_empty())
+        })
         .map(|value| truncate_context(&value))
         .unwrap_or_else(|| truncate_context(&err.to_string()));
 
@@ -791,14 +1957,7 @@ fn extract_error_details(err: &anyhow::Error) -> ErrorDetails {
 }
 
 fn truncate_context(value: &str) -> String {
-    if value.is_empty() {
-        return String::new();
-    }
-    let mut truncated: String = value.chars().take(256).collect();
-    if truncated.len() < value.len() {
-        truncated.push('…');
-    }
-    truncated
+    truncate_chars(value, 256)
 }
 
 fn status_from_error(err: &anyhow::Error) -> Option<StatusCode> {
@@ -871,6 +2030,15 @@ mod tests {
         assert_eq!(record_dedupe(&mut counts, "hash"), 1);
     }
 
+    #[test]
+    fn clean_sample_is_stable_and_near_rate() {
+        let sampled = (0..10_000).filter(|&id| in_clean_sample(id, 0.1)).count();
+        assert!((900..=1100).contains(&sampled), "sampled {sampled}");
+        assert_eq!(in_clean_sample(42, 0.5), in_clean_sample(42, 0.5));
+        assert!(!(0..1000).any(|id| in_clean_sample(id, 0.0)));
+        assert!((0..1000).all(|id| in_clean_sample(id, 1.0)));
+    }
+
     #[test]
     fn repo_not_found_is_permanent() {
         let err = anyhow::Error::new(GithubApiError::status(
@@ -926,6 +2094,21 @@ mod tests {
         assert_eq!(details.endpoint.as_deref(), Some("users/example"));
     }
 
+    #[test]
+    fn extract_error_details_surfaces_normalization_field() {
+        let raw = serde_json::json!({"id": 12, "number": "twelve"});
+        let err = normalizer::parse_payload::<normalizer::IssuePayload>("issue", &raw)
+            .map_err(anyhow::Error::new)
+            .context("fetching issues for acme/widgets")
+            .unwrap_err();
+        let details = extract_error_details(&err);
+        let context = details.context.expect("context");
+        assert!(
+            context.starts_with("normalizing issue 12: field `number`"),
+            "{context}"
+        );
+    }
+
     #[test]
     fn status_from_error_handles_plain_text_message() {
         let err = anyhow::anyhow!("unexpected status 404 Not Found");
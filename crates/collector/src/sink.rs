@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use common::config::{RecordSinkConfig, RecordSinkKind};
+use normalizer::models::{NormalizedComment, NormalizedIssue};
+use serde::Serialize;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Receives every normalized issue/comment the collector upserts, so other
+/// pipelines can consume the stream without reading our tables.
+///
+/// Records are the full normalized payloads, before body capping.
+#[async_trait]
+pub trait RecordSink: Send + Sync {
+    async fn write_issue(&self, issue: &NormalizedIssue) -> Result<()>;
+    async fn write_comment(&self, comment: &NormalizedComment) -> Result<()>;
+}
+
+pub struct NoopSink;
+
+#[async_trait]
+impl RecordSink for NoopSink {
+    async fn write_issue(&self, _issue: &NormalizedIssue) -> Result<()> {
+        Ok(())
+    }
+
+    async fn write_comment(&self, _comment: &NormalizedComment) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Appends one JSON object per line: `{"kind":"issue"|"comment","record":{..}}`.
+pub struct JsonLinesFileSink {
+    file: Mutex<File>,
+}
+
+#[derive(Serialize)]
+struct Line<'a, T> {
+    kind: &'static str,
+    record: &'a T,
+}
+
+impl JsonLinesFileSink {
+    pub async fn open(path: &str) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .with_context(|| format!("opening record sink file {path}"))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    async fn write_line<T: Serialize + Sync>(&self, kind: &'static str, record: &T) -> Result<()> {
+        let mut line = serde_json::to_vec(&Line { kind, record })?;
+        line.push(b'\n');
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecordSink for JsonLinesFileSink {
+    async fn write_issue(&self, issue: &NormalizedIssue) -> Result<()> {
+        self.write_line("issue", issue).await
+    }
+
+    async fn write_comment(&self, comment: &NormalizedComment) -> Result<()> {
+        self.write_line("comment", comment).await
+    }
+}
+
+pub async fn from_config(config: &RecordSinkConfig) -> Result<Arc<dyn RecordSink>> {
+    match config.kind {
+        RecordSinkKind::None => Ok(Arc::new(NoopSink)),
+        RecordSinkKind::File => {
+            let path = config
+                .path
+                .as_deref()
+                .context("collector.sink.path is required when collector.sink.kind = \"file\"")?;
+            Ok(Arc::new(JsonLinesFileSink::open(path).await?))
+        }
+    }
+}
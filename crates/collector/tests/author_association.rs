@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use collector::client::BrokerGithubClient;
+use collector::fetcher::{DataFetcher, GraphqlDataFetcher};
+use gh_broker::{GithubBroker, Priority};
+use http::{Request, Response, StatusCode};
+use serde_json::{json, Value};
+
+/// Lists one issue opened by a first-time contributor with a maintainer's
+/// comment embedded, and no pull requests.
+struct ListingBroker;
+
+fn issue_node() -> Value {
+    json!({
+        "databaseId": 501,
+        "number": 5,
+        "title": "Free crypto airdrop",
+        "body": "Claim now",
+        "state": "OPEN",
+        "createdAt": "2024-05-01T00:00:00Z",
+        "updatedAt": "2024-05-01T01:00:00Z",
+        "closedAt": null,
+        "authorAssociation": "FIRST_TIME_CONTRIBUTOR",
+        "author": null,
+        "comments": {
+            "totalCount": 1,
+            "pageInfo": { "hasNextPage": false, "endCursor": null },
+            "nodes": [{
+                "databaseId": 9001,
+                "body": "Closing as spam.",
+                "createdAt": "2024-05-01T00:30:00Z",
+                "updatedAt": null,
+                "authorAssociation": "OWNER",
+                "author": null
+            }]
+        }
+    })
+}
+
+impl GithubBroker for ListingBroker {
+    fn enqueue(
+        &self,
+        request: Request<Vec<u8>>,
+        _priority: Priority,
+    ) -> futures::future::BoxFuture<'static, Result<Response<Vec<u8>>>> {
+        let body = String::from_utf8_lossy(request.body()).to_string();
+        let connection = if body.contains("RepoPulls") {
+            json!({
+                "pullRequests": {
+                    "pageInfo": { "hasNextPage": false, "endCursor": null },
+                    "nodes": []
+                }
+            })
+        } else {
+            json!({
+                "issues": {
+                    "pageInfo": { "hasNextPage": false, "endCursor": null },
+                    "nodes": [issue_node()]
+                }
+            })
+        };
+        let payload = json!({ "data": { "repository": connection } })
+            .to_string()
+            .into_bytes();
+        let resp = Response::builder()
+            .status(StatusCode::OK)
+            .body(payload)
+            .unwrap();
+        Box::pin(async move { Ok(resp) })
+    }
+}
+
+#[tokio::test]
+async fn graphql_listing_carries_author_association() -> Result<()> {
+    let broker: Arc<dyn GithubBroker> = Arc::new(ListingBroker);
+    let rest = Arc::new(BrokerGithubClient::new(broker.clone(), "ua".into()));
+    let fetcher = GraphqlDataFetcher::new(broker, rest, "ua".into());
+
+    let page = fetcher.fetch_issues("o", "r", 1, None, None, 50).await?;
+    let issue = &page.items[0].issue;
+    assert_eq!(issue.id, 501);
+    assert_eq!(
+        issue.author_association.as_deref(),
+        Some("FIRST_TIME_CONTRIBUTOR")
+    );
+
+    // The embedded comment page is served without another request.
+    let comments = fetcher
+        .fetch_issue_comments("o", "r", 5, 501, None, 50)
+        .await?;
+    assert_eq!(comments.items.len(), 1);
+    assert_eq!(
+        comments.items[0].comment.author_association.as_deref(),
+        Some("OWNER")
+    );
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use std::sync::Arc;
+
+use analysis::{ContributionStats, FirstReply, RuleOutcome, Scorer};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use collector::fetcher::{
+    CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::metrics::DB_OPERATION_DURATION;
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::{CommentRow, IssueRow, Repositories, UserRow};
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedComment, NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+const COMMENTS: i64 = 25;
+
+/// Flags every comment and leaves issues clean.
+struct FlagCommentsScorer;
+
+#[async_trait]
+impl Scorer for FlagCommentsScorer {
+    fn version(&self) -> &str {
+        "stub_v1"
+    }
+
+    async fn score_issue(
+        &self,
+        _issue: &IssueRow,
+        _user: Option<&UserRow>,
+        _stats: ContributionStats,
+        _dedupe_hits_last_48h: u32,
+    ) -> Result<RuleOutcome> {
+        Ok(RuleOutcome::new())
+    }
+
+    async fn score_comment(
+        &self,
+        _comment: &CommentRow,
+        _user: Option<&UserRow>,
+        _stats: ContributionStats,
+        _dedupe_hits_last_48h: u32,
+        _first_reply: Option<&FirstReply>,
+    ) -> Result<RuleOutcome> {
+        Ok(RuleOutcome {
+            score: 1.0,
+            reasons: vec!["stub_model".into()],
+            contributions: vec![("stub_model".into(), 1.0)],
+            ..RuleOutcome::new()
+        })
+    }
+}
+
+/// One issue whose single comment page holds `COMMENTS` comments.
+struct OnePageFetcher;
+
+#[async_trait]
+impl DataFetcher for OnePageFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 1,
+                full_name: format!("{owner}/{name}"),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue: NormalizedIssue {
+                    id: 10,
+                    repo_id,
+                    number: 1,
+                    is_pull_request: false,
+                    state: "open".into(),
+                    title: "Release checklist".into(),
+                    body: Some("Tracking the steps for the next release.".into()),
+                    user_id: None,
+                    comments_count: COMMENTS,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    closed_at: None,
+                    state_reason: None,
+                    locked: false,
+                    author_association: None,
+                    dedupe_hash: "issue-10".into(),
+                    raw: json!({ "id": 10 }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        let start = Utc::now() - Duration::hours(1);
+        let items = (0..COMMENTS)
+            .map(|n| CommentRecord {
+                comment: NormalizedComment {
+                    id: 100 + n,
+                    issue_id,
+                    user_id: None,
+                    body: format!("Step {n} is done"),
+                    created_at: start + Duration::minutes(n),
+                    updated_at: None,
+                    author_association: None,
+                    dedupe_hash: format!("comment-{n}"),
+                    raw: json!({ "id": 100 + n }),
+                },
+                author: None,
+            })
+            .collect();
+        Ok(CommentPage {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("records have no authors")
+    }
+}
+
+fn statements(op: &str) -> u64 {
+    DB_OPERATION_DURATION
+        .with_label_values(&[op])
+        .get_sample_count()
+}
+
+#[tokio::test]
+async fn comment_page_is_stored_and_flagged_in_one_statement_each() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping comment_page_is_stored_and_flagged_in_one_statement_each: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("batched_comment_writes").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "widgets".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    Collector::new(
+        cfg.clone(),
+        Arc::new(OnePageFetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    )
+    .with_scorer(Arc::new(FlagCommentsScorer))
+    .run_once()
+    .await?;
+
+    assert_eq!(statements("comments.upsert_many"), 1);
+    assert_eq!(statements("comments.upsert"), 0);
+    assert_eq!(statements("spam_flags.upsert_many"), 1);
+    assert_eq!(statements("spam_flags.upsert"), 0);
+
+    let stored = db.comments().list_by_issue(10).await?;
+    let ids: Vec<_> = stored.iter().map(|comment| comment.id).collect();
+    assert_eq!(ids, (100..100 + COMMENTS).collect::<Vec<_>>());
+    for id in [100, 100 + COMMENTS - 1] {
+        let flags = db.spam_flags().list_for_subject("comment", id).await?;
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].reasons, vec!["stub_model".to_string()]);
+    }
+
+    handle.cleanup().await?;
+    Ok(())
+}
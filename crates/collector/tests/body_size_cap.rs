@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::fetcher::{
+    CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use common::text::TRUNCATION_MARKER;
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedComment, NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+const MAX_BODY_BYTES: usize = 256;
+
+/// Varied filler (so entropy rules stay quiet) with a template phrase past the cap.
+fn oversized_body() -> String {
+    let filler: Vec<String> = (0..200).map(|i| format!("word{i}")).collect();
+    format!("{} please fill out the template", filler.join(" "))
+}
+
+struct OversizedFetcher;
+
+#[async_trait]
+impl DataFetcher for OversizedFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 888,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({"id":888}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let body = oversized_body();
+        let issue = NormalizedIssue {
+            id: 1,
+            repo_id,
+            number: 1,
+            is_pull_request: false,
+            state: "open".into(),
+            title: "Big".into(),
+            body: Some(body.clone()),
+            user_id: None,
+            comments_count: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: "hash-full-issue".into(),
+            raw: json!({"id": 1, "body": body}),
+        };
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue,
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        let body = oversized_body();
+        Ok(CommentPage {
+            items: vec![CommentRecord {
+                comment: NormalizedComment {
+                    id: 10,
+                    issue_id,
+                    user_id: None,
+                    body: body.clone(),
+                    created_at: Utc::now(),
+                    updated_at: None,
+                    author_association: None,
+                    dedupe_hash: "hash-full-comment".into(),
+                    raw: json!({"id": 10, "body": body}),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        Ok(UserFetch::Missing(collector::fetcher::MissingUser {
+            id: user.id,
+            login: user.login.clone(),
+            status: None,
+        }))
+    }
+}
+
+#[tokio::test]
+async fn stored_bodies_are_capped_but_scored_in_full() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping stored_bodies_are_capped_but_scored_in_full: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("body_size_cap").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "o".into(),
+            name: "big".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        max_body_bytes: MAX_BODY_BYTES,
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        Arc::new(OversizedFetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+    collector.run_once().await?;
+
+    let issues = db.issues().list_by_repo(888, None).await?;
+    assert_eq!(issues.len(), 1);
+    let issue = &issues[0];
+    let body = issue.body.as_deref().expect("issue body stored");
+    assert!(body.len() <= MAX_BODY_BYTES);
+    assert!(body.ends_with(TRUNCATION_MARKER));
+    assert_eq!(issue.raw["body"].as_str(), Some(body));
+    assert_eq!(issue.dedupe_hash, "hash-full-issue");
+
+    let comments = db.comments().list_by_issue(issue.id).await?;
+    assert_eq!(comments.len(), 1);
+    assert!(comments[0].body.len() <= MAX_BODY_BYTES);
+    assert!(comments[0].body.ends_with(TRUNCATION_MARKER));
+    assert_eq!(comments[0].dedupe_hash, "hash-full-comment");
+
+    // The template phrase only exists past the cap, so a flag proves scoring saw the full text.
+    for (subject_type, subject_id) in [("issue", issue.id), ("comment", comments[0].id)] {
+        let flags = db
+            .spam_flags()
+            .list_for_subject(subject_type, subject_id)
+            .await?;
+        assert!(
+            flags
+                .iter()
+                .any(|flag| flag.reasons.contains(&"template_phrase".to_string())),
+            "{subject_type} should be scored on its full body"
+        );
+    }
+
+    handle.cleanup().await?;
+    Ok(())
+}
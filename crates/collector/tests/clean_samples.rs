@@ -0,0 +1,233 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+const CLEAN_ISSUES: i64 = 400;
+const SPAM_ISSUE: i64 = 10_000;
+
+/// Serves `CLEAN_ISSUES` ordinary bug reports and one contact-only spam issue.
+struct MixedFetcher;
+
+fn issue(id: i64, title: String, body: String) -> IssueRecord {
+    IssueRecord {
+        issue: NormalizedIssue {
+            id,
+            repo_id: 1,
+            number: id,
+            is_pull_request: false,
+            state: "open".into(),
+            title,
+            body: Some(body),
+            user_id: None,
+            comments_count: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: format!("issue-{id}"),
+            raw: json!({ "id": id }),
+        },
+        author: None,
+    }
+}
+
+fn spell(id: i64) -> String {
+    id.to_string()
+        .bytes()
+        .map(|digit| char::from(b'a' + digit - b'0'))
+        .collect()
+}
+
+#[async_trait]
+impl DataFetcher for MixedFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 1,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let mut items: Vec<IssueRecord> = (1..=CLEAN_ISSUES)
+            .map(|id| {
+                issue(
+                    id,
+                    // Spelled-out ids keep the titles distinct after digits
+                    // are stripped for `templated_title`.
+                    format!("Linker error in module {}", spell(id)),
+                    format!(
+                        "Building release {id} on Windows fails at the linker step with \
+                         error LNK{id}; it also happens on a clean checkout of main."
+                    ),
+                )
+            })
+            .collect();
+        items.push(issue(
+            SPAM_ISSUE,
+            "support".into(),
+            "telegram @helpdesk".into(),
+        ));
+        Ok(IssuePage {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        unreachable!("issues have no comments")
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("issues have no authors")
+    }
+}
+
+#[tokio::test]
+async fn clean_issues_are_sampled_at_configured_rate() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping clean_issues_are_sampled_at_configured_rate: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("clean_samples").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "widgets".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        clean_sample_rate: 0.25,
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        Arc::new(MixedFetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+    collector.run_once().await?;
+
+    let samples = db.spam_flags().list_feature_samples("issue", 1000).await?;
+    // 25% of 400 is 100; the id hash keeps the count close to that.
+    assert!(
+        (70..=130).contains(&samples.len()),
+        "sampled {} of {CLEAN_ISSUES} clean issues",
+        samples.len()
+    );
+    assert!(samples.iter().all(|s| s.subject_id <= CLEAN_ISSUES));
+    assert!(samples.iter().all(|s| s.version == "rules_v1"));
+    assert!(samples[0].features["body_length"].as_u64().unwrap() > 0);
+
+    // The spam issue is flagged instead of sampled.
+    let flags = db
+        .spam_flags()
+        .list_for_subject("issue", SPAM_ISSUE)
+        .await?;
+    assert_eq!(flags.len(), 1);
+    for sample in &samples {
+        assert!(db
+            .spam_flags()
+            .list_for_subject("issue", sample.subject_id)
+            .await?
+            .is_empty());
+    }
+
+    handle.cleanup().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn clean_sampling_is_off_by_default() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping clean_sampling_is_off_by_default: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("clean_samples_off").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "widgets".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        Arc::new(MixedFetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+    collector.run_once().await?;
+
+    assert!(db
+        .spam_flags()
+        .list_feature_samples("issue", 1000)
+        .await?
+        .is_empty());
+
+    handle.cleanup().await?;
+    Ok(())
+}
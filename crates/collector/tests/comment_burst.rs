@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use collector::fetcher::{
+    CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{
+    NormalizedComment, NormalizedIssue, NormalizedRepository, NormalizedUser,
+};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+const COMMENTS: i64 = 12;
+
+/// Repo `burst` gets a dozen replies 20 seconds apart, repo `drip` the same
+/// replies two hours apart. Every commenter is a day-old account.
+struct ThreadFetcher {
+    started: DateTime<Utc>,
+}
+
+fn repo_id(name: &str) -> i64 {
+    if name == "burst" {
+        1
+    } else {
+        2
+    }
+}
+
+#[async_trait]
+impl DataFetcher for ThreadFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        let id = repo_id(name);
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": id }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let id = repo_id * 100;
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue: NormalizedIssue {
+                    id,
+                    repo_id,
+                    number: 1,
+                    is_pull_request: false,
+                    state: "open".into(),
+                    title: "Release date for the next version".into(),
+                    body: Some(
+                        "Is there a rough timeline for shipping the next version with the \
+                         plugin loader changes merged last month?"
+                            .into(),
+                    ),
+                    user_id: None,
+                    comments_count: COMMENTS,
+                    created_at: self.started,
+                    updated_at: self.started,
+                    closed_at: None,
+                    state_reason: None,
+                    locked: false,
+                    author_association: None,
+                    dedupe_hash: format!("issue-{id}"),
+                    raw: json!({ "id": id }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        let gap = if issue_id == 100 {
+            Duration::seconds(20)
+        } else {
+            Duration::hours(2)
+        };
+        let items = (1..=COMMENTS)
+            .map(|n| {
+                let id = issue_id + n;
+                let author = UserRef {
+                    id: 1000 + id,
+                    login: format!("fresh-{id}"),
+                };
+                CommentRecord {
+                    comment: NormalizedComment {
+                        id,
+                        issue_id,
+                        user_id: Some(author.id),
+                        body: format!(
+                            "Reply number {n} here, I would also like to know when this lands."
+                        ),
+                        created_at: self.started + Duration::minutes(30) + gap * n as i32,
+                        updated_at: None,
+                        author_association: None,
+                        dedupe_hash: format!("comment-{id}"),
+                        raw: json!({ "id": id }),
+                    },
+                    author: Some(author),
+                }
+            })
+            .collect();
+        Ok(CommentPage {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        Ok(UserFetch::Found(NormalizedUser {
+            id: user.id,
+            login: user.login.clone(),
+            user_type: "User".into(),
+            site_admin: false,
+            created_at: Some(Utc::now() - Duration::days(1)),
+            followers: Some(0),
+            following: Some(0),
+            public_repos: Some(0),
+            bio: None,
+            blog: None,
+            raw: json!({ "id": user.id }),
+        }))
+    }
+}
+
+#[tokio::test]
+async fn clustered_new_account_replies_are_flagged_as_burst() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping clustered_new_account_replies_are_flagged_as_burst: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("comment_burst").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    for name in ["burst", "drip"] {
+        db.collection_jobs()
+            .create(CollectionJobCreate {
+                owner: "acme".into(),
+                name: name.into(),
+                priority: 0,
+            })
+            .await?;
+    }
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let fetcher = ThreadFetcher {
+        started: Utc::now() - Duration::days(2),
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        Arc::new(fetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+    collector.run_once().await?;
+
+    let has_burst = |flags: &[db::SpamFlagRow]| {
+        flags
+            .iter()
+            .any(|flag| flag.reasons.iter().any(|r| r == "comment_burst"))
+    };
+    for n in 1..=COMMENTS {
+        let burst = db.spam_flags().list_for_subject("comment", 100 + n).await?;
+        assert!(has_burst(&burst), "burst reply {n} should be flagged");
+
+        let drip = db.spam_flags().list_for_subject("comment", 200 + n).await?;
+        assert!(!has_burst(&drip), "slow reply {n} should not be flagged");
+    }
+
+    // The run refreshed the all-time ranking, so it already lists the burst.
+    let ranked = db
+        .spam_flags()
+        .top_spammy_users(None, 100, None, None)
+        .await?;
+    assert!(ranked
+        .iter()
+        .any(|summary| summary.login.starts_with("fresh-")));
+
+    handle.cleanup().await?;
+    Ok(())
+}
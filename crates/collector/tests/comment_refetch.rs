@@ -0,0 +1,216 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use collector::fetcher::{
+    CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::{CollectionJobCreate, CollectionJobUpdate, CollectionStatus};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedComment, NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+fn created_at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+}
+
+/// Serves one issue with `comments` replies; the issue's `updated_at` is the
+/// newest reply. Counts how often the comments are paged.
+struct ThreadFetcher {
+    comments: Mutex<i64>,
+    comment_fetches: AtomicUsize,
+}
+
+impl ThreadFetcher {
+    fn updated_at(comments: i64) -> DateTime<Utc> {
+        created_at() + Duration::hours(comments)
+    }
+}
+
+#[async_trait]
+impl DataFetcher for ThreadFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 1,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: created_at(),
+                pushed_at: None,
+                raw: json!({ "id": 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let comments = *self.comments.lock().unwrap();
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue: NormalizedIssue {
+                    id: 10,
+                    repo_id,
+                    number: 1,
+                    is_pull_request: false,
+                    state: "open".into(),
+                    title: "Panic when the cache directory is read-only".into(),
+                    body: Some(
+                        "Running with a read-only cache directory panics instead of \
+                         falling back to a temporary directory."
+                            .into(),
+                    ),
+                    user_id: None,
+                    comments_count: comments,
+                    created_at: created_at(),
+                    updated_at: Self::updated_at(comments),
+                    closed_at: None,
+                    state_reason: None,
+                    locked: false,
+                    author_association: None,
+                    dedupe_hash: "issue-10".into(),
+                    raw: json!({ "id": 10 }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue(
+        &self,
+        owner: &str,
+        name: &str,
+        repo_id: i64,
+        _number: i64,
+    ) -> Result<Option<IssueRecord>> {
+        let page = self
+            .fetch_issues(owner, name, repo_id, None, None, 1)
+            .await?;
+        Ok(page.items.into_iter().next())
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        self.comment_fetches.fetch_add(1, Ordering::SeqCst);
+        let comments = *self.comments.lock().unwrap();
+        let items = (1..=comments)
+            .map(|n| CommentRecord {
+                comment: NormalizedComment {
+                    id: 100 + n,
+                    issue_id,
+                    user_id: None,
+                    body: format!("Reproduced on my machine as well, reply number {n}."),
+                    created_at: Self::updated_at(n),
+                    updated_at: None,
+                    author_association: None,
+                    dedupe_hash: format!("comment-{n}"),
+                    raw: json!({ "id": 100 + n }),
+                },
+                author: None,
+            })
+            .collect();
+        Ok(CommentPage {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("records have no authors")
+    }
+}
+
+#[tokio::test]
+async fn comments_are_only_paged_when_the_thread_moved() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping comments_are_only_paged_when_the_thread_moved: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("comment_refetch").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    let job = db
+        .collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "widgets".into(),
+            priority: 0,
+        })
+        .await?;
+    let fetcher = Arc::new(ThreadFetcher {
+        comments: Mutex::new(1),
+        comment_fetches: AtomicUsize::new(0),
+    });
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+    let rerun = || async {
+        db.collection_jobs()
+            .update(CollectionJobUpdate {
+                id: job.id,
+                status: CollectionStatus::Pending,
+                error_message: None,
+            })
+            .await?;
+        collector.run_once().await
+    };
+
+    collector.run_once().await?;
+    assert_eq!(fetcher.comment_fetches.load(Ordering::SeqCst), 1);
+
+    // Same count, same updated_at: the stored comments are current.
+    rerun().await?;
+    assert_eq!(fetcher.comment_fetches.load(Ordering::SeqCst), 1);
+
+    // A new reply bumps the count and the issue's updated_at.
+    *fetcher.comments.lock().unwrap() = 2;
+    rerun().await?;
+    assert_eq!(fetcher.comment_fetches.load(Ordering::SeqCst), 2);
+    let stored = db.comments().list_by_issue(10).await?;
+    assert_eq!(
+        stored.iter().map(|c| c.id).collect::<Vec<_>>(),
+        vec![101, 102]
+    );
+
+    // Targeted refreshes always page the comments.
+    assert!(collector.refresh_issue("acme", "widgets", 1).await?);
+    assert_eq!(fetcher.comment_fetches.load(Ordering::SeqCst), 3);
+
+    handle.cleanup().await?;
+    Ok(())
+}
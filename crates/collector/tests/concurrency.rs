@@ -4,14 +4,16 @@ use std::time::Duration;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::Utc;
-use collector::fetcher::{CommentPage, DataFetcher, IssuePage, RepoSnapshot, UserFetch};
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
 use collector::service::Collector;
 use common::config::{CollectorConfig, FetchMode};
 use db::models::CollectionJobCreate;
 use db::pg::PgDatabase;
 use db::Repositories;
 use db_test_fixture::DbFixture;
-use normalizer::models::NormalizedRepository;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
 use normalizer::payloads::UserRef;
 use serde_json::json;
 use tokio::time::Instant;
@@ -150,6 +152,7 @@ async fn processes_repositories_in_parallel() -> Result<()> {
         run_once: true,
         fetch_mode: FetchMode::Hybrid,
         max_concurrent_repos: 2,
+        ..Default::default()
     };
     let collector = Collector::new(
         cfg.clone(),
@@ -191,3 +194,153 @@ async fn processes_repositories_in_parallel() -> Result<()> {
     handle.cleanup().await?;
     Ok(())
 }
+
+/// Every repo's single page is written by distinct authors, so each issue needs a user fetch.
+struct UserStormFetcher {
+    users: SleepyFetcher,
+}
+
+#[async_trait]
+impl DataFetcher for UserStormFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        let id: i64 = name.trim_start_matches('r').parse()?;
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": id }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let items = (0..5)
+            .map(|n| {
+                let id = repo_id * 100 + n;
+                IssueRecord {
+                    issue: NormalizedIssue {
+                        id,
+                        repo_id,
+                        number: n,
+                        is_pull_request: false,
+                        state: "open".into(),
+                        title: format!("issue {id}"),
+                        body: Some("a perfectly ordinary bug report body".into()),
+                        user_id: Some(id),
+                        comments_count: 0,
+                        created_at: Utc::now(),
+                        updated_at: Utc::now(),
+                        closed_at: None,
+                        state_reason: None,
+                        locked: false,
+                        author_association: None,
+                        dedupe_hash: format!("hash-{id}"),
+                        raw: json!({ "id": id }),
+                    },
+                    author: Some(UserRef {
+                        id,
+                        login: format!("author{id}"),
+                    }),
+                }
+            })
+            .collect();
+        Ok(IssuePage {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        self.users.inc();
+        tokio::time::sleep(Duration::from_millis(self.users.sleep_ms)).await;
+        self.users.dec();
+        Ok(UserFetch::Missing(collector::fetcher::MissingUser {
+            id: user.id,
+            login: user.login.clone(),
+            status: None,
+        }))
+    }
+}
+
+#[tokio::test]
+async fn user_fetches_are_bounded_across_repositories() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping user_fetches_are_bounded_across_repositories: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("user_fetch_bound").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    for i in 1..=4 {
+        db.collection_jobs()
+            .create(CollectionJobCreate {
+                owner: "o".into(),
+                name: format!("r{i}"),
+                priority: 0,
+            })
+            .await?;
+    }
+
+    let fetcher = Arc::new(UserStormFetcher {
+        users: SleepyFetcher::new(50),
+    });
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 4,
+        max_concurrent_user_fetches: 2,
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+    collector.run_once().await?;
+
+    // Four repos fetch authors at once, but only two lookups may be in flight.
+    let max_active = fetcher
+        .users
+        .max_active
+        .load(std::sync::atomic::Ordering::Relaxed);
+    assert_eq!(max_active, 2, "expected user fetches capped at 2");
+    let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+        .fetch_one(db.pool())
+        .await?;
+    assert_eq!(users, 20);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use analysis::{ContributionStats, FirstReply, RuleOutcome, Scorer};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use collector::fetcher::{
+    CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::{CommentRow, IssueRow, Repositories, UserRow};
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedComment, NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+/// Gives issues a fixed score and leaves comments clean, counting calls.
+#[derive(Default)]
+struct StubScorer {
+    issues: AtomicUsize,
+    comments: AtomicUsize,
+}
+
+#[async_trait]
+impl Scorer for StubScorer {
+    fn version(&self) -> &str {
+        "stub_v1"
+    }
+
+    async fn score_issue(
+        &self,
+        _issue: &IssueRow,
+        _user: Option<&UserRow>,
+        _stats: ContributionStats,
+        _dedupe_hits_last_48h: u32,
+    ) -> Result<RuleOutcome> {
+        self.issues.fetch_add(1, Ordering::SeqCst);
+        Ok(RuleOutcome {
+            score: 0.9,
+            reasons: vec!["stub_model".into()],
+            contributions: vec![("stub_model".into(), 0.9)],
+            ..RuleOutcome::new()
+        })
+    }
+
+    async fn score_comment(
+        &self,
+        _comment: &CommentRow,
+        _user: Option<&UserRow>,
+        _stats: ContributionStats,
+        _dedupe_hits_last_48h: u32,
+        _first_reply: Option<&FirstReply>,
+    ) -> Result<RuleOutcome> {
+        self.comments.fetch_add(1, Ordering::SeqCst);
+        Ok(RuleOutcome::new())
+    }
+}
+
+/// One ordinary issue with one comment that the rule engine would flag.
+struct OneThreadFetcher;
+
+#[async_trait]
+impl DataFetcher for OneThreadFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 1,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue: NormalizedIssue {
+                    id: 10,
+                    repo_id,
+                    number: 1,
+                    is_pull_request: false,
+                    state: "open".into(),
+                    title: "Docs build fails".into(),
+                    body: Some("The docs build fails on a clean checkout of main.".into()),
+                    user_id: None,
+                    comments_count: 1,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    closed_at: None,
+                    state_reason: None,
+                    locked: false,
+                    author_association: None,
+                    dedupe_hash: "issue-10".into(),
+                    raw: json!({ "id": 10 }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![CommentRecord {
+                comment: NormalizedComment {
+                    id: 11,
+                    issue_id,
+                    user_id: None,
+                    body: "telegram @helpdesk".into(),
+                    created_at: Utc::now(),
+                    updated_at: None,
+                    author_association: None,
+                    dedupe_hash: "comment-11".into(),
+                    raw: json!({ "id": 11 }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("records have no authors")
+    }
+}
+
+#[tokio::test]
+async fn collector_scores_with_injected_scorer() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping collector_scores_with_injected_scorer: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("custom_scorer").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "widgets".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let scorer = Arc::new(StubScorer::default());
+    let collector = Collector::new(
+        cfg.clone(),
+        Arc::new(OneThreadFetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    )
+    .with_scorer(scorer.clone());
+    collector.run_once().await?;
+
+    assert_eq!(scorer.issues.load(Ordering::SeqCst), 1);
+    assert_eq!(scorer.comments.load(Ordering::SeqCst), 1);
+
+    let issue_flags = db.spam_flags().list_for_subject("issue", 10).await?;
+    assert_eq!(issue_flags.len(), 1);
+    assert_eq!(issue_flags[0].version, "stub_v1");
+    assert_eq!(issue_flags[0].reasons, vec!["stub_model".to_string()]);
+
+    // The rule engine would flag this comment as contact-only; the stub does not.
+    assert!(db
+        .spam_flags()
+        .list_for_subject("comment", 11)
+        .await?
+        .is_empty());
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use collector::fetcher::{CommentPage, DataFetcher, IssuePage, RepoSnapshot, UserFetch};
+use collector::metrics::DB_UNAVAILABLE;
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::{CollectionJobCreate, CollectionJobRow, CollectionJobUpdate, CollectionStatus};
+use db::pg::PgDatabase;
+use db::{
+    CollectionJobRepository, CommentRepository, DbError, IssueRepository, RepoRepository,
+    Repositories, SpamFlagsRepository, UserRepository, WatermarkRepository,
+};
+use normalizer::payloads::UserRef;
+use sqlx::PgPool;
+use tokio::sync::oneshot;
+
+const FAILING_CALLS: usize = 5;
+
+/// Job queue whose first `FAILING_CALLS` reads fail as if Postgres were down,
+/// then comes back empty.
+#[derive(Default)]
+struct FlakyJobs {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl CollectionJobRepository for FlakyJobs {
+    async fn create(&self, _job: CollectionJobCreate) -> db::errors::Result<CollectionJobRow> {
+        unreachable!()
+    }
+
+    async fn get_pending(
+        &self,
+        _limit: i32,
+        _full_name: Option<&str>,
+    ) -> db::errors::Result<Vec<CollectionJobRow>> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < FAILING_CALLS {
+            return Err(DbError::Query(sqlx::Error::PoolTimedOut));
+        }
+        Ok(Vec::new())
+    }
+
+    async fn mark_in_progress(&self, _id: i64) -> db::errors::Result<()> {
+        unreachable!()
+    }
+
+    async fn update(&self, _update: CollectionJobUpdate) -> db::errors::Result<()> {
+        unreachable!()
+    }
+
+    async fn rename(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _new_owner: &str,
+        _new_name: &str,
+    ) -> db::errors::Result<bool> {
+        unreachable!()
+    }
+
+    async fn list(
+        &self,
+        _limit: i32,
+        _statuses: &[CollectionStatus],
+    ) -> db::errors::Result<Vec<CollectionJobRow>> {
+        unreachable!()
+    }
+}
+
+/// Only the job queue is used; everything else points at a pool that never connects.
+struct FlakyDb {
+    jobs: FlakyJobs,
+    unused: PgDatabase,
+}
+
+impl Repositories for FlakyDb {
+    fn repos(&self) -> &dyn RepoRepository {
+        self.unused.repos()
+    }
+    fn users(&self) -> &dyn UserRepository {
+        self.unused.users()
+    }
+    fn issues(&self) -> &dyn IssueRepository {
+        self.unused.issues()
+    }
+    fn comments(&self) -> &dyn CommentRepository {
+        self.unused.comments()
+    }
+    fn watermarks(&self) -> &dyn WatermarkRepository {
+        self.unused.watermarks()
+    }
+    fn spam_flags(&self) -> &dyn SpamFlagsRepository {
+        self.unused.spam_flags()
+    }
+    fn collection_jobs(&self) -> &dyn CollectionJobRepository {
+        &self.jobs
+    }
+}
+
+struct NoFetcher;
+
+#[async_trait]
+impl DataFetcher for NoFetcher {
+    async fn fetch_repo(&self, _owner: &str, _name: &str) -> Result<RepoSnapshot> {
+        unreachable!("no jobs are pending")
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _repo_id: i64,
+        _since: Option<chrono::DateTime<chrono::Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        unreachable!("no jobs are pending")
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        unreachable!("no jobs are pending")
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("no jobs are pending")
+    }
+}
+
+#[tokio::test]
+async fn run_loop_backs_off_while_the_database_is_unreachable() -> Result<()> {
+    let db = Arc::new(FlakyDb {
+        jobs: FlakyJobs::default(),
+        unused: PgDatabase::from_pool(PgPool::connect_lazy("postgres://localhost:1/unused")?),
+    });
+    let cfg = CollectorConfig {
+        // Once the database is back, the next run is an hour away.
+        interval_secs: 3600,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        db_unavailable_threshold: 1,
+        db_backoff_base_ms: 50,
+        db_backoff_max_ms: 200,
+        ..Default::default()
+    };
+    let collector = Arc::new(Collector::new(
+        cfg.clone(),
+        Arc::new(NoFetcher),
+        db.clone(),
+        cfg.max_concurrent_repos,
+    ));
+    let (stop, stopped) = oneshot::channel::<()>();
+    let running = tokio::spawn({
+        let collector = collector.clone();
+        async move {
+            collector
+                .run_until(async {
+                    let _ = stopped.await;
+                })
+                .await
+        }
+    });
+
+    // The first failure trips the breaker; runs then wait 50ms, 100ms, ...
+    // instead of the hour-long interval or a tight retry loop.
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    let calls = db.jobs.calls.load(Ordering::SeqCst);
+    assert!((1..=3).contains(&calls), "{calls} job reads in 120ms");
+    assert_eq!(DB_UNAVAILABLE.get(), 1);
+
+    // The first run that reaches the database clears the gauge and the
+    // regular interval applies again.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while db.jobs.calls.load(Ordering::SeqCst) <= FAILING_CALLS {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(DB_UNAVAILABLE.get(), 0);
+    let calls = db.jobs.calls.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(db.jobs.calls.load(Ordering::SeqCst), calls);
+
+    stop.send(()).unwrap();
+    running.await??;
+    Ok(())
+}
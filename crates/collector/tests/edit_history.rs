@@ -0,0 +1,180 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use collector::fetcher::{
+    CommentPage, ContentEdits, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+/// Issue 1 is a contact-me post that was edited hours after posting; issue 2
+/// is an ordinary report. Records which node ids had their edits requested.
+struct EditedFetcher {
+    created_at: DateTime<Utc>,
+    edit_lookups: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl DataFetcher for EditedFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 1,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let issue = |id: i64, title: &str, body: &str| IssueRecord {
+            issue: NormalizedIssue {
+                id,
+                repo_id,
+                number: id,
+                is_pull_request: false,
+                state: "open".into(),
+                title: title.into(),
+                body: Some(body.into()),
+                user_id: None,
+                comments_count: 0,
+                created_at: self.created_at,
+                updated_at: self.created_at + Duration::hours(3),
+                closed_at: None,
+                state_reason: None,
+                locked: false,
+                author_association: None,
+                dedupe_hash: format!("issue-{id}"),
+                raw: json!({ "id": id, "node_id": format!("I_node{id}") }),
+            },
+            author: None,
+        };
+        Ok(IssuePage {
+            items: vec![
+                issue(1, "Great offer", "telegram: @dealer99"),
+                issue(
+                    2,
+                    "Crash when the config file is missing",
+                    "Opening the settings page shows a blank screen instead of the \
+                     list of configured accounts.",
+                ),
+            ],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        unreachable!("issues have no comments")
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("issues have no authors")
+    }
+
+    async fn fetch_content_edits(&self, node_id: &str) -> Result<Option<ContentEdits>> {
+        self.edit_lookups.lock().unwrap().push(node_id.to_string());
+        Ok(Some(ContentEdits {
+            total_count: 2,
+            last_edited_at: Some(self.created_at + Duration::hours(3)),
+            last_editor: Some("link-swapper".into()),
+        }))
+    }
+}
+
+#[tokio::test]
+async fn suspicious_issues_are_rescored_with_their_edit_history() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping suspicious_issues_are_rescored_with_their_edit_history: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("edit_history").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "widgets".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        edit_history_min_score: 1.0,
+        ..Default::default()
+    };
+    let fetcher = Arc::new(EditedFetcher {
+        created_at: Utc::now() - Duration::days(1),
+        edit_lookups: Mutex::new(Vec::new()),
+    });
+    Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        repos,
+        cfg.max_concurrent_repos,
+    )
+    .run_once()
+    .await?;
+
+    // Only the subject over the preliminary threshold pays for the lookup.
+    assert_eq!(*fetcher.edit_lookups.lock().unwrap(), vec!["I_node1"]);
+
+    let edits = db
+        .spam_flags()
+        .get_content_edits("issue", 1)
+        .await?
+        .expect("edit history stored");
+    assert_eq!(edits.edit_count, 2);
+    assert_eq!(edits.last_editor_login.as_deref(), Some("link-swapper"));
+    assert!(db
+        .spam_flags()
+        .get_content_edits("issue", 2)
+        .await?
+        .is_none());
+
+    let flags = db.spam_flags().list_for_subject("issue", 1).await?;
+    assert!(
+        flags
+            .iter()
+            .any(|flag| flag.reasons.iter().any(|r| r == "edited_after_post")),
+        "{flags:?}"
+    );
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+const HUGE_PAGES: i64 = 12;
+
+// Serves one issue per page: the "huge" repo has HUGE_PAGES pages, every other repo one.
+#[derive(Default)]
+struct PagedFetcher {
+    fetch_log: Mutex<Vec<(String, i64)>>,
+}
+
+fn repo_id(name: &str) -> i64 {
+    match name {
+        "huge" => 1,
+        other => 100 + other.trim_start_matches("tiny").parse::<i64>().unwrap_or(0),
+    }
+}
+
+#[async_trait]
+impl DataFetcher for PagedFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: repo_id(name),
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let page: i64 = cursor.as_deref().map_or(0, |c| c.parse().unwrap());
+        self.fetch_log
+            .lock()
+            .unwrap()
+            .push((name.to_string(), page));
+        // Yield so other repo tasks can queue for a slot between pages.
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let pages = if name == "huge" { HUGE_PAGES } else { 1 };
+        let issue = NormalizedIssue {
+            id: repo_id * 1_000 + page,
+            repo_id,
+            number: page + 1,
+            is_pull_request: false,
+            state: "open".into(),
+            title: format!("issue {page}"),
+            body: Some("a perfectly ordinary bug report body".into()),
+            user_id: None,
+            comments_count: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: format!("hash-{repo_id}-{page}"),
+            raw: json!({}),
+        };
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue,
+                author: None,
+            }],
+            next_cursor: (page + 1 < pages).then(|| (page + 1).to_string()),
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("issues have no authors")
+    }
+}
+
+#[tokio::test]
+async fn tiny_repos_finish_before_huge_repo_drains() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping tiny_repos_finish_before_huge_repo_drains: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("fair_sched").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    // The huge repo has the highest priority so it is scheduled first.
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "o".into(),
+            name: "huge".into(),
+            priority: 10,
+        })
+        .await?;
+    for i in 0..3 {
+        db.collection_jobs()
+            .create(CollectionJobCreate {
+                owner: "o".into(),
+                name: format!("tiny{i}"),
+                priority: 0,
+            })
+            .await?;
+    }
+
+    let fetcher = Arc::new(PagedFetcher::default());
+    let cfg = CollectorConfig {
+        page_size: 50,
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Hybrid,
+        max_concurrent_repos: 1,
+        // Interleaving needs a repo waiting when the huge one yields its slot.
+        max_inflight_repos: 2,
+        ..Default::default()
+    };
+    let collector = Collector::new(cfg.clone(), fetcher.clone(), repos, 1);
+    collector.run_once().await?;
+
+    let log = fetcher.fetch_log.lock().unwrap().clone();
+    let position = |name: &str, page: i64| {
+        log.iter()
+            .position(|(n, p)| n == name && *p == page)
+            .unwrap_or_else(|| panic!("{name} page {page} not fetched: {log:?}"))
+    };
+    let huge_last = position("huge", HUGE_PAGES - 1);
+    assert_eq!(position("huge", 0), 0, "huge repo should start first");
+    for i in 0..3 {
+        let tiny = position(&format!("tiny{i}"), 0);
+        assert!(
+            tiny < huge_last,
+            "tiny{i} should complete before the huge repo drains: {log:?}"
+        );
+    }
+    assert_eq!(log.len() as i64, HUGE_PAGES + 3);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use std::sync::Arc;
+
+use analysis::{ContributionStats, FeatureSet, FirstReply, RuleOutcome, Scorer};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use collector::fetcher::{
+    CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::CollectorConfig;
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::{CommentRow, IssueRow, Repositories, UserRow};
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedComment, NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+fn features() -> FeatureSet {
+    FeatureSet {
+        body_length: 412,
+        url_count: 37,
+        mention_count: 4,
+        repeated_char_ratio: 0.25,
+        account_age_days: Some(1.5),
+        author_association: Some("NONE".into()),
+        ..Default::default()
+    }
+}
+
+/// Flags issues and comments with `features()`.
+struct LinkFarmScorer;
+
+impl LinkFarmScorer {
+    fn outcome() -> RuleOutcome {
+        RuleOutcome {
+            score: 2.0,
+            reasons: vec!["link_heavy".into()],
+            contributions: vec![("link_heavy".into(), 2.0)],
+            features: features(),
+        }
+    }
+}
+
+#[async_trait]
+impl Scorer for LinkFarmScorer {
+    fn version(&self) -> &str {
+        "stub_v1"
+    }
+
+    async fn score_issue(
+        &self,
+        _issue: &IssueRow,
+        _user: Option<&UserRow>,
+        _stats: ContributionStats,
+        _dedupe_hits_last_48h: u32,
+    ) -> Result<RuleOutcome> {
+        Ok(Self::outcome())
+    }
+
+    async fn score_comment(
+        &self,
+        _comment: &CommentRow,
+        _user: Option<&UserRow>,
+        _stats: ContributionStats,
+        _dedupe_hits_last_48h: u32,
+        _first_reply: Option<&FirstReply>,
+    ) -> Result<RuleOutcome> {
+        Ok(Self::outcome())
+    }
+}
+
+/// One issue with one comment.
+struct OneThreadFetcher;
+
+#[async_trait]
+impl DataFetcher for OneThreadFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 1,
+                full_name: format!("{owner}/{name}"),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue: NormalizedIssue {
+                    id: 10,
+                    repo_id,
+                    number: 1,
+                    is_pull_request: false,
+                    state: "open".into(),
+                    title: "Free downloads".into(),
+                    body: Some("See the links below.".into()),
+                    user_id: None,
+                    comments_count: 1,
+                    created_at: Utc::now() - Duration::hours(2),
+                    updated_at: Utc::now(),
+                    closed_at: None,
+                    state_reason: None,
+                    locked: false,
+                    author_association: None,
+                    dedupe_hash: "issue-10".into(),
+                    raw: json!({ "id": 10 }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![CommentRecord {
+                comment: NormalizedComment {
+                    id: 100,
+                    issue_id,
+                    user_id: None,
+                    body: "More links here.".into(),
+                    created_at: Utc::now() - Duration::hours(1),
+                    updated_at: None,
+                    author_association: None,
+                    dedupe_hash: "comment-100".into(),
+                    raw: json!({ "id": 100 }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("records have no authors")
+    }
+}
+
+#[tokio::test]
+async fn flag_features_round_trip_when_enabled() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping flag_features_round_trip_when_enabled: {err}");
+            return Ok(());
+        }
+    };
+
+    for store in [true, false] {
+        let handle = fixture.create(&format!("flag_features_{store}")).await?;
+        let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+        let repos: Arc<dyn Repositories> = db.clone();
+        db.collection_jobs()
+            .create(CollectionJobCreate {
+                owner: "acme".into(),
+                name: "widgets".into(),
+                priority: 0,
+            })
+            .await?;
+
+        let cfg = CollectorConfig {
+            run_once: true,
+            max_concurrent_repos: 1,
+            store_flag_features: store,
+            ..Default::default()
+        };
+        Collector::new(
+            cfg.clone(),
+            Arc::new(OneThreadFetcher),
+            repos,
+            cfg.max_concurrent_repos,
+        )
+        .with_scorer(Arc::new(LinkFarmScorer))
+        .run_once()
+        .await?;
+
+        for (subject_type, subject_id) in [("issue", 10), ("comment", 100)] {
+            let flags = db
+                .spam_flags()
+                .list_for_subject(subject_type, subject_id)
+                .await?;
+            assert_eq!(flags.len(), 1, "{subject_type} flag");
+            let stored = flags[0].features.clone();
+            if store {
+                let stored = stored.expect("features stored");
+                assert_eq!(stored["url_count"], json!(37));
+                assert_eq!(serde_json::from_value::<FeatureSet>(stored)?, features());
+            } else {
+                assert_eq!(stored, None, "{subject_type} features without the toggle");
+            }
+        }
+
+        handle.cleanup().await?;
+    }
+    Ok(())
+}
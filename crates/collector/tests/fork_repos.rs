@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use analysis::RuleEngine;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use collector::fetcher::{
+    CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::CollectorConfig;
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedComment, NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+/// A repository with one issue whose only comment is a support scam.
+struct ScamCommentFetcher {
+    is_fork: bool,
+}
+
+#[async_trait]
+impl DataFetcher for ScamCommentFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 1,
+                full_name: format!("{owner}/{name}"),
+                is_fork: self.is_fork,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue: NormalizedIssue {
+                    id: 10,
+                    repo_id,
+                    number: 1,
+                    is_pull_request: false,
+                    state: "open".into(),
+                    title: "Wallet sync fails".into(),
+                    body: Some("Syncing stops after the first block on every restart.".into()),
+                    user_id: None,
+                    comments_count: 1,
+                    created_at: Utc::now() - Duration::hours(2),
+                    updated_at: Utc::now(),
+                    closed_at: None,
+                    state_reason: None,
+                    locked: false,
+                    author_association: None,
+                    dedupe_hash: "issue-10".into(),
+                    raw: json!({ "id": 10 }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![CommentRecord {
+                comment: NormalizedComment {
+                    id: 100,
+                    issue_id,
+                    user_id: None,
+                    body: "telegram @helpdesk".into(),
+                    created_at: Utc::now() - Duration::hours(1),
+                    updated_at: None,
+                    author_association: None,
+                    dedupe_hash: "comment-100".into(),
+                    raw: json!({ "id": 100 }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("records have no authors")
+    }
+}
+
+/// Collects the scam comment and returns the reasons it was flagged with.
+async fn comment_reasons(
+    fixture: &DbFixture,
+    is_fork: bool,
+    skip_fork_scoring: bool,
+) -> Result<Option<Vec<String>>> {
+    let handle = fixture
+        .create(&format!("fork_repos_{is_fork}_{skip_fork_scoring}"))
+        .await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "wallet".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let cfg = CollectorConfig {
+        run_once: true,
+        max_concurrent_repos: 1,
+        skip_fork_scoring,
+        ..Default::default()
+    };
+    Collector::new(
+        cfg.clone(),
+        Arc::new(ScamCommentFetcher { is_fork }),
+        repos,
+        cfg.max_concurrent_repos,
+    )
+    .with_rule_engine(RuleEngine::default().with_fork_adjustment(1.0))
+    .run_once()
+    .await?;
+
+    assert_eq!(db.comments().list_by_issue(10).await?.len(), 1);
+    let flags = db.spam_flags().list_for_subject("comment", 100).await?;
+    handle.cleanup().await?;
+    Ok(flags.into_iter().next().map(|flag| flag.reasons))
+}
+
+#[tokio::test]
+async fn fork_adjustment_and_skipping_apply_only_to_forks() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping fork_adjustment_and_skipping_apply_only_to_forks: {err}");
+            return Ok(());
+        }
+    };
+
+    let upstream = comment_reasons(&fixture, false, false)
+        .await?
+        .expect("scam comment is flagged");
+    assert!(!upstream.contains(&"fork_repo".to_string()));
+
+    let fork = comment_reasons(&fixture, true, false)
+        .await?
+        .expect("scam comment is flagged");
+    assert!(fork.contains(&"fork_repo".to_string()));
+
+    // Skipping only affects forks; the fork's comment is stored but not scored.
+    let upstream = comment_reasons(&fixture, false, true).await?;
+    assert!(upstream.is_some());
+    assert_eq!(comment_reasons(&fixture, true, true).await?, None);
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::fetcher::{CommentPage, DataFetcher, IssuePage, RepoSnapshot, UserFetch};
+use collector::metrics::RUNS_TOTAL;
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::payloads::UserRef;
+use tokio::sync::oneshot;
+
+/// No jobs are queued, so the fetcher is never reached.
+struct UnusedFetcher;
+
+#[async_trait]
+impl DataFetcher for UnusedFetcher {
+    async fn fetch_repo(&self, _owner: &str, _name: &str) -> Result<RepoSnapshot> {
+        unreachable!("no jobs queued")
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        unreachable!("no jobs queued")
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        unreachable!("no jobs queued")
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("no jobs queued")
+    }
+}
+
+#[tokio::test]
+async fn shutdown_interrupts_the_wait_between_cycles() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping shutdown_interrupts_the_wait_between_cycles: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("graceful_shutdown").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    let cfg = CollectorConfig {
+        interval_secs: 3600,
+        run_once: false,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let collector = Arc::new(Collector::new(
+        cfg.clone(),
+        Arc::new(UnusedFetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    ));
+
+    let runs_before = RUNS_TOTAL.get();
+    let (tx, rx) = oneshot::channel::<()>();
+    let running = tokio::spawn({
+        let collector = collector.clone();
+        async move {
+            collector
+                .run_until(async {
+                    let _ = rx.await;
+                })
+                .await
+        }
+    });
+
+    // Let the first cycle finish so the loop is parked in its interval sleep.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while RUNS_TOTAL.get() == runs_before {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("first cycle should start");
+    tx.send(()).expect("collector still listening");
+
+    tokio::time::timeout(Duration::from_secs(5), running)
+        .await
+        .expect("collector should stop well before the next interval")??;
+
+    handle.cleanup().await?;
+    Ok(())
+}
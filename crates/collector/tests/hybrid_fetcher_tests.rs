@@ -58,6 +58,14 @@ impl GithubClient for StubClientFound {
     ) -> Result<Vec<serde_json::Value>> {
         unreachable!()
     }
+    async fn get_issue(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _issue_number: u64,
+    ) -> Result<serde_json::Value> {
+        unreachable!()
+    }
     async fn list_issue_comments(
         &self,
         _owner: &str,
@@ -81,6 +89,9 @@ impl GithubClient for StubClientFound {
         });
         Ok(value)
     }
+    async fn get_org_membership(&self, _org: &str, _login: &str) -> Result<bool> {
+        unreachable!()
+    }
 }
 
 struct StubClient404;
@@ -100,6 +111,14 @@ impl GithubClient for StubClient404 {
     ) -> Result<Vec<serde_json::Value>> {
         unreachable!()
     }
+    async fn get_issue(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _issue_number: u64,
+    ) -> Result<serde_json::Value> {
+        unreachable!()
+    }
     async fn list_issue_comments(
         &self,
         _owner: &str,
@@ -113,6 +132,9 @@ impl GithubClient for StubClient404 {
     async fn get_user(&self, login: &str) -> Result<serde_json::Value> {
         Err(GithubApiError::status(StatusCode::NOT_FOUND, format!("users/{login}")).into())
     }
+    async fn get_org_membership(&self, _org: &str, _login: &str) -> Result<bool> {
+        unreachable!()
+    }
 }
 
 #[tokio::test]
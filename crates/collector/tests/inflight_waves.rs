@@ -0,0 +1,251 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::fetcher::{CommentPage, DataFetcher, IssuePage, RepoSnapshot, UserFetch};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::{CollectionJobCreate, CollectionStatus};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::NormalizedRepository;
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+const JOBS: i64 = 12;
+
+/// Empty repos that take a moment to list. On every repo fetch it records how
+/// many jobs are claimed (`in_progress`) at that moment.
+struct CountingFetcher {
+    db: Arc<PgDatabase>,
+    max_in_progress: AtomicUsize,
+}
+
+#[async_trait]
+impl DataFetcher for CountingFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        let in_progress = self
+            .db
+            .collection_jobs()
+            .list(1000, &[CollectionStatus::InProgress])
+            .await?
+            .len();
+        self.max_in_progress
+            .fetch_max(in_progress, Ordering::SeqCst);
+        let id: i64 = name.trim_start_matches('r').parse()?;
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: id + 1,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": id + 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        Ok(IssuePage {
+            items: Vec::new(),
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        unreachable!("repos have no issues")
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("repos have no issues")
+    }
+}
+
+#[tokio::test]
+async fn pending_jobs_are_claimed_in_waves_bounded_by_capacity() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping pending_jobs_are_claimed_in_waves_bounded_by_capacity: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("inflight_waves").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    for n in 0..JOBS {
+        db.collection_jobs()
+            .create(CollectionJobCreate {
+                owner: "acme".into(),
+                name: format!("r{n}"),
+                priority: 0,
+            })
+            .await?;
+    }
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 2,
+        max_inflight_repos: 3,
+        ..Default::default()
+    };
+    let fetcher = Arc::new(CountingFetcher {
+        db: db.clone(),
+        max_in_progress: AtomicUsize::new(0),
+    });
+    let collector = Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+    collector.run_once().await?;
+
+    assert_eq!(fetcher.max_in_progress.load(Ordering::SeqCst), 3);
+    let completed = db
+        .collection_jobs()
+        .list(1000, &[CollectionStatus::Completed])
+        .await?;
+    assert_eq!(completed.len(), JOBS as usize);
+
+    handle.cleanup().await?;
+    Ok(())
+}
+
+/// Counts repo tasks that have started (`fetch_repo`) but not yet finished
+/// listing their single empty page, and remembers the peak.
+#[derive(Default)]
+struct LiveTaskFetcher {
+    live: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+#[async_trait]
+impl DataFetcher for LiveTaskFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        let live = self.live.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(live, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let id: i64 = name.trim_start_matches('r').parse()?;
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: id + 1,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": id + 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        self.live.fetch_sub(1, Ordering::SeqCst);
+        Ok(IssuePage {
+            items: Vec::new(),
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        unreachable!("repos have no issues")
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("repos have no issues")
+    }
+}
+
+#[tokio::test]
+async fn spawned_repo_tasks_never_exceed_the_inflight_bound() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping spawned_repo_tasks_never_exceed_the_inflight_bound: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("inflight_tasks").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    for n in 0..JOBS * 2 {
+        db.collection_jobs()
+            .create(CollectionJobCreate {
+                owner: "acme".into(),
+                name: format!("r{n}"),
+                priority: 0,
+            })
+            .await?;
+    }
+
+    // By default spawning is gated to the page-slot count: no task waits
+    // around holding its captured state for a slot.
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 3,
+        ..Default::default()
+    };
+    let fetcher = Arc::new(LiveTaskFetcher::default());
+    Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        repos,
+        cfg.max_concurrent_repos,
+    )
+    .run_once()
+    .await?;
+
+    assert_eq!(fetcher.peak.load(Ordering::SeqCst), 3);
+    let completed = db
+        .collection_jobs()
+        .list(1000, &[CollectionStatus::Completed])
+        .await?;
+    assert_eq!(completed.len(), (JOBS * 2) as usize);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::{CollectionJobCreate, UserRow};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository, NormalizedUser};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+fn author(id: i64, login: &str) -> UserRef {
+    UserRef {
+        id,
+        login: login.into(),
+    }
+}
+
+/// Three issues: one by an author the listing inlined, one by an old account
+/// already in the DB, one by an unknown author. Records `fetch_user` calls.
+#[derive(Default)]
+struct InliningFetcher {
+    user_fetches: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl DataFetcher for InliningFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 1,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let items = [
+            author(101, "inlined"),
+            author(102, "veteran"),
+            author(103, "stranger"),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(n, user)| {
+            let id = n as i64 + 1;
+            IssueRecord {
+                issue: NormalizedIssue {
+                    id,
+                    repo_id,
+                    number: id,
+                    is_pull_request: false,
+                    state: "open".into(),
+                    title: format!("Report from {}", user.login),
+                    body: Some("The export button does nothing on the reports page.".into()),
+                    user_id: Some(user.id),
+                    comments_count: 0,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    closed_at: None,
+                    state_reason: None,
+                    locked: false,
+                    author_association: None,
+                    dedupe_hash: format!("issue-{id}"),
+                    raw: json!({ "id": id }),
+                },
+                author: Some(user),
+            }
+        })
+        .collect();
+        Ok(IssuePage {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        unreachable!("issues have no comments")
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        self.user_fetches.lock().unwrap().push(user.login.clone());
+        Ok(UserFetch::Found(NormalizedUser {
+            id: user.id,
+            login: user.login.clone(),
+            user_type: "User".into(),
+            site_admin: false,
+            created_at: Some(Utc::now() - Duration::days(2)),
+            followers: Some(0),
+            following: Some(0),
+            public_repos: Some(0),
+            bio: None,
+            blog: None,
+            raw: json!({ "id": user.id, "login": user.login }),
+        }))
+    }
+
+    async fn inlined_user(&self, user: &UserRef) -> Option<NormalizedUser> {
+        (user.login == "inlined").then(|| NormalizedUser {
+            id: user.id,
+            login: user.login.clone(),
+            user_type: "User".into(),
+            site_admin: false,
+            created_at: Some(Utc::now() - Duration::days(1)),
+            followers: Some(0),
+            following: Some(0),
+            public_repos: Some(0),
+            bio: None,
+            blog: None,
+            raw: json!({ "id": user.id, "login": user.login }),
+        })
+    }
+}
+
+#[tokio::test]
+async fn inlined_and_settled_authors_are_not_fetched_again() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping inlined_and_settled_authors_are_not_fetched_again: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("inlined_users").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.users()
+        .upsert(UserRow {
+            id: 102,
+            login: "veteran".into(),
+            user_type: "User".into(),
+            site_admin: false,
+            created_at: Some(Utc::now() - Duration::days(400)),
+            followers: Some(50),
+            following: Some(3),
+            public_repos: Some(12),
+            bio: None,
+            blog: None,
+            raw: json!({ "id": 102, "login": "veteran" }),
+            found: true,
+        })
+        .await?;
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "widgets".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        skip_user_fetch_min_age_days: 30,
+        ..Default::default()
+    };
+    let fetcher = Arc::new(InliningFetcher::default());
+    Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        repos,
+        cfg.max_concurrent_repos,
+    )
+    .run_once()
+    .await?;
+
+    assert_eq!(*fetcher.user_fetches.lock().unwrap(), vec!["stranger"]);
+    let inlined = db
+        .users()
+        .get_by_login("inlined")
+        .await?
+        .expect("inlined author stored");
+    assert!(inlined.found);
+    assert!(inlined.created_at.is_some());
+    let veteran = db.users().get_by_login("veteran").await?.expect("kept");
+    assert_eq!(veteran.followers, Some(50));
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use collector::client::BrokerGithubClient;
+use collector::fetcher::RestDataFetcher;
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use gh_broker::{GithubBroker, Priority};
+use http::{Request, Response, StatusCode};
+use serde_json::json;
+
+/// Serves an empty repository for any `repos/{owner}/{name}` and records the
+/// priority each request was enqueued at.
+#[derive(Default)]
+struct RecordingBroker {
+    enqueued: Mutex<Vec<(String, Priority)>>,
+}
+
+impl GithubBroker for RecordingBroker {
+    fn enqueue(
+        &self,
+        request: Request<Vec<u8>>,
+        priority: Priority,
+    ) -> futures::future::BoxFuture<'static, Result<Response<Vec<u8>>>> {
+        let path = request.uri().path().trim_start_matches('/').to_string();
+        self.enqueued.lock().unwrap().push((path.clone(), priority));
+        let segments: Vec<&str> = path.split('/').collect();
+        let body = match segments.as_slice() {
+            ["repos", owner, name] => json!({
+                "id": if *name == "backlog" { 2 } else { 1 },
+                "full_name": format!("{owner}/{name}"),
+                "fork": false,
+                "created_at": "2020-01-01T00:00:00Z",
+                "pushed_at": null
+            }),
+            _ => json!([]),
+        };
+        let response = Response::builder()
+            .status(StatusCode::OK)
+            .body(body.to_string().into_bytes())
+            .unwrap();
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+#[tokio::test]
+async fn job_priority_sets_broker_priority_for_its_requests() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping job_priority_sets_broker_priority_for_its_requests: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("job_priority").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    for (name, priority) in [("urgent", 5), ("backlog", -1)] {
+        db.collection_jobs()
+            .create(CollectionJobCreate {
+                owner: "acme".into(),
+                name: name.into(),
+                priority,
+            })
+            .await?;
+    }
+
+    let broker = Arc::new(RecordingBroker::default());
+    let client = Arc::new(BrokerGithubClient::new(broker.clone(), "ua".into()));
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    Collector::new(
+        cfg.clone(),
+        Arc::new(RestDataFetcher::new(client)),
+        repos,
+        cfg.max_concurrent_repos,
+    )
+    .run_once()
+    .await?;
+
+    let enqueued = broker.enqueued.lock().unwrap().clone();
+    let priorities_for = |name: &str| -> Vec<Priority> {
+        let prefix = format!("repos/acme/{name}");
+        enqueued
+            .iter()
+            .filter(|(path, _)| path.starts_with(&prefix))
+            .map(|(_, priority)| *priority)
+            .collect()
+    };
+    let backlog = priorities_for("backlog");
+    assert!(backlog.len() >= 2, "repo and issue requests: {enqueued:?}");
+    assert!(
+        backlog.iter().all(|p| *p == Priority::Backfill),
+        "{enqueued:?}"
+    );
+    let urgent = priorities_for("urgent");
+    assert!(urgent.len() >= 2, "repo and issue requests: {enqueued:?}");
+    assert!(
+        urgent.iter().all(|p| *p == Priority::Critical),
+        "{enqueued:?}"
+    );
+
+    handle.cleanup().await?;
+    Ok(())
+}
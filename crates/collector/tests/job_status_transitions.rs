@@ -95,6 +95,7 @@ fn cfg() -> CollectorConfig {
         run_once: true,
         fetch_mode: FetchMode::Hybrid,
         max_concurrent_repos: 2,
+        ..Default::default()
     }
 }
 
@@ -125,7 +126,7 @@ async fn job_completes_on_success() -> Result<()> {
     let collector = Collector::new(cfgv.clone(), fetcher, repos, cfgv.max_concurrent_repos);
     collector.run_once().await?;
 
-    let listed = db.collection_jobs().list(10).await?;
+    let listed = db.collection_jobs().list(10, &[]).await?;
     let j = listed.into_iter().find(|j| j.id == job.id).unwrap();
     assert!(matches!(j.status, CollectionStatus::Completed));
 
@@ -162,7 +163,7 @@ async fn job_retries_on_transient_error() -> Result<()> {
     let collector = Collector::new(cfgv.clone(), fetcher, repos, cfgv.max_concurrent_repos);
     collector.run_once().await?;
 
-    let listed = db.collection_jobs().list(10).await?;
+    let listed = db.collection_jobs().list(10, &[]).await?;
     let j = listed.into_iter().find(|j| j.id == job.id).unwrap();
     // Transient: returns to pending with failure count incremented
     assert!(matches!(j.status, CollectionStatus::Pending));
@@ -201,7 +202,7 @@ async fn job_errors_on_permanent_error() -> Result<()> {
     let collector = Collector::new(cfgv.clone(), fetcher, repos, cfgv.max_concurrent_repos);
     collector.run_once().await?;
 
-    let listed = db.collection_jobs().list(10).await?;
+    let listed = db.collection_jobs().list(10, &[]).await?;
     let j = listed.into_iter().find(|j| j.id == job.id).unwrap();
     assert!(matches!(j.status, CollectionStatus::Error));
 
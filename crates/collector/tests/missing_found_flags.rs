@@ -1,19 +1,20 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, TimeZone, Utc};
 use collector::fetcher::{
     CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
 };
 use collector::service::Collector;
 use common::config::{CollectorConfig, FetchMode};
-use db::models::{CollectionJobCreate, IssueQuery};
+use db::models::{CollectionJobCreate, CollectionJobUpdate, CollectionStatus, IssueQuery};
 use db::pg::PgDatabase;
 use db::Repositories;
 use db_test_fixture::DbFixture;
 use http::StatusCode;
-use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::models::{NormalizedIssue, NormalizedRepository, NormalizedUser};
 use normalizer::payloads::UserRef;
 use serde_json::json;
 
@@ -24,6 +25,7 @@ fn cfg() -> CollectorConfig {
         run_once: true,
         fetch_mode: FetchMode::Hybrid,
         max_concurrent_repos: 1,
+        ..Default::default()
     }
 }
 
@@ -66,6 +68,9 @@ impl DataFetcher for MissingUserFetcher {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
             dedupe_hash: "h".into(),
             raw: json!({}),
         };
@@ -186,6 +191,9 @@ impl DataFetcher for MissingIssueCommentsFetcher {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
             dedupe_hash: "hh".into(),
             raw: json!({}),
         };
@@ -259,7 +267,9 @@ async fn issue_comments_404_marks_issue_found_false() -> Result<()> {
             repo_full_name: Some("o/r".into()),
             limit: Some(10),
             spam: None,
+            reviewed: None,
             since: None,
+            order_by: None,
         })
         .await?;
     let issue = rows
@@ -271,3 +281,187 @@ async fn issue_comments_404_marks_issue_found_false() -> Result<()> {
     handle.cleanup().await?;
     Ok(())
 }
+
+// Issue comments and the author 404 until `recovered` is set, as during a
+// temporary GitHub glitch. The issue's updated_at never changes, so the
+// watermark alone would never bring it back.
+struct GlitchFetcher {
+    recovered: AtomicBool,
+}
+
+fn glitch_updated_at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap()
+}
+
+#[async_trait]
+impl DataFetcher for GlitchFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 9020,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let issue = NormalizedIssue {
+            id: 9021,
+            repo_id,
+            number: 7,
+            is_pull_request: false,
+            state: "open".into(),
+            title: "t".into(),
+            body: None,
+            user_id: Some(4343),
+            comments_count: 1,
+            created_at: glitch_updated_at(),
+            updated_at: glitch_updated_at(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: "hg".into(),
+            raw: json!({}),
+        };
+        let items = if since.is_some_and(|since| since >= issue.updated_at) {
+            Vec::new()
+        } else {
+            vec![IssueRecord {
+                issue,
+                author: Some(UserRef {
+                    id: 4343,
+                    login: "flaky-user".into(),
+                }),
+            }]
+        };
+        Ok(IssuePage {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        owner: &str,
+        name: &str,
+        number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        if !self.recovered.load(Ordering::SeqCst) {
+            return Err(anyhow!(collector::client::GithubApiError::status(
+                StatusCode::NOT_FOUND,
+                format!("repos/{}/{}/issues/{}", owner, name, number)
+            )));
+        }
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        if !self.recovered.load(Ordering::SeqCst) {
+            return Ok(UserFetch::Missing(collector::fetcher::MissingUser {
+                id: user.id,
+                login: user.login.clone(),
+                status: Some(StatusCode::NOT_FOUND),
+            }));
+        }
+        Ok(UserFetch::Found(NormalizedUser {
+            id: user.id,
+            login: user.login.clone(),
+            user_type: "User".into(),
+            site_admin: false,
+            created_at: Some(Utc::now()),
+            followers: None,
+            following: None,
+            public_repos: None,
+            bio: None,
+            blog: None,
+            raw: json!({}),
+        }))
+    }
+}
+
+#[tokio::test]
+async fn resurrected_issue_and_user_flip_back_to_found() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping resurrected_issue_and_user_flip_back_to_found: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("issue_resurrected").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    let job = db
+        .collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "o".into(),
+            name: "r".into(),
+            priority: 0,
+        })
+        .await?;
+    let issue_found = || async {
+        let rows = db
+            .issues()
+            .query(IssueQuery {
+                repo_full_name: Some("o/r".into()),
+                ..Default::default()
+            })
+            .await?;
+        anyhow::Ok(
+            rows.iter()
+                .find(|i| i.number == 7)
+                .expect("issue row")
+                .found,
+        )
+    };
+
+    let fetcher = Arc::new(GlitchFetcher {
+        recovered: AtomicBool::new(false),
+    });
+    let cfgv = cfg();
+
+    Collector::new(cfgv.clone(), fetcher.clone(), repos.clone(), 1)
+        .run_once()
+        .await?;
+    assert!(!issue_found().await?);
+    let user = db.users().get_by_login("flaky-user").await?.expect("user");
+    assert!(!user.found);
+
+    fetcher.recovered.store(true, Ordering::SeqCst);
+    db.collection_jobs()
+        .update(CollectionJobUpdate {
+            id: job.id,
+            status: CollectionStatus::Pending,
+            error_message: None,
+        })
+        .await?;
+    Collector::new(cfgv.clone(), fetcher.clone(), repos.clone(), 1)
+        .run_once()
+        .await?;
+    assert!(issue_found().await?, "issue should be found again");
+    let user = db.users().get_by_login("flaky-user").await?.expect("user");
+    assert!(user.found, "user should be found again");
+
+    handle.cleanup().await?;
+    Ok(())
+}
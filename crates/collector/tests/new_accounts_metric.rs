@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::metrics::NEW_ACCOUNTS_SEEN;
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository, NormalizedUser};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+// Three issues: two by a two-day-old account, one by a years-old account.
+struct AuthoredFetcher;
+
+fn issue(id: i64, repo_id: i64) -> NormalizedIssue {
+    NormalizedIssue {
+        id,
+        repo_id,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("issue {id}"),
+        body: Some("a perfectly ordinary bug report body".into()),
+        user_id: None,
+        comments_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("hash-{id}"),
+        raw: json!({}),
+    }
+}
+
+fn author(id: i64, login: &str) -> Option<UserRef> {
+    Some(UserRef {
+        id,
+        login: login.into(),
+    })
+}
+
+#[async_trait]
+impl DataFetcher for AuthoredFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 9,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        Ok(IssuePage {
+            items: vec![
+                IssueRecord {
+                    issue: issue(1, repo_id),
+                    author: author(1, "fresh"),
+                },
+                IssueRecord {
+                    issue: issue(2, repo_id),
+                    author: author(1, "fresh"),
+                },
+                IssueRecord {
+                    issue: issue(3, repo_id),
+                    author: author(2, "veteran"),
+                },
+            ],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        let age = if user.login == "fresh" {
+            Duration::days(2)
+        } else {
+            Duration::days(3 * 365)
+        };
+        Ok(UserFetch::Found(NormalizedUser {
+            id: user.id,
+            login: user.login.clone(),
+            user_type: "User".into(),
+            site_admin: false,
+            created_at: Some(Utc::now() - age),
+            followers: None,
+            following: None,
+            public_repos: None,
+            bio: None,
+            blog: None,
+            raw: json!({}),
+        }))
+    }
+}
+
+#[tokio::test]
+async fn counts_distinct_new_account_authors() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping counts_distinct_new_account_authors: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("new_accounts").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "signup".into(),
+            name: "wave".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let cfg = CollectorConfig {
+        page_size: 50,
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Hybrid,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    Collector::new(cfg, Arc::new(AuthoredFetcher), repos, 1)
+        .run_once()
+        .await?;
+
+    assert_eq!(
+        NEW_ACCOUNTS_SEEN.with_label_values(&["signup/wave"]).get(),
+        1
+    );
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::client::{BrokerGithubClient, GithubClient};
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use gh_broker::broker::HttpExec;
+use gh_broker::{GithubBrokerBuilder, GithubToken};
+use http::{Request, Response};
+use normalizer::models::{NormalizedIssue, NormalizedRepository, NormalizedUser};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+/// GitHub's public membership endpoint: 204 for members, 404 otherwise.
+struct MembershipExec;
+
+#[async_trait]
+impl HttpExec for MembershipExec {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let status = if req.uri().path() == "/orgs/acme/public_members/alice" {
+            204
+        } else {
+            404
+        };
+        Ok(Response::builder().status(status).body(Vec::new())?)
+    }
+}
+
+#[tokio::test]
+async fn client_maps_membership_responses() -> Result<()> {
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(Arc::new(MembershipExec))
+    .build();
+    let client = BrokerGithubClient::new(broker, "test-agent".into());
+
+    assert!(client.get_org_membership("acme", "alice").await?);
+    assert!(!client.get_org_membership("acme", "mallory").await?);
+    Ok(())
+}
+
+/// Two issues per author with the same spammy body; only `alice` is in `acme`.
+struct MembershipFetcher {
+    checks: Mutex<HashMap<String, u32>>,
+}
+
+fn author(login: &str) -> UserRef {
+    UserRef {
+        id: if login == "alice" { 1 } else { 2 },
+        login: login.into(),
+    }
+}
+
+#[async_trait]
+impl DataFetcher for MembershipFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 321,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({"id": 321}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let items = [(1, "alice"), (2, "alice"), (3, "mallory"), (4, "mallory")]
+            .into_iter()
+            .map(|(id, login)| IssueRecord {
+                issue: NormalizedIssue {
+                    id,
+                    repo_id,
+                    number: id,
+                    is_pull_request: false,
+                    state: "open".into(),
+                    title: "Support".into(),
+                    body: Some("telegram @helpdesk".into()),
+                    user_id: Some(author(login).id),
+                    comments_count: 0,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    closed_at: None,
+                    state_reason: None,
+                    locked: false,
+                    author_association: None,
+                    dedupe_hash: format!("hash-{id}"),
+                    raw: json!({"id": id}),
+                },
+                author: Some(author(login)),
+            })
+            .collect();
+        Ok(IssuePage {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        Ok(UserFetch::Found(NormalizedUser {
+            id: user.id,
+            login: user.login.clone(),
+            user_type: "User".into(),
+            site_admin: false,
+            created_at: None,
+            followers: None,
+            following: None,
+            public_repos: None,
+            bio: None,
+            blog: None,
+            raw: json!({"login": user.login}),
+        }))
+    }
+
+    async fn fetch_org_membership(&self, org: &str, login: &str) -> Result<Option<bool>> {
+        *self
+            .checks
+            .lock()
+            .unwrap()
+            .entry(login.to_string())
+            .or_default() += 1;
+        Ok(Some(org == "acme" && login == "alice"))
+    }
+}
+
+#[tokio::test]
+async fn org_members_are_trusted_and_membership_is_cached() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping org_members_are_trusted_and_membership_is_cached: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("org_membership").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "widgets".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let fetcher = Arc::new(MembershipFetcher {
+        checks: Mutex::new(HashMap::new()),
+    });
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+    collector.run_once().await?;
+
+    for (issue_id, flagged) in [(1, false), (2, false), (3, true), (4, true)] {
+        let flags = db.spam_flags().list_for_subject("issue", issue_id).await?;
+        assert_eq!(!flags.is_empty(), flagged, "issue {issue_id}");
+    }
+
+    // One GitHub check per author, and the answer is stored for later runs.
+    let checks = fetcher.checks.lock().unwrap().clone();
+    assert_eq!(checks.get("alice"), Some(&1));
+    assert_eq!(checks.get("mallory"), Some(&1));
+    let alice = db.users().org_membership("acme", 1).await?.expect("cached");
+    assert!(alice.is_member);
+    let mallory = db.users().org_membership("acme", 2).await?.expect("cached");
+    assert!(!mallory.is_member);
+
+    handle.cleanup().await?;
+    Ok(())
+}
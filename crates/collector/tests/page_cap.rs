@@ -0,0 +1,159 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::{CollectionJobCreate, CollectionStatus};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+const MAX_PAGES: usize = 3;
+
+// Never runs out of pages: every page carries a cursor to the next one.
+#[derive(Default)]
+struct EndlessFetcher {
+    cursors: Mutex<Vec<Option<String>>>,
+}
+
+#[async_trait]
+impl DataFetcher for EndlessFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 9,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        self.cursors.lock().unwrap().push(cursor.clone());
+        let page: i64 = cursor.as_deref().map_or(0, |c| c.parse().unwrap());
+        let issue = NormalizedIssue {
+            id: 900 + page,
+            repo_id,
+            number: page + 1,
+            is_pull_request: false,
+            state: "open".into(),
+            title: format!("issue {page}"),
+            body: Some("a perfectly ordinary bug report body".into()),
+            user_id: None,
+            comments_count: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: format!("hash-{page}"),
+            raw: json!({}),
+        };
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue,
+                author: None,
+            }],
+            next_cursor: Some((page + 1).to_string()),
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("issues have no authors")
+    }
+}
+
+#[tokio::test]
+async fn page_cap_stops_run_and_keeps_job_pending() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping page_cap_stops_run_and_keeps_job_pending: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("page_cap").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    let job = db
+        .collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "o".into(),
+            name: "huge".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let fetcher = Arc::new(EndlessFetcher::default());
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        max_pages_per_repo: MAX_PAGES,
+        ..Default::default()
+    };
+
+    Collector::new(cfg.clone(), fetcher.clone(), repos.clone(), 1)
+        .run_once()
+        .await?;
+    assert_eq!(fetcher.cursors.lock().unwrap().len(), MAX_PAGES);
+    let progress = db
+        .watermarks()
+        .get_progress("o/huge")
+        .await?
+        .expect("cursor kept for the next run");
+    assert_eq!(progress.cursor, MAX_PAGES.to_string());
+    assert!(db.watermarks().get("o/huge").await?.is_none());
+    let jobs = db.collection_jobs().list(10, &[]).await?;
+    let stored = jobs.iter().find(|j| j.id == job.id).expect("job");
+    assert!(matches!(stored.status, CollectionStatus::Pending));
+
+    // The next run continues where the capped one stopped.
+    fetcher.cursors.lock().unwrap().clear();
+    Collector::new(cfg.clone(), fetcher.clone(), repos.clone(), 1)
+        .run_once()
+        .await?;
+    let cursors = fetcher.cursors.lock().unwrap().clone();
+    assert_eq!(cursors.len(), MAX_PAGES);
+    assert_eq!(cursors[0].as_deref(), Some("3"));
+
+    handle.cleanup().await?;
+    Ok(())
+}
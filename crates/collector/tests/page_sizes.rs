@@ -0,0 +1,141 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+// Records the per_page value of every issue and comment listing call.
+#[derive(Default)]
+struct RecordingFetcher {
+    issue_page_sizes: Mutex<Vec<u32>>,
+    comment_page_sizes: Mutex<Vec<u32>>,
+}
+
+#[async_trait]
+impl DataFetcher for RecordingFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 11,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        per_page: u32,
+    ) -> Result<IssuePage> {
+        self.issue_page_sizes.lock().unwrap().push(per_page);
+        let issue = NormalizedIssue {
+            id: 1100,
+            repo_id,
+            number: 1,
+            is_pull_request: false,
+            state: "open".into(),
+            title: "issue with comments".into(),
+            body: Some("a perfectly ordinary bug report body".into()),
+            user_id: None,
+            comments_count: 2,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: "hash-1100".into(),
+            raw: json!({}),
+        };
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue,
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        per_page: u32,
+    ) -> Result<CommentPage> {
+        self.comment_page_sizes.lock().unwrap().push(per_page);
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("issues have no authors")
+    }
+}
+
+#[tokio::test]
+async fn issue_and_comment_page_sizes_are_independent() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping issue_and_comment_page_sizes_are_independent: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("page_sizes").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "o".into(),
+            name: "r".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let fetcher = Arc::new(RecordingFetcher::default());
+    let cfg = CollectorConfig {
+        page_size: 50,
+        issue_page_size: Some(80),
+        comment_page_size: Some(30),
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Hybrid,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    Collector::new(cfg, fetcher.clone(), repos, 1)
+        .run_once()
+        .await?;
+
+    assert_eq!(*fetcher.issue_page_sizes.lock().unwrap(), vec![80]);
+    assert_eq!(*fetcher.comment_page_sizes.lock().unwrap(), vec![30]);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::CollectorConfig;
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+/// Lists issue #10 and pull request #11, both with a comment, and records which
+/// numbers had their comments paged.
+#[derive(Default)]
+struct CommentedPrFetcher {
+    comment_fetches: Mutex<Vec<i64>>,
+}
+
+fn issue(id: i64, repo_id: i64, number: i64, is_pull_request: bool) -> IssueRecord {
+    IssueRecord {
+        issue: NormalizedIssue {
+            id,
+            repo_id,
+            number,
+            is_pull_request,
+            state: "open".into(),
+            title: format!("Thread {number}"),
+            body: Some(format!("Details for thread {number} with enough words")),
+            user_id: None,
+            comments_count: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: format!("hash-{number}"),
+            raw: json!({}),
+        },
+        author: None,
+    }
+}
+
+#[async_trait]
+impl DataFetcher for CommentedPrFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 778,
+                full_name: format!("{owner}/{name}"),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({"id": 778}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        Ok(IssuePage {
+            items: vec![issue(1, repo_id, 10, false), issue(2, repo_id, 11, true)],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        self.comment_fetches.lock().unwrap().push(issue_number);
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        Ok(UserFetch::Missing(collector::fetcher::MissingUser {
+            id: user.id,
+            login: user.login.clone(),
+            status: None,
+        }))
+    }
+}
+
+#[tokio::test]
+async fn skip_pull_request_comments_only_skips_prs() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping skip_pull_request_comments_only_skips_prs: {err}");
+            return Ok(());
+        }
+    };
+
+    for (skip, expected) in [(false, vec![10, 11]), (true, vec![10])] {
+        let handle = fixture
+            .create(&format!("pr_comment_skipping_{skip}"))
+            .await?;
+        let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+        let repos: Arc<dyn Repositories> = db.clone();
+        db.collection_jobs()
+            .create(CollectionJobCreate {
+                owner: "o".into(),
+                name: "r".into(),
+                priority: 0,
+            })
+            .await?;
+
+        let fetcher = Arc::new(CommentedPrFetcher::default());
+        let cfg = CollectorConfig {
+            run_once: true,
+            max_concurrent_repos: 1,
+            skip_pull_request_comments: skip,
+            ..Default::default()
+        };
+        Collector::new(
+            cfg.clone(),
+            fetcher.clone(),
+            repos,
+            cfg.max_concurrent_repos,
+        )
+        .run_once()
+        .await?;
+
+        let mut fetched = fetcher.comment_fetches.lock().unwrap().clone();
+        fetched.sort();
+        assert_eq!(fetched, expected, "skip_pull_request_comments = {skip}");
+
+        handle.cleanup().await?;
+    }
+    Ok(())
+}
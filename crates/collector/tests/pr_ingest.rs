@@ -7,7 +7,7 @@ use collector::fetcher::{
     CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
 };
 use collector::service::Collector;
-use common::config::{CollectorConfig, FetchMode};
+use common::config::{CollectorConfig, FetchMode, PullRequestMode};
 use db::models::CollectionJobCreate;
 use db::pg::PgDatabase;
 use db::Repositories;
@@ -55,6 +55,9 @@ impl DataFetcher for PrIssueFetcher {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
             dedupe_hash: "hash-issue".into(),
             raw: json!({}),
         };
@@ -71,6 +74,9 @@ impl DataFetcher for PrIssueFetcher {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
             dedupe_hash: "hash-pr".into(),
             raw: json!({}),
         };
@@ -140,6 +146,7 @@ async fn ingests_prs_and_issues() -> Result<()> {
         run_once: true,
         fetch_mode: FetchMode::Hybrid,
         max_concurrent_repos: 1,
+        ..Default::default()
     };
     let collector = Collector::new(
         cfg.clone(),
@@ -156,7 +163,9 @@ async fn ingests_prs_and_issues() -> Result<()> {
             repo_full_name: Some("o/r".into()),
             limit: Some(100),
             spam: None,
+            reviewed: None,
             since: None,
+            order_by: None,
         })
         .await?;
     let mut seen_issue = false;
@@ -176,3 +185,71 @@ async fn ingests_prs_and_issues() -> Result<()> {
     handle.cleanup().await?;
     Ok(())
 }
+
+#[tokio::test]
+async fn pull_request_mode_controls_storage_and_scoring() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping pull_request_mode_controls_storage_and_scoring: {err}");
+            return Ok(());
+        }
+    };
+
+    // (mode, PR stored, PR scored)
+    for (mode, stored, scored) in [
+        (PullRequestMode::Include, true, true),
+        (PullRequestMode::Skip, false, false),
+        (PullRequestMode::Separate, true, false),
+    ] {
+        let handle = fixture
+            .create(&format!("pr_mode_{mode:?}").to_lowercase())
+            .await?;
+        let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+        let repos: Arc<dyn Repositories> = db.clone();
+        db.collection_jobs()
+            .create(CollectionJobCreate {
+                owner: "o".into(),
+                name: "r".into(),
+                priority: 0,
+            })
+            .await?;
+
+        let cfg = CollectorConfig {
+            interval_secs: 0,
+            run_once: true,
+            max_concurrent_repos: 1,
+            pull_request_mode: mode,
+            ..Default::default()
+        };
+        Collector::new(
+            cfg.clone(),
+            Arc::new(PrIssueFetcher),
+            repos,
+            cfg.max_concurrent_repos,
+        )
+        .run_once()
+        .await?;
+
+        let rows = db.issues().list_by_repo(777, None).await?;
+        assert!(
+            rows.iter().any(|row| row.number == 10),
+            "{mode:?}: issue stored"
+        );
+        assert_eq!(
+            rows.iter()
+                .any(|row| row.number == 11 && row.is_pull_request),
+            stored,
+            "{mode:?}: PR stored"
+        );
+
+        // Both two-word bodies trip the low-entropy rule when scored.
+        let issue_flags = db.spam_flags().list_for_subject("issue", 1).await?;
+        assert!(!issue_flags.is_empty(), "{mode:?}: issue scored");
+        let pr_flags = db.spam_flags().list_for_subject("issue", 2).await?;
+        assert_eq!(!pr_flags.is_empty(), scored, "{mode:?}: PR scored");
+
+        handle.cleanup().await?;
+    }
+    Ok(())
+}
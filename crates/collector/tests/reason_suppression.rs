@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+/// Each repo gets one issue whose only hit is the `template_phrase` rule.
+struct TemplateIssueFetcher;
+
+fn repo_id(name: &str) -> i64 {
+    if name == "docs" {
+        1
+    } else {
+        2
+    }
+}
+
+#[async_trait]
+impl DataFetcher for TemplateIssueFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        let id = repo_id(name);
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": id }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let id = repo_id * 10;
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue: NormalizedIssue {
+                    id,
+                    repo_id,
+                    number: 1,
+                    is_pull_request: false,
+                    state: "open".into(),
+                    title: "Retry backoff per endpoint".into(),
+                    body: Some(
+                        "Feature request: allow configuring the retry backoff per endpoint \
+                         so slow mirrors do not stall the whole queue."
+                            .into(),
+                    ),
+                    user_id: None,
+                    comments_count: 0,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    closed_at: None,
+                    state_reason: None,
+                    locked: false,
+                    author_association: None,
+                    dedupe_hash: format!("hash-{id}"),
+                    raw: json!({ "id": id }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("issues have no author")
+    }
+}
+
+#[tokio::test]
+async fn suppressed_reason_drops_subject_below_flag_threshold() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping suppressed_reason_drops_subject_below_flag_threshold: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("reason_suppression").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    for name in ["docs", "app"] {
+        db.collection_jobs()
+            .create(CollectionJobCreate {
+                owner: "acme".into(),
+                name: name.into(),
+                priority: 0,
+            })
+            .await?;
+    }
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        suppressed_reasons: HashMap::from([(
+            "acme/docs".to_string(),
+            vec!["template_phrase".to_string()],
+        )]),
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        Arc::new(TemplateIssueFetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+    collector.run_once().await?;
+
+    let docs = db.spam_flags().list_for_subject("issue", 10).await?;
+    assert!(docs.is_empty(), "suppressed repo should not be flagged");
+
+    let app = db.spam_flags().list_for_subject("issue", 20).await?;
+    assert_eq!(app.len(), 1);
+    assert_eq!(app[0].reasons, vec!["template_phrase".to_string()]);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::fetcher::{
+    CommentPage, CommentRecord, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use collector::sink::JsonLinesFileSink;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedComment, NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::{json, Value};
+
+struct TwoIssueFetcher;
+
+#[async_trait]
+impl DataFetcher for TwoIssueFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 555,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({"id": 555}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let items = (1..=2)
+            .map(|id| IssueRecord {
+                issue: NormalizedIssue {
+                    id,
+                    repo_id,
+                    number: id,
+                    is_pull_request: false,
+                    state: "open".into(),
+                    title: format!("issue {id}"),
+                    body: Some(format!("body of issue {id}")),
+                    user_id: None,
+                    comments_count: 1,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    closed_at: None,
+                    state_reason: None,
+                    locked: false,
+                    author_association: None,
+                    dedupe_hash: format!("issue-hash-{id}"),
+                    raw: json!({"id": id}),
+                },
+                author: None,
+            })
+            .collect();
+        Ok(IssuePage {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        let id = issue_id * 100;
+        Ok(CommentPage {
+            items: vec![CommentRecord {
+                comment: NormalizedComment {
+                    id,
+                    issue_id,
+                    user_id: None,
+                    body: format!("reply on issue {issue_id}"),
+                    created_at: Utc::now(),
+                    updated_at: None,
+                    author_association: None,
+                    dedupe_hash: format!("comment-hash-{id}"),
+                    raw: json!({"id": id}),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        Ok(UserFetch::Missing(collector::fetcher::MissingUser {
+            id: user.id,
+            login: user.login.clone(),
+            status: None,
+        }))
+    }
+}
+
+#[tokio::test]
+async fn file_sink_receives_every_normalized_record() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping file_sink_receives_every_normalized_record: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("record_sink").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "o".into(),
+            name: "sink".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let path = std::env::temp_dir().join(format!(
+        "record_sink_{}_{}.jsonl",
+        std::process::id(),
+        Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    let path_str = path.to_string_lossy().into_owned();
+    let sink = Arc::new(JsonLinesFileSink::open(&path_str).await?);
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        Arc::new(TwoIssueFetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    )
+    .with_sink(sink);
+    collector.run_once().await?;
+
+    let contents = std::fs::read_to_string(&path)?;
+    std::fs::remove_file(&path)?;
+    let lines: Vec<Value> = contents
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    let mut seen: Vec<(String, i64)> = lines
+        .iter()
+        .map(|line| {
+            (
+                line["kind"].as_str().unwrap_or_default().to_string(),
+                line["record"]["id"].as_i64().unwrap_or_default(),
+            )
+        })
+        .collect();
+    seen.sort();
+    assert_eq!(
+        seen,
+        vec![
+            ("comment".to_string(), 100),
+            ("comment".to_string(), 200),
+            ("issue".to_string(), 1),
+            ("issue".to_string(), 2),
+        ]
+    );
+    let issue: NormalizedIssue = serde_json::from_value(
+        lines
+            .iter()
+            .find(|line| line["kind"] == "issue" && line["record"]["id"] == 1)
+            .expect("issue 1 line")["record"]
+            .clone(),
+    )?;
+    assert_eq!(issue.dedupe_hash, "issue-hash-1");
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -80,6 +80,7 @@ fn test_config() -> CollectorConfig {
         run_once: true,
         fetch_mode: FetchMode::Rest,
         max_concurrent_repos: 2,
+        ..Default::default()
     }
 }
 
@@ -0,0 +1,291 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use collector::client::BrokerGithubClient;
+use collector::fetcher::{DataFetcher, RestDataFetcher, UserFetch};
+use gh_broker::broker::HttpExec;
+use gh_broker::{GithubBrokerBuilder, GithubToken};
+use http::{Request, Response, StatusCode};
+use normalizer::payloads::UserRef;
+
+/// Serves recorded GitHub responses from `tests/fixtures/github`, laid out by
+/// request path (`/repos/o/r/issues` -> `repos/o/r/issues.json`). Query strings
+/// are ignored; unrecorded paths get GitHub's 404 body.
+struct FixtureReplay {
+    root: PathBuf,
+    requested: Mutex<Vec<String>>,
+}
+
+impl FixtureReplay {
+    fn new() -> Self {
+        Self {
+            root: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/github"),
+            requested: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(byte) = u8::from_str_radix(&path[i + 1..i + 3], 16) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[async_trait]
+impl HttpExec for FixtureReplay {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let path = percent_decode(req.uri().path().trim_start_matches('/'));
+        self.requested.lock().unwrap().push(path.clone());
+        let file = self.root.join(format!("{path}.json"));
+        let response = match std::fs::read(&file) {
+            Ok(body) => Response::builder()
+                .status(StatusCode::OK)
+                .header(
+                    http::header::CONTENT_TYPE,
+                    "application/json; charset=utf-8",
+                )
+                .body(body)?,
+            Err(_) => Response::builder().status(StatusCode::NOT_FOUND).body(
+                br#"{"message":"Not Found","documentation_url":"https://docs.github.com/rest"}"#
+                    .to_vec(),
+            )?,
+        };
+        Ok(response)
+    }
+}
+
+fn replay_fetcher() -> (Arc<FixtureReplay>, RestDataFetcher) {
+    let replay = Arc::new(FixtureReplay::new());
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(replay.clone())
+    .build();
+    let client = Arc::new(BrokerGithubClient::new(broker, "fixture-replay".into()));
+    (replay, RestDataFetcher::new(client))
+}
+
+#[tokio::test]
+async fn normalizes_recorded_repo() -> Result<()> {
+    let (_, fetcher) = replay_fetcher();
+    let repo = fetcher.fetch_repo("octo-org", "widgets").await?.repository;
+
+    assert_eq!(repo.id, 1296269);
+    assert_eq!(repo.full_name, "octo-org/widgets");
+    assert!(!repo.is_fork);
+    assert_eq!(
+        repo.created_at,
+        Utc.with_ymd_and_hms(2011, 1, 26, 19, 1, 12).unwrap()
+    );
+    assert_eq!(
+        repo.pushed_at,
+        Some(Utc.with_ymd_and_hms(2024, 3, 10, 22, 41, 3).unwrap())
+    );
+    assert_eq!(repo.raw["license"]["spdx_id"], "MIT");
+    Ok(())
+}
+
+#[tokio::test]
+async fn normalizes_recorded_issue_listing() -> Result<()> {
+    let (replay, fetcher) = replay_fetcher();
+    let page = fetcher
+        .fetch_issues("octo-org", "widgets", 1296269, None, None, 100)
+        .await?;
+
+    assert_eq!(page.next_cursor, None, "short page ends pagination");
+    let numbers: Vec<i64> = page.items.iter().map(|r| r.issue.number).collect();
+    assert_eq!(numbers, vec![1347, 1346, 1290]);
+
+    let bug = &page.items[0];
+    assert_eq!(bug.issue.id, 1987654321);
+    assert_eq!(bug.issue.repo_id, 1296269);
+    assert!(!bug.issue.is_pull_request);
+    assert_eq!(bug.issue.state, "open");
+    assert_eq!(bug.issue.comments_count, 2);
+    assert_eq!(bug.issue.user_id, Some(583231));
+    assert_eq!(bug.issue.closed_at, None);
+    assert_eq!(bug.issue.state_reason, None);
+    assert_eq!(bug.issue.author_association.as_deref(), Some("CONTRIBUTOR"));
+    assert!(bug
+        .issue
+        .body
+        .as_deref()
+        .unwrap()
+        .contains("index out of bounds"));
+    assert_eq!(
+        bug.author.as_ref().map(|a| a.login.as_str()),
+        Some("octocat")
+    );
+    assert!(!bug.issue.dedupe_hash.is_empty());
+
+    // Pull requests come back from the issues endpoint with a `pull_request` object.
+    let pr = &page.items[1];
+    assert!(pr.issue.is_pull_request);
+    assert_eq!(pr.issue.state, "closed");
+    assert_eq!(pr.issue.body, None);
+    assert_eq!(
+        pr.issue.closed_at,
+        Some(Utc.with_ymd_and_hms(2024, 3, 5, 9, 14, 26).unwrap())
+    );
+    assert_eq!(
+        pr.author.as_ref().map(|a| a.login.as_str()),
+        Some("dependabot[bot]")
+    );
+
+    // Deleted accounts come back as `"user": null`.
+    let orphan = &page.items[2];
+    assert!(!orphan.issue.is_pull_request);
+    assert_eq!(orphan.issue.user_id, None);
+    assert!(orphan.author.is_none());
+    assert_eq!(orphan.issue.body.as_deref(), Some(""));
+    assert_ne!(orphan.issue.dedupe_hash, bug.issue.dedupe_hash);
+    assert_eq!(orphan.issue.state_reason.as_deref(), Some("not_planned"));
+    assert_eq!(orphan.issue.author_association.as_deref(), Some("NONE"));
+
+    assert_eq!(
+        *replay.requested.lock().unwrap(),
+        vec!["repos/octo-org/widgets/issues".to_string()]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn normalizes_recorded_comments() -> Result<()> {
+    let (_, fetcher) = replay_fetcher();
+    let page = fetcher
+        .fetch_issue_comments("octo-org", "widgets", 1347, 1987654321, None, 100)
+        .await?;
+
+    assert_eq!(page.next_cursor, None);
+    assert_eq!(page.items.len(), 2);
+    let first = &page.items[0];
+    assert_eq!(first.comment.id, 1985000001);
+    assert_eq!(first.comment.issue_id, 1987654321);
+    assert_eq!(first.comment.user_id, Some(480938));
+    assert_eq!(
+        first.comment.body,
+        "Thanks, I can reproduce this on `main`."
+    );
+    assert_eq!(
+        first.comment.updated_at,
+        Some(Utc.with_ymd_and_hms(2024, 3, 9, 8, 1, 55).unwrap())
+    );
+    assert_eq!(
+        first.author.as_ref().map(|a| a.login.as_str()),
+        Some("hubot")
+    );
+    assert_eq!(first.comment.author_association.as_deref(), Some("MEMBER"));
+    assert_eq!(page.items[1].comment.user_id, Some(583231));
+
+    // An issue without recorded comments is a 404, which reads as an empty page.
+    let empty = fetcher
+        .fetch_issue_comments("octo-org", "widgets", 1290, 1900000042, None, 100)
+        .await?;
+    assert!(empty.items.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn normalizes_recorded_users() -> Result<()> {
+    let (_, fetcher) = replay_fetcher();
+
+    let UserFetch::Found(octocat) = fetcher
+        .fetch_user(&UserRef {
+            id: 583231,
+            login: "octocat".into(),
+        })
+        .await?
+    else {
+        panic!("octocat is recorded");
+    };
+    assert_eq!(octocat.id, 583231);
+    assert_eq!(octocat.user_type, "User");
+    assert!(!octocat.site_admin);
+    assert_eq!(octocat.followers, Some(16843));
+    assert_eq!(octocat.following, Some(9));
+    assert_eq!(octocat.public_repos, Some(8));
+    assert_eq!(
+        octocat.created_at,
+        Some(Utc.with_ymd_and_hms(2011, 1, 25, 18, 44, 36).unwrap())
+    );
+
+    // Bot logins need escaping in the URL and omit the follower counts.
+    let UserFetch::Found(bot) = fetcher
+        .fetch_user(&UserRef {
+            id: 49699333,
+            login: "dependabot[bot]".into(),
+        })
+        .await?
+    else {
+        panic!("dependabot[bot] is recorded");
+    };
+    assert_eq!(bot.user_type, "Bot");
+    assert_eq!(bot.followers, None);
+    assert_eq!(bot.public_repos, None);
+
+    let missing = fetcher
+        .fetch_user(&UserRef {
+            id: 1,
+            login: "deleted-account".into(),
+        })
+        .await?;
+    match missing {
+        UserFetch::Missing(user) => {
+            assert_eq!(user.login, "deleted-account");
+            assert_eq!(user.status, Some(StatusCode::NOT_FOUND));
+        }
+        UserFetch::Found(_) => panic!("unrecorded user should be missing"),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn normalizes_recorded_single_issue() -> Result<()> {
+    let (replay, fetcher) = replay_fetcher();
+    let record = fetcher
+        .fetch_issue("octo-org", "widgets", 1296269, 1347)
+        .await?
+        .expect("issue 1347 is recorded");
+
+    // Same normalization as the listing entry for this issue.
+    let listed = fetcher
+        .fetch_issues("octo-org", "widgets", 1296269, None, None, 100)
+        .await?
+        .items
+        .remove(0);
+    assert_eq!(record.issue, listed.issue);
+    assert_eq!(
+        record.author.map(|a| a.login),
+        listed.author.map(|a| a.login)
+    );
+
+    assert_eq!(
+        fetcher
+            .fetch_issue("octo-org", "widgets", 1296269, 9999)
+            .await?
+            .map(|r| r.issue.id),
+        None
+    );
+    assert_eq!(
+        replay.requested.lock().unwrap()[0],
+        "repos/octo-org/widgets/issues/1347"
+    );
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use collector::client::{BrokerGithubClient, GithubApiError, GithubClient, MAX_PER_PAGE};
+use collector::fetcher::{DataFetcher, RestDataFetcher};
+use gh_broker::broker::HttpExec;
+use gh_broker::{GithubBrokerBuilder, GithubToken};
+use http::{Request, Response, StatusCode};
+
+/// Records request URIs and answers with a fixed status and body.
+struct RecordingExec {
+    status: u16,
+    body: &'static str,
+    uris: Mutex<Vec<String>>,
+}
+
+impl RecordingExec {
+    fn new(status: u16, body: &'static str) -> Arc<Self> {
+        Arc::new(Self {
+            status,
+            body,
+            uris: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+#[async_trait]
+impl HttpExec for RecordingExec {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        self.uris.lock().unwrap().push(req.uri().to_string());
+        Ok(Response::builder()
+            .status(self.status)
+            .header("content-type", "application/json")
+            .body(self.body.as_bytes().to_vec())?)
+    }
+}
+
+fn client(exec: Arc<RecordingExec>) -> BrokerGithubClient {
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec)
+    .build();
+    BrokerGithubClient::new(broker, "test-agent".into())
+}
+
+#[tokio::test]
+async fn paging_is_clamped_before_the_request_is_built() -> Result<()> {
+    let exec = RecordingExec::new(200, "[]");
+    let client = client(exec.clone());
+    let since =
+        Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap() + chrono::Duration::milliseconds(250);
+
+    client
+        .list_repo_issues("acme", "widgets", Some(since), 0, 250)
+        .await?;
+    client
+        .list_issue_comments("acme", "widgets", 7, 3, 0)
+        .await?;
+
+    let uris = exec.uris.lock().unwrap().clone();
+    assert_eq!(
+        uris,
+        vec![
+            "https://api.github.com/repos/acme/widgets/issues?state=all&sort=updated&direction=desc\
+             &page=1&per_page=100&since=2024-05-01T10%3A00%3A00Z",
+            "https://api.github.com/repos/acme/widgets/issues/7/comments?page=3&per_page=1",
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn unprocessable_response_names_the_rejected_parameter() -> Result<()> {
+    let exec = RecordingExec::new(
+        422,
+        r#"{"message":"Validation Failed","errors":[{"resource":"Issue","field":"since","code":"invalid"}]}"#,
+    );
+    let client = client(exec);
+
+    let err = client
+        .list_repo_issues("acme", "widgets", None, 1, 100)
+        .await
+        .unwrap_err();
+    let api_err = err
+        .downcast_ref::<GithubApiError>()
+        .expect("typed github error");
+    assert_eq!(api_err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(api_err.endpoint(), "repos/acme/widgets/issues");
+    match api_err {
+        GithubApiError::Unprocessable {
+            fields, message, ..
+        } => {
+            assert_eq!(fields, &vec!["since".to_string()]);
+            assert_eq!(message, "Validation Failed");
+        }
+        other => panic!("expected Unprocessable, got {other:?}"),
+    }
+    assert_eq!(
+        err.to_string(),
+        "github rejected since for repos/acme/widgets/issues: Validation Failed"
+    );
+    Ok(())
+}
+
+/// Serves `MAX_PER_PAGE` items for pages 1 and 2 and a single item for
+/// page 3, whatever `per_page` was asked for.
+struct FullPagesExec {
+    uris: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl HttpExec for FullPagesExec {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let uri = req.uri().to_string();
+        self.uris.lock().unwrap().push(uri.clone());
+        let page: u32 = url::Url::parse(&uri)?
+            .query_pairs()
+            .find(|(name, _)| name == "page")
+            .and_then(|(_, value)| value.parse().ok())
+            .unwrap_or(1);
+        let len = if page < 3 { MAX_PER_PAGE } else { 1 };
+        let first = (page - 1) * MAX_PER_PAGE + 1;
+        let items: Vec<_> = (first..first + len)
+            .map(|n| {
+                serde_json::json!({
+                    "id": n,
+                    "number": n,
+                    "state": "open",
+                    "title": format!("item {n}"),
+                    "body": "body",
+                    "user": null,
+                    "comments": 0,
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "updated_at": "2024-01-01T00:00:00Z",
+                })
+            })
+            .collect();
+        Ok(Response::builder()
+            .status(200)
+            .header("content-type", "application/json")
+            .body(serde_json::to_vec(&items)?)?)
+    }
+}
+
+#[tokio::test]
+async fn oversized_per_page_follows_every_full_page() -> Result<()> {
+    let exec = Arc::new(FullPagesExec {
+        uris: Mutex::new(Vec::new()),
+    });
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec.clone())
+    .build();
+    let client = Arc::new(BrokerGithubClient::new(broker, "test-agent".into()));
+    let fetcher = RestDataFetcher::new(client);
+
+    let mut cursors = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = fetcher
+            .fetch_issues("acme", "widgets", 1, None, cursor, 250)
+            .await?;
+        cursor = page.next_cursor;
+        cursors.push(cursor.clone());
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(cursors, vec![Some("2".into()), Some("3".into()), None]);
+
+    let mut comments = 0;
+    let mut cursor = None;
+    loop {
+        let page = fetcher
+            .fetch_issue_comments("acme", "widgets", 7, 7, cursor, 250)
+            .await?;
+        comments += page.items.len();
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(comments, 2 * MAX_PER_PAGE as usize + 1);
+
+    let uris = exec.uris.lock().unwrap().clone();
+    assert_eq!(uris.len(), 6, "{uris:?}");
+    assert!(
+        uris.iter().all(|uri| uri.contains("per_page=100")),
+        "{uris:?}"
+    );
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration as StdDuration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use collector::client::GithubClient;
+use collector::fetcher::{DataFetcher, RestDataFetcher};
+use serde_json::{json, Value};
+use tokio::sync::Notify;
+
+const PER_PAGE: u32 = 2;
+const PAGES: u32 = 3;
+
+fn newest() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+}
+
+fn issue(n: u32) -> Value {
+    json!({
+        "id": n,
+        "number": n,
+        "pull_request": null,
+        "state": "open",
+        "title": format!("issue {n}"),
+        "body": "body",
+        "user": null,
+        "comments": 0,
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": (newest() - Duration::hours(n as i64)).to_rfc3339(),
+        "closed_at": null,
+    })
+}
+
+fn comment(n: u32) -> Value {
+    json!({
+        "id": n,
+        "body": format!("comment {n}"),
+        "user": null,
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": null,
+    })
+}
+
+/// Serves `PAGES` pages (the last one short) of issues and comments, newest
+/// issue first, recording every page requested.
+#[derive(Default)]
+struct CountingClient {
+    issue_pages: Mutex<Vec<u32>>,
+    comment_pages: Mutex<Vec<u32>>,
+    requested: Notify,
+}
+
+impl CountingClient {
+    fn page_items(page: u32, item: fn(u32) -> Value) -> Vec<Value> {
+        let first = (page - 1) * PER_PAGE + 1;
+        let len = if page < PAGES { PER_PAGE } else { 1 };
+        (first..first + len).map(item).collect()
+    }
+
+    fn issue_pages(&self) -> Vec<u32> {
+        self.issue_pages.lock().unwrap().clone()
+    }
+
+    fn comment_pages(&self) -> Vec<u32> {
+        self.comment_pages.lock().unwrap().clone()
+    }
+
+    /// Waits until `pages()` lists `page`, as the caller would while still
+    /// processing the previous one.
+    async fn wait_for(&self, pages: impl Fn() -> Vec<u32>, page: u32) {
+        tokio::time::timeout(StdDuration::from_secs(5), async {
+            loop {
+                let requested = self.requested.notified();
+                if pages().contains(&page) {
+                    return;
+                }
+                requested.await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("page {page} was never requested"));
+    }
+}
+
+#[async_trait]
+impl GithubClient for CountingClient {
+    async fn get_repo(&self, _owner: &str, _repo: &str) -> Result<Value> {
+        unreachable!()
+    }
+    async fn list_repo_issues(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _since: Option<DateTime<Utc>>,
+        page: u32,
+        _per_page: u32,
+    ) -> Result<Vec<Value>> {
+        self.issue_pages.lock().unwrap().push(page);
+        self.requested.notify_waiters();
+        Ok(Self::page_items(page, issue))
+    }
+    async fn get_issue(&self, _owner: &str, _repo: &str, _issue_number: u64) -> Result<Value> {
+        unreachable!()
+    }
+    async fn list_issue_comments(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _issue_number: u64,
+        page: u32,
+        _per_page: u32,
+    ) -> Result<Vec<Value>> {
+        self.comment_pages.lock().unwrap().push(page);
+        self.requested.notify_waiters();
+        Ok(Self::page_items(page, comment))
+    }
+    async fn get_user(&self, _login: &str) -> Result<Value> {
+        unreachable!()
+    }
+    async fn get_org_membership(&self, _org: &str, _login: &str) -> Result<bool> {
+        unreachable!()
+    }
+}
+
+#[tokio::test]
+async fn next_page_is_requested_while_the_current_one_is_processed() -> Result<()> {
+    let client = Arc::new(CountingClient::default());
+    let fetcher = RestDataFetcher::new(client.clone()).with_prefetch(true);
+
+    let mut cursor = None;
+    for page in 1..=PAGES {
+        let issues = fetcher
+            .fetch_issues("o", "r", 1, None, cursor, PER_PAGE)
+            .await?;
+        assert_eq!(
+            issues.items[0].issue.number,
+            ((page - 1) * PER_PAGE + 1) as i64
+        );
+        if page < PAGES {
+            // Page N+1 is on its way before the caller asks for it.
+            client.wait_for(|| client.issue_pages(), page + 1).await;
+        }
+        cursor = issues.next_cursor;
+    }
+    assert_eq!(cursor, None);
+    // Each page went out once and nothing past the short last page.
+    assert_eq!(client.issue_pages(), vec![1, 2, 3]);
+
+    let mut cursor = None;
+    for page in 1..=PAGES {
+        let comments = fetcher
+            .fetch_issue_comments("o", "r", 1, 1, cursor, PER_PAGE)
+            .await?;
+        if page < PAGES {
+            client.wait_for(|| client.comment_pages(), page + 1).await;
+        }
+        cursor = comments.next_cursor;
+    }
+    assert_eq!(client.comment_pages(), vec![1, 2, 3]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn prefetch_stops_at_the_watermark_and_is_opt_in() -> Result<()> {
+    // Issue 2 sits on the watermark, so page 2 only holds older issues.
+    let since = Some(newest() - Duration::hours(2));
+    let client = Arc::new(CountingClient::default());
+    let fetcher = RestDataFetcher::new(client.clone()).with_prefetch(true);
+    let issues = fetcher
+        .fetch_issues("o", "r", 1, since, None, PER_PAGE)
+        .await?;
+    assert!(issues.next_cursor.is_some());
+    tokio::time::sleep(StdDuration::from_millis(50)).await;
+    assert_eq!(client.issue_pages(), vec![1]);
+
+    let client = Arc::new(CountingClient::default());
+    let fetcher = RestDataFetcher::new(client.clone());
+    fetcher
+        .fetch_issues("o", "r", 1, None, None, PER_PAGE)
+        .await?;
+    tokio::time::sleep(StdDuration::from_millis(50)).await;
+    assert_eq!(client.issue_pages(), vec![1]);
+
+    // A prefetched page the caller never asks for is dropped with the repo.
+    let client = Arc::new(CountingClient::default());
+    let fetcher = RestDataFetcher::new(client.clone()).with_prefetch(true);
+    fetcher
+        .fetch_issues("o", "r", 1, None, None, PER_PAGE)
+        .await?;
+    client.wait_for(|| client.issue_pages(), 2).await;
+    fetcher.discard_prefetches("o", "r");
+    fetcher
+        .fetch_issues("o", "r", 1, None, Some("2".into()), PER_PAGE)
+        .await?;
+    assert_eq!(client.issue_pages(), vec![1, 2, 2]);
+    Ok(())
+}
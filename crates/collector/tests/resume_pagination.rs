@@ -0,0 +1,177 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::{CollectionJobCreate, CollectionStatus};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use http::StatusCode;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+const PAGES: i64 = 4;
+const FAIL_AT_PAGE: i64 = 2;
+
+// Serves PAGES single-issue pages and fails once at FAIL_AT_PAGE, simulating a
+// collector that dies mid-pagination.
+struct FlakyPagedFetcher {
+    failed_once: AtomicBool,
+    cursors: Mutex<Vec<Option<String>>>,
+}
+
+#[async_trait]
+impl DataFetcher for FlakyPagedFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 7,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        self.cursors.lock().unwrap().push(cursor.clone());
+        let page: i64 = cursor.as_deref().map_or(0, |c| c.parse().unwrap());
+        if page == FAIL_AT_PAGE && !self.failed_once.swap(true, Ordering::SeqCst) {
+            return Err(anyhow!(gh_broker::HttpStatusError::new(
+                StatusCode::BAD_GATEWAY
+            )));
+        }
+        let issue = NormalizedIssue {
+            id: 700 + page,
+            repo_id,
+            number: page + 1,
+            is_pull_request: false,
+            state: "open".into(),
+            title: format!("issue {page}"),
+            body: Some("a perfectly ordinary bug report body".into()),
+            user_id: None,
+            comments_count: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: format!("hash-{page}"),
+            raw: json!({}),
+        };
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue,
+                author: None,
+            }],
+            next_cursor: (page + 1 < PAGES).then(|| (page + 1).to_string()),
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("issues have no authors")
+    }
+}
+
+#[tokio::test]
+async fn restart_resumes_from_last_completed_page() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping restart_resumes_from_last_completed_page: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("resume_pages").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    let job = db
+        .collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "o".into(),
+            name: "r".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let fetcher = Arc::new(FlakyPagedFetcher {
+        failed_once: AtomicBool::new(false),
+        cursors: Mutex::new(Vec::new()),
+    });
+    let cfg = CollectorConfig {
+        page_size: 50,
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Hybrid,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+
+    // First run dies at page 2; progress up to the last completed page is kept.
+    Collector::new(cfg.clone(), fetcher.clone(), repos.clone(), 1)
+        .run_once()
+        .await?;
+    let progress = db
+        .watermarks()
+        .get_progress("o/r")
+        .await?
+        .expect("progress persisted");
+    assert_eq!(progress.cursor, "2");
+    assert!(db.watermarks().get("o/r").await?.is_none());
+
+    // A fresh collector (restart) picks up at the stored cursor.
+    fetcher.cursors.lock().unwrap().clear();
+    Collector::new(cfg.clone(), fetcher.clone(), repos.clone(), 1)
+        .run_once()
+        .await?;
+    let cursors = fetcher.cursors.lock().unwrap().clone();
+    assert_eq!(
+        cursors,
+        vec![Some("2".to_string()), Some("3".to_string())],
+        "second run should resume instead of restarting from the first page"
+    );
+
+    assert!(db.watermarks().get_progress("o/r").await?.is_none());
+    assert!(db.watermarks().get("o/r").await?.is_some());
+    let jobs = db.collection_jobs().list(10, &[]).await?;
+    let job = jobs.iter().find(|j| j.id == job.id).expect("job");
+    assert!(matches!(job.status, CollectionStatus::Completed));
+
+    handle.cleanup().await?;
+    Ok(())
+}
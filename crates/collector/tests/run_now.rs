@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use chrono::Utc;
+use collector::fetcher::{CommentPage, DataFetcher, IssuePage, RepoSnapshot, UserFetch};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::NormalizedRepository;
+use normalizer::payloads::UserRef;
+use serde_json::json;
+use tokio::sync::Notify;
+use tower::ServiceExt;
+
+/// Holds the run inside `fetch_repo` until released.
+struct GatedFetcher {
+    entered: Notify,
+    release: Notify,
+}
+
+#[async_trait]
+impl DataFetcher for GatedFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        self.entered.notify_one();
+        self.release.notified().await;
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 42,
+                full_name: format!("{owner}/{name}"),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({"id": 42}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        Ok(IssuePage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("no issues returned")
+    }
+}
+
+async fn post_run_now(app: &axum::Router) -> Result<StatusCode> {
+    let response = app
+        .clone()
+        .oneshot(Request::post("/run-now").body(Body::empty())?)
+        .await?;
+    Ok(response.status())
+}
+
+#[tokio::test]
+async fn run_now_rejects_a_trigger_while_a_run_is_active() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping run_now_rejects_a_trigger_while_a_run_is_active: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("run_now").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "o".into(),
+            name: "r".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let fetcher = Arc::new(GatedFetcher {
+        entered: Notify::new(),
+        release: Notify::new(),
+    });
+    let cfg = CollectorConfig {
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let collector = Arc::new(Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        repos,
+        cfg.max_concurrent_repos,
+    ));
+    let app = collector::control::router(collector.clone());
+
+    assert_eq!(post_run_now(&app).await?, StatusCode::ACCEPTED);
+    fetcher.entered.notified().await;
+    assert_eq!(post_run_now(&app).await?, StatusCode::CONFLICT);
+
+    fetcher.release.notify_one();
+    // Waits for the triggered run to finish before running an empty cycle.
+    collector.run_once().await?;
+    assert_eq!(post_run_now(&app).await?, StatusCode::ACCEPTED);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -6,7 +6,7 @@ use chrono::Utc;
 use collector::fetcher::{CommentPage, DataFetcher, IssuePage, RepoSnapshot, UserFetch};
 use collector::service::Collector;
 use common::config::{CollectorConfig, FetchMode};
-use db::models::CollectionJobCreate;
+use db::models::{CollectionJobCreate, RepositoryRow, WatermarkUpdate};
 use db::pg::PgDatabase;
 use db::Repositories;
 use db_test_fixture::DbFixture;
@@ -14,20 +14,23 @@ use normalizer::models::NormalizedRepository;
 use normalizer::payloads::UserRef;
 use serde_json::json;
 
-struct MismatchFetcher;
+/// Answers every repo lookup with a fixed snapshot, whatever seed was requested.
+struct SnapshotFetcher {
+    id: i64,
+    full_name: &'static str,
+}
 
 #[async_trait]
-impl DataFetcher for MismatchFetcher {
+impl DataFetcher for SnapshotFetcher {
     async fn fetch_repo(&self, _owner: &str, _name: &str) -> Result<RepoSnapshot> {
-        // Intentionally return a different repo full_name than the requested seed
         Ok(RepoSnapshot {
             repository: NormalizedRepository {
-                id: 999_001,
-                full_name: "octocat/Spoon-Knife".into(),
+                id: self.id,
+                full_name: self.full_name.into(),
                 is_fork: false,
                 created_at: Utc::now(),
                 pushed_at: None,
-                raw: json!({"id": 999_001}),
+                raw: json!({"id": self.id}),
             },
         })
     }
@@ -41,7 +44,6 @@ impl DataFetcher for MismatchFetcher {
         _cursor: Option<String>,
         _per_page: u32,
     ) -> Result<IssuePage> {
-        // Not reached due to mismatch guard
         Ok(IssuePage {
             items: Vec::new(),
             next_cursor: None,
@@ -57,7 +59,6 @@ impl DataFetcher for MismatchFetcher {
         _cursor: Option<String>,
         _per_page: u32,
     ) -> Result<CommentPage> {
-        // Not reached due to mismatch guard
         Ok(CommentPage {
             items: Vec::new(),
             next_cursor: None,
@@ -80,6 +81,7 @@ fn test_config() -> CollectorConfig {
         run_once: true,
         fetch_mode: FetchMode::Rest,
         max_concurrent_repos: 1,
+        ..Default::default()
     }
 }
 
@@ -105,8 +107,11 @@ async fn seed_repo_mismatch_is_guarded_and_marked_error() -> Result<()> {
         })
         .await?;
 
-    // Run collector with mismatching fetcher
-    let fetcher = Arc::new(MismatchFetcher) as Arc<dyn DataFetcher>;
+    // Run collector with a fetcher returning a different, never-seen repository
+    let fetcher = Arc::new(SnapshotFetcher {
+        id: 999_001,
+        full_name: "octocat/Spoon-Knife",
+    }) as Arc<dyn DataFetcher>;
     let repos: Arc<dyn Repositories> = Arc::new(db.clone());
     let cfg = test_config();
     let collector = Collector::new(
@@ -139,3 +144,71 @@ async fn seed_repo_mismatch_is_guarded_and_marked_error() -> Result<()> {
     handle.cleanup().await?;
     Ok(())
 }
+
+#[tokio::test]
+async fn renamed_repo_updates_job_instead_of_erroring() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping renamed_repo_updates_job_instead_of_erroring: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("collector_seed_renamed").await?;
+    let db = PgDatabase::connect(handle.database_url()).await?;
+
+    // A previous run ingested the repository under its old name.
+    db.repos()
+        .upsert(RepositoryRow {
+            id: 999_002,
+            full_name: "octocat/Old-Name".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({"id": 999_002}),
+        })
+        .await?;
+    let last_updated = Utc::now() - chrono::Duration::days(1);
+    db.watermarks()
+        .set(WatermarkUpdate {
+            repo_full_name: "octocat/Old-Name".into(),
+            last_updated,
+        })
+        .await?;
+    let job = db
+        .collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "octocat".into(),
+            name: "Old-Name".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let fetcher = Arc::new(SnapshotFetcher {
+        id: 999_002,
+        full_name: "octocat/New-Name",
+    }) as Arc<dyn DataFetcher>;
+    let repos: Arc<dyn Repositories> = Arc::new(db.clone());
+    let cfg = test_config();
+    let collector = Collector::new(cfg.clone(), fetcher, repos, cfg.max_concurrent_repos);
+    collector.run_once().await?;
+
+    let row: (String, String, String) =
+        sqlx::query_as("SELECT status::text, owner, name FROM collection_jobs WHERE id = $1")
+            .bind(job.id)
+            .fetch_one(db.pool())
+            .await?;
+    assert_eq!(
+        row,
+        ("completed".into(), "octocat".into(), "New-Name".into())
+    );
+
+    let repo = db.repos().get_by_id(999_002).await?.expect("repo row kept");
+    assert_eq!(repo.full_name, "octocat/New-Name");
+    assert!(db.watermarks().get("octocat/Old-Name").await?.is_none());
+    assert!(db.watermarks().get("octocat/New-Name").await?.is_some());
+    assert!(collector::metrics::REPO_RENAMED_TOTAL.get() >= 1);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use collector::client::GithubClient;
+use collector::fetcher::{DataFetcher, GraphqlDataFetcher, RestDataFetcher};
+use gh_broker::{GithubBroker, Priority};
+use http::{Request, Response, StatusCode};
+use serde_json::{json, Value};
+
+fn since() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+}
+
+// (id, updated_at): one after the watermark, one exactly on it, one just before.
+fn issue_times() -> Vec<(i64, DateTime<Utc>)> {
+    vec![
+        (1, since() + Duration::hours(1)),
+        (2, since()),
+        (3, since() - Duration::seconds(1)),
+    ]
+}
+
+// REST client that ignores `since`, so only the fetcher's own boundary applies.
+struct UnfilteredRestClient;
+
+#[async_trait]
+impl GithubClient for UnfilteredRestClient {
+    async fn get_repo(&self, _owner: &str, _repo: &str) -> Result<Value> {
+        unreachable!()
+    }
+    async fn list_repo_issues(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _since: Option<DateTime<Utc>>,
+        _page: u32,
+        _per_page: u32,
+    ) -> Result<Vec<Value>> {
+        Ok(issue_times()
+            .into_iter()
+            .map(|(id, updated_at)| {
+                json!({
+                    "id": id,
+                    "number": id,
+                    "pull_request": null,
+                    "state": "open",
+                    "title": format!("issue {id}"),
+                    "body": "body",
+                    "user": null,
+                    "comments": 0,
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "updated_at": updated_at.to_rfc3339(),
+                    "closed_at": null,
+                })
+            })
+            .collect())
+    }
+    async fn get_issue(&self, _owner: &str, _repo: &str, _issue_number: u64) -> Result<Value> {
+        unreachable!()
+    }
+    async fn list_issue_comments(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _issue_number: u64,
+        _page: u32,
+        _per_page: u32,
+    ) -> Result<Vec<Value>> {
+        unreachable!()
+    }
+    async fn get_user(&self, _login: &str) -> Result<Value> {
+        unreachable!()
+    }
+    async fn get_org_membership(&self, _org: &str, _login: &str) -> Result<bool> {
+        unreachable!()
+    }
+}
+
+fn graphql_node(id: i64, updated_at: DateTime<Utc>) -> Value {
+    json!({
+        "databaseId": id,
+        "number": id,
+        "title": format!("item {id}"),
+        "body": "body",
+        "state": "OPEN",
+        "createdAt": "2024-01-01T00:00:00Z",
+        "updatedAt": updated_at.to_rfc3339(),
+        "closedAt": null,
+        "author": null,
+        "comments": {
+            "totalCount": 0,
+            "pageInfo": { "hasNextPage": false, "endCursor": null },
+            "nodes": []
+        }
+    })
+}
+
+// Serves the same issues as REST (ignoring filterBy.since) plus one pull request
+// last updated before the watermark, which GraphQL cannot filter server-side.
+struct UnfilteredGraphqlBroker;
+
+impl GithubBroker for UnfilteredGraphqlBroker {
+    fn enqueue(
+        &self,
+        request: Request<Vec<u8>>,
+        _priority: Priority,
+    ) -> futures::future::BoxFuture<'static, Result<Response<Vec<u8>>>> {
+        let body = String::from_utf8_lossy(request.body()).to_string();
+        let connection = if body.contains("RepoPulls") {
+            json!({
+                "pullRequests": {
+                    "pageInfo": { "hasNextPage": true, "endCursor": "pr-2" },
+                    "nodes": [graphql_node(10, since() - Duration::minutes(5))]
+                }
+            })
+        } else {
+            let nodes: Vec<Value> = issue_times()
+                .into_iter()
+                .map(|(id, updated_at)| graphql_node(id, updated_at))
+                .collect();
+            json!({
+                "issues": {
+                    "pageInfo": { "hasNextPage": false, "endCursor": null },
+                    "nodes": nodes
+                }
+            })
+        };
+        let payload = json!({ "data": { "repository": connection } })
+            .to_string()
+            .into_bytes();
+        let resp = Response::builder()
+            .status(StatusCode::OK)
+            .header("content-type", "application/json")
+            .body(payload)
+            .unwrap();
+        Box::pin(async move { Ok(resp) })
+    }
+}
+
+async fn fetched_ids(fetcher: &dyn DataFetcher) -> Result<(Vec<i64>, Option<String>)> {
+    let page = fetcher
+        .fetch_issues("o", "r", 1, Some(since()), None, 50)
+        .await?;
+    let mut ids: Vec<i64> = page.items.iter().map(|r| r.issue.id).collect();
+    ids.sort();
+    Ok((ids, page.next_cursor))
+}
+
+#[tokio::test]
+async fn rest_and_graphql_agree_on_since_boundary() -> Result<()> {
+    let rest = RestDataFetcher::new(Arc::new(UnfilteredRestClient));
+    let graphql = GraphqlDataFetcher::new(
+        Arc::new(UnfilteredGraphqlBroker),
+        Arc::new(UnfilteredRestClient),
+        "ua".into(),
+    );
+
+    let (rest_ids, _) = fetched_ids(&rest).await?;
+    let (graphql_ids, graphql_cursor) = fetched_ids(&graphql).await?;
+
+    // Inclusive on the boundary, exclusive before it, in both modes.
+    assert_eq!(rest_ids, vec![1, 2]);
+    assert_eq!(graphql_ids, rest_ids);
+    // The stale pull request ends PR pagination instead of paging through history.
+    assert_eq!(graphql_cursor, None);
+    Ok(())
+}
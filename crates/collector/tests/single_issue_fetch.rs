@@ -0,0 +1,322 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use collector::client::BrokerGithubClient;
+use collector::fetcher::{
+    CommentPage, CommentRecord, DataFetcher, GraphqlDataFetcher, IssuePage, IssueRecord,
+    RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::IssueQuery;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use gh_broker::{GithubBroker, Priority};
+use http::{Request, Response, StatusCode};
+use normalizer::models::{NormalizedComment, NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::{json, Value};
+
+/// Answers `issueOrPullRequest` for #7 (an open issue), #8 (a merged PR) and
+/// #9 (an issue closed as not planned), and GitHub's NOT_FOUND error for
+/// anything else.
+struct SingleIssueBroker;
+
+fn graphql_node(number: i64) -> Option<Value> {
+    let author = json!({
+        "__typename": "User",
+        "login": "octocat",
+        "databaseId": 583231,
+        "isSiteAdmin": false,
+        "createdAt": "2011-01-25T18:44:36Z",
+        "followers": { "totalCount": 10 },
+        "following": { "totalCount": 2 },
+        "repositories": { "totalCount": 8 }
+    });
+    match number {
+        7 => Some(json!({
+            "__typename": "Issue",
+            "databaseId": 7007,
+            "number": 7,
+            "title": "Crash on empty config",
+            "body": "Starting with an empty config panics.",
+            "state": "OPEN",
+            "createdAt": "2024-03-01T10:00:00Z",
+            "updatedAt": "2024-03-02T10:00:00Z",
+            "closedAt": null,
+            "stateReason": null,
+            "authorAssociation": "FIRST_TIME_CONTRIBUTOR",
+            "comments": { "totalCount": 3 },
+            "author": author
+        })),
+        8 => Some(json!({
+            "__typename": "PullRequest",
+            "databaseId": 8008,
+            "number": 8,
+            "title": "Handle empty config",
+            "body": null,
+            "state": "MERGED",
+            "createdAt": "2024-03-03T10:00:00Z",
+            "updatedAt": "2024-03-04T10:00:00Z",
+            "closedAt": "2024-03-04T10:00:00Z",
+            "authorAssociation": "MEMBER",
+            "comments": { "totalCount": 0 },
+            "author": null
+        })),
+        9 => Some(json!({
+            "__typename": "Issue",
+            "databaseId": 9009,
+            "number": 9,
+            "title": "Please add a dark theme to the CLI",
+            "body": "Would love a dark theme.",
+            "state": "CLOSED",
+            "createdAt": "2024-03-05T10:00:00Z",
+            "updatedAt": "2024-03-06T10:00:00Z",
+            "closedAt": "2024-03-06T10:00:00Z",
+            "stateReason": "NOT_PLANNED",
+            "comments": { "totalCount": 0 },
+            "author": null
+        })),
+        _ => None,
+    }
+}
+
+impl GithubBroker for SingleIssueBroker {
+    fn enqueue(
+        &self,
+        request: Request<Vec<u8>>,
+        _priority: Priority,
+    ) -> futures::future::BoxFuture<'static, Result<Response<Vec<u8>>>> {
+        let payload: Value = serde_json::from_slice(request.body()).unwrap();
+        assert!(payload["query"]
+            .as_str()
+            .unwrap()
+            .contains("issueOrPullRequest(number: $number)"));
+        let number = payload["variables"]["number"].as_i64().unwrap();
+        let body = match graphql_node(number) {
+            Some(node) => json!({ "data": { "repository": { "issueOrPullRequest": node } } }),
+            None => json!({
+                "data": { "repository": { "issueOrPullRequest": null } },
+                "errors": [{
+                    "type": "NOT_FOUND",
+                    "path": ["repository", "issueOrPullRequest"],
+                    "message": format!(
+                        "Could not resolve to an issue or pull request with the number of {number}."
+                    )
+                }]
+            }),
+        };
+        let resp = Response::builder()
+            .status(StatusCode::OK)
+            .body(body.to_string().into_bytes())
+            .unwrap();
+        Box::pin(async move { Ok(resp) })
+    }
+}
+
+fn graphql_fetcher() -> GraphqlDataFetcher {
+    let broker: Arc<dyn GithubBroker> = Arc::new(SingleIssueBroker);
+    let rest = Arc::new(BrokerGithubClient::new(broker.clone(), "ua".into()));
+    GraphqlDataFetcher::new(broker, rest, "ua".into())
+}
+
+#[tokio::test]
+async fn graphql_fetches_single_issue_and_pull_request() -> Result<()> {
+    let fetcher = graphql_fetcher();
+
+    let issue = fetcher
+        .fetch_issue("octo-org", "widgets", 42, 7)
+        .await?
+        .expect("issue 7 exists");
+    assert_eq!(issue.issue.id, 7007);
+    assert_eq!(issue.issue.repo_id, 42);
+    assert_eq!(issue.issue.number, 7);
+    assert!(!issue.issue.is_pull_request);
+    assert_eq!(issue.issue.state, "open");
+    assert_eq!(issue.issue.comments_count, 3);
+    assert_eq!(issue.issue.user_id, Some(583231));
+    assert_eq!(
+        issue.issue.updated_at,
+        Utc.with_ymd_and_hms(2024, 3, 2, 10, 0, 0).unwrap()
+    );
+    assert_eq!(issue.issue.state_reason, None);
+    assert_eq!(
+        issue.issue.author_association.as_deref(),
+        Some("FIRST_TIME_CONTRIBUTOR")
+    );
+    assert_eq!(issue.author.map(|a| a.login), Some("octocat".to_string()));
+
+    let pr = fetcher
+        .fetch_issue("octo-org", "widgets", 42, 8)
+        .await?
+        .expect("pull request 8 exists");
+    assert!(pr.issue.is_pull_request);
+    assert_eq!(pr.issue.state, "closed");
+    assert_eq!(pr.issue.body, None);
+    assert!(pr.author.is_none());
+    assert_eq!(pr.issue.state_reason, None);
+    assert_eq!(pr.issue.author_association.as_deref(), Some("MEMBER"));
+
+    let declined = fetcher
+        .fetch_issue("octo-org", "widgets", 42, 9)
+        .await?
+        .expect("issue 9 exists");
+    assert_eq!(declined.issue.state, "closed");
+    assert_eq!(declined.issue.state_reason.as_deref(), Some("not_planned"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn graphql_single_issue_not_found_is_none() -> Result<()> {
+    let missing = graphql_fetcher()
+        .fetch_issue("octo-org", "widgets", 42, 99)
+        .await?;
+    assert!(missing.is_none());
+    Ok(())
+}
+
+/// Serves issue #5 of `acme/widgets` with one comment; every other number is gone.
+struct RefreshFetcher;
+
+#[async_trait]
+impl DataFetcher for RefreshFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 1,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        unreachable!("refresh must not page the repository")
+    }
+
+    async fn fetch_issue(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        number: i64,
+    ) -> Result<Option<IssueRecord>> {
+        if number != 5 {
+            return Ok(None);
+        }
+        Ok(Some(IssueRecord {
+            issue: NormalizedIssue {
+                id: 500,
+                repo_id,
+                number,
+                is_pull_request: false,
+                state: "open".into(),
+                title: "Docs build fails".into(),
+                body: Some("The docs build fails on a clean checkout of main.".into()),
+                user_id: None,
+                comments_count: 1,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                closed_at: None,
+                state_reason: None,
+                locked: false,
+                author_association: None,
+                dedupe_hash: "issue-500".into(),
+                raw: json!({ "id": 500 }),
+            },
+            author: None,
+        }))
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![CommentRecord {
+                comment: NormalizedComment {
+                    id: 501,
+                    issue_id,
+                    user_id: None,
+                    body: "Same here, it started after the theme upgrade.".into(),
+                    created_at: Utc::now(),
+                    updated_at: None,
+                    author_association: None,
+                    dedupe_hash: "comment-501".into(),
+                    raw: json!({ "id": 501 }),
+                },
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("records have no authors")
+    }
+}
+
+#[tokio::test]
+async fn refresh_issue_stores_one_issue_and_its_comments() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping refresh_issue_stores_one_issue_and_its_comments: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("single_issue_refresh").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        Arc::new(RefreshFetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+
+    assert!(collector.refresh_issue("acme", "widgets", 5).await?);
+    let issues = db
+        .issues()
+        .query(IssueQuery {
+            repo_full_name: Some("acme/widgets".into()),
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(issues.iter().map(|i| i.id).collect::<Vec<_>>(), vec![500]);
+    let comments = db.comments().list_by_issue(500).await?;
+    assert_eq!(comments.iter().map(|c| c.id).collect::<Vec<_>>(), vec![501]);
+    assert!(db.watermarks().get("acme/widgets").await?.is_none());
+
+    assert!(!collector.refresh_issue("acme", "widgets", 6).await?);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::{CollectionJobCreate, CollectionJobUpdate, CollectionStatus};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+fn created_at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+}
+
+/// Serves two open issues until `closed` is set. Then #1 is locked and
+/// closed ten minutes after creation, #2 closed as not planned hours later.
+/// With `upper_case`, states come back the way GraphQL listings spell them.
+struct ClosingFetcher {
+    closed: AtomicBool,
+    upper_case: AtomicBool,
+}
+
+fn issue(
+    repo_id: i64,
+    number: i64,
+    closed: Option<(Duration, &str)>,
+    locked: bool,
+) -> NormalizedIssue {
+    let closed_at = closed.map(|(after, _)| created_at() + after);
+    NormalizedIssue {
+        id: number,
+        repo_id,
+        number,
+        is_pull_request: false,
+        state: if closed.is_some() { "closed" } else { "open" }.into(),
+        title: format!("Free gift card #{number}"),
+        body: Some("Claim it now".into()),
+        user_id: None,
+        comments_count: 0,
+        created_at: created_at(),
+        updated_at: closed_at.unwrap_or_else(created_at),
+        closed_at,
+        state_reason: closed.map(|(_, reason)| reason.to_string()),
+        locked,
+        author_association: None,
+        dedupe_hash: format!("issue-{number}"),
+        raw: json!({ "id": number }),
+    }
+}
+
+#[async_trait]
+impl DataFetcher for ClosingFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 777,
+                full_name: format!("{owner}/{name}"),
+                is_fork: false,
+                created_at: created_at(),
+                pushed_at: None,
+                raw: json!({ "id": 777 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let items = if self.closed.load(Ordering::SeqCst) {
+            vec![
+                issue(repo_id, 1, Some((Duration::minutes(10), "completed")), true),
+                issue(repo_id, 2, Some((Duration::hours(3), "not_planned")), false),
+            ]
+        } else {
+            vec![
+                issue(repo_id, 1, None, false),
+                issue(repo_id, 2, None, false),
+            ]
+        };
+        let upper_case = self.upper_case.load(Ordering::SeqCst);
+        Ok(IssuePage {
+            items: items
+                .into_iter()
+                .map(|mut issue| {
+                    if upper_case {
+                        issue.state = issue.state.to_uppercase();
+                    }
+                    IssueRecord {
+                        issue,
+                        author: None,
+                    }
+                })
+                .collect(),
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        unreachable!("issues have no comments")
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("issues have no authors")
+    }
+}
+
+#[tokio::test]
+async fn lock_and_rapid_closure_are_recorded_across_runs() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping lock_and_rapid_closure_are_recorded_across_runs: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("state_transitions").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+    let job = db
+        .collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "o".into(),
+            name: "r".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let fetcher = Arc::new(ClosingFetcher {
+        closed: AtomicBool::new(false),
+        upper_case: AtomicBool::new(false),
+    });
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        rapid_closure_window_mins: 30,
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+
+    collector.run_once().await?;
+    assert!(db.issues().list_transitions(1).await?.is_empty());
+
+    fetcher.closed.store(true, Ordering::SeqCst);
+    db.collection_jobs()
+        .update(CollectionJobUpdate {
+            id: job.id,
+            status: CollectionStatus::Pending,
+            error_message: None,
+        })
+        .await?;
+    collector.run_once().await?;
+
+    let stored = db.issues().list_by_repo(777, None).await?;
+    let locked: Vec<_> = stored
+        .iter()
+        .map(|issue| (issue.number, issue.state.as_str(), issue.locked))
+        .collect();
+    assert!(locked.contains(&(1, "closed", true)));
+    assert!(locked.contains(&(2, "closed", false)));
+    assert!(db.issues().fingerprint(1).await?.expect("stored").locked);
+
+    let first = db.issues().list_transitions(1).await?;
+    assert_eq!(first.len(), 1);
+    assert_eq!(
+        (
+            first[0].from_state.as_str(),
+            first[0].to_state.as_str(),
+            first[0].from_locked,
+            first[0].to_locked,
+        ),
+        ("open", "closed", false, true)
+    );
+    assert!(first[0].rapid_maintainer_closure);
+
+    // Closed by a maintainer, but well outside the window.
+    let second = db.issues().list_transitions(2).await?;
+    assert_eq!(second.len(), 1);
+    assert!(!second[0].rapid_maintainer_closure);
+
+    // The same states spelled in upper case are not a transition.
+    fetcher.upper_case.store(true, Ordering::SeqCst);
+    db.collection_jobs()
+        .update(CollectionJobUpdate {
+            id: job.id,
+            status: CollectionStatus::Pending,
+            error_message: None,
+        })
+        .await?;
+    collector.run_once().await?;
+    assert_eq!(db.issues().list_transitions(1).await?.len(), 1);
+    assert_eq!(db.issues().list_transitions(2).await?.len(), 1);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::CollectionJobCreate;
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+/// Issues 1-5 are titled "Bug #<n>" an hour apart; issue 6 has its own title.
+struct FloodFetcher {
+    started: DateTime<Utc>,
+}
+
+#[async_trait]
+impl DataFetcher for FloodFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 1,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: Utc::now(),
+                pushed_at: None,
+                raw: json!({ "id": 1 }),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let items = (1..=6)
+            .map(|n| {
+                let title = if n == 6 {
+                    "Crash when the config file is missing".to_string()
+                } else {
+                    format!("Bug #{n}")
+                };
+                let created_at = self.started + Duration::hours(n);
+                IssueRecord {
+                    issue: NormalizedIssue {
+                        id: n,
+                        repo_id,
+                        number: n,
+                        is_pull_request: false,
+                        state: "open".into(),
+                        title,
+                        body: Some(format!(
+                            "Opening the settings page number {n} shows a blank screen \
+                             instead of the list of configured accounts."
+                        )),
+                        user_id: None,
+                        comments_count: 0,
+                        created_at,
+                        updated_at: created_at,
+                        closed_at: None,
+                        state_reason: None,
+                        locked: false,
+                        author_association: None,
+                        dedupe_hash: format!("issue-{n}"),
+                        raw: json!({ "id": n }),
+                    },
+                    author: None,
+                }
+            })
+            .collect();
+        Ok(IssuePage {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        unreachable!("issues have no comments")
+    }
+
+    async fn fetch_user(&self, _user: &UserRef) -> Result<UserFetch> {
+        unreachable!("issues have no authors")
+    }
+}
+
+#[tokio::test]
+async fn repeated_numbered_titles_are_flagged_as_templated() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping repeated_numbered_titles_are_flagged_as_templated: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("templated_titles").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    db.collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "acme".into(),
+            name: "widgets".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let fetcher = FloodFetcher {
+        started: Utc::now() - Duration::days(1),
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        Arc::new(fetcher),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+    collector.run_once().await?;
+
+    let templated = |flags: &[db::SpamFlagRow]| {
+        flags
+            .iter()
+            .any(|flag| flag.reasons.iter().any(|r| r == "templated_title"))
+    };
+    // Each issue sees the ones stored before it: #4 is the first with three.
+    for n in 1..=6 {
+        let flags = db.spam_flags().list_for_subject("issue", n).await?;
+        assert_eq!(
+            templated(&flags),
+            (4..=5).contains(&n),
+            "issue {n}: {flags:?}"
+        );
+    }
+
+    handle.cleanup().await?;
+    Ok(())
+}
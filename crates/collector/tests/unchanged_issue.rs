@@ -0,0 +1,192 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use collector::fetcher::{
+    CommentPage, DataFetcher, IssuePage, IssueRecord, RepoSnapshot, UserFetch,
+};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::{CollectionJobCreate, CollectionJobUpdate, CollectionStatus};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::{NormalizedIssue, NormalizedRepository};
+use normalizer::payloads::UserRef;
+use serde_json::json;
+
+fn created_at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+}
+
+/// Serves one issue whose body never changes; the test moves `updated_at`
+/// and `state` between runs.
+struct EditableIssueFetcher {
+    issue: Mutex<(DateTime<Utc>, &'static str)>,
+}
+
+impl EditableIssueFetcher {
+    fn set(&self, updated_at: DateTime<Utc>, state: &'static str) {
+        *self.issue.lock().unwrap() = (updated_at, state);
+    }
+}
+
+#[async_trait]
+impl DataFetcher for EditableIssueFetcher {
+    async fn fetch_repo(&self, owner: &str, name: &str) -> Result<RepoSnapshot> {
+        Ok(RepoSnapshot {
+            repository: NormalizedRepository {
+                id: 777,
+                full_name: format!("{}/{}", owner, name),
+                is_fork: false,
+                created_at: created_at(),
+                pushed_at: None,
+                raw: json!({"id": 777}),
+            },
+        })
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        repo_id: i64,
+        _since: Option<DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        let (updated_at, state) = *self.issue.lock().unwrap();
+        let issue = NormalizedIssue {
+            id: 1,
+            repo_id,
+            number: 1,
+            is_pull_request: false,
+            state: state.into(),
+            title: "Help".into(),
+            body: Some("please fill out the template".into()),
+            user_id: None,
+            comments_count: 0,
+            created_at: created_at(),
+            updated_at,
+            closed_at: (state == "closed").then_some(updated_at),
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: "same-hash".into(),
+            raw: json!({"id": 1}),
+        };
+        Ok(IssuePage {
+            items: vec![IssueRecord {
+                issue,
+                author: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        Ok(CommentPage {
+            items: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        Ok(UserFetch::Missing(collector::fetcher::MissingUser {
+            id: user.id,
+            login: user.login.clone(),
+            status: None,
+        }))
+    }
+}
+
+#[tokio::test]
+async fn unchanged_issue_is_not_rescored_or_rewritten() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping unchanged_issue_is_not_rescored_or_rewritten: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("unchanged_issue").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    let repos: Arc<dyn Repositories> = db.clone();
+
+    let job = db
+        .collection_jobs()
+        .create(CollectionJobCreate {
+            owner: "o".into(),
+            name: "r".into(),
+            priority: 0,
+        })
+        .await?;
+
+    let fetcher = Arc::new(EditableIssueFetcher {
+        issue: Mutex::new((created_at() + Duration::hours(1), "open")),
+    });
+    let cfg = CollectorConfig {
+        interval_secs: 0,
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        max_concurrent_repos: 1,
+        ..Default::default()
+    };
+    let collector = Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        repos,
+        cfg.max_concurrent_repos,
+    );
+
+    let rerun = || async {
+        db.collection_jobs()
+            .update(CollectionJobUpdate {
+                id: job.id,
+                status: CollectionStatus::Pending,
+                error_message: None,
+            })
+            .await?;
+        collector.run_once().await
+    };
+
+    collector.run_once().await?;
+    let history = db.spam_flags().list_history_for_subject("issue", 1).await?;
+    assert_eq!(history.len(), 1, "first sighting is scored");
+
+    // Label toggle: updated_at moves, content and state do not.
+    let relabelled_at = created_at() + Duration::hours(2);
+    fetcher.set(relabelled_at, "open");
+    rerun().await?;
+    let history = db.spam_flags().list_history_for_subject("issue", 1).await?;
+    assert_eq!(history.len(), 1, "unchanged issue must not be re-scored");
+    let stored = db.issues().list_by_repo(777, None).await?;
+    assert_eq!(stored[0].updated_at, created_at() + Duration::hours(1));
+    let watermark = db.watermarks().get("o/r").await?.expect("watermark");
+    assert_eq!(
+        watermark.last_updated, relabelled_at,
+        "watermark still advances"
+    );
+
+    // Closing keeps the content hash, so the row is rewritten without re-scoring.
+    let closed_at = created_at() + Duration::hours(3);
+    fetcher.set(closed_at, "closed");
+    rerun().await?;
+    let history = db.spam_flags().list_history_for_subject("issue", 1).await?;
+    assert_eq!(history.len(), 1, "state change alone must not re-score");
+    let stored = db.issues().list_by_repo(777, None).await?;
+    assert_eq!(stored[0].state, "closed");
+    assert_eq!(stored[0].closed_at, Some(closed_at));
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use chrono::Utc;
+use collector::fetcher::{CommentPage, DataFetcher, IssuePage, RepoSnapshot, UserFetch};
+use collector::service::Collector;
+use common::config::{CollectorConfig, FetchMode};
+use db::models::{CommentRow, IssueRow, OrphanedAuthor, RepositoryRow};
+use db::pg::PgDatabase;
+use db::Repositories;
+use db_test_fixture::DbFixture;
+use normalizer::models::NormalizedUser;
+use normalizer::payloads::UserRef;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+/// Serves any user it is asked for, recording the lookups.
+#[derive(Default)]
+struct UserFetcher {
+    looked_up: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl DataFetcher for UserFetcher {
+    async fn fetch_repo(&self, _owner: &str, _name: &str) -> Result<RepoSnapshot> {
+        unreachable!("repair only fetches users")
+    }
+
+    async fn fetch_issues(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _repo_id: i64,
+        _since: Option<chrono::DateTime<Utc>>,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<IssuePage> {
+        unreachable!("repair only fetches users")
+    }
+
+    async fn fetch_issue_comments(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _issue_number: i64,
+        _issue_id: i64,
+        _cursor: Option<String>,
+        _per_page: u32,
+    ) -> Result<CommentPage> {
+        unreachable!("repair only fetches users")
+    }
+
+    async fn fetch_user(&self, user: &UserRef) -> Result<UserFetch> {
+        self.looked_up.lock().unwrap().push(user.login.clone());
+        Ok(UserFetch::Found(NormalizedUser {
+            id: user.id,
+            login: user.login.clone(),
+            user_type: "User".into(),
+            site_admin: false,
+            created_at: Some(Utc::now()),
+            followers: Some(0),
+            following: Some(0),
+            public_repos: Some(0),
+            bio: None,
+            blog: None,
+            raw: json!({"id": user.id, "login": user.login}),
+        }))
+    }
+}
+
+fn issue(id: i64, user_id: i64, raw: Value) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id: 1,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("issue {id}"),
+        body: Some("body".into()),
+        user_id: Some(user_id),
+        comments_count: 1,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("hash-{id}"),
+        raw,
+        found: true,
+    }
+}
+
+#[tokio::test]
+async fn repair_pass_finds_and_backfills_missing_authors() -> Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(f) => f,
+        Err(err) => {
+            eprintln!("skipping repair_pass_finds_and_backfills_missing_authors: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create("user_repair").await?;
+    let db = Arc::new(PgDatabase::connect(handle.database_url()).await?);
+    // Stands in for rows written before the author was stored.
+    sqlx::query("ALTER TABLE issues DROP CONSTRAINT issues_user_id_fkey")
+        .execute(db.pool())
+        .await?;
+    sqlx::query("ALTER TABLE comments DROP CONSTRAINT comments_user_id_fkey")
+        .execute(db.pool())
+        .await?;
+
+    db.repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "acme/widgets".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({"id": 1}),
+        })
+        .await?;
+    db.issues()
+        .upsert(issue(
+            10,
+            77,
+            json!({"user": {"id": 77, "login": "lost-author"}}),
+        ))
+        .await?;
+    db.comments()
+        .upsert(CommentRow {
+            id: 20,
+            issue_id: 10,
+            user_id: Some(78),
+            body: "comment".into(),
+            created_at: Utc::now(),
+            updated_at: None,
+            author_association: None,
+            dedupe_hash: "hash-20".into(),
+            raw: json!({}),
+            found: true,
+        })
+        .await?;
+
+    let orphans = db.users().list_orphaned_authors(10).await?;
+    assert_eq!(
+        orphans,
+        vec![
+            OrphanedAuthor {
+                subject_type: "issue".into(),
+                subject_id: 10,
+                user_id: 77,
+                login: Some("lost-author".into()),
+            },
+            OrphanedAuthor {
+                subject_type: "comment".into(),
+                subject_id: 20,
+                user_id: 78,
+                login: None,
+            },
+        ]
+    );
+
+    let fetcher = Arc::new(UserFetcher::default());
+    let cfg = CollectorConfig {
+        run_once: true,
+        fetch_mode: FetchMode::Rest,
+        ..Default::default()
+    };
+    let collector = Arc::new(Collector::new(
+        cfg.clone(),
+        fetcher.clone(),
+        db.clone(),
+        cfg.max_concurrent_repos,
+    ));
+    let response = collector::control::router(collector)
+        .oneshot(Request::post("/maintenance/repair-users").body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(body, json!({"orphaned": 2, "repaired": 1, "failed": 1}));
+
+    assert_eq!(*fetcher.looked_up.lock().unwrap(), vec!["lost-author"]);
+    let user = db.users().get_by_id(77).await?.expect("author backfilled");
+    assert_eq!(user.login, "lost-author");
+    assert!(user.found);
+    // Without a login the comment's author cannot be looked up.
+    let remaining = db.users().list_orphaned_authors(10).await?;
+    assert_eq!(
+        remaining
+            .iter()
+            .map(|orphan| orphan.subject_id)
+            .collect::<Vec<_>>(),
+        vec![20]
+    );
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -2,6 +2,7 @@ use std::path::Path;
 
 use std::collections::HashMap;
 
+use chrono::{DateTime, Timelike, Utc};
 use config::{Config, ConfigError, Environment, File};
 use serde::Deserialize;
 use serde_with::{formats::CommaSeparator, serde_as, StringWithSeparator};
@@ -14,6 +15,8 @@ pub struct AppConfig {
     pub broker: BrokerConfig,
     pub api: ApiConfig,
     pub observability: ObservabilityConfig,
+    #[serde(default)]
+    pub analysis: AnalysisConfig,
 }
 
 impl AppConfig {
@@ -22,9 +25,20 @@ impl AppConfig {
     }
 
     pub fn load_from_path<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        Self::load_with_overrides(path, None, &[])
+    }
+
+    /// Loads configuration with an optional extra config file layered over the
+    /// defaults, followed by explicit key overrides (e.g. from CLI flags) which
+    /// take precedence over the environment.
+    pub fn load_with_overrides<P: AsRef<Path>>(
+        path: P,
+        config_file: Option<&Path>,
+        overrides: &[(&str, String)],
+    ) -> Result<Self, ConfigError> {
         dotenvy::dotenv().ok();
 
-        Config::builder()
+        let mut builder = Config::builder()
             .add_source(
                 File::with_name(
                     path.as_ref()
@@ -42,10 +56,17 @@ impl AppConfig {
                         .as_ref(),
                 )
                 .required(false),
-            )
-            .add_source(Environment::default().separator("__"))
-            .build()?
-            .try_deserialize()
+            );
+        if let Some(config_file) = config_file {
+            builder = builder.add_source(File::from(config_file).required(true));
+        }
+        builder = builder.add_source(Environment::default().separator("__"));
+        for (key, value) in overrides {
+            builder = builder.set_override(*key, value.as_str())?;
+        }
+        let config: Self = builder.build()?.try_deserialize()?;
+        config.github.validate()?;
+        Ok(config)
     }
 }
 
@@ -54,14 +75,45 @@ pub struct DatabaseConfig {
     pub url: String,
     #[serde(default)]
     pub test_admin_url: Option<String>,
+    /// Startup connection attempts before giving up.
+    #[serde(default = "DatabaseConfig::default_connect_attempts")]
+    pub connect_attempts: u32,
+    /// First retry delay; doubles per attempt.
+    #[serde(default = "DatabaseConfig::default_connect_base_delay_ms")]
+    pub connect_base_delay_ms: u64,
+    /// Each delay is randomized by up to this fraction so replicas starting
+    /// together don't retry in lockstep.
+    #[serde(default = "DatabaseConfig::default_connect_jitter_frac")]
+    pub connect_jitter_frac: f32,
+}
+
+impl DatabaseConfig {
+    fn default_connect_attempts() -> u32 {
+        5
+    }
+
+    fn default_connect_base_delay_ms() -> u64 {
+        500
+    }
+
+    fn default_connect_jitter_frac() -> f32 {
+        0.5
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct GithubToken {
     pub id: String,
     pub secret: String,
+    /// Budgets (`core`, `search`, `graphql`) the token may serve, e.g. only
+    /// `["core", "search"]` for a fine-grained token; unset serves all.
+    #[serde(default)]
+    pub budgets: Option<Vec<String>>,
 }
 
+/// User-Agent sent to GitHub unless `github.user_agent` overrides it.
+pub const DEFAULT_USER_AGENT: &str = "github-spam-lab";
+
 #[serde_as]
 #[derive(Debug, Clone, Deserialize)]
 pub struct GithubConfig {
@@ -75,11 +127,50 @@ pub struct GithubConfig {
     pub token_secrets: Vec<String>,
     #[serde(default = "GithubConfig::default_user_agent")]
     pub user_agent: String,
+    /// Query parameters masked in logged request URLs and broker request
+    /// keys, on top of the built-in list and any token-shaped value.
+    #[serde_as(as = "StringWithSeparator::<CommaSeparator, String>")]
+    #[serde(default)]
+    pub redact_query_params: Vec<String>,
+    /// PEM file of extra root certificates trusted for GitHub traffic, e.g. a
+    /// TLS-intercepting proxy's CA; the built-in roots stay trusted.
+    #[serde(default)]
+    pub ca_bundle_path: Option<String>,
+    /// GitHub App credentials; the collector mints installation tokens from
+    /// them in addition to any personal access tokens above.
+    #[serde(default)]
+    pub app: Option<GithubAppConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubAppConfig {
+    pub app_id: String,
+    pub installation_id: u64,
+    /// PEM private key downloaded from the App settings page.
+    pub private_key_path: String,
 }
 
 impl GithubConfig {
     fn default_user_agent() -> String {
-        "github-spam-lab".to_string()
+        DEFAULT_USER_AGENT.to_string()
+    }
+
+    /// GitHub soft-blocks requests without a meaningful User-Agent, so an
+    /// empty (or unsendable) `user_agent` is a configuration error.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let user_agent = self.user_agent.trim();
+        if user_agent.is_empty() {
+            return Err(ConfigError::Message(
+                "github.user_agent must not be empty".into(),
+            ));
+        }
+        if !user_agent.chars().all(|c| c == ' ' || c.is_ascii_graphic()) {
+            return Err(ConfigError::Message(format!(
+                "github.user_agent {:?} must be printable ASCII",
+                self.user_agent
+            )));
+        }
+        Ok(())
     }
 
     pub fn resolved_tokens(&self) -> Result<Vec<GithubToken>, ConfigError> {
@@ -96,7 +187,11 @@ impl GithubConfig {
             .iter()
             .cloned()
             .zip(self.token_secrets.iter().cloned())
-            .map(|(id, secret)| GithubToken { id, secret })
+            .map(|(id, secret)| GithubToken {
+                id,
+                secret,
+                budgets: None,
+            })
             .collect())
     }
 }
@@ -107,15 +202,215 @@ pub struct CollectorConfig {
     pub interval_secs: u64,
     #[serde(default = "CollectorConfig::default_page_size")]
     pub page_size: u32,
+    /// Issues per listing page; falls back to `page_size`.
+    #[serde(default)]
+    pub issue_page_size: Option<u32>,
+    /// Comments per page, including those embedded in GraphQL issue pages; falls back to `page_size`.
+    #[serde(default)]
+    pub comment_page_size: Option<u32>,
     #[serde(default)]
     pub run_once: bool,
     #[serde(default)]
     pub fetch_mode: FetchMode,
+    #[serde(default)]
+    pub pull_request_mode: PullRequestMode,
+    /// Don't page comments of pull request rows; their conversation is rarely
+    /// worth the API cost on PR-heavy repos.
+    #[serde(default)]
+    pub skip_pull_request_comments: bool,
+    /// Store issues/comments of fork repositories without scoring them.
+    #[serde(default)]
+    pub skip_fork_scoring: bool,
+    /// REST/hybrid modes request the next issue/comment page while the
+    /// current one is processed.
+    #[serde(default)]
+    pub rest_prefetch: bool,
+    /// Store the `FeatureSet` behind each spam flag in `spam_flags.features`
+    /// so reviewers see exact values; off by default to limit storage growth.
+    #[serde(default)]
+    pub store_flag_features: bool,
+    /// Record issue state/lock changes seen between runs in
+    /// `issue_state_transitions`, counting open -> closed within this many
+    /// minutes of creation by someone other than the author as a rapid
+    /// maintainer closure; `0` disables tracking.
+    #[serde(default)]
+    pub rapid_closure_window_mins: u64,
+    #[serde(default)]
+    pub dedupe_mode: DedupeMode,
+    /// What to do with payloads missing a field the normalizer requires.
+    #[serde(default)]
+    pub normalization_leniency: Leniency,
     #[serde(default = "CollectorConfig::default_max_concurrent_repos")]
     pub max_concurrent_repos: usize,
+    /// Repo jobs claimed and in flight at once; further pending jobs are loaded
+    /// as running ones finish. `0` uses `max_concurrent_repos`; set it higher so
+    /// a waiting repo takes the page slot whenever a running one yields it.
+    #[serde(default)]
+    pub max_inflight_repos: usize,
+    /// Upper bound on `users/{login}` lookups in flight across all repos, so author
+    /// enrichment cannot crowd issue/comment pagination out of the core budget.
+    #[serde(default = "CollectorConfig::default_max_concurrent_user_fetches")]
+    pub max_concurrent_user_fetches: usize,
+    /// Accounts created within this many days count as new in `collector_new_accounts_seen`.
+    #[serde(default = "CollectorConfig::default_new_account_window_days")]
+    pub new_account_window_days: u32,
+    /// How long a cached org membership answer is trusted before re-checking GitHub.
+    #[serde(default = "CollectorConfig::default_org_membership_ttl_days")]
+    pub org_membership_ttl_days: u32,
+    /// Stored issue/comment bodies are cut to this many bytes; `0` keeps them whole.
+    /// Scoring and `dedupe_hash` always use the full text.
+    #[serde(default = "CollectorConfig::default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// A repository job still running after this many seconds is abandoned
+    /// (its outstanding broker requests are cancelled) and marked failed; `0` disables.
+    #[serde(default)]
+    pub repo_deadline_secs: u64,
+    /// Restrict a run to a single `owner/name` repository.
+    #[serde(default)]
+    pub repo: Option<String>,
+    /// Log the jobs a run would process without fetching or writing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Extra destination for normalized issues/comments, written alongside the DB upsert.
+    #[serde(default)]
+    pub sink: RecordSinkConfig,
+    /// Reason codes ignored per `owner/name` repo: their weight is removed from the
+    /// score before deciding whether to flag.
+    #[serde(default)]
+    pub suppressed_reasons: HashMap<String, Vec<String>>,
+    /// Fraction (`0.0`-`1.0`) of issues/comments that score zero whose features
+    /// are kept in `feature_samples` as labelled negatives; `0` disables.
+    #[serde(default)]
+    pub clean_sample_rate: f64,
+    /// Issue pages a repository job may walk in one run before it stops, keeps its
+    /// cursor and stays pending for the next run; `0` is unlimited.
+    #[serde(default)]
+    pub max_pages_per_repo: usize,
+    /// Issues/comments scoring at least this much have their GraphQL edit history
+    /// fetched and are re-scored with it; `0` disables the extra lookups.
+    #[serde(default)]
+    pub edit_history_min_score: f32,
+    /// Issues/comments scoring at least this much have the accounts behind their
+    /// reactions fetched via GraphQL and are re-scored with them; `0` disables it.
+    #[serde(default)]
+    pub reactions_min_score: f32,
+    /// Authors already stored with an account at least this many days old are not
+    /// re-fetched from `users/{login}`; `0` always fetches.
+    #[serde(default)]
+    pub skip_user_fetch_min_age_days: u32,
+    /// UTC hour (`0`-`23`) at which backfill jobs (negative priority) may start
+    /// being dispatched. Together with `backfill_end_hour` it keeps them pending
+    /// outside that window, leaving the rate limit to fresher work.
+    #[serde(default)]
+    pub backfill_start_hour: Option<u32>,
+    /// UTC hour at which the backfill window closes; it may wrap past midnight.
+    #[serde(default)]
+    pub backfill_end_hour: Option<u32>,
+    /// Consecutive database connection failures (not query errors) after which
+    /// the run loop stops claiming jobs and backs off; `0` disables it.
+    #[serde(default = "CollectorConfig::default_db_unavailable_threshold")]
+    pub db_unavailable_threshold: u32,
+    /// First pause once the database counts as unavailable; doubles per
+    /// further failed run.
+    #[serde(default = "CollectorConfig::default_db_backoff_base_ms")]
+    pub db_backoff_base_ms: u64,
+    /// Upper bound on that pause.
+    #[serde(default = "CollectorConfig::default_db_backoff_max_ms")]
+    pub db_backoff_max_ms: u64,
+}
+
+impl Default for CollectorConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: Self::default_interval_secs(),
+            page_size: Self::default_page_size(),
+            issue_page_size: None,
+            comment_page_size: None,
+            run_once: false,
+            fetch_mode: FetchMode::default(),
+            pull_request_mode: PullRequestMode::default(),
+            skip_pull_request_comments: false,
+            skip_fork_scoring: false,
+            rest_prefetch: false,
+            store_flag_features: false,
+            rapid_closure_window_mins: 0,
+            dedupe_mode: DedupeMode::default(),
+            normalization_leniency: Leniency::default(),
+            max_concurrent_repos: Self::default_max_concurrent_repos(),
+            max_inflight_repos: 0,
+            max_concurrent_user_fetches: Self::default_max_concurrent_user_fetches(),
+            new_account_window_days: Self::default_new_account_window_days(),
+            org_membership_ttl_days: Self::default_org_membership_ttl_days(),
+            max_body_bytes: Self::default_max_body_bytes(),
+            repo_deadline_secs: 0,
+            repo: None,
+            dry_run: false,
+            sink: RecordSinkConfig::default(),
+            suppressed_reasons: HashMap::new(),
+            clean_sample_rate: 0.0,
+            max_pages_per_repo: 0,
+            edit_history_min_score: 0.0,
+            reactions_min_score: 0.0,
+            skip_user_fetch_min_age_days: 0,
+            backfill_start_hour: None,
+            backfill_end_hour: None,
+            db_unavailable_threshold: Self::default_db_unavailable_threshold(),
+            db_backoff_base_ms: Self::default_db_backoff_base_ms(),
+            db_backoff_max_ms: Self::default_db_backoff_max_ms(),
+        }
+    }
 }
 
 impl CollectorConfig {
+    pub fn issue_page_size(&self) -> u32 {
+        self.issue_page_size.unwrap_or(self.page_size)
+    }
+
+    pub fn comment_page_size(&self) -> u32 {
+        self.comment_page_size.unwrap_or(self.page_size)
+    }
+
+    /// Suppressed reason codes for `repo` (`owner/name`, case-insensitive).
+    pub fn suppressed_reasons_for(&self, repo: &str) -> &[String] {
+        self.suppressed_reasons
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(repo))
+            .map(|(_, reasons)| reasons.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Whether backfill jobs may be dispatched at `now`: always, unless both
+    /// window hours are set and differ. `22` to `6` allows 22:00-05:59 UTC.
+    pub fn backfill_allowed_at(&self, now: DateTime<Utc>) -> bool {
+        let (Some(start), Some(end)) = (self.backfill_start_hour, self.backfill_end_hour) else {
+            return true;
+        };
+        let hour = now.hour();
+        match start.cmp(&end) {
+            std::cmp::Ordering::Equal => true,
+            std::cmp::Ordering::Less => (start..end).contains(&hour),
+            std::cmp::Ordering::Greater => hour >= start || hour < end,
+        }
+    }
+
+    /// Pause before the next run after `failures` consecutive database
+    /// connection failures, or `None` while below `db_unavailable_threshold`.
+    pub fn db_backoff(&self, failures: u32) -> Option<std::time::Duration> {
+        if self.db_unavailable_threshold == 0 || failures < self.db_unavailable_threshold {
+            return None;
+        }
+        let exp = (failures - self.db_unavailable_threshold).min(16);
+        let delay = self.db_backoff_base_ms.saturating_mul(1 << exp);
+        Some(std::time::Duration::from_millis(
+            delay.min(self.db_backoff_max_ms),
+        ))
+    }
+
+    pub fn repo_deadline(&self) -> Option<std::time::Duration> {
+        (self.repo_deadline_secs > 0)
+            .then(|| std::time::Duration::from_secs(self.repo_deadline_secs))
+    }
+
     const fn default_interval_secs() -> u64 {
         300
     }
@@ -127,6 +422,34 @@ impl CollectorConfig {
     const fn default_max_concurrent_repos() -> usize {
         4
     }
+
+    const fn default_max_concurrent_user_fetches() -> usize {
+        2
+    }
+
+    const fn default_new_account_window_days() -> u32 {
+        7
+    }
+
+    const fn default_org_membership_ttl_days() -> u32 {
+        30
+    }
+
+    const fn default_max_body_bytes() -> usize {
+        65_536
+    }
+
+    const fn default_db_unavailable_threshold() -> u32 {
+        3
+    }
+
+    const fn default_db_backoff_base_ms() -> u64 {
+        5_000
+    }
+
+    const fn default_db_backoff_max_ms() -> u64 {
+        300_000
+    }
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
@@ -138,6 +461,75 @@ pub enum FetchMode {
     Hybrid,
 }
 
+/// What text of an issue/comment body feeds its `dedupe_hash`.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupeMode {
+    /// The whole body.
+    #[default]
+    FullText,
+    /// The body without markdown blockquotes (and the "On ... wrote:" line
+    /// introducing them) or a trailing `-- ` signature, so the same spam line
+    /// quoting different messages still collides.
+    StripQuotes,
+}
+
+/// How the normalizer treats payloads missing a field it requires. Either way
+/// the miss is counted in `normalizer_missing_fields_total`.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Leniency {
+    /// Fail the payload.
+    #[default]
+    Strict,
+    /// Fill the field's registered default and carry on; fields without one
+    /// still fail.
+    Lenient,
+}
+
+/// How the collector treats pull requests returned by issue listings.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PullRequestMode {
+    /// Stored and scored exactly like issues.
+    #[default]
+    Include,
+    /// Neither stored nor scored; their comments are skipped too.
+    Skip,
+    /// Stored (with `is_pull_request = true`) but left out of issue scoring, so
+    /// PR spam can follow its own policy. There is no dedicated PR table yet.
+    Separate,
+}
+
+impl PullRequestMode {
+    pub fn stores(self) -> bool {
+        self != PullRequestMode::Skip
+    }
+
+    pub fn scores(self) -> bool {
+        self == PullRequestMode::Include
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct RecordSinkConfig {
+    #[serde(default)]
+    pub kind: RecordSinkKind,
+    /// JSON-lines file appended to when `kind = "file"`.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordSinkKind {
+    /// Postgres only.
+    #[default]
+    None,
+    File,
+}
+
+#[serde_as]
 #[derive(Debug, Clone, Deserialize)]
 pub struct BrokerConfig {
     #[serde(default = "BrokerConfig::default_max_inflight")]
@@ -145,6 +537,8 @@ pub struct BrokerConfig {
     #[serde(default = "BrokerConfig::default_per_repo_inflight")]
     pub per_repo_inflight: usize,
     #[serde(default)]
+    pub critical_reserve: i64,
+    #[serde(default)]
     pub distributed: bool,
     #[serde(default = "BrokerConfig::default_cache_capacity")]
     pub cache_capacity: usize,
@@ -158,8 +552,23 @@ pub struct BrokerConfig {
     pub jitter_frac: f32,
     #[serde(default, deserialize_with = "parse_weights")]
     pub weights: HashMap<String, [u32; 3]>,
+    /// Let backfill borrow scheduling slots critical/normal leave idle.
+    #[serde(default)]
+    pub work_conserving: bool,
     #[serde(default, deserialize_with = "parse_queue_bounds")]
     pub queue_bounds: HashMap<String, usize>,
+    /// Longest a request waits for a rate-limit reset when every token is
+    /// exhausted before it fails so its job can be retried; unset waits forever.
+    #[serde(default)]
+    pub max_rate_limit_wait_secs: Option<u64>,
+    /// GraphQL page-size variables, outermost connection first, used to
+    /// estimate a query's cost when the response does not report it.
+    #[serde_as(as = "StringWithSeparator::<CommaSeparator, String>")]
+    #[serde(default = "BrokerConfig::default_graphql_cost_page_variables")]
+    pub graphql_cost_page_variables: Vec<String>,
+    /// Estimated connection requests charged as one GraphQL point.
+    #[serde(default = "BrokerConfig::default_graphql_cost_requests_per_point")]
+    pub graphql_cost_requests_per_point: u64,
 }
 
 impl BrokerConfig {
@@ -190,11 +599,42 @@ impl BrokerConfig {
     const fn default_jitter_frac() -> f32 {
         0.2
     }
+
+    fn default_graphql_cost_page_variables() -> Vec<String> {
+        vec![
+            "perPage".to_string(),
+            "commentsPerPage".to_string(),
+            "first".to_string(),
+        ]
+    }
+
+    const fn default_graphql_cost_requests_per_point() -> u64 {
+        100
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AnalysisConfig {
+    /// TOML file of weighted keyword/regex categories scored by the rule engine.
+    #[serde(default)]
+    pub keyword_patterns_path: Option<String>,
+    /// Most a single reason code may add to a score, so one outlier signal
+    /// cannot outweigh several moderate ones. Reasons not listed are uncapped.
+    #[serde(default)]
+    pub reason_caps: HashMap<String, f32>,
+    /// Added to the score of flagged issues/comments in fork repositories:
+    /// positive for more scrutiny, negative for less. `0.0` ignores forks.
+    #[serde(default)]
+    pub fork_adjustment: f32,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ApiConfig {
     pub bind: String,
+    /// Key required (as `Authorization: Bearer <key>`) by endpoints that
+    /// expose raw GitHub payloads. Those endpoints are refused while unset.
+    #[serde(default)]
+    pub api_key: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -203,6 +643,15 @@ pub struct ObservabilityConfig {
     pub metrics_path: String,
     #[serde(default = "ObservabilityConfig::default_metrics_bind")]
     pub metrics_bind: String,
+    /// Distinct `repo` label values per-repository metrics may use before
+    /// further repositories are folded into `other`.
+    #[serde(default = "ObservabilityConfig::default_max_repo_labels")]
+    pub max_repo_labels: usize,
+    /// How long the API reuses its per-repository issue/comment/user counts
+    /// across `/metrics` scrapes before re-running the aggregate queries.
+    /// `0` recomputes on every scrape.
+    #[serde(default = "ObservabilityConfig::default_entity_counts_ttl_secs")]
+    pub entity_counts_ttl_secs: u64,
 }
 
 impl ObservabilityConfig {
@@ -213,6 +662,14 @@ impl ObservabilityConfig {
     fn default_metrics_bind() -> String {
         "0.0.0.0:9091".to_string()
     }
+
+    fn default_max_repo_labels() -> usize {
+        crate::labels::DEFAULT_MAX_REPO_LABELS
+    }
+
+    fn default_entity_counts_ttl_secs() -> u64 {
+        60
+    }
 }
 
 fn parse_weights<'de, D>(deserializer: D) -> Result<HashMap<String, [u32; 3]>, D::Error>
@@ -271,6 +728,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
     use serde_json::json;
 
     #[derive(Deserialize)]
@@ -291,6 +749,63 @@ mod tests {
         assert_eq!(wrapper.mode, FetchMode::Graphql);
     }
 
+    #[test]
+    fn page_sizes_fall_back_to_page_size() {
+        let cfg: CollectorConfig =
+            serde_json::from_value(json!({ "page_size": 60, "comment_page_size": 25 })).unwrap();
+        assert_eq!(cfg.issue_page_size(), 60);
+        assert_eq!(cfg.comment_page_size(), 25);
+    }
+
+    #[test]
+    fn backfill_window_wraps_past_midnight() {
+        let at = |hour| Utc.with_ymd_and_hms(2024, 3, 1, hour, 30, 0).unwrap();
+        let unrestricted = CollectorConfig::default();
+        assert!(unrestricted.backfill_allowed_at(at(12)));
+
+        let cfg = CollectorConfig {
+            backfill_start_hour: Some(22),
+            backfill_end_hour: Some(6),
+            ..Default::default()
+        };
+        assert!(!cfg.backfill_allowed_at(at(12)));
+        assert!(!cfg.backfill_allowed_at(at(21)));
+        assert!(cfg.backfill_allowed_at(at(22)));
+        assert!(cfg.backfill_allowed_at(at(3)));
+        assert!(!cfg.backfill_allowed_at(at(6)));
+
+        let daytime = CollectorConfig {
+            backfill_start_hour: Some(9),
+            backfill_end_hour: Some(17),
+            ..Default::default()
+        };
+        assert!(daytime.backfill_allowed_at(at(9)));
+        assert!(!daytime.backfill_allowed_at(at(17)));
+    }
+
+    #[test]
+    fn db_backoff_doubles_from_threshold_up_to_max() {
+        let cfg = CollectorConfig {
+            db_unavailable_threshold: 3,
+            db_backoff_base_ms: 100,
+            db_backoff_max_ms: 500,
+            ..Default::default()
+        };
+        let ms = |failures| cfg.db_backoff(failures).map(|d| d.as_millis());
+        assert_eq!(ms(2), None);
+        assert_eq!(ms(3), Some(100));
+        assert_eq!(ms(4), Some(200));
+        assert_eq!(ms(5), Some(400));
+        assert_eq!(ms(6), Some(500));
+        assert_eq!(ms(u32::MAX), Some(500));
+
+        let disabled = CollectorConfig {
+            db_unavailable_threshold: 0,
+            ..cfg
+        };
+        assert_eq!(disabled.db_backoff(10), None);
+    }
+
     #[test]
     fn github_config_parses_csv_tokens() {
         let data = json!({
@@ -305,6 +820,24 @@ mod tests {
         assert_eq!(tokens[1].secret, "secretB");
     }
 
+    #[test]
+    fn github_config_rejects_empty_user_agent() {
+        let parse = |user_agent: &str| -> GithubConfig {
+            serde_json::from_value(json!({ "user_agent": user_agent })).expect("config parsed")
+        };
+        assert!(parse("acme-spam-lab/1.0 (ops@example.com)")
+            .validate()
+            .is_ok());
+        for bad in ["", "   ", "lab\r\nX-Injected: 1"] {
+            let err = parse(bad).validate().expect_err("user agent rejected");
+            assert!(err.to_string().contains("github.user_agent"), "{err}");
+        }
+
+        let defaulted: GithubConfig = serde_json::from_value(json!({})).expect("config parsed");
+        assert_eq!(defaulted.user_agent, DEFAULT_USER_AGENT);
+        assert!(defaulted.validate().is_ok());
+    }
+
     #[test]
     fn broker_config_parses_weights_and_bounds() {
         let data = json!({
@@ -1,4 +1,5 @@
 use std::fmt::Debug;
+use std::time::Duration;
 
 pub type Result<T, E = AppError> = std::result::Result<T, E>;
 
@@ -10,6 +11,10 @@ pub enum AppError {
     Database(#[source] anyhow::Error),
     #[error("http error: {0}")]
     Http(#[source] anyhow::Error),
+    /// GitHub's rate limit is exhausted; the operation can be retried once
+    /// `retry_after` has elapsed, when GitHub told us how long that is.
+    #[error("rate limited by GitHub{}", retry_after.map(|d| format!(", retry after {}s", d.as_secs())).unwrap_or_default())]
+    RateLimited { retry_after: Option<Duration> },
     #[error("not found: {0}")]
     NotFound(&'static str),
     #[error(transparent)]
@@ -24,4 +29,8 @@ impl AppError {
     pub fn http(err: impl Into<anyhow::Error>) -> Self {
         Self::Http(err.into())
     }
+
+    pub fn rate_limited(retry_after: Option<Duration>) -> Self {
+        Self::RateLimited { retry_after }
+    }
 }
@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Label value that repositories beyond the cap are folded into.
+pub const OTHER_REPO_LABEL: &str = "other";
+
+pub const DEFAULT_MAX_REPO_LABELS: usize = 200;
+
+/// Hands out `repo` label values for metrics that are updated incrementally.
+///
+/// The first `limit` distinct repositories keep their own label; every later
+/// one is reported as [`OTHER_REPO_LABEL`], so a long-running collector cannot
+/// grow an unbounded number of series.
+pub struct RepoLabels {
+    limit: AtomicUsize,
+    admitted: Mutex<HashSet<String>>,
+}
+
+impl RepoLabels {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: AtomicUsize::new(limit),
+            admitted: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Changes the cap. Repositories already admitted keep their label.
+    pub fn set_limit(&self, limit: usize) {
+        self.limit.store(limit, Ordering::Relaxed);
+    }
+
+    pub fn label(&self, repo: &str) -> String {
+        let mut admitted = self.admitted.lock().expect("repo labels lock poisoned");
+        if admitted.contains(repo) {
+            return repo.to_string();
+        }
+        if admitted.len() < self.limit.load(Ordering::Relaxed) {
+            admitted.insert(repo.to_string());
+            return repo.to_string();
+        }
+        OTHER_REPO_LABEL.to_string()
+    }
+}
+
+impl Default for RepoLabels {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_REPO_LABELS)
+    }
+}
+
+/// Keeps the `limit` largest `(repo, value)` rows and sums the remainder into
+/// a single [`OTHER_REPO_LABEL`] row. Used for gauges recomputed from scratch.
+pub fn fold_top_repos(mut rows: Vec<(String, i64)>, limit: usize) -> Vec<(String, i64)> {
+    if rows.len() <= limit {
+        return rows;
+    }
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let other: i64 = rows.drain(limit..).map(|(_, value)| value).sum();
+    rows.push((OTHER_REPO_LABEL.to_string(), other));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repos_beyond_cap_fold_into_other() {
+        let labels = RepoLabels::new(2);
+        assert_eq!(labels.label("acme/a"), "acme/a");
+        assert_eq!(labels.label("acme/b"), "acme/b");
+        assert_eq!(labels.label("acme/c"), OTHER_REPO_LABEL);
+        // Admitted repos keep their label after the cap is reached.
+        assert_eq!(labels.label("acme/a"), "acme/a");
+
+        labels.set_limit(3);
+        assert_eq!(labels.label("acme/d"), "acme/d");
+        assert_eq!(labels.label("acme/c"), OTHER_REPO_LABEL);
+    }
+
+    #[test]
+    fn fold_keeps_largest_repos() {
+        let rows = vec![
+            ("acme/small".to_string(), 1),
+            ("acme/big".to_string(), 50),
+            ("acme/tiny".to_string(), 2),
+            ("acme/mid".to_string(), 10),
+        ];
+        assert_eq!(
+            fold_top_repos(rows.clone(), 2),
+            vec![
+                ("acme/big".to_string(), 50),
+                ("acme/mid".to_string(), 10),
+                (OTHER_REPO_LABEL.to_string(), 3),
+            ]
+        );
+        assert_eq!(fold_top_repos(rows.clone(), 4), rows);
+        assert_eq!(
+            fold_top_repos(rows, 0),
+            vec![(OTHER_REPO_LABEL.to_string(), 63)]
+        );
+    }
+}
@@ -1,6 +1,10 @@
 pub mod config;
 pub mod errors;
+pub mod labels;
 pub mod logging;
+pub mod metrics_json;
+pub mod redact;
+pub mod shutdown;
 pub mod text;
 
 pub use crate::config::AppConfig;
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use prometheus::proto::{Metric, MetricFamily, MetricType};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct MetricFamilyJson {
+    pub name: String,
+    pub help: String,
+    /// `counter`, `gauge`, `histogram`, `summary` or `untyped`.
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub samples: Vec<SampleJson>,
+}
+
+/// One labelled series. Counters, gauges and untyped metrics carry `value`;
+/// histograms and summaries carry `count`/`sum` plus their buckets or quantiles.
+#[derive(Debug, Serialize)]
+pub struct SampleJson {
+    pub labels: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sum: Option<f64>,
+    /// Cumulative counts keyed by upper bound (`le`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buckets: Option<Vec<(f64, u64)>>,
+    /// Values keyed by quantile.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantiles: Option<Vec<(f64, f64)>>,
+}
+
+/// Everything registered in the default registry, as JSON-serializable
+/// families for tooling that cannot parse the text exposition format.
+pub fn gather() -> Vec<MetricFamilyJson> {
+    encode(&prometheus::gather())
+}
+
+pub fn encode(families: &[MetricFamily]) -> Vec<MetricFamilyJson> {
+    families
+        .iter()
+        .map(|family| {
+            let kind = family.get_field_type();
+            MetricFamilyJson {
+                name: family.get_name().to_string(),
+                help: family.get_help().to_string(),
+                kind: type_name(kind),
+                samples: family
+                    .get_metric()
+                    .iter()
+                    .map(|metric| sample(kind, metric))
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+fn type_name(kind: MetricType) -> &'static str {
+    match kind {
+        MetricType::COUNTER => "counter",
+        MetricType::GAUGE => "gauge",
+        MetricType::HISTOGRAM => "histogram",
+        MetricType::SUMMARY => "summary",
+        MetricType::UNTYPED => "untyped",
+    }
+}
+
+fn sample(kind: MetricType, metric: &Metric) -> SampleJson {
+    let mut sample = SampleJson {
+        labels: metric
+            .get_label()
+            .iter()
+            .map(|pair| (pair.get_name().to_string(), pair.get_value().to_string()))
+            .collect(),
+        value: None,
+        count: None,
+        sum: None,
+        buckets: None,
+        quantiles: None,
+    };
+    match kind {
+        MetricType::COUNTER => sample.value = Some(metric.get_counter().get_value()),
+        MetricType::GAUGE => sample.value = Some(metric.get_gauge().get_value()),
+        MetricType::UNTYPED => sample.value = Some(metric.get_untyped().get_value()),
+        MetricType::HISTOGRAM => {
+            let histogram = metric.get_histogram();
+            sample.count = Some(histogram.get_sample_count());
+            sample.sum = Some(histogram.get_sample_sum());
+            sample.buckets = Some(
+                histogram
+                    .get_bucket()
+                    .iter()
+                    .map(|b| (b.get_upper_bound(), b.get_cumulative_count()))
+                    .collect(),
+            );
+        }
+        MetricType::SUMMARY => {
+            let summary = metric.get_summary();
+            sample.count = Some(summary.get_sample_count());
+            sample.sum = Some(summary.get_sample_sum());
+            sample.quantiles = Some(
+                summary
+                    .get_quantile()
+                    .iter()
+                    .map(|q| (q.get_quantile(), q.get_value()))
+                    .collect(),
+            );
+        }
+    }
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::{Histogram, HistogramOpts, IntCounterVec, Opts, Registry};
+
+    use super::*;
+
+    #[test]
+    fn counters_and_histograms_render_per_sample() {
+        let registry = Registry::new();
+        let counter = IntCounterVec::new(Opts::new("jobs_total", "Jobs run"), &["status"]).unwrap();
+        let histogram =
+            Histogram::with_opts(HistogramOpts::new("job_seconds", "Job time").buckets(vec![1.0]))
+                .unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+        registry.register(Box::new(histogram.clone())).unwrap();
+        counter.with_label_values(&["ok"]).inc_by(3);
+        histogram.observe(0.5);
+        histogram.observe(2.0);
+
+        let json = serde_json::to_value(encode(&registry.gather())).unwrap();
+        assert_eq!(
+            json[0],
+            serde_json::json!({
+                "name": "job_seconds",
+                "help": "Job time",
+                "type": "histogram",
+                "samples": [{ "labels": {}, "count": 2, "sum": 2.5, "buckets": [[1.0, 1]] }]
+            })
+        );
+        assert_eq!(
+            json[1],
+            serde_json::json!({
+                "name": "jobs_total",
+                "help": "Jobs run",
+                "type": "counter",
+                "samples": [{ "labels": { "status": "ok" }, "value": 3.0 }]
+            })
+        );
+    }
+}
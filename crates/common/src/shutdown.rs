@@ -0,0 +1,60 @@
+use tracing::info;
+
+/// SIGINT/SIGTERM listener shared by the service binaries.
+///
+/// Handlers are registered in [`ShutdownSignal::install`] rather than on first
+/// poll, so a SIGTERM that arrives during startup is not lost to the default
+/// (kill) disposition.
+pub struct ShutdownSignal {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl ShutdownSignal {
+    pub fn install() -> std::io::Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            terminate: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?,
+        })
+    }
+
+    /// Resolves once the process is asked to stop.
+    pub async fn recv(mut self) {
+        let signal = self.wait().await;
+        info!(signal, "shutdown signal received");
+    }
+
+    #[cfg(unix)]
+    async fn wait(&mut self) -> &'static str {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => "SIGINT",
+            _ = self.terminate.recv() => "SIGTERM",
+        }
+    }
+
+    #[cfg(not(unix))]
+    async fn wait(&mut self) -> &'static str {
+        let _ = tokio::signal::ctrl_c().await;
+        "ctrl-c"
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn sigterm_resolves_installed_signal() {
+        let signal = ShutdownSignal::install().expect("install handlers");
+        let status = std::process::Command::new("kill")
+            .args(["-TERM", &std::process::id().to_string()])
+            .status()
+            .expect("run kill");
+        assert!(status.success());
+
+        tokio::time::timeout(Duration::from_secs(5), signal.recv())
+            .await
+            .expect("SIGTERM should resolve the shutdown future");
+    }
+}
@@ -10,4 +10,27 @@ pub enum DbError {
     Config(#[source] anyhow::Error),
 }
 
+impl DbError {
+    /// The database could not be reached (refused or dropped connection, pool
+    /// timeout, server shutting down), as opposed to a query it rejected.
+    pub fn is_unavailable(&self) -> bool {
+        let DbError::Query(err) = self else {
+            return false;
+        };
+        match err {
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => true,
+            // Class 08 is connection exceptions; 57P01-57P03 are shutdowns
+            // and a server not yet accepting connections.
+            sqlx::Error::Database(err) => err.code().is_some_and(|code| {
+                code.starts_with("08") || matches!(code.as_ref(), "57P01" | "57P02" | "57P03")
+            }),
+            _ => false,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, DbError>;
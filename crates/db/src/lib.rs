@@ -1,4 +1,5 @@
 pub mod errors;
+pub mod metrics;
 pub mod models;
 pub mod pg;
 pub mod repositories;
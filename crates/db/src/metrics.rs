@@ -0,0 +1,42 @@
+use std::future::Future;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use prometheus::{register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec};
+
+use crate::errors::Result;
+
+pub static DB_OPERATION_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "db_operation_duration_seconds",
+        "Latency of repository operations against Postgres grouped by operation",
+        &["op"],
+        vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]
+    )
+    .expect("db operation duration histogram")
+});
+
+pub static DB_OPERATION_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "db_operation_errors_total",
+        "Repository operations that returned an error grouped by operation",
+        &["op"]
+    )
+    .expect("db operation errors counter")
+});
+
+/// Times `fut` under `op` (`<repository>.<method>`) and counts it as an error if it fails.
+pub(crate) async fn observe<T, F>(op: &'static str, fut: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    let start = Instant::now();
+    let result = fut.await;
+    DB_OPERATION_DURATION
+        .with_label_values(&[op])
+        .observe(start.elapsed().as_secs_f64());
+    if result.is_err() {
+        DB_OPERATION_ERRORS.with_label_values(&[op]).inc();
+    }
+    result
+}
@@ -12,6 +12,16 @@ pub enum CollectionStatus {
     Error,  // Permanent error - will not retry
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "review_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    #[default]
+    Unreviewed,
+    Confirmed,
+    FalsePositive,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct RepositoryRow {
     pub id: i64,
@@ -33,6 +43,8 @@ pub struct UserRow {
     pub followers: Option<i64>,
     pub following: Option<i64>,
     pub public_repos: Option<i64>,
+    pub bio: Option<String>,
+    pub blog: Option<String>,
     pub raw: serde_json::Value,
     pub found: bool,
 }
@@ -51,11 +63,53 @@ pub struct IssueRow {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub closed_at: Option<DateTime<Utc>>,
+    pub state_reason: Option<String>,
+    pub locked: bool,
+    pub author_association: Option<String>,
     pub dedupe_hash: String,
     pub raw: serde_json::Value,
     pub found: bool,
 }
 
+/// The stored columns the collector compares to decide whether an issue changed.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct IssueFingerprint {
+    pub dedupe_hash: String,
+    pub state: String,
+    pub comments_count: i64,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub locked: bool,
+    pub updated_at: DateTime<Utc>,
+    pub found: bool,
+}
+
+/// A state or lock change seen between the stored and the incoming issue.
+#[derive(Debug, Clone)]
+pub struct IssueStateTransition {
+    pub issue_id: i64,
+    /// The issue's author.
+    pub user_id: Option<i64>,
+    pub from_state: String,
+    pub to_state: String,
+    pub from_locked: bool,
+    pub to_locked: bool,
+    /// Closed shortly after creation by someone other than the author.
+    pub rapid_maintainer_closure: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct IssueStateTransitionRow {
+    pub id: i64,
+    pub issue_id: i64,
+    pub user_id: Option<i64>,
+    pub from_state: String,
+    pub to_state: String,
+    pub from_locked: bool,
+    pub to_locked: bool,
+    pub rapid_maintainer_closure: bool,
+    pub observed_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct CommentRow {
     pub id: i64,
@@ -64,6 +118,7 @@ pub struct CommentRow {
     pub body: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub author_association: Option<String>,
     pub dedupe_hash: String,
     pub raw: serde_json::Value,
     pub found: bool,
@@ -78,6 +133,16 @@ pub struct SpamFlagRow {
     pub reasons: Vec<String>,
     pub version: String,
     pub created_at: DateTime<Utc>,
+    // History rows carry no review state and fall back to the defaults.
+    #[sqlx(default)]
+    pub reviewed_status: ReviewStatus,
+    #[sqlx(default)]
+    pub reviewed_at: Option<DateTime<Utc>>,
+    #[sqlx(default)]
+    pub reviewed_by: Option<String>,
+    /// Serialized `FeatureSet` the flag was scored from, when stored.
+    #[sqlx(default)]
+    pub features: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -90,8 +155,11 @@ pub struct CollectorWatermarkRow {
 pub struct IssueQuery {
     pub repo_full_name: Option<String>,
     pub spam: Option<SpamFilter>,
+    pub reviewed: Option<ReviewStatus>,
     pub since: Option<DateTime<Utc>>,
     pub limit: Option<i64>,
+    /// Result ordering; `None` is `IssueOrder::UpdatedDesc`.
+    pub order_by: Option<IssueOrder>,
 }
 
 #[derive(Debug, Clone)]
@@ -101,6 +169,15 @@ pub enum SpamFilter {
     All,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IssueOrder {
+    #[default]
+    UpdatedDesc,
+    CreatedDesc,
+    /// Highest spam flag score first; unflagged issues last.
+    ScoreDesc,
+}
+
 #[derive(Debug, Clone)]
 pub struct SpamFlagUpsert {
     pub subject_type: String,
@@ -108,6 +185,63 @@ pub struct SpamFlagUpsert {
     pub score: f32,
     pub reasons: Vec<String>,
     pub version: String,
+    /// Serialized `FeatureSet` behind the flag; `None` keeps the column empty.
+    pub features: Option<serde_json::Value>,
+}
+
+/// Features of an issue/comment that scored zero, kept as a training negative.
+#[derive(Debug, Clone)]
+pub struct FeatureSampleUpsert {
+    pub subject_type: String,
+    pub subject_id: i64,
+    pub features: serde_json::Value,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FeatureSampleRow {
+    pub subject_type: String,
+    pub subject_id: i64,
+    pub features: serde_json::Value,
+    pub version: String,
+    pub sampled_at: DateTime<Utc>,
+}
+
+/// Edit history summary of an issue/comment.
+#[derive(Debug, Clone)]
+pub struct ContentEditsUpsert {
+    pub subject_type: String,
+    pub subject_id: i64,
+    pub edit_count: i32,
+    pub last_edited_at: Option<DateTime<Utc>>,
+    pub last_editor_login: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ContentEditsRow {
+    pub subject_type: String,
+    pub subject_id: i64,
+    pub edit_count: i32,
+    pub last_edited_at: Option<DateTime<Utc>>,
+    pub last_editor_login: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// One account's reaction to an issue/comment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct ReactionRow {
+    pub user_login: String,
+    /// GraphQL `ReactionContent`, e.g. `THUMBS_UP`.
+    pub content: String,
+    /// When the reacting account was created, if GitHub reported it.
+    pub user_created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpamFlagReview {
+    pub id: i64,
+    pub status: ReviewStatus,
+    pub reviewed_by: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -116,6 +250,21 @@ pub struct WatermarkUpdate {
     pub last_updated: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CollectorProgressRow {
+    pub repo_full_name: String,
+    pub cursor: String,
+    pub newest_ts: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProgressUpdate {
+    pub repo_full_name: String,
+    pub cursor: String,
+    pub newest_ts: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct TempDb {
     pub name: String,
@@ -128,6 +277,120 @@ impl TempDb {
     }
 }
 
+/// What a user has posted across all collected repositories. Flag counts skip
+/// subjects a reviewer marked `false_positive`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, FromRow)]
+pub struct UserActivitySummary {
+    pub issue_count: i64,
+    pub comment_count: i64,
+    pub flagged_issue_count: i64,
+    pub flagged_comment_count: i64,
+    pub first_activity_at: Option<DateTime<Utc>>,
+    pub last_activity_at: Option<DateTime<Utc>>,
+}
+
+/// Aggregate of the flags on everything a user authored. Skips flags a
+/// reviewer marked `false_positive`; reasons are distinct and sorted.
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
+pub struct UserSpamSummary {
+    pub flag_count: i64,
+    pub total_score: f32,
+    pub avg_score: f32,
+    pub reasons: Vec<String>,
+    pub first_flagged_at: Option<DateTime<Utc>>,
+    pub last_flagged_at: Option<DateTime<Utc>>,
+}
+
+/// Stored volume of one repository. Flag counts skip subjects a reviewer
+/// marked `false_positive`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, FromRow)]
+pub struct RepoSummary {
+    pub issue_count: i64,
+    pub comment_count: i64,
+    /// Distinct issue and comment authors.
+    pub distinct_authors: i64,
+    pub flagged_issue_count: i64,
+    pub flagged_comment_count: i64,
+}
+
+/// What [`RepoRepository::delete_repo`](crate::RepoRepository::delete_repo)
+/// removed for one repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoDeletion {
+    pub repository: bool,
+    pub issues: u64,
+    pub comments: u64,
+    /// Current spam flags of the removed issues and comments.
+    pub spam_flags: u64,
+    pub watermark: bool,
+    pub collection_job: bool,
+}
+
+impl RepoDeletion {
+    /// Nothing was stored under the name.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Width of the buckets in [`ScoreBucket`] series.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrendBucket {
+    Hour,
+    #[default]
+    Day,
+}
+
+impl TrendBucket {
+    /// The `date_trunc` field name.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TrendBucket::Hour => "hour",
+            TrendBucket::Day => "day",
+        }
+    }
+}
+
+/// Flags raised in one time bucket of a repository, by flag `created_at`.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct ScoreBucket {
+    pub bucket_start: DateTime<Utc>,
+    pub flag_count: i64,
+    pub total_score: f32,
+}
+
+/// Cached answer to "is this user a public member of `org`?".
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct OrgMembershipRow {
+    pub org: String,
+    pub user_id: i64,
+    pub is_member: bool,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// An issue or comment whose `user_id` has no `users` row.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct OrphanedAuthor {
+    pub subject_type: String,
+    pub subject_id: i64,
+    pub user_id: i64,
+    /// The author's login from the stored payload, when it carries one.
+    pub login: Option<String>,
+}
+
+/// A flag joined to its subject for the cross-repo feed. For comments, `issue_number`
+/// and `title` describe the parent issue.
+#[derive(Debug, Clone, FromRow)]
+pub struct RecentFlagRow {
+    #[sqlx(flatten)]
+    pub flag: SpamFlagRow,
+    pub repo_full_name: String,
+    pub issue_number: i64,
+    pub title: String,
+    pub author_login: Option<String>,
+    pub snippet: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct ActorSpamSummary {
     pub login: String,
@@ -137,6 +400,49 @@ pub struct ActorSpamSummary {
     pub reasons: Vec<String>,
 }
 
+impl ActorSpamSummary {
+    /// Position of this row in the `top_spammy_users` ordering, for fetching
+    /// the page after it.
+    pub fn cursor(&self) -> SpammyUserCursor {
+        SpammyUserCursor {
+            total_score: self.total_score,
+            flag_count: self.flag_count,
+            login: self.login.clone(),
+        }
+    }
+}
+
+/// Keyset for `top_spammy_users`, which orders by `total_score DESC,
+/// flag_count DESC, login ASC`; rows after it in that order are returned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpammyUserCursor {
+    pub total_score: f32,
+    pub flag_count: i64,
+    pub login: String,
+}
+
+/// Issues/comments sharing one `dedupe_hash`: the same body posted repeatedly.
+#[derive(Debug, Clone)]
+pub struct DedupeCluster {
+    pub dedupe_hash: String,
+    pub size: i64,
+    pub distinct_authors: i64,
+    /// Oldest first, at most [`MAX_CLUSTER_MEMBERS`].
+    pub members: Vec<ClusterMember>,
+}
+
+/// Members listed per [`DedupeCluster`]; `size` still counts all of them.
+pub const MAX_CLUSTER_MEMBERS: i64 = 100;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ClusterMember {
+    pub subject_type: String,
+    pub subject_id: i64,
+    pub dedupe_hash: String,
+    pub author_login: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct CollectionJobRow {
     pub id: i64,
@@ -1,22 +1,120 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::{postgres::PgPoolOptions, PgPool, Postgres, QueryBuilder, Row};
+use common::text::normalize_title;
+use sqlx::postgres::{PgPoolOptions, PgRow};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row, Transaction};
 use tokio::time::{sleep, Duration};
 use tracing::{instrument, warn};
 
 use crate::errors::{DbError, Result};
+use crate::metrics;
 use crate::models::{
-    ActorSpamSummary, CollectionJobCreate, CollectionJobRow, CollectionJobUpdate, CollectionStatus,
-    CollectorWatermarkRow, CommentRow, IssueQuery, IssueRow, RepositoryRow, SpamFlagRow,
-    SpamFlagUpsert, UserRow, WatermarkUpdate,
+    ActorSpamSummary, ClusterMember, CollectionJobCreate, CollectionJobRow, CollectionJobUpdate,
+    CollectionStatus, CollectorProgressRow, CollectorWatermarkRow, CommentRow, ContentEditsRow,
+    ContentEditsUpsert, DedupeCluster, FeatureSampleRow, FeatureSampleUpsert, IssueFingerprint,
+    IssueOrder, IssueQuery, IssueRow, IssueStateTransition, IssueStateTransitionRow,
+    OrgMembershipRow, OrphanedAuthor, ProgressUpdate, ReactionRow, RecentFlagRow, RepoDeletion,
+    RepoSummary, RepositoryRow, ScoreBucket, SpamFlagReview, SpamFlagRow, SpamFlagUpsert,
+    SpammyUserCursor, TrendBucket, UserActivitySummary, UserRow, UserSpamSummary, WatermarkUpdate,
+    MAX_CLUSTER_MEMBERS,
 };
 use crate::repositories::{
     CollectionJobRepository, CommentRepository, IssueRepository, RepoRepository, Repositories,
     SpamFlagsRepository, UserRepository, WatermarkRepository,
 };
 
+fn actor_spam_summaries(rows: Vec<PgRow>) -> Vec<ActorSpamSummary> {
+    let mut summaries = Vec::new();
+    for row in rows {
+        let login: Option<String> = row.try_get("login").ok();
+        if let Some(login) = login {
+            let avg_score: f64 = row
+                .try_get::<Option<f64>, _>("avg_score")
+                .unwrap_or(Some(0.0))
+                .unwrap_or(0.0);
+            let total_score: f32 = row
+                .try_get::<Option<f32>, _>("total_score")
+                .unwrap_or(Some(0.0))
+                .unwrap_or(0.0);
+            let flag_count: i64 = row
+                .try_get::<Option<i64>, _>("flag_count")
+                .unwrap_or(Some(0))
+                .unwrap_or(0);
+            let reasons: Vec<String> = row
+                .try_get::<Option<Vec<String>>, _>("reasons")
+                .unwrap_or(Some(Vec::new()))
+                .unwrap_or_default();
+            summaries.push(ActorSpamSummary {
+                login,
+                avg_score: avg_score as f32,
+                total_score,
+                flag_count,
+                reasons,
+            });
+        }
+    }
+    summaries
+}
+
+/// Recomputes the `spammy_user_totals` rows of the logins in the
+/// `changed_spammy_users` temp table, returning how many rows were written.
+async fn rebuild_spammy_user_totals(tx: &mut Transaction<'_, Postgres>) -> Result<u64> {
+    sqlx::query(
+        "DELETE FROM spammy_user_totals WHERE login IN (SELECT login FROM changed_spammy_users)",
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(DbError::Query)?;
+    // Same aggregation as the live ranking, for the changed authors only.
+    sqlx::query(
+        r#"
+        INSERT INTO spammy_user_totals (login, avg_score, total_score, flag_count, reasons)
+        SELECT login, AVG(score), SUM(score::float8)::real, COUNT(*),
+               ARRAY_AGG(DISTINCT reason)
+        FROM (
+            SELECT
+                COALESCE(u.login, uc.login) AS login,
+                sf.score,
+                unnest(sf.reasons) AS reason
+            FROM spam_flags sf
+            LEFT JOIN issues i ON sf.subject_type = 'issue' AND sf.subject_id = i.id
+            LEFT JOIN users u ON i.user_id = u.id
+            LEFT JOIN comments c ON sf.subject_type = 'comment' AND sf.subject_id = c.id
+            LEFT JOIN users uc ON c.user_id = uc.id
+        ) flagged
+        WHERE login IN (SELECT login FROM changed_spammy_users)
+        GROUP BY login
+        "#,
+    )
+    .execute(&mut **tx)
+    .await
+    .map(|result| result.rows_affected())
+    .map_err(DbError::Query)
+}
+
+/// `rows` with each one superseded by a later row of the same key dropped,
+/// in first-seen order: one statement cannot `ON CONFLICT DO UPDATE` a row
+/// twice.
+fn last_per_key<T, K: Eq + Hash>(rows: Vec<T>, key: impl Fn(&T) -> K) -> Vec<T> {
+    let mut positions = HashMap::with_capacity(rows.len());
+    let mut kept: Vec<T> = Vec::with_capacity(rows.len());
+    for row in rows {
+        match positions.entry(key(&row)) {
+            Entry::Occupied(entry) => kept[*entry.get()] = row,
+            Entry::Vacant(entry) => {
+                entry.insert(kept.len());
+                kept.push(row);
+            }
+        }
+    }
+    kept
+}
+
 pub async fn run_migrations(pool: &PgPool) -> Result<()> {
     sqlx::migrate!("../../migrations")
         .run(pool)
@@ -36,11 +134,52 @@ pub struct PgDatabase {
     collection_job_repo: Arc<PgCollectionJobRepository>,
 }
 
+/// Startup connection retry policy for [`PgDatabase::connect_with`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectRetry {
+    pub attempts: u32,
+    pub base_delay: Duration,
+    pub jitter_frac: f32,
+}
+
+impl Default for ConnectRetry {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            base_delay: Duration::from_millis(500),
+            jitter_frac: 0.5,
+        }
+    }
+}
+
+impl From<&common::config::DatabaseConfig> for ConnectRetry {
+    fn from(config: &common::config::DatabaseConfig) -> Self {
+        Self {
+            attempts: config.connect_attempts,
+            base_delay: Duration::from_millis(config.connect_base_delay_ms),
+            jitter_frac: config.connect_jitter_frac,
+        }
+    }
+}
+
+impl ConnectRetry {
+    /// Wait before retry number `attempt` (1-based): `base_delay * 2^(attempt - 1)`,
+    /// doubling at most five times, then moved by up to `jitter_frac` either way.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(5);
+        let nominal = self.base_delay.saturating_mul(1 << exp);
+        let jitter = self.jitter_frac.clamp(0.0, 1.0) as f64;
+        let scale = 1.0 + jitter * (fastrand::f64() * 2.0 - 1.0);
+        nominal.mul_f64(scale)
+    }
+}
+
 impl PgDatabase {
     pub async fn connect(database_url: &str) -> Result<Self> {
-        const MAX_ATTEMPTS: u32 = 5;
-        const BASE_DELAY_MS: u64 = 500;
+        Self::connect_with(database_url, ConnectRetry::default()).await
+    }
 
+    pub async fn connect_with(database_url: &str, retry: ConnectRetry) -> Result<Self> {
         let mut attempts = 0;
         loop {
             match PgPoolOptions::new()
@@ -54,12 +193,11 @@ impl PgDatabase {
                 }
                 Err(err) => {
                     attempts += 1;
-                    if attempts >= MAX_ATTEMPTS {
+                    if attempts >= retry.attempts {
                         return Err(DbError::Query(err));
                     }
 
-                    let exp = (attempts - 1).min(5);
-                    let backoff = Duration::from_millis(BASE_DELAY_MS * (1u64 << exp));
+                    let backoff = retry.delay(attempts);
                     warn!(
                         attempts,
                         error = %err,
@@ -137,57 +275,266 @@ struct PgRepoRepository {
 impl RepoRepository for PgRepoRepository {
     #[instrument(skip(self, repo), fields(full_name = %repo.full_name))]
     async fn upsert(&self, repo: RepositoryRow) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO repositories (id, full_name, is_fork, created_at, pushed_at, raw)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            ON CONFLICT (id) DO UPDATE
-                SET full_name = EXCLUDED.full_name,
-                    is_fork = EXCLUDED.is_fork,
-                    created_at = EXCLUDED.created_at,
-                    pushed_at = EXCLUDED.pushed_at,
-                    raw = EXCLUDED.raw
-            "#,
-        )
-        .bind(repo.id)
-        .bind(repo.full_name)
-        .bind(repo.is_fork)
-        .bind(repo.created_at)
-        .bind(repo.pushed_at)
-        .bind(repo.raw)
-        .execute(&self.pool)
-        .await
-        .map(|_| ())
-        .map_err(DbError::Query)
+        metrics::observe("repos.upsert", async move {
+            sqlx::query(
+                r#"
+                INSERT INTO repositories (id, full_name, is_fork, created_at, pushed_at, raw)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (id) DO UPDATE
+                    SET full_name = EXCLUDED.full_name,
+                        is_fork = EXCLUDED.is_fork,
+                        created_at = EXCLUDED.created_at,
+                        pushed_at = EXCLUDED.pushed_at,
+                        raw = EXCLUDED.raw
+                "#,
+            )
+            .bind(repo.id)
+            .bind(repo.full_name)
+            .bind(repo.is_fork)
+            .bind(repo.created_at)
+            .bind(repo.pushed_at)
+            .bind(repo.raw)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
     }
 
     async fn get_by_full_name(&self, full_name: &str) -> Result<Option<RepositoryRow>> {
-        sqlx::query_as::<_, RepositoryRow>(
-            r#"
-            SELECT id, full_name, is_fork, created_at, pushed_at, raw
-            FROM repositories
-            WHERE full_name = $1
-            "#,
-        )
-        .bind(full_name)
-        .fetch_optional(&self.pool)
+        metrics::observe("repos.get_by_full_name", async move {
+            sqlx::query_as::<_, RepositoryRow>(
+                r#"
+                SELECT id, full_name, is_fork, created_at, pushed_at, raw
+                FROM repositories
+                WHERE full_name = $1
+                "#,
+            )
+            .bind(full_name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn get_by_id(&self, id: i64) -> Result<Option<RepositoryRow>> {
+        metrics::observe("repos.get_by_id", async move {
+            sqlx::query_as::<_, RepositoryRow>(
+                r#"
+                SELECT id, full_name, is_fork, created_at, pushed_at, raw
+                FROM repositories
+                WHERE id = $1
+                "#,
+            )
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
         .await
-        .map_err(DbError::Query)
     }
 
     async fn list(&self, limit: i64) -> Result<Vec<RepositoryRow>> {
-        sqlx::query_as::<_, RepositoryRow>(
-            r#"
-            SELECT id, full_name, is_fork, created_at, pushed_at, raw
-            FROM repositories
-            ORDER BY full_name
-            LIMIT $1
-            "#,
-        )
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(DbError::Query)
+        metrics::observe("repos.list", async move {
+            sqlx::query_as::<_, RepositoryRow>(
+                r#"
+                SELECT id, full_name, is_fork, created_at, pushed_at, raw
+                FROM repositories
+                ORDER BY full_name
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+    async fn summary(&self, full_name: &str) -> Result<Option<RepoSummary>> {
+        metrics::observe("repos.summary", async move {
+            sqlx::query_as::<_, RepoSummary>(
+                r#"
+                WITH repo AS (
+                    SELECT id FROM repositories WHERE full_name = $1
+                ),
+                activity AS (
+                    SELECT 'issue' AS subject_type, i.id, i.user_id
+                    FROM issues i
+                    JOIN repo ON repo.id = i.repo_id
+                    UNION ALL
+                    SELECT 'comment' AS subject_type, c.id, c.user_id
+                    FROM comments c
+                    JOIN issues i ON i.id = c.issue_id
+                    JOIN repo ON repo.id = i.repo_id
+                ),
+                flagged AS (
+                    SELECT a.*, EXISTS (
+                        SELECT 1 FROM spam_flags sf
+                        WHERE sf.subject_type = a.subject_type
+                          AND sf.subject_id = a.id
+                          AND sf.reviewed_status <> 'false_positive'
+                    ) AS is_flagged
+                    FROM activity a
+                )
+                SELECT counts.*
+                FROM repo
+                CROSS JOIN LATERAL (
+                    SELECT
+                        COUNT(*) FILTER (WHERE subject_type = 'issue') AS issue_count,
+                        COUNT(*) FILTER (WHERE subject_type = 'comment') AS comment_count,
+                        COUNT(DISTINCT user_id) AS distinct_authors,
+                        COUNT(*) FILTER (WHERE subject_type = 'issue' AND is_flagged) AS flagged_issue_count,
+                        COUNT(*) FILTER (WHERE subject_type = 'comment' AND is_flagged) AS flagged_comment_count
+                    FROM flagged
+                ) counts
+                "#,
+            )
+            .bind(full_name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_repo(&self, full_name: &str, delete_job: bool) -> Result<RepoDeletion> {
+        metrics::observe("repos.delete_repo", async move {
+            let mut tx = self.pool.begin().await.map_err(DbError::Query)?;
+            // Same lock order as `refresh_spammy_user_totals`, whose aggregate
+            // is corrected below for the authors losing flags.
+            sqlx::query("LOCK TABLE spam_flags IN SHARE ROW EXCLUSIVE MODE")
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::Query)?;
+            sqlx::query("SELECT 1 FROM spammy_user_totals_state FOR UPDATE")
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::Query)?;
+            sqlx::query(
+                r#"
+                CREATE TEMP TABLE purged_subjects ON COMMIT DROP AS
+                WITH repo_issues AS (
+                    SELECT i.id, i.user_id
+                    FROM issues i
+                    JOIN repositories r ON r.id = i.repo_id
+                    WHERE r.full_name = $1
+                )
+                SELECT 'issue'::text AS subject_type, id AS subject_id, user_id
+                FROM repo_issues
+                UNION ALL
+                SELECT 'comment'::text, c.id, c.user_id
+                FROM comments c
+                JOIN repo_issues ri ON ri.id = c.issue_id
+                "#,
+            )
+            .bind(full_name)
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::Query)?;
+            sqlx::query(
+                r#"
+                CREATE TEMP TABLE changed_spammy_users ON COMMIT DROP AS
+                SELECT DISTINCT u.login
+                FROM purged_subjects p
+                JOIN spam_flags sf
+                    ON sf.subject_type = p.subject_type AND sf.subject_id = p.subject_id
+                JOIN users u ON u.id = p.user_id
+                "#,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::Query)?;
+
+            let mut deletion = RepoDeletion::default();
+            // Subject tables carry no foreign keys, so they go first while
+            // the issues and comments they point at can still be resolved.
+            for table in [
+                "spam_flags",
+                "spam_flag_history",
+                "feature_samples",
+                "content_edits",
+                "reactions",
+            ] {
+                let deleted = sqlx::query(&format!(
+                    r#"
+                    DELETE FROM {table}
+                    WHERE (subject_type, subject_id) IN (
+                        SELECT subject_type, subject_id FROM purged_subjects
+                    )
+                    "#
+                ))
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::Query)?
+                .rows_affected();
+                if table == "spam_flags" {
+                    deletion.spam_flags = deleted;
+                }
+            }
+            deletion.comments = sqlx::query(
+                r#"
+                DELETE FROM comments
+                WHERE id IN (
+                    SELECT subject_id FROM purged_subjects WHERE subject_type = 'comment'
+                )
+                "#,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::Query)?
+            .rows_affected();
+            deletion.issues = sqlx::query(
+                r#"
+                DELETE FROM issues
+                WHERE id IN (
+                    SELECT subject_id FROM purged_subjects WHERE subject_type = 'issue'
+                )
+                "#,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::Query)?
+            .rows_affected();
+            deletion.repository = sqlx::query("DELETE FROM repositories WHERE full_name = $1")
+                .bind(full_name)
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::Query)?
+                .rows_affected()
+                > 0;
+            rebuild_spammy_user_totals(&mut tx).await?;
+
+            deletion.watermark =
+                sqlx::query("DELETE FROM collector_watermarks WHERE repo_full_name = $1")
+                    .bind(full_name)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(DbError::Query)?
+                    .rows_affected()
+                    > 0;
+            sqlx::query("DELETE FROM collector_progress WHERE repo_full_name = $1")
+                .bind(full_name)
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::Query)?;
+            if delete_job {
+                deletion.collection_job =
+                    sqlx::query("DELETE FROM collection_jobs WHERE full_name = $1")
+                        .bind(full_name)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(DbError::Query)?
+                        .rows_affected()
+                        > 0;
+            }
+            tx.commit().await.map_err(DbError::Query)?;
+            Ok(deletion)
+        })
+        .await
     }
 }
 
@@ -199,64 +546,232 @@ struct PgUserRepository {
 #[async_trait]
 impl UserRepository for PgUserRepository {
     async fn upsert(&self, user: UserRow) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO users (id, login, type, site_admin, created_at, followers, following, public_repos, raw, found)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-            ON CONFLICT (id) DO UPDATE
-                SET login = EXCLUDED.login,
-                    type = EXCLUDED.type,
-                    site_admin = EXCLUDED.site_admin,
-                    created_at = EXCLUDED.created_at,
-                    followers = EXCLUDED.followers,
-                    following = EXCLUDED.following,
-                    public_repos = EXCLUDED.public_repos,
-                    raw = EXCLUDED.raw,
-                    found = EXCLUDED.found
-            "#
-        )
-        .bind(user.id)
-        .bind(user.login)
-        .bind(user.user_type)
-        .bind(user.site_admin)
-        .bind(user.created_at)
-        .bind(user.followers)
-        .bind(user.following)
-        .bind(user.public_repos)
-        .bind(user.raw)
-        .bind(user.found)
-        .execute(&self.pool)
-        .await
-        .map(|_| ())
-        .map_err(DbError::Query)
+        metrics::observe("users.upsert", async move {
+            sqlx::query(
+                r#"
+                INSERT INTO users (id, login, type, site_admin, created_at, followers, following, public_repos, bio, blog, raw, found)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                ON CONFLICT (id) DO UPDATE
+                    SET login = EXCLUDED.login,
+                        type = EXCLUDED.type,
+                        site_admin = EXCLUDED.site_admin,
+                        created_at = EXCLUDED.created_at,
+                        followers = EXCLUDED.followers,
+                        following = EXCLUDED.following,
+                        public_repos = EXCLUDED.public_repos,
+                        bio = EXCLUDED.bio,
+                        blog = EXCLUDED.blog,
+                        raw = EXCLUDED.raw,
+                        found = EXCLUDED.found
+                "#
+            )
+            .bind(user.id)
+            .bind(user.login)
+            .bind(user.user_type)
+            .bind(user.site_admin)
+            .bind(user.created_at)
+            .bind(user.followers)
+            .bind(user.following)
+            .bind(user.public_repos)
+            .bind(user.bio)
+            .bind(user.blog)
+            .bind(user.raw)
+            .bind(user.found)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
     }
 
     async fn get_by_id(&self, id: i64) -> Result<Option<UserRow>> {
-        sqlx::query_as::<_, UserRow>(
-            r#"
-            SELECT id, login, type as "user_type", site_admin, created_at, followers, following, public_repos, raw, found
-            FROM users
-            WHERE id = $1
-            "#
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
+        metrics::observe("users.get_by_id", async move {
+            sqlx::query_as::<_, UserRow>(
+                r#"
+                SELECT id, login, type as "user_type", site_admin, created_at, followers, following, public_repos, bio, blog, raw, found
+                FROM users
+                WHERE id = $1
+                "#
+            )
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
         .await
-        .map_err(DbError::Query)
     }
 
     async fn get_by_login(&self, login: &str) -> Result<Option<UserRow>> {
-        sqlx::query_as::<_, UserRow>(
-            r#"
-            SELECT id, login, type as "user_type", site_admin, created_at, followers, following, public_repos, raw, found
-            FROM users
-            WHERE login = $1
-            "#
-        )
-        .bind(login)
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(DbError::Query)
+        metrics::observe("users.get_by_login", async move {
+            sqlx::query_as::<_, UserRow>(
+                r#"
+                SELECT id, login, type as "user_type", site_admin, created_at, followers, following, public_repos, bio, blog, raw, found
+                FROM users
+                WHERE login = $1
+                "#
+            )
+            .bind(login)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn activity_summary(&self, login: &str) -> Result<UserActivitySummary> {
+        metrics::observe("users.activity_summary", async move {
+            sqlx::query_as::<_, UserActivitySummary>(
+                r#"
+                WITH activity AS (
+                    SELECT 'issue' AS subject_type, i.id, i.created_at
+                    FROM issues i
+                    JOIN users u ON u.id = i.user_id
+                    WHERE u.login = $1
+                    UNION ALL
+                    SELECT 'comment' AS subject_type, c.id, c.created_at
+                    FROM comments c
+                    JOIN users u ON u.id = c.user_id
+                    WHERE u.login = $1
+                ),
+                flagged AS (
+                    SELECT a.*, EXISTS (
+                        SELECT 1 FROM spam_flags sf
+                        WHERE sf.subject_type = a.subject_type
+                          AND sf.subject_id = a.id
+                          AND sf.reviewed_status <> 'false_positive'
+                    ) AS is_flagged
+                    FROM activity a
+                )
+                SELECT
+                    COUNT(*) FILTER (WHERE subject_type = 'issue') AS issue_count,
+                    COUNT(*) FILTER (WHERE subject_type = 'comment') AS comment_count,
+                    COUNT(*) FILTER (WHERE subject_type = 'issue' AND is_flagged) AS flagged_issue_count,
+                    COUNT(*) FILTER (WHERE subject_type = 'comment' AND is_flagged) AS flagged_comment_count,
+                    MIN(created_at) AS first_activity_at,
+                    MAX(created_at) AS last_activity_at
+                FROM flagged
+                "#,
+            )
+            .bind(login)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn spam_summary(&self, login: &str) -> Result<UserSpamSummary> {
+        metrics::observe("users.spam_summary", async move {
+            sqlx::query_as::<_, UserSpamSummary>(
+                r#"
+                WITH authored AS (
+                    SELECT 'issue' AS subject_type, i.id
+                    FROM issues i
+                    JOIN users u ON u.id = i.user_id
+                    WHERE u.login = $1
+                    UNION ALL
+                    SELECT 'comment' AS subject_type, c.id
+                    FROM comments c
+                    JOIN users u ON u.id = c.user_id
+                    WHERE u.login = $1
+                ),
+                flags AS (
+                    SELECT sf.score, sf.reasons, sf.created_at
+                    FROM spam_flags sf
+                    JOIN authored a ON sf.subject_type = a.subject_type AND sf.subject_id = a.id
+                    WHERE sf.reviewed_status <> 'false_positive'
+                )
+                SELECT
+                    COUNT(*) AS flag_count,
+                    COALESCE(SUM(score), 0)::real AS total_score,
+                    COALESCE(AVG(score), 0)::real AS avg_score,
+                    COALESCE(
+                        (SELECT ARRAY_AGG(DISTINCT reason ORDER BY reason)
+                         FROM flags, unnest(flags.reasons) AS reason),
+                        '{}'
+                    ) AS reasons,
+                    MIN(created_at) AS first_flagged_at,
+                    MAX(created_at) AS last_flagged_at
+                FROM flags
+                "#,
+            )
+            .bind(login)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn org_membership(&self, org: &str, user_id: i64) -> Result<Option<OrgMembershipRow>> {
+        metrics::observe("users.org_membership", async move {
+            sqlx::query_as::<_, OrgMembershipRow>(
+                r#"
+                SELECT org, user_id, is_member, checked_at
+                FROM org_memberships
+                WHERE org = $1 AND user_id = $2
+                "#,
+            )
+            .bind(org)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn set_org_membership(&self, org: &str, user_id: i64, is_member: bool) -> Result<()> {
+        metrics::observe("users.set_org_membership", async move {
+            sqlx::query(
+                r#"
+                INSERT INTO org_memberships (org, user_id, is_member, checked_at)
+                VALUES ($1, $2, $3, NOW())
+                ON CONFLICT (org, user_id) DO UPDATE
+                    SET is_member = EXCLUDED.is_member,
+                        checked_at = EXCLUDED.checked_at
+                "#,
+            )
+            .bind(org)
+            .bind(user_id)
+            .bind(is_member)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn list_orphaned_authors(&self, limit: i64) -> Result<Vec<OrphanedAuthor>> {
+        metrics::observe("users.list_orphaned_authors", async move {
+            sqlx::query_as::<_, OrphanedAuthor>(
+                r#"
+                SELECT subject_type, subject_id, user_id, login
+                FROM (
+                    SELECT 'issue' AS subject_type, i.id AS subject_id, i.user_id,
+                           i.raw->'user'->>'login' AS login, i.created_at
+                    FROM issues i
+                    LEFT JOIN users u ON u.id = i.user_id
+                    WHERE i.user_id IS NOT NULL AND u.id IS NULL
+                    UNION ALL
+                    SELECT 'comment', c.id, c.user_id,
+                           c.raw->'user'->>'login', c.created_at
+                    FROM comments c
+                    LEFT JOIN users u ON u.id = c.user_id
+                    WHERE c.user_id IS NOT NULL AND u.id IS NULL
+                ) orphans
+                ORDER BY created_at, subject_type, subject_id
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
     }
 }
 
@@ -268,104 +783,132 @@ struct PgIssueRepository {
 #[async_trait]
 impl IssueRepository for PgIssueRepository {
     async fn upsert(&self, issue: IssueRow) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO issues (
-                id, repo_id, number, is_pull_request, state, title, body, user_id,
-                comments_count, created_at, updated_at, closed_at, dedupe_hash, raw, found
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
-            ON CONFLICT (id) DO UPDATE
-                SET repo_id = EXCLUDED.repo_id,
-                    number = EXCLUDED.number,
-                    is_pull_request = EXCLUDED.is_pull_request,
-                    state = EXCLUDED.state,
-                    title = EXCLUDED.title,
-                    body = EXCLUDED.body,
-                    user_id = EXCLUDED.user_id,
-                    comments_count = EXCLUDED.comments_count,
-                    created_at = EXCLUDED.created_at,
-                    updated_at = EXCLUDED.updated_at,
-                    closed_at = EXCLUDED.closed_at,
-                    dedupe_hash = EXCLUDED.dedupe_hash,
-                    raw = EXCLUDED.raw,
-                    found = EXCLUDED.found
-            "#,
-        )
-        .bind(issue.id)
-        .bind(issue.repo_id)
-        .bind(issue.number)
-        .bind(issue.is_pull_request)
-        .bind(issue.state)
-        .bind(issue.title)
-        .bind(issue.body)
-        .bind(issue.user_id)
-        .bind(issue.comments_count)
-        .bind(issue.created_at)
-        .bind(issue.updated_at)
-        .bind(issue.closed_at)
-        .bind(issue.dedupe_hash)
-        .bind(issue.raw)
-        .bind(issue.found)
-        .execute(&self.pool)
-        .await
-        .map(|_| ())
-        .map_err(DbError::Query)
+        metrics::observe("issues.upsert", async move {
+            sqlx::query(
+                r#"
+                INSERT INTO issues (
+                    id, repo_id, number, is_pull_request, state, title, body, user_id,
+                    comments_count, created_at, updated_at, closed_at, state_reason, dedupe_hash, raw, found,
+                    normalized_title, author_association, locked
+                )
+                VALUES (
+                    $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                    $19
+                )
+                ON CONFLICT (id) DO UPDATE
+                    SET repo_id = EXCLUDED.repo_id,
+                        number = EXCLUDED.number,
+                        is_pull_request = EXCLUDED.is_pull_request,
+                        state = EXCLUDED.state,
+                        title = EXCLUDED.title,
+                        body = EXCLUDED.body,
+                        user_id = EXCLUDED.user_id,
+                        comments_count = EXCLUDED.comments_count,
+                        created_at = EXCLUDED.created_at,
+                        updated_at = EXCLUDED.updated_at,
+                        closed_at = EXCLUDED.closed_at,
+                        state_reason = EXCLUDED.state_reason,
+                        dedupe_hash = EXCLUDED.dedupe_hash,
+                        raw = EXCLUDED.raw,
+                        found = EXCLUDED.found,
+                        normalized_title = EXCLUDED.normalized_title,
+                        author_association = EXCLUDED.author_association,
+                        locked = EXCLUDED.locked
+                "#,
+            )
+            .bind(issue.id)
+            .bind(issue.repo_id)
+            .bind(issue.number)
+            .bind(issue.is_pull_request)
+            .bind(issue.state)
+            .bind(&issue.title)
+            .bind(issue.body)
+            .bind(issue.user_id)
+            .bind(issue.comments_count)
+            .bind(issue.created_at)
+            .bind(issue.updated_at)
+            .bind(issue.closed_at)
+            .bind(issue.state_reason)
+            .bind(issue.dedupe_hash)
+            .bind(issue.raw)
+            .bind(issue.found)
+            .bind(normalize_title(&issue.title))
+            .bind(issue.author_association)
+            .bind(issue.locked)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
     }
 
     async fn query(&self, query: IssueQuery) -> Result<Vec<IssueRow>> {
-        let mut builder = QueryBuilder::<Postgres>::new(
-            r#"
-            SELECT id, repo_id, number, is_pull_request, state, title, body, user_id,
-                   comments_count, created_at, updated_at, closed_at, dedupe_hash, raw, found
-            FROM issues
-            "#,
-        );
-
-        let mut has_where = false;
-
-        if let Some(repo) = &query.repo_full_name {
-            builder.push(" WHERE repo_id = (SELECT id FROM repositories WHERE full_name = ");
-            builder.push_bind(repo);
-            builder.push(") ");
-            has_where = true;
-        }
+        metrics::observe("issues.query", async move {
+            let mut builder = QueryBuilder::<Postgres>::new(
+                r#"
+                SELECT id, repo_id, number, is_pull_request, state, title, body, user_id,
+                       comments_count, created_at, updated_at, closed_at, state_reason, locked,
+                       author_association, dedupe_hash, raw, found
+                FROM issues
+                "#,
+            );
 
-        if let Some(since) = query.since {
-            builder.push(if has_where { " AND" } else { " WHERE" });
-            builder.push(" updated_at >= ");
-            builder.push_bind(since);
-            builder.push(" ");
-            has_where = true;
-        }
+            let mut has_where = false;
 
-        if let Some(filter) = query.spam {
-            match filter {
-                crate::models::SpamFilter::Likely => {
-                    builder.push(if has_where { " AND" } else { " WHERE" });
-                    builder.push(
-                        " EXISTS (SELECT 1 FROM spam_flags WHERE subject_type = 'issue' AND subject_id = issues.id AND score >= 2.5)",
-                    );
+            if let Some(repo) = &query.repo_full_name {
+                builder.push(" WHERE repo_id = (SELECT id FROM repositories WHERE full_name = ");
+                builder.push_bind(repo);
+                builder.push(") ");
+                has_where = true;
+            }
+
+            if let Some(since) = query.since {
+                builder.push(if has_where { " AND" } else { " WHERE" });
+                builder.push(" updated_at >= ");
+                builder.push_bind(since);
+                builder.push(" ");
+                has_where = true;
+            }
+
+            // Score threshold and review state must hold for the same flag, so they share one EXISTS
+            let min_score = match query.spam {
+                Some(crate::models::SpamFilter::Likely) => Some(" AND score >= 2.5"),
+                Some(crate::models::SpamFilter::Suspicious) => Some(" AND score >= 1.0"),
+                Some(crate::models::SpamFilter::All) | None => None,
+            };
+            if min_score.is_some() || query.reviewed.is_some() {
+                builder.push(if has_where { " AND" } else { " WHERE" });
+                builder.push(
+                    " EXISTS (SELECT 1 FROM spam_flags WHERE subject_type = 'issue' AND subject_id = issues.id",
+                );
+                if let Some(clause) = min_score {
+                    builder.push(clause);
                 }
-                crate::models::SpamFilter::Suspicious => {
-                    builder.push(if has_where { " AND" } else { " WHERE" });
-                    builder.push(
-                        " EXISTS (SELECT 1 FROM spam_flags WHERE subject_type = 'issue' AND subject_id = issues.id AND score >= 1.0)",
-                    );
+                if let Some(status) = query.reviewed {
+                    builder.push(" AND reviewed_status = ");
+                    builder.push_bind(status);
                 }
-                crate::models::SpamFilter::All => {}
+                builder.push(") ");
             }
-        }
 
-        builder.push(" ORDER BY updated_at DESC ");
+            builder.push(match query.order_by.unwrap_or_default() {
+                IssueOrder::UpdatedDesc => " ORDER BY updated_at DESC ",
+                IssueOrder::CreatedDesc => " ORDER BY created_at DESC ",
+                IssueOrder::ScoreDesc => {
+                    " ORDER BY (SELECT MAX(score) FROM spam_flags WHERE subject_type = 'issue' AND subject_id = issues.id) DESC NULLS LAST, updated_at DESC "
+                }
+            });
 
-        if let Some(limit) = query.limit {
-            builder.push(" LIMIT ");
-            builder.push_bind(limit);
-        }
+            if let Some(limit) = query.limit {
+                builder.push(" LIMIT ");
+                builder.push_bind(limit);
+            }
 
-        let query = builder.build_query_as::<IssueRow>();
-        query.fetch_all(&self.pool).await.map_err(DbError::Query)
+            let query = builder.build_query_as::<IssueRow>();
+            query.fetch_all(&self.pool).await.map_err(DbError::Query)
+        })
+        .await
     }
 
     async fn list_by_repo(
@@ -373,38 +916,203 @@ impl IssueRepository for PgIssueRepository {
         repo_id: i64,
         since: Option<DateTime<Utc>>,
     ) -> Result<Vec<IssueRow>> {
-        if let Some(since) = since {
+        metrics::observe("issues.list_by_repo", async move {
+            if let Some(since) = since {
+                sqlx::query_as::<_, IssueRow>(
+                    r#"
+                    SELECT id, repo_id, number, is_pull_request, state, title, body,
+                           user_id, comments_count, created_at, updated_at, closed_at, state_reason, locked,
+                           author_association, dedupe_hash, raw, found
+                    FROM issues
+                    WHERE repo_id = $1 AND updated_at >= $2
+                    ORDER BY updated_at DESC
+                    "#,
+                )
+                .bind(repo_id)
+                .bind(since)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(DbError::Query)
+            } else {
+                sqlx::query_as::<_, IssueRow>(
+                    r#"
+                    SELECT id, repo_id, number, is_pull_request, state, title, body,
+                           user_id, comments_count, created_at, updated_at, closed_at, state_reason, locked,
+                           author_association, dedupe_hash, raw, found
+                    FROM issues
+                    WHERE repo_id = $1
+                    ORDER BY updated_at DESC
+                    "#,
+                )
+                .bind(repo_id)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(DbError::Query)
+            }
+        })
+        .await
+    }
+
+    async fn list_missing(&self, repo_id: i64, limit: i64) -> Result<Vec<IssueRow>> {
+        metrics::observe("issues.list_missing", async move {
             sqlx::query_as::<_, IssueRow>(
                 r#"
                 SELECT id, repo_id, number, is_pull_request, state, title, body,
-                       user_id, comments_count, created_at, updated_at, closed_at,
-                       dedupe_hash, raw, found
+                       user_id, comments_count, created_at, updated_at, closed_at, state_reason, locked,
+                       author_association, dedupe_hash, raw, found
                 FROM issues
-                WHERE repo_id = $1 AND updated_at >= $2
+                WHERE repo_id = $1 AND NOT found
                 ORDER BY updated_at DESC
+                LIMIT $2
                 "#,
             )
             .bind(repo_id)
-            .bind(since)
+            .bind(limit)
             .fetch_all(&self.pool)
             .await
             .map_err(DbError::Query)
-        } else {
-            sqlx::query_as::<_, IssueRow>(
+        })
+        .await
+    }
+
+    async fn fingerprint(&self, id: i64) -> Result<Option<IssueFingerprint>> {
+        metrics::observe("issues.fingerprint", async move {
+            sqlx::query_as::<_, IssueFingerprint>(
                 r#"
-                SELECT id, repo_id, number, is_pull_request, state, title, body,
-                       user_id, comments_count, created_at, updated_at, closed_at,
-                       dedupe_hash, raw, found
+                SELECT dedupe_hash, state, comments_count, closed_at, locked, updated_at, found
+                FROM issues
+                WHERE id = $1
+                "#,
+            )
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn count_same_title(&self, issue: &IssueRow, window: chrono::Duration) -> Result<i64> {
+        let normalized = normalize_title(&issue.title);
+        if normalized.is_empty() {
+            return Ok(0);
+        }
+        let repo_id = issue.repo_id;
+        let id = issue.id;
+        let from = issue.created_at - window;
+        let to = issue.created_at + window;
+        metrics::observe("issues.count_same_title", async move {
+            sqlx::query_scalar::<_, i64>(
+                r#"
+                SELECT COUNT(*)
                 FROM issues
                 WHERE repo_id = $1
-                ORDER BY updated_at DESC
+                  AND normalized_title = $2
+                  AND id <> $3
+                  AND created_at BETWEEN $4 AND $5
                 "#,
             )
             .bind(repo_id)
+            .bind(normalized)
+            .bind(id)
+            .bind(from)
+            .bind(to)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn raw(&self, id: i64) -> Result<Option<serde_json::Value>> {
+        metrics::observe("issues.raw", async move {
+            sqlx::query_scalar::<_, serde_json::Value>("SELECT raw FROM issues WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn count_closed_as(&self, user_id: i64, state_reason: &str) -> Result<i64> {
+        metrics::observe("issues.count_closed_as", async move {
+            sqlx::query_scalar::<_, i64>(
+                r#"
+                SELECT COUNT(*)
+                FROM issues
+                WHERE user_id = $1 AND state = 'closed' AND state_reason = $2
+                "#,
+            )
+            .bind(user_id)
+            .bind(state_reason)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn record_transition(&self, transition: IssueStateTransition) -> Result<()> {
+        metrics::observe("issues.record_transition", async move {
+            sqlx::query(
+                r#"
+                INSERT INTO issue_state_transitions (
+                    issue_id, user_id, from_state, to_state, from_locked, to_locked,
+                    rapid_maintainer_closure
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(transition.issue_id)
+            .bind(transition.user_id)
+            .bind(transition.from_state)
+            .bind(transition.to_state)
+            .bind(transition.from_locked)
+            .bind(transition.to_locked)
+            .bind(transition.rapid_maintainer_closure)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn list_transitions(&self, issue_id: i64) -> Result<Vec<IssueStateTransitionRow>> {
+        metrics::observe("issues.list_transitions", async move {
+            sqlx::query_as::<_, IssueStateTransitionRow>(
+                r#"
+                SELECT id, issue_id, user_id, from_state, to_state, from_locked, to_locked,
+                       rapid_maintainer_closure, observed_at
+                FROM issue_state_transitions
+                WHERE issue_id = $1
+                ORDER BY id
+                "#,
+            )
+            .bind(issue_id)
             .fetch_all(&self.pool)
             .await
             .map_err(DbError::Query)
-        }
+        })
+        .await
+    }
+
+    async fn count_rapid_closures(&self, user_id: i64) -> Result<i64> {
+        metrics::observe("issues.count_rapid_closures", async move {
+            sqlx::query_scalar::<_, i64>(
+                r#"
+                SELECT COUNT(DISTINCT issue_id)
+                FROM issue_state_transitions
+                WHERE user_id = $1 AND rapid_maintainer_closure
+                "#,
+            )
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
     }
 }
 
@@ -416,55 +1124,168 @@ struct PgCommentRepository {
 #[async_trait]
 impl CommentRepository for PgCommentRepository {
     async fn upsert(&self, comment: CommentRow) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO comments (
-                id, issue_id, user_id, body, created_at, updated_at, dedupe_hash, raw, found
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            ON CONFLICT (id) DO UPDATE
-                SET issue_id = EXCLUDED.issue_id,
-                    user_id = EXCLUDED.user_id,
-                    body = EXCLUDED.body,
-                    created_at = EXCLUDED.created_at,
-                    updated_at = EXCLUDED.updated_at,
-                    dedupe_hash = EXCLUDED.dedupe_hash,
-                    raw = EXCLUDED.raw,
-                    found = EXCLUDED.found
-            "#,
-        )
-        .bind(comment.id)
-        .bind(comment.issue_id)
-        .bind(comment.user_id)
-        .bind(comment.body)
-        .bind(comment.created_at)
-        .bind(comment.updated_at)
-        .bind(comment.dedupe_hash)
-        .bind(comment.raw)
-        .bind(comment.found)
-        .execute(&self.pool)
-        .await
-        .map(|_| ())
-        .map_err(DbError::Query)
-    }
-
-    async fn list_by_issue(&self, issue_id: i64) -> Result<Vec<CommentRow>> {
-        sqlx::query_as::<_, CommentRow>(
-            r#"
-            SELECT id, issue_id, user_id, body, created_at, updated_at, dedupe_hash, raw
-                   , found
-            FROM comments
-            WHERE issue_id = $1
-            ORDER BY created_at
-            "#,
-        )
-        .bind(issue_id)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(DbError::Query)
-    }
-}
-
+        metrics::observe("comments.upsert", async move {
+            sqlx::query(
+                r#"
+                INSERT INTO comments (
+                    id, issue_id, user_id, body, created_at, updated_at, dedupe_hash, raw, found,
+                    author_association
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                ON CONFLICT (id) DO UPDATE
+                    SET issue_id = EXCLUDED.issue_id,
+                        user_id = EXCLUDED.user_id,
+                        body = EXCLUDED.body,
+                        created_at = EXCLUDED.created_at,
+                        updated_at = EXCLUDED.updated_at,
+                        dedupe_hash = EXCLUDED.dedupe_hash,
+                        raw = EXCLUDED.raw,
+                        found = EXCLUDED.found,
+                        author_association = EXCLUDED.author_association
+                "#,
+            )
+            .bind(comment.id)
+            .bind(comment.issue_id)
+            .bind(comment.user_id)
+            .bind(comment.body)
+            .bind(comment.created_at)
+            .bind(comment.updated_at)
+            .bind(comment.dedupe_hash)
+            .bind(comment.raw)
+            .bind(comment.found)
+            .bind(comment.author_association)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn upsert_many(&self, comments: Vec<CommentRow>) -> Result<()> {
+        let comments = last_per_key(comments, |comment| comment.id);
+        if comments.is_empty() {
+            return Ok(());
+        }
+        metrics::observe("comments.upsert_many", async move {
+            let mut ids = Vec::with_capacity(comments.len());
+            let mut issue_ids = Vec::with_capacity(comments.len());
+            let mut user_ids = Vec::with_capacity(comments.len());
+            let mut bodies = Vec::with_capacity(comments.len());
+            let mut created = Vec::with_capacity(comments.len());
+            let mut updated = Vec::with_capacity(comments.len());
+            let mut hashes = Vec::with_capacity(comments.len());
+            let mut raws = Vec::with_capacity(comments.len());
+            let mut found = Vec::with_capacity(comments.len());
+            let mut associations = Vec::with_capacity(comments.len());
+            for comment in comments {
+                ids.push(comment.id);
+                issue_ids.push(comment.issue_id);
+                user_ids.push(comment.user_id);
+                bodies.push(comment.body);
+                created.push(comment.created_at);
+                updated.push(comment.updated_at);
+                hashes.push(comment.dedupe_hash);
+                raws.push(comment.raw);
+                found.push(comment.found);
+                associations.push(comment.author_association);
+            }
+            sqlx::query(
+                r#"
+                INSERT INTO comments (
+                    id, issue_id, user_id, body, created_at, updated_at, dedupe_hash, raw, found,
+                    author_association
+                )
+                SELECT c.id, c.issue_id, c.user_id, c.body, c.created_at, c.updated_at,
+                       c.dedupe_hash, c.raw, c.found, c.author_association
+                FROM UNNEST(
+                    $1::int8[], $2::int8[], $3::int8[], $4::text[], $5::timestamptz[],
+                    $6::timestamptz[], $7::text[], $8::jsonb[], $9::bool[], $10::text[]
+                ) WITH ORDINALITY AS c(
+                    id, issue_id, user_id, body, created_at, updated_at, dedupe_hash, raw, found,
+                    author_association, position
+                )
+                ORDER BY c.position
+                ON CONFLICT (id) DO UPDATE
+                    SET issue_id = EXCLUDED.issue_id,
+                        user_id = EXCLUDED.user_id,
+                        body = EXCLUDED.body,
+                        created_at = EXCLUDED.created_at,
+                        updated_at = EXCLUDED.updated_at,
+                        dedupe_hash = EXCLUDED.dedupe_hash,
+                        raw = EXCLUDED.raw,
+                        found = EXCLUDED.found,
+                        author_association = EXCLUDED.author_association
+                "#,
+            )
+            .bind(ids)
+            .bind(issue_ids)
+            .bind(user_ids)
+            .bind(bodies)
+            .bind(created)
+            .bind(updated)
+            .bind(hashes)
+            .bind(raws)
+            .bind(found)
+            .bind(associations)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn list_by_issue(&self, issue_id: i64) -> Result<Vec<CommentRow>> {
+        metrics::observe("comments.list_by_issue", async move {
+            sqlx::query_as::<_, CommentRow>(
+                r#"
+                SELECT id, issue_id, user_id, body, created_at, updated_at, author_association,
+                       dedupe_hash, raw, found
+                FROM comments
+                WHERE issue_id = $1
+                ORDER BY created_at
+                "#,
+            )
+            .bind(issue_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn raw(&self, id: i64) -> Result<Option<serde_json::Value>> {
+        metrics::observe("comments.raw", async move {
+            sqlx::query_scalar::<_, serde_json::Value>("SELECT raw FROM comments WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn count_by_repo(&self, repo_full_name: &str) -> Result<i64> {
+        metrics::observe("comments.count_by_repo", async move {
+            sqlx::query_scalar::<_, i64>(
+                r#"
+                SELECT COUNT(*)
+                FROM comments c
+                JOIN issues i ON i.id = c.issue_id
+                JOIN repositories r ON r.id = i.repo_id
+                WHERE r.full_name = $1
+                "#,
+            )
+            .bind(repo_full_name)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+}
+
 #[derive(Clone)]
 struct PgWatermarkRepository {
     pool: PgPool,
@@ -473,34 +1294,141 @@ struct PgWatermarkRepository {
 #[async_trait]
 impl WatermarkRepository for PgWatermarkRepository {
     async fn get(&self, repo_full_name: &str) -> Result<Option<CollectorWatermarkRow>> {
-        sqlx::query_as::<_, CollectorWatermarkRow>(
-            r#"
-            SELECT repo_full_name, last_updated
-            FROM collector_watermarks
-            WHERE repo_full_name = $1
-            "#,
-        )
-        .bind(repo_full_name)
-        .fetch_optional(&self.pool)
+        metrics::observe("watermarks.get", async move {
+            sqlx::query_as::<_, CollectorWatermarkRow>(
+                r#"
+                SELECT repo_full_name, last_updated
+                FROM collector_watermarks
+                WHERE repo_full_name = $1
+                "#,
+            )
+            .bind(repo_full_name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
         .await
-        .map_err(DbError::Query)
     }
 
     async fn set(&self, watermark: WatermarkUpdate) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO collector_watermarks (repo_full_name, last_updated)
-            VALUES ($1, $2)
-            ON CONFLICT (repo_full_name) DO UPDATE
-                SET last_updated = EXCLUDED.last_updated
-            "#,
-        )
-        .bind(watermark.repo_full_name)
-        .bind(watermark.last_updated)
-        .execute(&self.pool)
-        .await
-        .map(|_| ())
-        .map_err(DbError::Query)
+        metrics::observe("watermarks.set", async move {
+            sqlx::query(
+                r#"
+                INSERT INTO collector_watermarks (repo_full_name, last_updated)
+                VALUES ($1, $2)
+                ON CONFLICT (repo_full_name) DO UPDATE
+                    SET last_updated = EXCLUDED.last_updated
+                "#,
+            )
+            .bind(watermark.repo_full_name)
+            .bind(watermark.last_updated)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn get_progress(&self, repo_full_name: &str) -> Result<Option<CollectorProgressRow>> {
+        metrics::observe("watermarks.get_progress", async move {
+            sqlx::query_as::<_, CollectorProgressRow>(
+                r#"
+                SELECT repo_full_name, cursor, newest_ts, updated_at
+                FROM collector_progress
+                WHERE repo_full_name = $1
+                "#,
+            )
+            .bind(repo_full_name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn set_progress(&self, progress: ProgressUpdate) -> Result<()> {
+        metrics::observe("watermarks.set_progress", async move {
+            sqlx::query(
+                r#"
+                INSERT INTO collector_progress (repo_full_name, cursor, newest_ts)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (repo_full_name) DO UPDATE
+                    SET cursor = EXCLUDED.cursor,
+                        newest_ts = EXCLUDED.newest_ts,
+                        updated_at = now()
+                "#,
+            )
+            .bind(progress.repo_full_name)
+            .bind(progress.cursor)
+            .bind(progress.newest_ts)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn clear_progress(&self, repo_full_name: &str) -> Result<()> {
+        metrics::observe("watermarks.clear_progress", async move {
+            sqlx::query(
+                r#"
+                DELETE FROM collector_progress
+                WHERE repo_full_name = $1
+                "#,
+            )
+            .bind(repo_full_name)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn delete(&self, repo_full_name: &str) -> Result<bool> {
+        metrics::observe("watermarks.delete", async move {
+            sqlx::query(
+                r#"
+                DELETE FROM collector_watermarks
+                WHERE repo_full_name = $1
+                "#,
+            )
+            .bind(repo_full_name)
+            .execute(&self.pool)
+            .await
+            .map(|result| result.rows_affected() > 0)
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<()> {
+        metrics::observe("watermarks.rename", async move {
+            sqlx::query(
+                r#"
+                WITH moved_watermark AS (
+                    UPDATE collector_watermarks
+                    SET repo_full_name = $2
+                    WHERE repo_full_name = $1
+                      AND NOT EXISTS (SELECT 1 FROM collector_watermarks WHERE repo_full_name = $2)
+                    RETURNING repo_full_name
+                )
+                UPDATE collector_progress
+                SET repo_full_name = $2
+                WHERE repo_full_name = $1
+                  AND NOT EXISTS (SELECT 1 FROM collector_progress WHERE repo_full_name = $2)
+                "#,
+            )
+            .bind(from)
+            .bind(to)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
     }
 }
 
@@ -512,24 +1440,97 @@ struct PgSpamFlagsRepository {
 #[async_trait]
 impl SpamFlagsRepository for PgSpamFlagsRepository {
     async fn upsert(&self, flag: SpamFlagUpsert) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO spam_flags (subject_type, subject_id, score, reasons, version)
-            VALUES ($1, $2, $3, $4, $5)
-            ON CONFLICT (subject_type, subject_id, version) DO UPDATE
-                SET score = EXCLUDED.score,
-                    reasons = EXCLUDED.reasons
-            "#,
-        )
-        .bind(flag.subject_type)
-        .bind(flag.subject_id)
-        .bind(flag.score)
-        .bind(flag.reasons)
-        .bind(flag.version)
-        .execute(&self.pool)
-        .await
-        .map(|_| ())
-        .map_err(DbError::Query)
+        metrics::observe("spam_flags.upsert", async move {
+            sqlx::query(
+                r#"
+                WITH upserted AS (
+                    INSERT INTO spam_flags (subject_type, subject_id, score, reasons, version, features)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    ON CONFLICT (subject_type, subject_id, version) DO UPDATE
+                        SET score = EXCLUDED.score,
+                            reasons = EXCLUDED.reasons,
+                            features = EXCLUDED.features
+                    RETURNING subject_type, subject_id, score, reasons, version
+                )
+                -- Every assessment is kept in history, even when it overwrites the latest flag
+                INSERT INTO spam_flag_history (subject_type, subject_id, score, reasons, version)
+                SELECT subject_type, subject_id, score, reasons, version FROM upserted
+                "#,
+            )
+            .bind(flag.subject_type)
+            .bind(flag.subject_id)
+            .bind(flag.score)
+            .bind(flag.reasons)
+            .bind(flag.version)
+            .bind(flag.features)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn upsert_many(&self, flags: Vec<SpamFlagUpsert>) -> Result<()> {
+        let flags = last_per_key(flags, |flag| {
+            (
+                flag.subject_type.clone(),
+                flag.subject_id,
+                flag.version.clone(),
+            )
+        });
+        if flags.is_empty() {
+            return Ok(());
+        }
+        metrics::observe("spam_flags.upsert_many", async move {
+            let mut subject_types = Vec::with_capacity(flags.len());
+            let mut subject_ids = Vec::with_capacity(flags.len());
+            let mut scores = Vec::with_capacity(flags.len());
+            let mut reasons = Vec::with_capacity(flags.len());
+            let mut versions = Vec::with_capacity(flags.len());
+            let mut features = Vec::with_capacity(flags.len());
+            for flag in flags {
+                subject_types.push(flag.subject_type);
+                subject_ids.push(flag.subject_id);
+                scores.push(flag.score);
+                // Postgres arrays cannot be ragged, so each flag's reasons travel as JSON.
+                reasons.push(serde_json::Value::from(flag.reasons));
+                versions.push(flag.version);
+                features.push(flag.features);
+            }
+            sqlx::query(
+                r#"
+                WITH upserted AS (
+                    INSERT INTO spam_flags (subject_type, subject_id, score, reasons, version, features)
+                    SELECT f.subject_type, f.subject_id, f.score,
+                           ARRAY(SELECT jsonb_array_elements_text(f.reasons)), f.version, f.features
+                    FROM UNNEST(
+                        $1::text[], $2::int8[], $3::float4[], $4::jsonb[], $5::text[], $6::jsonb[]
+                    ) WITH ORDINALITY
+                        AS f(subject_type, subject_id, score, reasons, version, features, position)
+                    ORDER BY f.position
+                    ON CONFLICT (subject_type, subject_id, version) DO UPDATE
+                        SET score = EXCLUDED.score,
+                            reasons = EXCLUDED.reasons,
+                            features = EXCLUDED.features
+                    RETURNING subject_type, subject_id, score, reasons, version
+                )
+                INSERT INTO spam_flag_history (subject_type, subject_id, score, reasons, version)
+                SELECT subject_type, subject_id, score, reasons, version FROM upserted
+                "#,
+            )
+            .bind(subject_types)
+            .bind(subject_ids)
+            .bind(scores)
+            .bind(reasons)
+            .bind(versions)
+            .bind(features)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
     }
 
     async fn list_for_subject(
@@ -537,89 +1538,531 @@ impl SpamFlagsRepository for PgSpamFlagsRepository {
         subject_type: &str,
         subject_id: i64,
     ) -> Result<Vec<SpamFlagRow>> {
-        sqlx::query_as::<_, SpamFlagRow>(
-            r#"
-            SELECT id, subject_type, subject_id, score, reasons, version, created_at
-            FROM spam_flags
-            WHERE subject_type = $1 AND subject_id = $2
-            ORDER BY created_at DESC
-            "#,
-        )
-        .bind(subject_type)
-        .bind(subject_id)
-        .fetch_all(&self.pool)
+        metrics::observe("spam_flags.list_for_subject", async move {
+            sqlx::query_as::<_, SpamFlagRow>(
+                r#"
+                SELECT id, subject_type, subject_id, score, reasons, version, created_at,
+                       reviewed_status, reviewed_at, reviewed_by, features
+                FROM spam_flags
+                WHERE subject_type = $1 AND subject_id = $2
+                ORDER BY created_at DESC
+                "#,
+            )
+            .bind(subject_type)
+            .bind(subject_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn review(&self, review: SpamFlagReview) -> Result<Option<SpamFlagRow>> {
+        metrics::observe("spam_flags.review", async move {
+            sqlx::query_as::<_, SpamFlagRow>(
+                r#"
+                UPDATE spam_flags
+                SET reviewed_status = $2,
+                    reviewed_at = now(),
+                    reviewed_by = $3
+                WHERE id = $1
+                RETURNING id, subject_type, subject_id, score, reasons, version, created_at,
+                          reviewed_status, reviewed_at, reviewed_by, features
+                "#,
+            )
+            .bind(review.id)
+            .bind(review.status)
+            .bind(review.reviewed_by)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn recent_flags(
+        &self,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<RecentFlagRow>> {
+        metrics::observe("spam_flags.recent_flags", async move {
+            sqlx::query_as::<_, RecentFlagRow>(
+                r#"
+                SELECT sf.id, sf.subject_type, sf.subject_id, sf.score, sf.reasons, sf.version,
+                       sf.created_at, sf.reviewed_status, sf.reviewed_at, sf.reviewed_by,
+                       r.full_name AS repo_full_name,
+                       i.number AS issue_number,
+                       i.title,
+                       u.login AS author_login,
+                       LEFT(COALESCE(c.body, i.body, ''), 280) AS snippet
+                FROM spam_flags sf
+                LEFT JOIN comments c ON sf.subject_type = 'comment' AND c.id = sf.subject_id
+                JOIN issues i ON i.id = CASE WHEN sf.subject_type = 'issue' THEN sf.subject_id ELSE c.issue_id END
+                JOIN repositories r ON r.id = i.repo_id
+                LEFT JOIN users u ON u.id = CASE WHEN sf.subject_type = 'issue' THEN i.user_id ELSE c.user_id END
+                WHERE ($1::timestamptz IS NULL OR sf.created_at >= $1)
+                ORDER BY sf.created_at DESC, sf.id DESC
+                LIMIT $2
+                "#,
+            )
+            .bind(since)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn score_timeseries(
+        &self,
+        repo_full_name: &str,
+        bucket: TrendBucket,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ScoreBucket>> {
+        metrics::observe("spam_flags.score_timeseries", async move {
+            sqlx::query_as::<_, ScoreBucket>(
+                r#"
+                SELECT date_trunc($2, sf.created_at, 'UTC') AS bucket_start,
+                       COUNT(*) AS flag_count,
+                       SUM(sf.score)::REAL AS total_score
+                FROM spam_flags sf
+                LEFT JOIN comments c ON sf.subject_type = 'comment' AND c.id = sf.subject_id
+                JOIN issues i ON i.id = CASE WHEN sf.subject_type = 'issue' THEN sf.subject_id ELSE c.issue_id END
+                JOIN repositories r ON r.id = i.repo_id
+                WHERE r.full_name = $1
+                  AND sf.reviewed_status <> 'false_positive'
+                  AND ($3::timestamptz IS NULL OR sf.created_at >= $3)
+                GROUP BY bucket_start
+                ORDER BY bucket_start
+                "#,
+            )
+            .bind(repo_full_name)
+            .bind(bucket.as_str())
+            .bind(since)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn list_history_for_subject(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+    ) -> Result<Vec<SpamFlagRow>> {
+        metrics::observe("spam_flags.list_history_for_subject", async move {
+            sqlx::query_as::<_, SpamFlagRow>(
+                r#"
+                SELECT id, subject_type, subject_id, score, reasons, version, created_at
+                FROM spam_flag_history
+                WHERE subject_type = $1 AND subject_id = $2
+                ORDER BY created_at DESC, id DESC
+                "#,
+            )
+            .bind(subject_type)
+            .bind(subject_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
         .await
-        .map_err(DbError::Query)
     }
 
     async fn top_spammy_users(
         &self,
         since: Option<DateTime<Utc>>,
         limit: i64,
+        half_life_days: Option<f64>,
+        after: Option<SpammyUserCursor>,
     ) -> Result<Vec<ActorSpamSummary>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT
-                login,
-                AVG(score) AS avg_score,
-                SUM(score) AS total_score,
-                COUNT(*) AS flag_count,
-                ARRAY_AGG(DISTINCT reason) AS reasons
-            FROM (
-                SELECT
-                    COALESCE(u.login, uc.login) AS login,
-                    sf.score,
-                    sf.created_at,
-                    unnest(sf.reasons) AS reason
-                FROM spam_flags sf
-                LEFT JOIN issues i ON sf.subject_type = 'issue' AND sf.subject_id = i.id
+        let (after_score, after_flags, after_login) = match after {
+            Some(cursor) => (
+                Some(cursor.total_score),
+                Some(cursor.flag_count),
+                Some(cursor.login),
+            ),
+            None => (None, None, None),
+        };
+        if since.is_none() && half_life_days.is_none() {
+            // The all-time ranking is served from the aggregate the collector
+            // refreshes after each run; reads never take its lock.
+            return metrics::observe("spam_flags.top_spammy_users_cached", async move {
+                let rows = sqlx::query(
+                    r#"
+                    SELECT login, avg_score, total_score, flag_count, reasons
+                    FROM spammy_user_totals
+                    WHERE $2::real IS NULL
+                       OR total_score < $2
+                       OR (total_score = $2 AND flag_count < $3)
+                       OR (total_score = $2 AND flag_count = $3 AND login > $4)
+                    ORDER BY total_score DESC, flag_count DESC, login ASC
+                    LIMIT $1
+                    "#,
+                )
+                .bind(limit)
+                .bind(after_score)
+                .bind(after_flags)
+                .bind(after_login)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(DbError::Query)?;
+                Ok(actor_spam_summaries(rows))
+            })
+            .await;
+        }
+        metrics::observe("spam_flags.top_spammy_users", async move {
+            let rows = sqlx::query(
+                r#"
+                WITH ranked AS (
+                    SELECT
+                        login,
+                        AVG(score) AS avg_score,
+                        SUM(
+                            CASE WHEN $3::float8 IS NULL THEN score
+                            ELSE score * power(0.5, EXTRACT(EPOCH FROM (now() - created_at)) / ($3 * 86400))
+                            END
+                        )::real AS total_score,
+                        COUNT(*) AS flag_count,
+                        ARRAY_AGG(DISTINCT reason) AS reasons
+                    FROM (
+                        SELECT
+                            COALESCE(u.login, uc.login) AS login,
+                            sf.score,
+                            sf.created_at,
+                            unnest(sf.reasons) AS reason
+                        FROM spam_flags sf
+                        LEFT JOIN issues i ON sf.subject_type = 'issue' AND sf.subject_id = i.id
+                        LEFT JOIN users u ON i.user_id = u.id
+                        LEFT JOIN comments c ON sf.subject_type = 'comment' AND sf.subject_id = c.id
+                        LEFT JOIN users uc ON c.user_id = uc.id
+                        WHERE ($1::timestamptz IS NULL OR sf.created_at >= $1)
+                    ) flagged
+                    WHERE login IS NOT NULL
+                    GROUP BY login
+                )
+                SELECT login, avg_score, total_score, flag_count, reasons
+                FROM ranked
+                WHERE $4::real IS NULL
+                   OR total_score < $4
+                   OR (total_score = $4 AND flag_count < $5)
+                   OR (total_score = $4 AND flag_count = $5 AND login > $6)
+                ORDER BY total_score DESC, flag_count DESC, login ASC
+                LIMIT $2
+                "#,
+            )
+            .bind(since)
+            .bind(limit)
+            .bind(half_life_days)
+            .bind(after_score)
+            .bind(after_flags)
+            .bind(after_login)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)?;
+            Ok(actor_spam_summaries(rows))
+        })
+        .await
+    }
+
+    async fn refresh_spammy_user_totals(&self) -> Result<u64> {
+        metrics::observe("spam_flags.refresh_spammy_user_totals", async move {
+            let mut tx = self.pool.begin().await.map_err(DbError::Query)?;
+            // Holds off flag upserts (which append history in the same statement)
+            // so no history row can commit behind the new high-water mark.
+            sqlx::query("LOCK TABLE spam_flags IN SHARE MODE")
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::Query)?;
+            let last_history_id: i64 = sqlx::query_scalar(
+                "SELECT last_history_id FROM spammy_user_totals_state FOR UPDATE",
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::Query)?;
+            sqlx::query(
+                r#"
+                CREATE TEMP TABLE changed_spammy_users ON COMMIT DROP AS
+                SELECT DISTINCT COALESCE(u.login, uc.login) AS login
+                FROM spam_flag_history h
+                LEFT JOIN issues i ON h.subject_type = 'issue' AND h.subject_id = i.id
                 LEFT JOIN users u ON i.user_id = u.id
-                LEFT JOIN comments c ON sf.subject_type = 'comment' AND sf.subject_id = c.id
+                LEFT JOIN comments c ON h.subject_type = 'comment' AND h.subject_id = c.id
                 LEFT JOIN users uc ON c.user_id = uc.id
-                WHERE ($1::timestamptz IS NULL OR sf.created_at >= $1)
-            ) flagged
-            WHERE login IS NOT NULL
-            GROUP BY login
-            ORDER BY total_score DESC
-            LIMIT $2
-            "#,
-        )
-        .bind(since)
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(DbError::Query)?;
-
-        let mut summaries = Vec::new();
-        for row in rows {
-            let login: Option<String> = row.try_get("login").ok();
-            if let Some(login) = login {
-                let avg_score: f64 = row
-                    .try_get::<Option<f64>, _>("avg_score")
-                    .unwrap_or(Some(0.0))
-                    .unwrap_or(0.0);
-                let total_score: f32 = row
-                    .try_get::<Option<f32>, _>("total_score")
-                    .unwrap_or(Some(0.0))
-                    .unwrap_or(0.0);
-                let flag_count: i64 = row
-                    .try_get::<Option<i64>, _>("flag_count")
-                    .unwrap_or(Some(0))
-                    .unwrap_or(0);
-                let reasons: Vec<String> = row
-                    .try_get::<Option<Vec<String>>, _>("reasons")
-                    .unwrap_or(Some(Vec::new()))
-                    .unwrap_or_default();
-                summaries.push(ActorSpamSummary {
-                    login,
-                    avg_score: avg_score as f32,
-                    total_score,
-                    flag_count,
-                    reasons,
-                });
+                WHERE h.id > $1 AND COALESCE(u.login, uc.login) IS NOT NULL
+                "#,
+            )
+            .bind(last_history_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::Query)?;
+            let refreshed = rebuild_spammy_user_totals(&mut tx).await?;
+            sqlx::query(
+                r#"
+                UPDATE spammy_user_totals_state
+                SET last_history_id = (
+                        SELECT COALESCE(MAX(id), $1) FROM spam_flag_history
+                    ),
+                    refreshed_at = now()
+                "#,
+            )
+            .bind(last_history_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::Query)?;
+            tx.commit().await.map_err(DbError::Query)?;
+            Ok(refreshed)
+        })
+        .await
+    }
+
+    async fn dedupe_clusters(&self, min_size: i64, limit: i64) -> Result<Vec<DedupeCluster>> {
+        metrics::observe("spam_flags.dedupe_clusters", async move {
+            let rows = sqlx::query(
+                r#"
+                WITH subjects AS (
+                    SELECT dedupe_hash, user_id FROM issues
+                    UNION ALL
+                    SELECT dedupe_hash, user_id FROM comments
+                )
+                SELECT dedupe_hash, COUNT(*) AS size, COUNT(DISTINCT user_id) AS distinct_authors
+                FROM subjects
+                GROUP BY dedupe_hash
+                HAVING COUNT(*) >= $1
+                ORDER BY size DESC, dedupe_hash
+                LIMIT $2
+                "#,
+            )
+            .bind(min_size)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)?;
+            let mut clusters = rows
+                .into_iter()
+                .map(|row| {
+                    Ok(DedupeCluster {
+                        dedupe_hash: row.try_get("dedupe_hash")?,
+                        size: row.try_get("size")?,
+                        distinct_authors: row.try_get("distinct_authors")?,
+                        members: Vec::new(),
+                    })
+                })
+                .collect::<std::result::Result<Vec<_>, sqlx::Error>>()
+                .map_err(DbError::Query)?;
+            if clusters.is_empty() {
+                return Ok(clusters);
             }
-        }
-        Ok(summaries)
+
+            let hashes: Vec<String> = clusters
+                .iter()
+                .map(|cluster| cluster.dedupe_hash.clone())
+                .collect();
+            let members = sqlx::query_as::<_, ClusterMember>(
+                r#"
+                SELECT subject_type, subject_id, dedupe_hash, author_login, created_at
+                FROM (
+                    SELECT s.subject_type, s.subject_id, s.dedupe_hash, u.login AS author_login,
+                           s.created_at,
+                           ROW_NUMBER() OVER (
+                               PARTITION BY s.dedupe_hash ORDER BY s.created_at, s.subject_id
+                           ) AS position
+                    FROM (
+                        SELECT 'issue' AS subject_type, id AS subject_id, dedupe_hash, user_id, created_at
+                        FROM issues WHERE dedupe_hash = ANY($1)
+                        UNION ALL
+                        SELECT 'comment', id, dedupe_hash, user_id, created_at
+                        FROM comments WHERE dedupe_hash = ANY($1)
+                    ) s
+                    LEFT JOIN users u ON u.id = s.user_id
+                ) ranked
+                WHERE position <= $2
+                ORDER BY created_at, subject_id
+                "#,
+            )
+            .bind(&hashes)
+            .bind(MAX_CLUSTER_MEMBERS)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)?;
+            for member in members {
+                if let Some(cluster) = clusters
+                    .iter_mut()
+                    .find(|cluster| cluster.dedupe_hash == member.dedupe_hash)
+                {
+                    cluster.members.push(member);
+                }
+            }
+            Ok(clusters)
+        })
+        .await
+    }
+
+    async fn upsert_feature_sample(&self, sample: FeatureSampleUpsert) -> Result<()> {
+        metrics::observe("spam_flags.upsert_feature_sample", async move {
+            sqlx::query(
+                r#"
+                INSERT INTO feature_samples (subject_type, subject_id, features, version)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (subject_type, subject_id) DO UPDATE
+                    SET features = EXCLUDED.features,
+                        version = EXCLUDED.version,
+                        sampled_at = NOW()
+                "#,
+            )
+            .bind(sample.subject_type)
+            .bind(sample.subject_id)
+            .bind(sample.features)
+            .bind(sample.version)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn list_feature_samples(
+        &self,
+        subject_type: &str,
+        limit: i64,
+    ) -> Result<Vec<FeatureSampleRow>> {
+        metrics::observe("spam_flags.list_feature_samples", async move {
+            sqlx::query_as::<_, FeatureSampleRow>(
+                r#"
+                SELECT subject_type, subject_id, features, version, sampled_at
+                FROM feature_samples
+                WHERE subject_type = $1
+                ORDER BY subject_id
+                LIMIT $2
+                "#,
+            )
+            .bind(subject_type)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn upsert_content_edits(&self, edits: ContentEditsUpsert) -> Result<()> {
+        metrics::observe("spam_flags.upsert_content_edits", async move {
+            sqlx::query(
+                r#"
+                INSERT INTO content_edits
+                    (subject_type, subject_id, edit_count, last_edited_at, last_editor_login)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (subject_type, subject_id) DO UPDATE
+                    SET edit_count = EXCLUDED.edit_count,
+                        last_edited_at = EXCLUDED.last_edited_at,
+                        last_editor_login = EXCLUDED.last_editor_login,
+                        fetched_at = NOW()
+                "#,
+            )
+            .bind(edits.subject_type)
+            .bind(edits.subject_id)
+            .bind(edits.edit_count)
+            .bind(edits.last_edited_at)
+            .bind(edits.last_editor_login)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn get_content_edits(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+    ) -> Result<Option<ContentEditsRow>> {
+        metrics::observe("spam_flags.get_content_edits", async move {
+            sqlx::query_as::<_, ContentEditsRow>(
+                r#"
+                SELECT subject_type, subject_id, edit_count, last_edited_at,
+                       last_editor_login, fetched_at
+                FROM content_edits
+                WHERE subject_type = $1 AND subject_id = $2
+                "#,
+            )
+            .bind(subject_type)
+            .bind(subject_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn replace_reactions(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+        reactions: Vec<ReactionRow>,
+    ) -> Result<()> {
+        metrics::observe("spam_flags.replace_reactions", async move {
+            let mut tx = self.pool.begin().await.map_err(DbError::Query)?;
+            sqlx::query("DELETE FROM reactions WHERE subject_type = $1 AND subject_id = $2")
+                .bind(subject_type)
+                .bind(subject_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::Query)?;
+            let mut logins = Vec::with_capacity(reactions.len());
+            let mut contents = Vec::with_capacity(reactions.len());
+            let mut created = Vec::with_capacity(reactions.len());
+            for reaction in reactions {
+                logins.push(reaction.user_login);
+                contents.push(reaction.content);
+                created.push(reaction.user_created_at);
+            }
+            sqlx::query(
+                r#"
+                INSERT INTO reactions
+                    (subject_type, subject_id, user_login, content, user_created_at)
+                SELECT $1, $2, r.user_login, r.content, r.user_created_at
+                FROM UNNEST($3::text[], $4::text[], $5::timestamptz[])
+                    AS r(user_login, content, user_created_at)
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(subject_type)
+            .bind(subject_id)
+            .bind(logins)
+            .bind(contents)
+            .bind(created)
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::Query)?;
+            tx.commit().await.map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn list_reactions(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+    ) -> Result<Vec<ReactionRow>> {
+        metrics::observe("spam_flags.list_reactions", async move {
+            sqlx::query_as::<_, ReactionRow>(
+                r#"
+                SELECT user_login, content, user_created_at
+                FROM reactions
+                WHERE subject_type = $1 AND subject_id = $2
+                ORDER BY user_login, content
+                "#,
+            )
+            .bind(subject_type)
+            .bind(subject_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
     }
 }
 
@@ -631,153 +2074,212 @@ struct PgCollectionJobRepository {
 #[async_trait]
 impl CollectionJobRepository for PgCollectionJobRepository {
     async fn create(&self, job: CollectionJobCreate) -> Result<CollectionJobRow> {
-        sqlx::query_as::<_, CollectionJobRow>(
-            r#"
-            INSERT INTO collection_jobs (owner, name, priority)
-            VALUES ($1, $2, $3)
-            ON CONFLICT (owner, name) DO UPDATE
-                SET priority = EXCLUDED.priority,
-                    -- If a job is in a permanent error state, allow POST /repos to reset it
-                    status = CASE WHEN collection_jobs.status = 'error' THEN 'pending' ELSE collection_jobs.status END,
-                    failure_count = CASE WHEN collection_jobs.status = 'error' THEN 0 ELSE collection_jobs.failure_count END,
-                    error_message = CASE WHEN collection_jobs.status = 'error' THEN NULL ELSE collection_jobs.error_message END,
-                    updated_at = now()
-            RETURNING id, owner, name, full_name, status, priority,
-                      last_attempt_at, last_completed_at, failure_count, error_message,
-                      created_at, updated_at
-            "#,
-        )
-        .bind(job.owner)
-        .bind(job.name)
-        .bind(job.priority)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(DbError::Query)
-    }
-
-    async fn get_pending(&self, limit: i32) -> Result<Vec<CollectionJobRow>> {
-        sqlx::query_as::<_, CollectionJobRow>(
-            r#"
-            SELECT id, owner, name, full_name, status, priority,
-                   last_attempt_at, last_completed_at, failure_count, error_message,
-                   created_at, updated_at
-            FROM collection_jobs
-            WHERE status = 'pending'
-            ORDER BY priority DESC, created_at ASC
-            LIMIT $1
-            "#,
-        )
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(DbError::Query)
+        metrics::observe("collection_jobs.create", async move {
+            sqlx::query_as::<_, CollectionJobRow>(
+                r#"
+                INSERT INTO collection_jobs (owner, name, priority)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (owner, name) DO UPDATE
+                    SET priority = EXCLUDED.priority,
+                        -- If a job is in a permanent error state, allow POST /repos to reset it
+                        status = CASE WHEN collection_jobs.status = 'error' THEN 'pending' ELSE collection_jobs.status END,
+                        failure_count = CASE WHEN collection_jobs.status = 'error' THEN 0 ELSE collection_jobs.failure_count END,
+                        error_message = CASE WHEN collection_jobs.status = 'error' THEN NULL ELSE collection_jobs.error_message END,
+                        updated_at = now()
+                RETURNING id, owner, name, full_name, status, priority,
+                          last_attempt_at, last_completed_at, failure_count, error_message,
+                          created_at, updated_at
+                "#,
+            )
+            .bind(job.owner)
+            .bind(job.name)
+            .bind(job.priority)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn get_pending(
+        &self,
+        limit: i32,
+        full_name: Option<&str>,
+    ) -> Result<Vec<CollectionJobRow>> {
+        metrics::observe("collection_jobs.get_pending", async move {
+            sqlx::query_as::<_, CollectionJobRow>(
+                r#"
+                SELECT id, owner, name, full_name, status, priority,
+                       last_attempt_at, last_completed_at, failure_count, error_message,
+                       created_at, updated_at
+                FROM collection_jobs
+                WHERE status = 'pending'
+                  AND ($2::text IS NULL OR lower(full_name) = lower($2))
+                ORDER BY priority DESC, created_at ASC
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .bind(full_name)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
+        .await
     }
 
     async fn mark_in_progress(&self, id: i64) -> Result<()> {
-        sqlx::query(
-            r#"
-            UPDATE collection_jobs
-            SET status = 'in_progress',
-                last_attempt_at = now(),
-                updated_at = now()
-            WHERE id = $1
-            "#,
-        )
-        .bind(id)
-        .execute(&self.pool)
-        .await
-        .map(|_| ())
-        .map_err(DbError::Query)
+        metrics::observe("collection_jobs.mark_in_progress", async move {
+            sqlx::query(
+                r#"
+                UPDATE collection_jobs
+                SET status = 'in_progress',
+                    last_attempt_at = now(),
+                    updated_at = now()
+                WHERE id = $1
+                "#,
+            )
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::Query)
+        })
+        .await
+    }
+
+    async fn rename(
+        &self,
+        owner: &str,
+        name: &str,
+        new_owner: &str,
+        new_name: &str,
+    ) -> Result<bool> {
+        metrics::observe("collection_jobs.rename", async move {
+            let result = sqlx::query(
+                r#"
+                UPDATE collection_jobs
+                SET owner = $3,
+                    name = $4,
+                    updated_at = now()
+                WHERE owner = $1 AND name = $2
+                  AND NOT EXISTS (
+                      SELECT 1 FROM collection_jobs WHERE owner = $3 AND name = $4
+                  )
+                "#,
+            )
+            .bind(owner)
+            .bind(name)
+            .bind(new_owner)
+            .bind(new_name)
+            .execute(&self.pool)
+            .await
+            .map_err(DbError::Query)?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
     }
 
     async fn update(&self, update: CollectionJobUpdate) -> Result<()> {
-        let status_change = match update.status {
-            CollectionStatus::Completed => {
-                sqlx::query(
-                    r#"
-                    UPDATE collection_jobs
-                    SET status = $1,
-                        last_completed_at = now(),
-                        error_message = NULL,
-                        failure_count = 0,
-                        updated_at = now()
-                    WHERE id = $2
-                    "#,
-                )
-                .bind(update.status)
-                .bind(update.id)
-                .execute(&self.pool)
-                .await
-            }
-            CollectionStatus::Failed => {
-                // Transient failure - return to pending for retry
-                sqlx::query(
-                    r#"
-                    UPDATE collection_jobs
-                    SET status = 'pending',
-                        error_message = $1,
-                        failure_count = failure_count + 1,
-                        updated_at = now()
-                    WHERE id = $2
-                    "#,
-                )
-                .bind(update.error_message)
-                .bind(update.id)
-                .execute(&self.pool)
-                .await
-            }
-            CollectionStatus::Error => {
-                // Permanent error - do not retry
-                sqlx::query(
-                    r#"
-                    UPDATE collection_jobs
-                    SET status = 'error',
-                        error_message = $1,
-                        failure_count = failure_count + 1,
-                        updated_at = now()
-                    WHERE id = $2
-                    "#,
-                )
-                .bind(update.error_message)
-                .bind(update.id)
-                .execute(&self.pool)
-                .await
-            }
-            _ => {
-                sqlx::query(
-                    r#"
-                    UPDATE collection_jobs
-                    SET status = $1,
-                        error_message = $2,
-                        updated_at = now()
-                    WHERE id = $3
-                    "#,
-                )
-                .bind(update.status)
-                .bind(update.error_message)
-                .bind(update.id)
-                .execute(&self.pool)
-                .await
-            }
-        };
+        metrics::observe("collection_jobs.update", async move {
+            let status_change = match update.status {
+                CollectionStatus::Completed => {
+                    sqlx::query(
+                        r#"
+                        UPDATE collection_jobs
+                        SET status = $1,
+                            last_completed_at = now(),
+                            error_message = NULL,
+                            failure_count = 0,
+                            updated_at = now()
+                        WHERE id = $2
+                        "#,
+                    )
+                    .bind(update.status)
+                    .bind(update.id)
+                    .execute(&self.pool)
+                    .await
+                }
+                CollectionStatus::Failed => {
+                    // Transient failure - return to pending for retry
+                    sqlx::query(
+                        r#"
+                        UPDATE collection_jobs
+                        SET status = 'pending',
+                            error_message = $1,
+                            failure_count = failure_count + 1,
+                            updated_at = now()
+                        WHERE id = $2
+                        "#,
+                    )
+                    .bind(update.error_message)
+                    .bind(update.id)
+                    .execute(&self.pool)
+                    .await
+                }
+                CollectionStatus::Error => {
+                    // Permanent error - do not retry
+                    sqlx::query(
+                        r#"
+                        UPDATE collection_jobs
+                        SET status = 'error',
+                            error_message = $1,
+                            failure_count = failure_count + 1,
+                            updated_at = now()
+                        WHERE id = $2
+                        "#,
+                    )
+                    .bind(update.error_message)
+                    .bind(update.id)
+                    .execute(&self.pool)
+                    .await
+                }
+                _ => {
+                    sqlx::query(
+                        r#"
+                        UPDATE collection_jobs
+                        SET status = $1,
+                            error_message = $2,
+                            updated_at = now()
+                        WHERE id = $3
+                        "#,
+                    )
+                    .bind(update.status)
+                    .bind(update.error_message)
+                    .bind(update.id)
+                    .execute(&self.pool)
+                    .await
+                }
+            };
 
-        status_change.map(|_| ()).map_err(DbError::Query)
+            status_change.map(|_| ()).map_err(DbError::Query)
+        })
+        .await
     }
 
-    async fn list(&self, limit: i32) -> Result<Vec<CollectionJobRow>> {
-        sqlx::query_as::<_, CollectionJobRow>(
-            r#"
-            SELECT id, owner, name, full_name, status, priority,
-                   last_attempt_at, last_completed_at, failure_count, error_message,
-                   created_at, updated_at
-            FROM collection_jobs
-            ORDER BY updated_at DESC
-            LIMIT $1
-            "#,
-        )
-        .bind(limit)
-        .fetch_all(&self.pool)
+    async fn list(
+        &self,
+        limit: i32,
+        statuses: &[CollectionStatus],
+    ) -> Result<Vec<CollectionJobRow>> {
+        metrics::observe("collection_jobs.list", async move {
+            sqlx::query_as::<_, CollectionJobRow>(
+                r#"
+                SELECT id, owner, name, full_name, status, priority,
+                       last_attempt_at, last_completed_at, failure_count, error_message,
+                       created_at, updated_at
+                FROM collection_jobs
+                WHERE cardinality($2::collection_status[]) = 0 OR status = ANY($2)
+                ORDER BY updated_at DESC
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .bind(statuses)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Query)
+        })
         .await
-        .map_err(DbError::Query)
     }
 }
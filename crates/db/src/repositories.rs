@@ -3,16 +3,28 @@ use chrono::{DateTime, Utc};
 
 use crate::errors::Result;
 use crate::models::{
-    ActorSpamSummary, CollectionJobCreate, CollectionJobRow, CollectionJobUpdate,
-    CollectorWatermarkRow, CommentRow, IssueQuery, IssueRow, RepositoryRow, SpamFlagRow,
-    SpamFlagUpsert, UserRow, WatermarkUpdate,
+    ActorSpamSummary, CollectionJobCreate, CollectionJobRow, CollectionJobUpdate, CollectionStatus,
+    CollectorProgressRow, CollectorWatermarkRow, CommentRow, ContentEditsRow, ContentEditsUpsert,
+    DedupeCluster, FeatureSampleRow, FeatureSampleUpsert, IssueFingerprint, IssueQuery, IssueRow,
+    IssueStateTransition, IssueStateTransitionRow, OrgMembershipRow, OrphanedAuthor,
+    ProgressUpdate, ReactionRow, RecentFlagRow, RepoDeletion, RepoSummary, RepositoryRow,
+    ScoreBucket, SpamFlagReview, SpamFlagRow, SpamFlagUpsert, SpammyUserCursor, TrendBucket,
+    UserActivitySummary, UserRow, UserSpamSummary, WatermarkUpdate,
 };
 
 #[async_trait]
 pub trait RepoRepository: Send + Sync {
     async fn upsert(&self, repo: RepositoryRow) -> Result<()>;
     async fn get_by_full_name(&self, full_name: &str) -> Result<Option<RepositoryRow>>;
+    async fn get_by_id(&self, id: i64) -> Result<Option<RepositoryRow>>;
     async fn list(&self, limit: i64) -> Result<Vec<RepositoryRow>>;
+    /// Issue/comment/author/flag counts for one repository; `None` when it is
+    /// not stored.
+    async fn summary(&self, full_name: &str) -> Result<Option<RepoSummary>>;
+    /// Removes a repository with its issues, comments, their spam flags and
+    /// derived rows, and its watermark, in one transaction; with `delete_job`
+    /// also its collection job. Users are shared across repositories and kept.
+    async fn delete_repo(&self, full_name: &str, delete_job: bool) -> Result<RepoDeletion>;
 }
 
 #[async_trait]
@@ -20,6 +32,16 @@ pub trait UserRepository: Send + Sync {
     async fn upsert(&self, user: UserRow) -> Result<()>;
     async fn get_by_id(&self, id: i64) -> Result<Option<UserRow>>;
     async fn get_by_login(&self, login: &str) -> Result<Option<UserRow>>;
+    /// Issue/comment counts for `login`; all zeros when the user posted nothing.
+    async fn activity_summary(&self, login: &str) -> Result<UserActivitySummary>;
+    /// Flags on `login`'s issues and comments; zero/empty when none.
+    async fn spam_summary(&self, login: &str) -> Result<UserSpamSummary>;
+    async fn org_membership(&self, org: &str, user_id: i64) -> Result<Option<OrgMembershipRow>>;
+    /// Stores a membership check, refreshing `checked_at`.
+    async fn set_org_membership(&self, org: &str, user_id: i64, is_member: bool) -> Result<()>;
+    /// Issues and comments whose `user_id` has no `users` row, oldest first,
+    /// at most `limit`.
+    async fn list_orphaned_authors(&self, limit: i64) -> Result<Vec<OrphanedAuthor>>;
 }
 
 #[async_trait]
@@ -31,42 +53,159 @@ pub trait IssueRepository: Send + Sync {
         repo_id: i64,
         since: Option<DateTime<Utc>>,
     ) -> Result<Vec<IssueRow>>;
+    /// Issues previously marked `found = false`, most recently updated first.
+    async fn list_missing(&self, repo_id: i64, limit: i64) -> Result<Vec<IssueRow>>;
+    /// Change-detection columns for a stored issue, without reading body or raw JSON.
+    async fn fingerprint(&self, id: i64) -> Result<Option<IssueFingerprint>>;
+    /// The GitHub payload stored for the issue, as received.
+    async fn raw(&self, id: i64) -> Result<Option<serde_json::Value>>;
+    /// Issues by `user_id` currently closed with the given `state_reason`.
+    async fn count_closed_as(&self, user_id: i64, state_reason: &str) -> Result<i64>;
+    /// Other issues in the same repo whose normalized title matches `issue`'s,
+    /// created within `window` of it either way.
+    async fn count_same_title(&self, issue: &IssueRow, window: chrono::Duration) -> Result<i64>;
+    async fn record_transition(&self, transition: IssueStateTransition) -> Result<()>;
+    /// Recorded transitions of the issue, oldest first.
+    async fn list_transitions(&self, issue_id: i64) -> Result<Vec<IssueStateTransitionRow>>;
+    /// Issues by `user_id` recorded as closed by a maintainer shortly after creation.
+    async fn count_rapid_closures(&self, user_id: i64) -> Result<i64>;
 }
 
 #[async_trait]
 pub trait CommentRepository: Send + Sync {
     async fn upsert(&self, comment: CommentRow) -> Result<()>;
+    /// Upserts `comments` in one statement, as [`CommentRepository::upsert`]
+    /// would one by one: of rows sharing an `id`, the last wins.
+    async fn upsert_many(&self, comments: Vec<CommentRow>) -> Result<()>;
     async fn list_by_issue(&self, issue_id: i64) -> Result<Vec<CommentRow>>;
+    /// The GitHub payload stored for the comment, as received.
+    async fn raw(&self, id: i64) -> Result<Option<serde_json::Value>>;
+    /// Comments on all issues of the repository `owner/name`.
+    async fn count_by_repo(&self, repo_full_name: &str) -> Result<i64>;
 }
 
 #[async_trait]
 pub trait WatermarkRepository: Send + Sync {
     async fn get(&self, repo_full_name: &str) -> Result<Option<CollectorWatermarkRow>>;
     async fn set(&self, watermark: WatermarkUpdate) -> Result<()>;
+    async fn get_progress(&self, repo_full_name: &str) -> Result<Option<CollectorProgressRow>>;
+    async fn set_progress(&self, progress: ProgressUpdate) -> Result<()>;
+    async fn clear_progress(&self, repo_full_name: &str) -> Result<()>;
+    /// Removes a repo's watermark so the next run pages it from the start.
+    /// Returns `false` when there was none.
+    async fn delete(&self, repo_full_name: &str) -> Result<bool>;
+    /// Moves the watermark and any in-flight progress to a repo's new name.
+    /// Existing state under `to` is left untouched.
+    async fn rename(&self, from: &str, to: &str) -> Result<()>;
 }
 
 #[async_trait]
 pub trait SpamFlagsRepository: Send + Sync {
     async fn upsert(&self, flag: SpamFlagUpsert) -> Result<()>;
+    /// Upserts `flags` in one statement, as [`SpamFlagsRepository::upsert`]
+    /// would one by one. Of flags for the same subject and version, only the
+    /// last is stored and kept in history.
+    async fn upsert_many(&self, flags: Vec<SpamFlagUpsert>) -> Result<()>;
     async fn list_for_subject(
         &self,
         subject_type: &str,
         subject_id: i64,
     ) -> Result<Vec<SpamFlagRow>>;
+    async fn list_history_for_subject(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+    ) -> Result<Vec<SpamFlagRow>>;
+    /// Records a reviewer verdict; returns `None` when the flag does not exist.
+    async fn review(&self, review: SpamFlagReview) -> Result<Option<SpamFlagRow>>;
+    /// Latest flags across all repositories, newest first.
+    async fn recent_flags(
+        &self,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<RecentFlagRow>>;
+    /// Ranks authors by summed flag score, ties broken by flag count then
+    /// login. With `half_life_days`, each flag counts
+    /// `score * 0.5^(age / half_life)` so recent activity outranks old.
+    /// `after` continues from a row of a previous page. Without `since` or
+    /// `half_life_days` the ranking is served from the aggregate as of the
+    /// last [`SpamFlagsRepository::refresh_spammy_user_totals`].
     async fn top_spammy_users(
         &self,
         since: Option<DateTime<Utc>>,
         limit: i64,
+        half_life_days: Option<f64>,
+        after: Option<SpammyUserCursor>,
     ) -> Result<Vec<ActorSpamSummary>>;
+    /// Brings the all-time per-author aggregate behind `top_spammy_users`
+    /// (without `since` or `half_life_days`) up to date, recomputing only
+    /// authors with flags written since the last refresh. Returns how many
+    /// author rows were rewritten. Blocks flag upserts while it runs, so the
+    /// collector calls it once per run rather than readers per request.
+    async fn refresh_spammy_user_totals(&self) -> Result<u64>;
+    /// Flag counts and summed scores of one repository's issues and comments
+    /// per `bucket`, oldest first. Empty buckets are omitted and flags marked
+    /// `false_positive` are skipped.
+    async fn score_timeseries(
+        &self,
+        repo_full_name: &str,
+        bucket: TrendBucket,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ScoreBucket>>;
+    /// Groups issues and comments by `dedupe_hash`, keeping groups of at least
+    /// `min_size`, largest first.
+    async fn dedupe_clusters(&self, min_size: i64, limit: i64) -> Result<Vec<DedupeCluster>>;
+    /// Stores the features of a clean subject, replacing an earlier sample.
+    async fn upsert_feature_sample(&self, sample: FeatureSampleUpsert) -> Result<()>;
+    async fn list_feature_samples(
+        &self,
+        subject_type: &str,
+        limit: i64,
+    ) -> Result<Vec<FeatureSampleRow>>;
+    /// Stores the edit history summary of a subject, replacing an earlier one.
+    async fn upsert_content_edits(&self, edits: ContentEditsUpsert) -> Result<()>;
+    async fn get_content_edits(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+    ) -> Result<Option<ContentEditsRow>>;
+    /// Replaces the stored reactions of a subject with `reactions`.
+    async fn replace_reactions(
+        &self,
+        subject_type: &str,
+        subject_id: i64,
+        reactions: Vec<ReactionRow>,
+    ) -> Result<()>;
+    async fn list_reactions(&self, subject_type: &str, subject_id: i64)
+        -> Result<Vec<ReactionRow>>;
 }
 
 #[async_trait]
 pub trait CollectionJobRepository: Send + Sync {
     async fn create(&self, job: CollectionJobCreate) -> Result<CollectionJobRow>;
-    async fn get_pending(&self, limit: i32) -> Result<Vec<CollectionJobRow>>;
+    /// Up to `limit` pending jobs, highest priority first; only the job for
+    /// `full_name` (case-insensitive) when given, wherever it ranks.
+    async fn get_pending(
+        &self,
+        limit: i32,
+        full_name: Option<&str>,
+    ) -> Result<Vec<CollectionJobRow>>;
     async fn mark_in_progress(&self, id: i64) -> Result<()>;
     async fn update(&self, update: CollectionJobUpdate) -> Result<()>;
-    async fn list(&self, limit: i32) -> Result<Vec<CollectionJobRow>>;
+    /// Points the job for `owner/name` at a renamed repository. Returns `false`
+    /// when no such job exists or another job already uses the new name.
+    async fn rename(
+        &self,
+        owner: &str,
+        name: &str,
+        new_owner: &str,
+        new_name: &str,
+    ) -> Result<bool>;
+    async fn list(
+        &self,
+        limit: i32,
+        statuses: &[CollectionStatus],
+    ) -> Result<Vec<CollectionJobRow>>;
 }
 
 pub trait Repositories: Send + Sync {
@@ -0,0 +1,79 @@
+use chrono::Utc;
+use db::models::CommentRow;
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+#[tokio::test]
+async fn author_association_round_trips() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping author_association_round_trips: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("author_association").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    database
+        .issues()
+        .upsert(IssueRow {
+            id: 10,
+            repo_id: 1,
+            number: 1,
+            is_pull_request: false,
+            state: "open".into(),
+            title: "Question".into(),
+            body: None,
+            user_id: None,
+            comments_count: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: Some("FIRST_TIME_CONTRIBUTOR".into()),
+            dedupe_hash: "issue-10".into(),
+            raw: json!({}),
+            found: true,
+        })
+        .await?;
+    database
+        .comments()
+        .upsert(CommentRow {
+            id: 100,
+            issue_id: 10,
+            user_id: None,
+            body: "Answered in the docs.".into(),
+            created_at: Utc::now(),
+            updated_at: None,
+            author_association: Some("MEMBER".into()),
+            dedupe_hash: "comment-100".into(),
+            raw: json!({}),
+            found: true,
+        })
+        .await?;
+
+    let issues = database.issues().list_by_repo(1, None).await?;
+    assert_eq!(
+        issues[0].author_association.as_deref(),
+        Some("FIRST_TIME_CONTRIBUTOR")
+    );
+    let comments = database.comments().list_by_issue(10).await?;
+    assert_eq!(comments[0].author_association.as_deref(), Some("MEMBER"));
+
+    handle.cleanup().await?;
+    Ok(())
+}
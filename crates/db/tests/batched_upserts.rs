@@ -0,0 +1,156 @@
+use chrono::{Duration, TimeZone, Utc};
+use db::models::{CommentRow, SpamFlagUpsert};
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+fn comment(id: i64, body: &str) -> CommentRow {
+    CommentRow {
+        id,
+        issue_id: 10,
+        user_id: None,
+        body: body.into(),
+        created_at: Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap() + Duration::minutes(id),
+        updated_at: None,
+        author_association: Some("NONE".into()),
+        dedupe_hash: format!("comment-{id}"),
+        raw: json!({ "id": id }),
+        found: true,
+    }
+}
+
+fn flag(subject_id: i64, score: f32, reasons: &[&str]) -> SpamFlagUpsert {
+    SpamFlagUpsert {
+        subject_type: "comment".into(),
+        subject_id,
+        score,
+        reasons: reasons.iter().map(|reason| reason.to_string()).collect(),
+        version: "rules_v1".into(),
+        features: None,
+    }
+}
+
+#[tokio::test]
+async fn upsert_many_matches_single_upserts() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping upsert_many_matches_single_upserts: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("batched_upserts").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    database
+        .issues()
+        .upsert(IssueRow {
+            id: 10,
+            repo_id: 1,
+            number: 1,
+            is_pull_request: false,
+            state: "open".into(),
+            title: "Question".into(),
+            body: None,
+            user_id: None,
+            comments_count: 3,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: "issue-10".into(),
+            raw: json!({}),
+            found: true,
+        })
+        .await?;
+
+    database.comments().upsert_many(Vec::new()).await?;
+    database
+        .comments()
+        .upsert_many(vec![comment(100, "first"), comment(101, "second")])
+        .await?;
+    // Updates an existing row, and of two rows for one id the later wins.
+    database
+        .comments()
+        .upsert_many(vec![
+            comment(101, "edited"),
+            comment(102, "third"),
+            comment(101, "edited again"),
+        ])
+        .await?;
+    let stored: Vec<_> = database
+        .comments()
+        .list_by_issue(10)
+        .await?
+        .into_iter()
+        .map(|comment| (comment.id, comment.body, comment.author_association))
+        .collect();
+    assert_eq!(
+        stored
+            .iter()
+            .map(|(id, body, _)| (*id, body.as_str()))
+            .collect::<Vec<_>>(),
+        vec![(100, "first"), (101, "edited again"), (102, "third")]
+    );
+    assert!(stored
+        .iter()
+        .all(|(_, _, association)| association.as_deref() == Some("NONE")));
+
+    database.spam_flags().upsert_many(Vec::new()).await?;
+    database
+        .spam_flags()
+        .upsert_many(vec![
+            flag(100, 1.0, &["contact_only"]),
+            flag(101, 2.0, &[]),
+            flag(100, 1.5, &["contact_only", "link_heavy"]),
+        ])
+        .await?;
+    database
+        .spam_flags()
+        .upsert_many(vec![flag(101, 3.0, &["dedupe_hit"])])
+        .await?;
+
+    let first = database
+        .spam_flags()
+        .list_for_subject("comment", 100)
+        .await?;
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0].score, 1.5);
+    assert_eq!(first[0].reasons, vec!["contact_only", "link_heavy"]);
+    let second = database
+        .spam_flags()
+        .list_for_subject("comment", 101)
+        .await?;
+    assert_eq!(second[0].score, 3.0);
+    assert_eq!(second[0].reasons, vec!["dedupe_hit"]);
+
+    // History keeps one entry per statement a flag was written in.
+    let history = database
+        .spam_flags()
+        .list_history_for_subject("comment", 100)
+        .await?;
+    assert_eq!(history.len(), 1);
+    let history = database
+        .spam_flags()
+        .list_history_for_subject("comment", 101)
+        .await?;
+    let mut reasons: Vec<_> = history.iter().map(|flag| flag.reasons.clone()).collect();
+    reasons.sort();
+    assert_eq!(reasons, vec![Vec::new(), vec!["dedupe_hit".to_string()]]);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use db::models::{CollectionJobCreate, CollectionJobUpdate, CollectionStatus};
+use db::{pg::PgDatabase, Repositories};
+use db_test_fixture::DbFixture;
+
+#[tokio::test]
+async fn list_filters_jobs_by_status() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping list_filters_jobs_by_status: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("job_filter").await?;
+
+    let database = PgDatabase::connect(handle.database_url()).await?;
+    let jobs = database.collection_jobs();
+
+    let mut ids = Vec::new();
+    for name in ["pending", "done", "broken", "running"] {
+        let job = jobs
+            .create(CollectionJobCreate {
+                owner: "o".into(),
+                name: name.into(),
+                priority: 0,
+            })
+            .await?;
+        ids.push(job.id);
+    }
+    jobs.update(CollectionJobUpdate {
+        id: ids[1],
+        status: CollectionStatus::Completed,
+        error_message: None,
+    })
+    .await?;
+    jobs.update(CollectionJobUpdate {
+        id: ids[2],
+        status: CollectionStatus::Error,
+        error_message: Some("not found".into()),
+    })
+    .await?;
+    jobs.mark_in_progress(ids[3]).await?;
+
+    let all = jobs.list(10, &[]).await?;
+    assert_eq!(all.len(), 4);
+
+    let errored = jobs.list(10, &[CollectionStatus::Error]).await?;
+    assert_eq!(errored.len(), 1);
+    assert_eq!(errored[0].full_name, "o/broken");
+
+    let mut active: Vec<String> = jobs
+        .list(
+            10,
+            &[CollectionStatus::Pending, CollectionStatus::InProgress],
+        )
+        .await?
+        .into_iter()
+        .map(|job| job.full_name)
+        .collect();
+    active.sort();
+    assert_eq!(active, vec!["o/pending", "o/running"]);
+
+    drop(database);
+    handle.cleanup().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_pending_finds_a_named_job_below_the_limit() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping get_pending_finds_a_named_job_below_the_limit: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("job_pending_name").await?;
+
+    let database = PgDatabase::connect(handle.database_url()).await?;
+    let jobs = database.collection_jobs();
+    for (name, priority) in [("urgent", 10), ("busy", 5), ("Target", 0)] {
+        jobs.create(CollectionJobCreate {
+            owner: "o".into(),
+            name: name.into(),
+            priority,
+        })
+        .await?;
+    }
+
+    let top: Vec<_> = jobs
+        .get_pending(2, None)
+        .await?
+        .into_iter()
+        .map(|job| job.full_name)
+        .collect();
+    assert_eq!(top, vec!["o/urgent", "o/busy"]);
+
+    let named = jobs.get_pending(1, Some("O/target")).await?;
+    assert_eq!(named.len(), 1);
+    assert_eq!(named[0].full_name, "o/Target");
+    assert!(jobs.get_pending(1, Some("o/missing")).await?.is_empty());
+
+    drop(database);
+    handle.cleanup().await?;
+    Ok(())
+}
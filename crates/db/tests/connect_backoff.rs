@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use db::pg::ConnectRetry;
+
+#[test]
+fn connect_delay_doubles_within_jitter_bounds() {
+    let retry = ConnectRetry {
+        attempts: 5,
+        base_delay: Duration::from_millis(500),
+        jitter_frac: 0.5,
+    };
+    for (attempt, nominal_ms) in [(1, 500u64), (2, 1_000), (3, 2_000), (4, 4_000)] {
+        let nominal = Duration::from_millis(nominal_ms);
+        let delays: Vec<Duration> = (0..200).map(|_| retry.delay(attempt)).collect();
+        for delay in &delays {
+            assert!(
+                *delay >= nominal / 2,
+                "attempt {attempt}: {delay:?} too short"
+            );
+            assert!(
+                *delay <= nominal * 3 / 2,
+                "attempt {attempt}: {delay:?} too long"
+            );
+        }
+        // Replicas must not all pick the same instant.
+        assert!(delays.iter().any(|d| *d != delays[0]));
+    }
+}
+
+#[test]
+fn connect_delay_caps_doubling_and_honours_zero_jitter() {
+    let retry = ConnectRetry {
+        jitter_frac: 0.0,
+        ..Default::default()
+    };
+    assert_eq!(retry.delay(1), Duration::from_millis(500));
+    assert_eq!(retry.delay(6), Duration::from_millis(16_000));
+    assert_eq!(retry.delay(30), Duration::from_millis(16_000));
+}
@@ -0,0 +1,75 @@
+use chrono::Utc;
+use db::metrics::{DB_OPERATION_DURATION, DB_OPERATION_ERRORS};
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+#[tokio::test]
+async fn repository_calls_record_latency_and_errors() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping repository_calls_record_latency_and_errors: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("db_metrics").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    let upserts = DB_OPERATION_DURATION.with_label_values(&["repos.upsert"]);
+    let upserts_before = upserts.get_sample_count();
+    let upsert_errors_before = DB_OPERATION_ERRORS
+        .with_label_values(&["repos.upsert"])
+        .get();
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    assert_eq!(upserts.get_sample_count(), upserts_before + 1);
+    assert_eq!(
+        DB_OPERATION_ERRORS
+            .with_label_values(&["repos.upsert"])
+            .get(),
+        upsert_errors_before
+    );
+
+    // Unknown repo_id violates the foreign key.
+    let issue_errors = DB_OPERATION_ERRORS.with_label_values(&["issues.upsert"]);
+    let issue_errors_before = issue_errors.get();
+    let result = database
+        .issues()
+        .upsert(IssueRow {
+            id: 10,
+            repo_id: 999,
+            number: 1,
+            is_pull_request: false,
+            state: "open".into(),
+            title: "orphan".into(),
+            body: None,
+            user_id: None,
+            comments_count: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+            dedupe_hash: "hash-10".into(),
+            raw: json!({}),
+            found: true,
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(issue_errors.get(), issue_errors_before + 1);
+
+    drop(database);
+    handle.cleanup().await?;
+    Ok(())
+}
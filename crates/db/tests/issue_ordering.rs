@@ -0,0 +1,99 @@
+use chrono::{Duration, Utc};
+use db::models::{IssueOrder, IssueQuery, SpamFlagUpsert};
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+/// `age_days` ago for creation; updates land in the reverse order.
+fn issue(id: i64, age_days: i64) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id: 1,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("issue {id}"),
+        body: None,
+        user_id: None,
+        comments_count: 0,
+        created_at: Utc::now() - Duration::days(age_days),
+        updated_at: Utc::now() - Duration::days(10 - age_days),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("hash-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+#[tokio::test]
+async fn issue_query_honours_order_by() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping issue_query_honours_order_by: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("issue_ordering").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    // 1 is newest-created, 3 is most recently updated.
+    for (id, age_days) in [(1, 1), (2, 5), (3, 9)] {
+        database.issues().upsert(issue(id, age_days)).await?;
+    }
+    // Issue 2 has the highest flag, issue 1 a low one, issue 3 none.
+    for (subject_id, score) in [(1, 1.0), (2, 2.0), (2, 4.5)] {
+        database
+            .spam_flags()
+            .upsert(SpamFlagUpsert {
+                subject_type: "issue".into(),
+                subject_id,
+                score,
+                reasons: vec!["contact_only".into()],
+                version: format!("rules_v{score}"),
+                features: None,
+            })
+            .await?;
+    }
+
+    let ids = |order_by| {
+        let database = &database;
+        async move {
+            anyhow::Ok(
+                database
+                    .issues()
+                    .query(IssueQuery {
+                        order_by,
+                        ..Default::default()
+                    })
+                    .await?
+                    .into_iter()
+                    .map(|row| row.id)
+                    .collect::<Vec<_>>(),
+            )
+        }
+    };
+
+    assert_eq!(ids(None).await?, vec![3, 2, 1]);
+    assert_eq!(ids(Some(IssueOrder::UpdatedDesc)).await?, vec![3, 2, 1]);
+    assert_eq!(ids(Some(IssueOrder::CreatedDesc)).await?, vec![1, 2, 3]);
+    assert_eq!(ids(Some(IssueOrder::ScoreDesc)).await?, vec![2, 1, 3]);
+
+    drop(database);
+    handle.cleanup().await?;
+    Ok(())
+}
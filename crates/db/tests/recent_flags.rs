@@ -0,0 +1,153 @@
+use chrono::{Duration, Utc};
+use db::models::{CommentRow, SpamFlagUpsert};
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow, UserRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+fn issue(id: i64, title: &str, body: &str) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id: 1,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: title.into(),
+        body: Some(body.into()),
+        user_id: Some(7),
+        comments_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("issue-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn flag(subject_type: &str, subject_id: i64) -> SpamFlagUpsert {
+    SpamFlagUpsert {
+        subject_type: subject_type.into(),
+        subject_id,
+        score: 2.0,
+        reasons: vec!["contact_only".into()],
+        version: "rules_v1".into(),
+        features: None,
+    }
+}
+
+#[tokio::test]
+async fn recent_flags_are_newest_first_and_respect_since() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping recent_flags_are_newest_first_and_respect_since: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("recent_flags").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    database
+        .users()
+        .upsert(UserRow {
+            id: 7,
+            login: "spammer".into(),
+            user_type: "User".into(),
+            site_admin: false,
+            created_at: None,
+            followers: None,
+            following: None,
+            public_repos: None,
+            bio: None,
+            blog: None,
+            raw: json!({}),
+            found: true,
+        })
+        .await?;
+    database
+        .issues()
+        .upsert(issue(10, "Old", "old spam"))
+        .await?;
+    database
+        .issues()
+        .upsert(issue(11, "Newer", "newer spam"))
+        .await?;
+    database
+        .comments()
+        .upsert(CommentRow {
+            id: 100,
+            issue_id: 11,
+            user_id: Some(7),
+            body: "contact me on telegram".into(),
+            created_at: Utc::now(),
+            updated_at: None,
+            author_association: None,
+            dedupe_hash: "comment-100".into(),
+            raw: json!({}),
+            found: true,
+        })
+        .await?;
+
+    let now = Utc::now();
+    for (subject_type, subject_id, age) in [
+        ("issue", 10, Duration::days(3)),
+        ("issue", 11, Duration::hours(2)),
+        ("comment", 100, Duration::minutes(5)),
+    ] {
+        database
+            .spam_flags()
+            .upsert(flag(subject_type, subject_id))
+            .await?;
+        sqlx::query(
+            "UPDATE spam_flags SET created_at = $3 WHERE subject_type = $1 AND subject_id = $2",
+        )
+        .bind(subject_type)
+        .bind(subject_id)
+        .bind(now - age)
+        .execute(handle.pool())
+        .await?;
+    }
+
+    let feed = database.spam_flags().recent_flags(None, 10).await?;
+    let order: Vec<_> = feed
+        .iter()
+        .map(|row| (row.flag.subject_type.as_str(), row.flag.subject_id))
+        .collect();
+    assert_eq!(order, vec![("comment", 100), ("issue", 11), ("issue", 10)]);
+
+    // Comments report their parent issue alongside their own body and author.
+    let comment = &feed[0];
+    assert_eq!(comment.repo_full_name, "o/r");
+    assert_eq!(comment.issue_number, 11);
+    assert_eq!(comment.title, "Newer");
+    assert_eq!(comment.author_login.as_deref(), Some("spammer"));
+    assert_eq!(comment.snippet, "contact me on telegram");
+
+    let since = database
+        .spam_flags()
+        .recent_flags(Some(now - Duration::days(1)), 10)
+        .await?;
+    assert_eq!(since.len(), 2);
+    assert!(since.iter().all(|row| row.flag.subject_id != 10));
+
+    let limited = database.spam_flags().recent_flags(None, 1).await?;
+    assert_eq!(limited.len(), 1);
+    assert_eq!(limited[0].flag.subject_id, 100);
+
+    handle.cleanup().await?;
+    Ok(())
+}
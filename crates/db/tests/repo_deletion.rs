@@ -0,0 +1,293 @@
+use chrono::Utc;
+use db::models::{
+    CollectionJobCreate, CollectionStatus, CommentRow, ContentEditsUpsert, FeatureSampleUpsert,
+    ProgressUpdate, ReactionRow, RepoDeletion, SpamFlagUpsert, WatermarkUpdate,
+};
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow, UserRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+fn repo(id: i64, full_name: &str) -> RepositoryRow {
+    RepositoryRow {
+        id,
+        full_name: full_name.into(),
+        is_fork: false,
+        created_at: Utc::now(),
+        pushed_at: None,
+        raw: json!({}),
+    }
+}
+
+fn user(id: i64, login: &str) -> UserRow {
+    UserRow {
+        id,
+        login: login.into(),
+        user_type: "User".into(),
+        site_admin: false,
+        created_at: None,
+        followers: None,
+        following: None,
+        public_repos: None,
+        bio: None,
+        blog: None,
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn issue(id: i64, repo_id: i64, user_id: i64) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("issue {id}"),
+        body: None,
+        user_id: Some(user_id),
+        comments_count: 1,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("issue-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn comment(id: i64, issue_id: i64, user_id: i64) -> CommentRow {
+    CommentRow {
+        id,
+        issue_id,
+        user_id: Some(user_id),
+        body: format!("comment {id}"),
+        created_at: Utc::now(),
+        updated_at: None,
+        author_association: None,
+        dedupe_hash: format!("comment-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn flag(subject_type: &str, subject_id: i64, score: f32) -> SpamFlagUpsert {
+    SpamFlagUpsert {
+        subject_type: subject_type.into(),
+        subject_id,
+        score,
+        reasons: vec!["contact_only".into()],
+        version: "rules_v1".into(),
+        features: None,
+    }
+}
+
+/// Seeds issues, comments and every per-subject table for `repo_id`.
+async fn seed_graph(
+    database: &PgDatabase,
+    repo_id: i64,
+    full_name: &str,
+    issue_author: i64,
+    comment_author: i64,
+) -> anyhow::Result<()> {
+    let issue_id = repo_id * 10;
+    let comment_id = repo_id * 100;
+    database.repos().upsert(repo(repo_id, full_name)).await?;
+    database
+        .issues()
+        .upsert(issue(issue_id, repo_id, issue_author))
+        .await?;
+    database
+        .comments()
+        .upsert(comment(comment_id, issue_id, comment_author))
+        .await?;
+    let spam_flags = database.spam_flags();
+    spam_flags.upsert(flag("issue", issue_id, 2.0)).await?;
+    spam_flags.upsert(flag("comment", comment_id, 1.0)).await?;
+    spam_flags
+        .upsert_feature_sample(FeatureSampleUpsert {
+            subject_type: "comment".into(),
+            subject_id: comment_id,
+            features: json!({}),
+            version: "rules_v1".into(),
+        })
+        .await?;
+    spam_flags
+        .upsert_content_edits(ContentEditsUpsert {
+            subject_type: "issue".into(),
+            subject_id: issue_id,
+            edit_count: 2,
+            last_edited_at: None,
+            last_editor_login: None,
+        })
+        .await?;
+    spam_flags
+        .replace_reactions(
+            "issue",
+            issue_id,
+            vec![ReactionRow {
+                user_login: "booster".into(),
+                content: "THUMBS_UP".into(),
+                user_created_at: None,
+            }],
+        )
+        .await?;
+    database
+        .watermarks()
+        .set(WatermarkUpdate {
+            repo_full_name: full_name.into(),
+            last_updated: Utc::now(),
+        })
+        .await?;
+    database
+        .watermarks()
+        .set_progress(ProgressUpdate {
+            repo_full_name: full_name.into(),
+            cursor: "page-2".into(),
+            newest_ts: None,
+        })
+        .await?;
+    let (owner, name) = full_name.split_once('/').unwrap();
+    database
+        .collection_jobs()
+        .create(CollectionJobCreate {
+            owner: owner.into(),
+            name: name.into(),
+            priority: 0,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Rows of per-subject tables whose issue or comment no longer exists.
+async fn orphans(database: &PgDatabase) -> anyhow::Result<i64> {
+    let mut total = 0;
+    for table in [
+        "spam_flags",
+        "spam_flag_history",
+        "feature_samples",
+        "content_edits",
+        "reactions",
+    ] {
+        let count: i64 = sqlx::query_scalar(&format!(
+            r#"
+            SELECT COUNT(*) FROM {table} t
+            WHERE NOT EXISTS (
+                SELECT 1 FROM issues i WHERE t.subject_type = 'issue' AND i.id = t.subject_id
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM comments c WHERE t.subject_type = 'comment' AND c.id = t.subject_id
+            )
+            "#
+        ))
+        .fetch_one(database.pool())
+        .await?;
+        total += count;
+    }
+    Ok(total)
+}
+
+#[tokio::test]
+async fn delete_repo_removes_its_graph_and_keeps_users() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping delete_repo_removes_its_graph_and_keeps_users: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("repo_deletion").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    for (user_id, login) in [(1, "alice"), (2, "bob")] {
+        database.users().upsert(user(user_id, login)).await?;
+    }
+    // alice posts in both repositories, bob only in the deleted one.
+    seed_graph(&database, 1, "acme/widgets", 2, 1).await?;
+    seed_graph(&database, 2, "acme/gadgets", 1, 1).await?;
+    database.spam_flags().refresh_spammy_user_totals().await?;
+    let before = database
+        .spam_flags()
+        .top_spammy_users(None, 10, None, None)
+        .await?;
+    assert_eq!(before.len(), 2);
+
+    let deletion = database.repos().delete_repo("acme/widgets", false).await?;
+    assert_eq!(
+        deletion,
+        RepoDeletion {
+            repository: true,
+            issues: 1,
+            comments: 1,
+            spam_flags: 2,
+            watermark: true,
+            collection_job: false,
+        }
+    );
+
+    assert!(database
+        .repos()
+        .get_by_full_name("acme/widgets")
+        .await?
+        .is_none());
+    assert_eq!(orphans(&database).await?, 0);
+    assert!(database.watermarks().get("acme/widgets").await?.is_none());
+    assert!(database
+        .watermarks()
+        .get_progress("acme/widgets")
+        .await?
+        .is_none());
+    assert!(database.users().get_by_login("bob").await?.is_some());
+    let jobs = database
+        .collection_jobs()
+        .list(10, &[CollectionStatus::Pending])
+        .await?;
+    assert_eq!(jobs.len(), 2);
+
+    // The other repository is untouched.
+    assert_eq!(database.issues().list_missing(2, 10).await?.len(), 0);
+    assert_eq!(database.comments().list_by_issue(20).await?.len(), 1);
+    assert_eq!(
+        database
+            .spam_flags()
+            .list_for_subject("comment", 200)
+            .await?
+            .len(),
+        1
+    );
+    assert!(database
+        .spam_flags()
+        .get_content_edits("issue", 20)
+        .await?
+        .is_some());
+    assert!(database.watermarks().get("acme/gadgets").await?.is_some());
+
+    // The cached ranking drops bob and keeps only alice's remaining flags.
+    let after = database
+        .spam_flags()
+        .top_spammy_users(None, 10, None, None)
+        .await?;
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0].login, "alice");
+    assert_eq!(after[0].total_score, 3.0);
+
+    // Purging also drops the job; a second delete finds nothing.
+    let deletion = database.repos().delete_repo("acme/widgets", true).await?;
+    assert_eq!(
+        deletion,
+        RepoDeletion {
+            collection_job: true,
+            ..Default::default()
+        }
+    );
+    assert!(database
+        .repos()
+        .delete_repo("acme/widgets", true)
+        .await?
+        .is_empty());
+
+    handle.cleanup().await?;
+    Ok(())
+}
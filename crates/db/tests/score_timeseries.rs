@@ -0,0 +1,133 @@
+use chrono::{Duration, TimeZone, Utc};
+use db::models::{CommentRow, SpamFlagUpsert, TrendBucket};
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+fn repo(id: i64, full_name: &str) -> RepositoryRow {
+    RepositoryRow {
+        id,
+        full_name: full_name.into(),
+        is_fork: false,
+        created_at: Utc::now(),
+        pushed_at: None,
+        raw: json!({}),
+    }
+}
+
+fn issue(id: i64, repo_id: i64) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("Issue {id}"),
+        body: Some("spam".into()),
+        user_id: None,
+        comments_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("issue-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn flag(subject_type: &str, subject_id: i64, score: f32) -> SpamFlagUpsert {
+    SpamFlagUpsert {
+        subject_type: subject_type.into(),
+        subject_id,
+        score,
+        reasons: vec!["contact_only".into()],
+        version: "rules_v1".into(),
+        features: None,
+    }
+}
+
+#[tokio::test]
+async fn score_timeseries_buckets_flags_of_one_repo() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping score_timeseries_buckets_flags_of_one_repo: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("score_timeseries").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    database.repos().upsert(repo(1, "o/r")).await?;
+    database.repos().upsert(repo(2, "o/other")).await?;
+    for (id, repo_id) in [(10, 1), (11, 1), (20, 2)] {
+        database.issues().upsert(issue(id, repo_id)).await?;
+    }
+    database
+        .comments()
+        .upsert(CommentRow {
+            id: 100,
+            issue_id: 11,
+            user_id: None,
+            body: "contact me on telegram".into(),
+            created_at: Utc::now(),
+            updated_at: None,
+            author_association: None,
+            dedupe_hash: "comment-100".into(),
+            raw: json!({}),
+            found: true,
+        })
+        .await?;
+
+    let day_one = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+    let day_two = Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap();
+    for (subject_type, subject_id, score, created_at) in [
+        ("issue", 10, 2.0, day_one + Duration::hours(10)),
+        ("issue", 11, 1.5, day_two + Duration::hours(9)),
+        ("comment", 100, 3.0, day_two + Duration::hours(15)),
+        // Another repository's flag stays out of the series.
+        ("issue", 20, 5.0, day_two + Duration::hours(9)),
+    ] {
+        database
+            .spam_flags()
+            .upsert(flag(subject_type, subject_id, score))
+            .await?;
+        sqlx::query(
+            "UPDATE spam_flags SET created_at = $3 WHERE subject_type = $1 AND subject_id = $2",
+        )
+        .bind(subject_type)
+        .bind(subject_id)
+        .bind(created_at)
+        .execute(handle.pool())
+        .await?;
+    }
+
+    let daily = database
+        .spam_flags()
+        .score_timeseries("o/r", TrendBucket::Day, None)
+        .await?;
+    let buckets: Vec<_> = daily
+        .iter()
+        .map(|bucket| (bucket.bucket_start, bucket.flag_count, bucket.total_score))
+        .collect();
+    assert_eq!(buckets, vec![(day_one, 1, 2.0), (day_two, 2, 4.5)]);
+
+    let hourly = database
+        .spam_flags()
+        .score_timeseries("o/r", TrendBucket::Hour, None)
+        .await?;
+    assert_eq!(hourly.len(), 3);
+
+    let since = database
+        .spam_flags()
+        .score_timeseries("o/r", TrendBucket::Day, Some(day_two))
+        .await?;
+    assert_eq!(since.len(), 1);
+    assert_eq!(since[0].bucket_start, day_two);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use db::{models::SpamFlagUpsert, pg::PgDatabase, Repositories};
+use db_test_fixture::DbFixture;
+
+fn flag(score: f32, reasons: &[&str]) -> SpamFlagUpsert {
+    SpamFlagUpsert {
+        subject_type: "issue".into(),
+        subject_id: 7,
+        score,
+        reasons: reasons.iter().map(|r| r.to_string()).collect(),
+        version: "rules_v1".into(),
+        features: None,
+    }
+}
+
+#[tokio::test]
+async fn spam_flag_upsert_retains_history() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping spam_flag_upsert_retains_history: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("spam_flag_history").await?;
+
+    let database = PgDatabase::connect(handle.database_url()).await?;
+    let spam_flags = database.spam_flags();
+
+    spam_flags.upsert(flag(1.5, &["short_body"])).await?;
+    spam_flags
+        .upsert(flag(3.0, &["short_body", "link_heavy"]))
+        .await?;
+
+    let latest = spam_flags.list_for_subject("issue", 7).await?;
+    assert_eq!(latest.len(), 1);
+    assert_eq!(latest[0].score, 3.0);
+
+    let history = spam_flags.list_history_for_subject("issue", 7).await?;
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].score, 3.0);
+    assert_eq!(history[0].reasons, vec!["short_body", "link_heavy"]);
+    assert_eq!(history[1].score, 1.5);
+    assert_eq!(history[1].reasons, vec!["short_body"]);
+
+    drop(database);
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use chrono::Utc;
+use db::models::{IssueQuery, ReviewStatus, SpamFlagReview, SpamFlagUpsert};
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+fn issue(id: i64) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id: 1,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("issue {id}"),
+        body: None,
+        user_id: None,
+        comments_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("hash-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn flag(subject_id: i64, score: f32) -> SpamFlagUpsert {
+    SpamFlagUpsert {
+        subject_type: "issue".into(),
+        subject_id,
+        score,
+        reasons: vec!["link_heavy".into()],
+        version: "rules_v1".into(),
+        features: None,
+    }
+}
+
+#[tokio::test]
+async fn review_sets_status_and_filters_issue_queue() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping review_sets_status_and_filters_issue_queue: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("spam_flag_review").await?;
+
+    let database = PgDatabase::connect(handle.database_url()).await?;
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    for id in [10, 11, 12] {
+        database.issues().upsert(issue(id)).await?;
+        database.spam_flags().upsert(flag(id, 3.0)).await?;
+    }
+
+    let flag_id = database.spam_flags().list_for_subject("issue", 10).await?[0].id;
+    let reviewed = database
+        .spam_flags()
+        .review(SpamFlagReview {
+            id: flag_id,
+            status: ReviewStatus::Confirmed,
+            reviewed_by: Some("alice".into()),
+        })
+        .await?
+        .expect("flag exists");
+    assert_eq!(reviewed.reviewed_status, ReviewStatus::Confirmed);
+    assert_eq!(reviewed.reviewed_by.as_deref(), Some("alice"));
+    assert!(reviewed.reviewed_at.is_some());
+
+    // Re-scoring keeps the reviewer's verdict
+    database.spam_flags().upsert(flag(10, 3.5)).await?;
+    let latest = database.spam_flags().list_for_subject("issue", 10).await?;
+    assert_eq!(latest[0].reviewed_status, ReviewStatus::Confirmed);
+
+    let missing = database
+        .spam_flags()
+        .review(SpamFlagReview {
+            id: -1,
+            status: ReviewStatus::FalsePositive,
+            reviewed_by: None,
+        })
+        .await?;
+    assert!(missing.is_none());
+
+    let mut unreviewed: Vec<i64> = database
+        .issues()
+        .query(IssueQuery {
+            reviewed: Some(ReviewStatus::Unreviewed),
+            ..Default::default()
+        })
+        .await?
+        .into_iter()
+        .map(|row| row.id)
+        .collect();
+    unreviewed.sort();
+    assert_eq!(unreviewed, vec![11, 12]);
+
+    let confirmed: Vec<i64> = database
+        .issues()
+        .query(IssueQuery {
+            reviewed: Some(ReviewStatus::Confirmed),
+            spam: Some(db::models::SpamFilter::Likely),
+            ..Default::default()
+        })
+        .await?
+        .into_iter()
+        .map(|row| row.id)
+        .collect();
+    assert_eq!(confirmed, vec![10]);
+
+    drop(database);
+    handle.cleanup().await?;
+    Ok(())
+}
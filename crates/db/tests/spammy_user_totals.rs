@@ -0,0 +1,190 @@
+use chrono::{TimeZone, Utc};
+use db::models::{ActorSpamSummary, CommentRow, SpamFlagUpsert};
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow, UserRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+fn user(id: i64, login: &str) -> UserRow {
+    UserRow {
+        id,
+        login: login.into(),
+        user_type: "User".into(),
+        site_admin: false,
+        created_at: None,
+        followers: None,
+        following: None,
+        public_repos: None,
+        bio: None,
+        blog: None,
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn issue(id: i64, user_id: i64) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id: 1,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("issue {id}"),
+        body: None,
+        user_id: Some(user_id),
+        comments_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("issue-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn comment(id: i64, issue_id: i64, user_id: i64) -> CommentRow {
+    CommentRow {
+        id,
+        issue_id,
+        user_id: Some(user_id),
+        body: format!("comment {id}"),
+        created_at: Utc::now(),
+        updated_at: None,
+        author_association: None,
+        dedupe_hash: format!("comment-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn flag(subject_type: &str, subject_id: i64, score: f32, reasons: &[&str]) -> SpamFlagUpsert {
+    SpamFlagUpsert {
+        subject_type: subject_type.into(),
+        subject_id,
+        score,
+        reasons: reasons.iter().map(|reason| reason.to_string()).collect(),
+        version: "rules_v1".into(),
+        features: None,
+    }
+}
+
+fn ranking(summaries: Vec<ActorSpamSummary>) -> Vec<(String, f32, i64, Vec<String>)> {
+    summaries
+        .into_iter()
+        .map(|summary| {
+            let mut reasons = summary.reasons;
+            reasons.sort();
+            (
+                summary.login,
+                summary.total_score,
+                summary.flag_count,
+                reasons,
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn incremental_refresh_matches_full_recompute() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping incremental_refresh_matches_full_recompute: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("spammy_user_totals").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+    // Every flag is newer than this, so `since` forces the live recompute.
+    let epoch = Some(Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap());
+
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    for (user_id, login) in [(1, "alice"), (2, "bob"), (3, "carol")] {
+        database.users().upsert(user(user_id, login)).await?;
+    }
+    for (issue_id, user_id) in [(10, 1), (20, 2), (30, 3)] {
+        database.issues().upsert(issue(issue_id, user_id)).await?;
+    }
+    database.comments().upsert(comment(100, 10, 2)).await?;
+    database
+        .spam_flags()
+        .upsert_many(vec![
+            flag("issue", 10, 2.0, &["contact_only"]),
+            flag("issue", 20, 1.0, &["link_heavy"]),
+        ])
+        .await?;
+
+    // The first refresh builds the aggregate from every flag.
+    assert_eq!(database.spam_flags().refresh_spammy_user_totals().await?, 2);
+    assert_eq!(database.spam_flags().refresh_spammy_user_totals().await?, 0);
+
+    // New flags, a rescored flag and a first flag for carol.
+    database
+        .spam_flags()
+        .upsert(flag("comment", 100, 1.5, &["contact_only", "dedupe_hit"]))
+        .await?;
+    database
+        .spam_flags()
+        .upsert(flag("issue", 10, 3.0, &["contact_only"]))
+        .await?;
+    database
+        .spam_flags()
+        .upsert(flag("issue", 30, 0.5, &["new_account"]))
+        .await?;
+
+    // Only the authors of those flags are recomputed.
+    assert_eq!(database.spam_flags().refresh_spammy_user_totals().await?, 3);
+    let cached = database
+        .spam_flags()
+        .top_spammy_users(None, 10, None, None)
+        .await?;
+    let full = database
+        .spam_flags()
+        .top_spammy_users(epoch, 10, None, None)
+        .await?;
+    assert_eq!(ranking(cached.clone()), ranking(full));
+    assert_eq!(
+        cached
+            .iter()
+            .map(|summary| summary.login.as_str())
+            .collect::<Vec<_>>(),
+        vec!["bob", "alice", "carol"]
+    );
+
+    // Reads serve the aggregate as of the last refresh.
+    database
+        .spam_flags()
+        .upsert(flag("issue", 30, 6.0, &["new_account"]))
+        .await?;
+    let stale = database
+        .spam_flags()
+        .top_spammy_users(None, 10, None, None)
+        .await?;
+    assert_eq!(stale[0].login, "bob");
+    assert_eq!(database.spam_flags().refresh_spammy_user_totals().await?, 1);
+    let cached = database
+        .spam_flags()
+        .top_spammy_users(None, 10, None, None)
+        .await?;
+    assert_eq!(cached[0].login, "carol");
+    let full = database
+        .spam_flags()
+        .top_spammy_users(epoch, 10, None, None)
+        .await?;
+    assert_eq!(ranking(cached), ranking(full));
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use chrono::{Duration, Utc};
+use db::models::SpamFlagUpsert;
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow, UserRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+fn user(id: i64, login: &str) -> UserRow {
+    UserRow {
+        id,
+        login: login.into(),
+        user_type: "User".into(),
+        site_admin: false,
+        created_at: None,
+        followers: None,
+        following: None,
+        public_repos: None,
+        bio: None,
+        blog: None,
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn issue(id: i64, user_id: i64) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id: 1,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("issue {id}"),
+        body: None,
+        user_id: Some(user_id),
+        comments_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("issue-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+#[tokio::test]
+async fn decay_ranks_recent_spammer_above_old_heavy_one() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping decay_ranks_recent_spammer_above_old_heavy_one: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("top_spammy_decay").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    database.users().upsert(user(1, "old_heavy")).await?;
+    database.users().upsert(user(2, "recent_light")).await?;
+    database.issues().upsert(issue(10, 1)).await?;
+    database.issues().upsert(issue(20, 2)).await?;
+
+    for (subject_id, score) in [(10, 10.0), (20, 3.0)] {
+        database
+            .spam_flags()
+            .upsert(SpamFlagUpsert {
+                subject_type: "issue".into(),
+                subject_id,
+                score,
+                reasons: vec!["contact_only".into()],
+                version: "rules_v1".into(),
+                features: None,
+            })
+            .await?;
+    }
+    sqlx::query("UPDATE spam_flags SET created_at = $1 WHERE subject_id = 10")
+        .bind(Utc::now() - Duration::days(60))
+        .execute(database.pool())
+        .await?;
+    database.spam_flags().refresh_spammy_user_totals().await?;
+
+    let plain = database
+        .spam_flags()
+        .top_spammy_users(None, 10, None, None)
+        .await?;
+    let logins: Vec<&str> = plain.iter().map(|s| s.login.as_str()).collect();
+    assert_eq!(logins, vec!["old_heavy", "recent_light"]);
+    assert_eq!(plain[0].total_score, 10.0);
+
+    let decayed = database
+        .spam_flags()
+        .top_spammy_users(None, 10, Some(7.0), None)
+        .await?;
+    let logins: Vec<&str> = decayed.iter().map(|s| s.login.as_str()).collect();
+    assert_eq!(logins, vec!["recent_light", "old_heavy"]);
+    // 60 days is ~8.6 half-lives, so the old flag is worth a few hundredths.
+    assert!(decayed[1].total_score < 0.1);
+    assert!((decayed[0].total_score - 3.0).abs() < 0.01);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use chrono::Utc;
+use db::models::SpamFlagUpsert;
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow, UserRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+fn user(id: i64, login: &str) -> UserRow {
+    UserRow {
+        id,
+        login: login.into(),
+        user_type: "User".into(),
+        site_admin: false,
+        created_at: None,
+        followers: None,
+        following: None,
+        public_repos: None,
+        bio: None,
+        blog: None,
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn issue(id: i64, user_id: i64) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id: 1,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("issue {id}"),
+        body: None,
+        user_id: Some(user_id),
+        comments_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("issue-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+#[tokio::test]
+async fn tied_scores_order_by_flag_count_then_login_and_page_stably() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping tied_scores_order_by_flag_count_then_login_and_page_stably: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("top_spammy_ordering").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    // carol, bob and alice all total 4.0; alice got there with two flags.
+    for (user_id, login) in [(1, "carol"), (2, "bob"), (3, "alice"), (4, "dave")] {
+        database.users().upsert(user(user_id, login)).await?;
+    }
+    for (issue_id, user_id, score) in [
+        (10, 1, 4.0),
+        (20, 2, 4.0),
+        (30, 3, 2.0),
+        (31, 3, 2.0),
+        (40, 4, 5.0),
+    ] {
+        database.issues().upsert(issue(issue_id, user_id)).await?;
+        database
+            .spam_flags()
+            .upsert(SpamFlagUpsert {
+                subject_type: "issue".into(),
+                subject_id: issue_id,
+                score,
+                reasons: vec!["contact_only".into()],
+                version: "rules_v1".into(),
+                features: None,
+            })
+            .await?;
+    }
+
+    database.spam_flags().refresh_spammy_user_totals().await?;
+
+    let expected = vec!["dave", "alice", "bob", "carol"];
+    for _ in 0..3 {
+        let all = database
+            .spam_flags()
+            .top_spammy_users(None, 10, None, None)
+            .await?;
+        let logins: Vec<&str> = all.iter().map(|s| s.login.as_str()).collect();
+        assert_eq!(logins, expected);
+    }
+
+    // Paging two at a time walks the same order without gaps or repeats.
+    let mut paged = Vec::new();
+    let mut after = None;
+    loop {
+        let page = database
+            .spam_flags()
+            .top_spammy_users(None, 2, None, after)
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = Some(last.cursor());
+        paged.extend(page.into_iter().map(|s| s.login));
+    }
+    assert_eq!(paged, expected);
+
+    handle.cleanup().await?;
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use chrono::{Duration, SubsecRound, Utc};
+use db::models::{CommentRow, ReviewStatus, SpamFlagReview, SpamFlagUpsert, UserActivitySummary};
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow, UserRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+fn user(id: i64, login: &str) -> UserRow {
+    UserRow {
+        id,
+        login: login.into(),
+        user_type: "User".into(),
+        site_admin: false,
+        created_at: None,
+        followers: None,
+        following: None,
+        public_repos: None,
+        bio: None,
+        blog: None,
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn issue(id: i64, user_id: i64, created_at: chrono::DateTime<Utc>) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id: 1,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("issue {id}"),
+        body: None,
+        user_id: Some(user_id),
+        comments_count: 0,
+        created_at,
+        updated_at: created_at,
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("issue-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn comment(id: i64, issue_id: i64, user_id: i64, created_at: chrono::DateTime<Utc>) -> CommentRow {
+    CommentRow {
+        id,
+        issue_id,
+        user_id: Some(user_id),
+        body: "comment".into(),
+        created_at,
+        updated_at: None,
+        author_association: None,
+        dedupe_hash: format!("comment-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn flag(subject_type: &str, subject_id: i64) -> SpamFlagUpsert {
+    SpamFlagUpsert {
+        subject_type: subject_type.into(),
+        subject_id,
+        score: 2.0,
+        reasons: vec!["contact_only".into()],
+        version: "rules_v1".into(),
+        features: None,
+    }
+}
+
+#[tokio::test]
+async fn activity_summary_counts_posts_and_flags_per_user() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping activity_summary_counts_posts_and_flags_per_user: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("user_activity_summary").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    database.users().upsert(user(1, "spammer")).await?;
+    database.users().upsert(user(2, "maintainer")).await?;
+
+    let first = (Utc::now() - Duration::days(3)).trunc_subsecs(0);
+    let last = (Utc::now() - Duration::hours(1)).trunc_subsecs(0);
+    database.issues().upsert(issue(10, 1, first)).await?;
+    database
+        .issues()
+        .upsert(issue(11, 1, first + Duration::hours(1)))
+        .await?;
+    database.issues().upsert(issue(12, 2, first)).await?;
+    database
+        .comments()
+        .upsert(comment(100, 12, 1, last))
+        .await?;
+    database
+        .comments()
+        .upsert(comment(101, 12, 1, last - Duration::minutes(5)))
+        .await?;
+    database
+        .comments()
+        .upsert(comment(102, 10, 2, last + Duration::minutes(5)))
+        .await?;
+
+    database.spam_flags().upsert(flag("issue", 10)).await?;
+    database.spam_flags().upsert(flag("comment", 100)).await?;
+    database.spam_flags().upsert(flag("comment", 101)).await?;
+    database.spam_flags().upsert(flag("issue", 12)).await?;
+    // Dismissed by a reviewer, so it no longer counts as flagged.
+    let dismissed = database
+        .spam_flags()
+        .list_for_subject("comment", 101)
+        .await?[0]
+        .id;
+    database
+        .spam_flags()
+        .review(SpamFlagReview {
+            id: dismissed,
+            status: ReviewStatus::FalsePositive,
+            reviewed_by: Some("alice".into()),
+        })
+        .await?;
+
+    let summary = database.users().activity_summary("spammer").await?;
+    assert_eq!(
+        summary,
+        UserActivitySummary {
+            issue_count: 2,
+            comment_count: 2,
+            flagged_issue_count: 1,
+            flagged_comment_count: 1,
+            first_activity_at: Some(first),
+            last_activity_at: Some(last),
+        }
+    );
+
+    let unknown = database.users().activity_summary("nobody").await?;
+    assert_eq!(unknown, UserActivitySummary::default());
+
+    handle.cleanup().await?;
+    Ok(())
+}
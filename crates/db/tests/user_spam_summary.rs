@@ -0,0 +1,162 @@
+use chrono::Utc;
+use db::models::{CommentRow, ReviewStatus, SpamFlagReview, SpamFlagUpsert, UserSpamSummary};
+use db::{pg::PgDatabase, IssueRow, Repositories, RepositoryRow, UserRow};
+use db_test_fixture::DbFixture;
+use serde_json::json;
+
+fn user(id: i64, login: &str) -> UserRow {
+    UserRow {
+        id,
+        login: login.into(),
+        user_type: "User".into(),
+        site_admin: false,
+        created_at: None,
+        followers: None,
+        following: None,
+        public_repos: None,
+        bio: None,
+        blog: None,
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn issue(id: i64, user_id: i64, created_at: chrono::DateTime<Utc>) -> IssueRow {
+    IssueRow {
+        id,
+        repo_id: 1,
+        number: id,
+        is_pull_request: false,
+        state: "open".into(),
+        title: format!("issue {id}"),
+        body: None,
+        user_id: Some(user_id),
+        comments_count: 0,
+        created_at,
+        updated_at: created_at,
+        closed_at: None,
+        state_reason: None,
+        locked: false,
+        author_association: None,
+        dedupe_hash: format!("issue-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn comment(id: i64, issue_id: i64, user_id: i64, created_at: chrono::DateTime<Utc>) -> CommentRow {
+    CommentRow {
+        id,
+        issue_id,
+        user_id: Some(user_id),
+        body: "comment".into(),
+        created_at,
+        updated_at: None,
+        author_association: None,
+        dedupe_hash: format!("comment-{id}"),
+        raw: json!({}),
+        found: true,
+    }
+}
+
+fn flag(subject_type: &str, subject_id: i64, score: f32, reasons: &[&str]) -> SpamFlagUpsert {
+    SpamFlagUpsert {
+        subject_type: subject_type.into(),
+        subject_id,
+        score,
+        reasons: reasons.iter().map(|reason| reason.to_string()).collect(),
+        version: "rules_v1".into(),
+        features: None,
+    }
+}
+
+#[tokio::test]
+async fn spam_summary_aggregates_flags_on_authored_content() -> anyhow::Result<()> {
+    let fixture = match DbFixture::from_env() {
+        Ok(fixture) => fixture,
+        Err(err) => {
+            eprintln!("skipping spam_summary_aggregates_flags_on_authored_content: {err}");
+            return Ok(());
+        }
+    };
+    let handle = fixture.create_unmigrated("user_spam_summary").await?;
+    let database = PgDatabase::connect(handle.database_url()).await?;
+
+    database
+        .repos()
+        .upsert(RepositoryRow {
+            id: 1,
+            full_name: "o/r".into(),
+            is_fork: false,
+            created_at: Utc::now(),
+            pushed_at: None,
+            raw: json!({}),
+        })
+        .await?;
+    database.users().upsert(user(1, "spammer")).await?;
+    database.users().upsert(user(2, "maintainer")).await?;
+
+    let now = Utc::now();
+    database.issues().upsert(issue(10, 1, now)).await?;
+    database.issues().upsert(issue(11, 2, now)).await?;
+    database.comments().upsert(comment(100, 11, 1, now)).await?;
+    database.comments().upsert(comment(101, 11, 1, now)).await?;
+    database.comments().upsert(comment(102, 10, 2, now)).await?;
+
+    database
+        .spam_flags()
+        .upsert(flag("issue", 10, 3.0, &["link_heavy", "contact_only"]))
+        .await?;
+    database
+        .spam_flags()
+        .upsert(flag("comment", 100, 1.0, &["contact_only"]))
+        .await?;
+    database
+        .spam_flags()
+        .upsert(flag("comment", 101, 5.0, &["dedupe_hit"]))
+        .await?;
+    // Someone else's content does not count towards the spammer.
+    database
+        .spam_flags()
+        .upsert(flag("comment", 102, 4.0, &["new_account"]))
+        .await?;
+    // Dismissed by a reviewer, so neither its score nor its reason counts.
+    let dismissed = database
+        .spam_flags()
+        .list_for_subject("comment", 101)
+        .await?[0]
+        .id;
+    database
+        .spam_flags()
+        .review(SpamFlagReview {
+            id: dismissed,
+            status: ReviewStatus::FalsePositive,
+            reviewed_by: Some("alice".into()),
+        })
+        .await?;
+
+    let first = database.spam_flags().list_for_subject("issue", 10).await?[0].created_at;
+    let last = database
+        .spam_flags()
+        .list_for_subject("comment", 100)
+        .await?[0]
+        .created_at;
+    let summary = database.users().spam_summary("spammer").await?;
+    assert_eq!(
+        summary,
+        UserSpamSummary {
+            flag_count: 2,
+            total_score: 4.0,
+            avg_score: 2.0,
+            reasons: vec!["contact_only".into(), "link_heavy".into()],
+            first_flagged_at: Some(first),
+            last_flagged_at: Some(last),
+        }
+    );
+
+    let unflagged = database.users().spam_summary("nobody").await?;
+    assert_eq!(unflagged, UserSpamSummary::default());
+
+    handle.cleanup().await?;
+    Ok(())
+}
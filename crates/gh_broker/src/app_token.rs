@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use http::{header, Request};
+use ring::rand::SystemRandom;
+use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::broker::HttpExec;
+use crate::error::HttpStatusError;
+use crate::metrics;
+use crate::token::TokenProvider;
+
+/// GitHub accepts app JWTs valid for at most ten minutes.
+const JWT_LIFETIME_SECS: i64 = 9 * 60;
+/// Backdates `iat` to tolerate clock drift against GitHub.
+const JWT_CLOCK_SKEW_SECS: i64 = 60;
+
+/// Installation token for a GitHub App, minted from the app's private key and
+/// exchanged again once the current token is within `refresh_margin` of expiry.
+pub struct AppInstallationToken {
+    app_id: String,
+    installation_id: u64,
+    key: RsaKeyPair,
+    rng: SystemRandom,
+    exec: Arc<dyn HttpExec>,
+    api_base: String,
+    refresh_margin: Duration,
+    // Held across the exchange so concurrent callers share one refresh.
+    current: Mutex<Option<InstallationToken>>,
+}
+
+struct InstallationToken {
+    secret: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl AppInstallationToken {
+    /// `private_key_pem` is the key downloaded from the app settings (PKCS#1
+    /// `RSA PRIVATE KEY`) or its PKCS#8 `PRIVATE KEY` conversion.
+    pub fn new(
+        app_id: impl Into<String>,
+        installation_id: u64,
+        private_key_pem: &str,
+        exec: Arc<dyn HttpExec>,
+    ) -> Result<Self> {
+        Ok(Self {
+            app_id: app_id.into(),
+            installation_id,
+            key: parse_rsa_key(private_key_pem)?,
+            rng: SystemRandom::new(),
+            exec,
+            api_base: "https://api.github.com".to_string(),
+            refresh_margin: Duration::minutes(5),
+            current: Mutex::new(None),
+        })
+    }
+
+    /// For GitHub Enterprise Server, e.g. `https://ghe.example.com/api/v3`.
+    pub fn api_base(mut self, base: impl Into<String>) -> Self {
+        self.api_base = base.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    /// RS256 JWT identifying the app itself, used only for the token exchange.
+    pub fn app_jwt(&self, now: DateTime<Utc>) -> Result<String> {
+        let header = URL_SAFE_NO_PAD.encode(json!({"alg": "RS256", "typ": "JWT"}).to_string());
+        let claims = URL_SAFE_NO_PAD.encode(
+            json!({
+                "iat": now.timestamp() - JWT_CLOCK_SKEW_SECS,
+                "exp": now.timestamp() + JWT_LIFETIME_SECS,
+                "iss": self.app_id,
+            })
+            .to_string(),
+        );
+        let signing_input = format!("{header}.{claims}");
+        let mut signature = vec![0; self.key.public().modulus_len()];
+        self.key
+            .sign(
+                &RSA_PKCS1_SHA256,
+                &self.rng,
+                signing_input.as_bytes(),
+                &mut signature,
+            )
+            .map_err(|_| anyhow!("failed to sign GitHub App JWT"))?;
+        Ok(format!(
+            "{signing_input}.{}",
+            URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+
+    async fn exchange(&self) -> Result<InstallationToken> {
+        let endpoint = format!("app/installations/{}/access_tokens", self.installation_id);
+        let request = Request::post(format!("{}/{endpoint}", self.api_base))
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", self.app_jwt(Utc::now())?),
+            )
+            .header(header::ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .body(Vec::new())?;
+        let response = self.exec.execute(request).await?;
+        if !response.status().is_success() {
+            return Err(HttpStatusError::with_endpoint(response.status(), endpoint).into());
+        }
+        let body: AccessTokenResponse = serde_json::from_slice(response.body())
+            .context("decoding installation access token response")?;
+        Ok(InstallationToken {
+            secret: body.token,
+            expires_at: body.expires_at,
+        })
+    }
+}
+
+#[async_trait]
+impl TokenProvider for AppInstallationToken {
+    async fn secret(&self) -> Result<String> {
+        let mut current = self.current.lock().await;
+        if let Some(token) = current.as_ref() {
+            if token.expires_at - Utc::now() > self.refresh_margin {
+                return Ok(token.secret.clone());
+            }
+        }
+        let fresh = match self.exchange().await {
+            Ok(token) => token,
+            Err(err) => {
+                metrics::APP_TOKEN_REFRESHES
+                    .with_label_values(&["error"])
+                    .inc();
+                return Err(err);
+            }
+        };
+        metrics::APP_TOKEN_REFRESHES
+            .with_label_values(&["success"])
+            .inc();
+        info!(
+            installation_id = self.installation_id,
+            expires_at = %fresh.expires_at,
+            "refreshed GitHub App installation token"
+        );
+        let secret = fresh.secret.clone();
+        *current = Some(fresh);
+        Ok(secret)
+    }
+}
+
+fn parse_rsa_key(pem: &str) -> Result<RsaKeyPair> {
+    let label = pem
+        .lines()
+        .find_map(|line| {
+            line.trim()
+                .strip_prefix("-----BEGIN ")
+                .and_then(|rest| rest.strip_suffix("-----"))
+        })
+        .ok_or_else(|| anyhow!("GitHub App private key is not PEM encoded"))?;
+    let body: String = pem
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let der = STANDARD
+        .decode(body)
+        .context("decoding GitHub App private key")?;
+    let key = match label {
+        "RSA PRIVATE KEY" => RsaKeyPair::from_der(&der),
+        "PRIVATE KEY" => RsaKeyPair::from_pkcs8(&der),
+        other => return Err(anyhow!("unsupported private key type: {other}")),
+    };
+    key.map_err(|err| anyhow!("invalid GitHub App private key: {err}"))
+}
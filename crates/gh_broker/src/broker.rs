@@ -4,18 +4,21 @@ use std::time::Duration;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use common::config::DEFAULT_USER_AGENT;
 use futures::FutureExt;
 use http::{header, HeaderValue, Request, Response, StatusCode};
-use tokio::sync::{mpsc, oneshot, Mutex, Semaphore};
+use tokio::sync::{mpsc, oneshot, Mutex, Notify, Semaphore};
 use tokio::time::sleep;
-use tracing::{debug, warn};
+use tracing::{debug, info_span, warn, Instrument, Span};
 
 use crate::backoff::exponential_jitter_backoff;
 use crate::cache::{CachedResponse, ResponseCache};
-use crate::error::HttpStatusError;
+use crate::cost::GraphqlCostModel;
+use crate::error::{GraphqlRateLimitedError, HttpStatusError, TokensExhaustedError};
 use crate::metrics;
-use crate::model::{parse_rate_limit, parse_retry_after, Budget, GithubRequest};
-use crate::token::{GithubToken, TokenPool, TokenSelection};
+use crate::model::{parse_rate_limit, parse_retry_after, Budget, GithubRequest, RateLimitUpdate};
+use crate::throttle_log::ThrottleLog;
+use crate::token::{GithubToken, TokenBudgetSnapshot, TokenPool, TokenProvider, TokenSelection};
 
 #[async_trait]
 pub trait HttpExec: Send + Sync {
@@ -28,13 +31,47 @@ pub struct ReqwestExecutor {
 
 impl Default for ReqwestExecutor {
     fn default() -> Self {
-        Self::new()
+        Self::new(DEFAULT_USER_AGENT)
     }
 }
 
 impl ReqwestExecutor {
-    pub fn new() -> Self {
-        let mut builder = reqwest::Client::builder().user_agent("github-spam-lab");
+    /// Client sending `user_agent` (normally `github.user_agent`) on requests
+    /// that do not set their own.
+    pub fn new(user_agent: &str) -> Self {
+        let client = Self::client_builder(user_agent)
+            .build()
+            .expect("reqwest client");
+        Self { client }
+    }
+
+    /// Like [`ReqwestExecutor::new`], also trusting every certificate in the
+    /// PEM `ca_bundle` as a root, e.g. a TLS-intercepting proxy's CA. The
+    /// built-in roots stay trusted.
+    pub fn with_ca_bundle(user_agent: &str, ca_bundle: &[u8]) -> Result<Self> {
+        let certs = reqwest::Certificate::from_pem_bundle(ca_bundle)?;
+        if certs.is_empty() {
+            anyhow::bail!("CA bundle contains no PEM certificates");
+        }
+        let builder = certs
+            .into_iter()
+            .fold(Self::client_builder(user_agent), |builder, cert| {
+                builder.add_root_certificate(cert)
+            });
+        Ok(Self {
+            client: builder.build()?,
+        })
+    }
+
+    fn client_builder(user_agent: &str) -> reqwest::ClientBuilder {
+        // Advertise `Accept-Encoding: gzip, br` and decode responses before they
+        // reach the broker, so bodies (including those cached for ETag
+        // revalidation) are always plain JSON. Callers must not set
+        // `Accept-Encoding` themselves, or reqwest leaves the body encoded.
+        let mut builder = reqwest::Client::builder()
+            .user_agent(user_agent.to_string())
+            .gzip(true)
+            .brotli(true);
 
         // Honour standard proxy environment variables so all outbound
         // GitHub traffic can be routed through an HTTP CONNECT proxy
@@ -48,9 +85,7 @@ impl ReqwestExecutor {
                 builder = builder.proxy(p);
             }
         }
-
-        let client = builder.build().expect("reqwest client");
-        Self { client }
+        builder
     }
 }
 
@@ -71,17 +106,36 @@ impl HttpExec for ReqwestExecutor {
 }
 
 pub trait GithubBroker: Send + Sync {
+    /// Dropping the returned future cancels the caller's interest: once every
+    /// caller for a request is gone, queued work for it is skipped and retries
+    /// or rate-limit waits are abandoned.
     fn enqueue(
         &self,
         request: Request<Vec<u8>>,
         priority: crate::model::Priority,
     ) -> futures::future::BoxFuture<'static, Result<Response<Vec<u8>>>>;
+
+    /// The broker's own per-token accounting, including locally consumed cost.
+    /// Brokers without a token pool report nothing.
+    fn rate_limit_snapshot(&self) -> futures::future::BoxFuture<'static, Vec<TokenBudgetSnapshot>> {
+        Box::pin(async { Vec::new() })
+    }
+
+    /// Distinct requests that still have at least one caller waiting.
+    fn pending_requests(&self) -> usize {
+        0
+    }
 }
 
 struct WorkItem {
     request: GithubRequest,
     key: String,
     cached: Option<CachedResponse>,
+    // Fired when the last waiter for `key` goes away.
+    abandoned: Arc<Notify>,
+    // Span of the enqueuing caller; budget workers run the request under it so
+    // traces stay connected across the queue boundary.
+    parent_span: Span,
 }
 
 type QueueSenders = HashMap<(Budget, crate::model::Priority), mpsc::Sender<WorkItem>>;
@@ -105,16 +159,23 @@ type ReceiversMap = HashMap<
 #[derive(Clone)]
 pub struct GithubBrokerBuilder {
     tokens: Vec<GithubToken>,
+    token_providers: HashMap<String, Arc<dyn TokenProvider>>,
     http_exec: Option<Arc<dyn HttpExec>>,
     queue_bounds: HashMap<(Budget, crate::model::Priority), usize>,
     weights: HashMap<Budget, [u32; 3]>,
+    work_conserving: bool,
     max_inflight: usize,
     per_repo_inflight: usize,
+    critical_reserve: i64,
     cache_capacity: usize,
     cache_ttl: Duration,
     backoff_base: Duration,
     backoff_max: Duration,
     jitter_frac: f32,
+    max_rate_limit_wait: Option<Duration>,
+    graphql_cost: GraphqlCostModel,
+    throttle_log_interval: Duration,
+    redact_params: Vec<String>,
 }
 
 impl GithubBrokerBuilder {
@@ -133,19 +194,43 @@ impl GithubBrokerBuilder {
 
         Self {
             tokens,
+            token_providers: HashMap::new(),
             http_exec: None,
             queue_bounds,
             weights,
+            work_conserving: false,
             max_inflight: 32,
             per_repo_inflight: 2,
+            critical_reserve: 0,
             cache_capacity: 5000,
             cache_ttl: Duration::from_secs(600),
             backoff_base: Duration::from_millis(500),
             backoff_max: Duration::from_millis(60_000),
             jitter_frac: 0.2,
+            max_rate_limit_wait: None,
+            graphql_cost: GraphqlCostModel::default(),
+            throttle_log_interval: Duration::from_secs(60),
+            redact_params: Vec::new(),
         }
     }
 
+    /// Adds a pool entry `id` whose secret is fetched from `provider` before each
+    /// request (e.g. an [`crate::AppInstallationToken`]) instead of being static.
+    pub fn token_provider(
+        mut self,
+        id: impl Into<String>,
+        provider: Arc<dyn TokenProvider>,
+    ) -> Self {
+        let id = id.into();
+        self.tokens.push(GithubToken {
+            id: id.clone(),
+            secret: String::new(),
+            budgets: None,
+        });
+        self.token_providers.insert(id, provider);
+        self
+    }
+
     pub fn http_exec(mut self, exec: Arc<dyn HttpExec>) -> Self {
         self.http_exec = Some(exec);
         self
@@ -161,6 +246,11 @@ impl GithubBrokerBuilder {
         self
     }
 
+    pub fn critical_reserve(mut self, reserve: i64) -> Self {
+        self.critical_reserve = reserve;
+        self
+    }
+
     pub fn cache(mut self, capacity: usize, ttl: Duration) -> Self {
         self.cache_capacity = capacity;
         self.cache_ttl = ttl;
@@ -180,6 +270,14 @@ impl GithubBrokerBuilder {
         self
     }
 
+    /// Lets backfill use the slots of a scheduling cycle that critical and
+    /// normal leave idle, instead of only its own weight. Backfill yields as
+    /// soon as either higher-priority queue has work again.
+    pub fn work_conserving(mut self, enabled: bool) -> Self {
+        self.work_conserving = enabled;
+        self
+    }
+
     pub fn backoff(mut self, base: Duration, max: Duration, jitter: f32) -> Self {
         self.backoff_base = base;
         self.backoff_max = max;
@@ -187,12 +285,42 @@ impl GithubBrokerBuilder {
         self
     }
 
+    /// Fails a request with [`TokensExhaustedError`] when every token is
+    /// rate-limited for longer than `max`, instead of holding its inflight
+    /// slot until the earliest reset. Unbounded by default.
+    pub fn max_rate_limit_wait(mut self, max: Duration) -> Self {
+        self.max_rate_limit_wait = Some(max);
+        self
+    }
+
+    /// Estimates the cost of GraphQL responses that omit `rateLimit.cost`.
+    pub fn graphql_cost_model(mut self, model: GraphqlCostModel) -> Self {
+        self.graphql_cost = model;
+        self
+    }
+
+    /// Logs `retry-after` and secondary-limit responses at most once per
+    /// budget per `interval` (with the number skipped since); the rest go to
+    /// `debug`. One minute by default.
+    pub fn throttle_log_interval(mut self, interval: Duration) -> Self {
+        self.throttle_log_interval = interval;
+        self
+    }
+
+    /// Masks these query parameters in logged request keys, besides
+    /// [`common::redact::REDACTED_QUERY_PARAMS`].
+    pub fn redact_query_params(mut self, params: Vec<String>) -> Self {
+        self.redact_params = params;
+        self
+    }
+
     pub fn build(self) -> Arc<dyn GithubBroker> {
         let exec = self
             .http_exec
-            .unwrap_or_else(|| Arc::new(ReqwestExecutor::new()));
+            .unwrap_or_else(|| Arc::new(ReqwestExecutor::default()));
 
-        let token_pool = TokenPool::new(self.tokens.clone());
+        let token_pool =
+            TokenPool::new(self.tokens.clone()).with_critical_reserve(self.critical_reserve);
         let cache = ResponseCache::new(self.cache_capacity, self.cache_ttl);
 
         let (senders, receivers) = Self::build_queues(&self.queue_bounds);
@@ -200,22 +328,36 @@ impl GithubBrokerBuilder {
         let inner = Arc::new(Inner {
             http_exec: exec,
             token_pool,
+            token_providers: self.token_providers,
             cache,
-            pending: Mutex::new(HashMap::new()),
+            pending: std::sync::Mutex::new(HashMap::new()),
             inflight: Arc::new(Semaphore::new(self.max_inflight)),
             per_repo_limit: self.per_repo_inflight,
             per_repo: Mutex::new(HashMap::new()),
             backoff_base: self.backoff_base,
             backoff_max: self.backoff_max,
             jitter: self.jitter_frac,
+            max_rate_limit_wait: self.max_rate_limit_wait,
+            graphql_cost: self.graphql_cost,
+            throttle_log: ThrottleLog::new(self.throttle_log_interval),
+            redact_params: self.redact_params,
         });
 
-        for (budget, (rx_crit, rx_norm, rx_back)) in receivers {
+        for (budget, (critical, normal, backfill)) in receivers {
             let inner = inner.clone();
             let weights = self.weights.get(&budget).cloned().unwrap_or([1, 1, 1]);
-            tokio::spawn(async move {
-                run_budget(inner, budget, rx_crit, rx_norm, rx_back, weights).await;
-            });
+            let queues = Arc::new(Mutex::new(BudgetQueues {
+                critical,
+                normal,
+                backfill,
+            }));
+            tokio::spawn(supervise_budget(
+                inner,
+                budget,
+                queues,
+                weights,
+                self.work_conserving,
+            ));
         }
 
         // Background metrics refresh loop: propagate per-token and aggregated
@@ -292,43 +434,145 @@ impl GithubBrokerBuilder {
 
 struct PendingEntry {
     waiters: Vec<oneshot::Sender<Result<BrokerResponse>>>,
+    abandoned: Arc<Notify>,
+}
+
+/// What every waiter of a failed request needs to rebuild its own error.
+/// `anyhow::Error` is not `Clone`, so the typed errors callers downcast to are
+/// kept apart and everything else travels as its message.
+enum SharedError {
+    RateLimited(GraphqlRateLimitedError),
+    TokensExhausted(TokensExhaustedError),
+    Status(HttpStatusError),
+    Other(String),
+}
+
+impl SharedError {
+    fn from_error(err: &anyhow::Error) -> Self {
+        if let Some(limited) = err.downcast_ref::<GraphqlRateLimitedError>() {
+            return Self::RateLimited(limited.clone());
+        }
+        if let Some(exhausted) = err.downcast_ref::<TokensExhaustedError>() {
+            return Self::TokensExhausted(exhausted.clone());
+        }
+        if let Some(http) = err.downcast_ref::<HttpStatusError>() {
+            return Self::Status(http.clone());
+        }
+        Self::Other(err.to_string())
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::RateLimited(_) => "rate_limited",
+            Self::TokensExhausted(_) => "tokens_exhausted",
+            Self::Status(_) => "status",
+            Self::Other(_) => "other",
+        }
+    }
+
+    fn to_error(&self) -> anyhow::Error {
+        match self {
+            Self::RateLimited(limited) => limited.clone().into(),
+            Self::TokensExhausted(exhausted) => exhausted.clone().into(),
+            Self::Status(http) => http.clone().into(),
+            Self::Other(message) => anyhow::anyhow!("{}", message),
+        }
+    }
 }
 
 struct Inner {
     http_exec: Arc<dyn HttpExec>,
     token_pool: TokenPool,
+    token_providers: HashMap<String, Arc<dyn TokenProvider>>,
     cache: ResponseCache,
-    pending: Mutex<HashMap<String, PendingEntry>>,
+    // Never held across an await, so waiter guards can release it from `Drop`.
+    pending: std::sync::Mutex<HashMap<String, PendingEntry>>,
     inflight: Arc<Semaphore>,
     per_repo_limit: usize,
     per_repo: Mutex<HashMap<String, Arc<Semaphore>>>,
     backoff_base: Duration,
     backoff_max: Duration,
     jitter: f32,
+    max_rate_limit_wait: Option<Duration>,
+    graphql_cost: GraphqlCostModel,
+    throttle_log: ThrottleLog,
+    redact_params: Vec<String>,
 }
 
 impl Inner {
-    async fn register_waiter(
+    /// Returns the abandonment signal when the caller is the first waiter and
+    /// must dispatch the work itself.
+    fn register_waiter(
         &self,
         key: &str,
-    ) -> (oneshot::Receiver<Result<BrokerResponse>>, bool) {
+    ) -> (
+        oneshot::Receiver<Result<BrokerResponse>>,
+        Option<Arc<Notify>>,
+    ) {
         let (tx, rx) = oneshot::channel();
-        let mut guard = self.pending.lock().await;
+        let mut guard = self.pending.lock().expect("pending lock poisoned");
         match guard.get_mut(key) {
             Some(entry) => {
                 entry.waiters.push(tx);
-                (rx, false)
+                (rx, None)
             }
             None => {
-                guard.insert(key.to_string(), PendingEntry { waiters: vec![tx] });
-                (rx, true)
+                let abandoned = Arc::new(Notify::new());
+                guard.insert(
+                    key.to_string(),
+                    PendingEntry {
+                        waiters: vec![tx],
+                        abandoned: abandoned.clone(),
+                    },
+                );
+                (rx, Some(abandoned))
             }
         }
     }
 
+    /// Drops waiters whose receivers are gone; removes the entry and signals
+    /// the worker once none are left.
+    fn release_waiter(&self, key: &str) {
+        let mut guard = self.pending.lock().expect("pending lock poisoned");
+        let Some(entry) = guard.get_mut(key) else {
+            return;
+        };
+        entry.waiters.retain(|waiter| !waiter.is_closed());
+        if entry.waiters.is_empty() {
+            if let Some(entry) = guard.remove(key) {
+                entry.abandoned.notify_one();
+            }
+        }
+    }
+
+    /// Work is abandoned once its entry is gone or has been replaced by a
+    /// newer request for the same key, which dispatches its own work.
+    fn is_abandoned(&self, key: &str, abandoned: &Arc<Notify>) -> bool {
+        let guard = self.pending.lock().expect("pending lock poisoned");
+        !guard
+            .get(key)
+            .is_some_and(|entry| Arc::ptr_eq(&entry.abandoned, abandoned))
+    }
+
+    /// Synchronous, non-panicking variant of `finish` for use while unwinding.
+    fn fail_waiters(&self, key: &str, message: &str) {
+        let Ok(mut guard) = self.pending.lock() else {
+            return;
+        };
+        if let Some(entry) = guard.remove(key) {
+            for waiter in entry.waiters {
+                let _ = waiter.send(Err(anyhow::anyhow!("{}", message)));
+            }
+        }
+    }
+
+    fn pending_len(&self) -> usize {
+        self.pending.lock().expect("pending lock poisoned").len()
+    }
+
     async fn finish(&self, key: String, result: Result<BrokerResponse>) {
         let waiters = {
-            let mut guard = self.pending.lock().await;
+            let mut guard = self.pending.lock().expect("pending lock poisoned");
             guard
                 .remove(&key)
                 .map(|entry| entry.waiters)
@@ -341,9 +585,14 @@ impl Inner {
                 }
             }
             Err(e) => {
-                let err_msg = e.to_string();
+                let shared = SharedError::from_error(e);
+                if waiters.len() > 1 {
+                    metrics::COALESCED_ERRORS
+                        .with_label_values(&[shared.kind()])
+                        .inc_by(waiters.len() as u64 - 1);
+                }
                 for waiter in waiters {
-                    let _ = waiter.send(Err(anyhow::anyhow!("{}", err_msg)));
+                    let _ = waiter.send(Err(shared.to_error()));
                 }
             }
         }
@@ -403,8 +652,12 @@ impl GithubBroker for LocalGithubBroker {
         priority: crate::model::Priority,
     ) -> futures::future::BoxFuture<'static, Result<Response<Vec<u8>>>> {
         let broker = self.clone();
+        let parent_span = Span::current();
         async move {
             let mut gh_req = GithubRequest::new(request, priority)?;
+            if !broker.inner.redact_params.is_empty() {
+                gh_req.redact_params(&broker.inner.redact_params);
+            }
             let cache_key = broker.inner.cache_key(&gh_req);
             let cached = if let Some(ref key) = cache_key {
                 broker.inner.cache.get(key).await
@@ -420,14 +673,22 @@ impl GithubBroker for LocalGithubBroker {
                 }
             }
 
-            let (rx, should_dispatch) = broker.inner.register_waiter(gh_req.key()).await;
+            // Declared before `rx` so it drops after it: the release must see
+            // this caller's sender as closed.
+            let _release = WaiterGuard {
+                inner: broker.inner.clone(),
+                key: gh_req.key().to_string(),
+            };
+            let (rx, abandoned) = broker.inner.register_waiter(gh_req.key());
 
-            if should_dispatch {
+            if let Some(abandoned) = abandoned {
                 broker
                     .dispatch(WorkItem {
                         key: gh_req.key().to_string(),
                         request: gh_req,
                         cached: cached.clone(),
+                        abandoned,
+                        parent_span,
                     })
                     .await?;
             }
@@ -437,6 +698,27 @@ impl GithubBroker for LocalGithubBroker {
         }
         .boxed()
     }
+
+    fn rate_limit_snapshot(&self) -> futures::future::BoxFuture<'static, Vec<TokenBudgetSnapshot>> {
+        let inner = self.inner.clone();
+        async move { inner.token_pool.snapshot().await }.boxed()
+    }
+
+    fn pending_requests(&self) -> usize {
+        self.inner.pending_len()
+    }
+}
+
+/// Releases an `enqueue` caller's waiter when its future completes or is dropped.
+struct WaiterGuard {
+    inner: Arc<Inner>,
+    key: String,
+}
+
+impl Drop for WaiterGuard {
+    fn drop(&mut self) {
+        self.inner.release_waiter(&self.key);
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -487,39 +769,99 @@ impl BrokerResponse {
     }
 }
 
+// Idle workers still tick this often so their heartbeat keeps advancing.
+const WORKER_IDLE_TICK: Duration = Duration::from_secs(1);
+
+// Shared with the supervisor so a respawned worker picks up the same queues.
+struct BudgetQueues {
+    critical: mpsc::Receiver<WorkItem>,
+    normal: mpsc::Receiver<WorkItem>,
+    backfill: mpsc::Receiver<WorkItem>,
+}
+
+/// Runs the budget worker, respawning it if it panics; returns once its
+/// queues are closed.
+async fn supervise_budget(
+    inner: Arc<Inner>,
+    budget: Budget,
+    queues: Arc<Mutex<BudgetQueues>>,
+    weights: [u32; 3],
+    work_conserving: bool,
+) {
+    loop {
+        let worker = tokio::spawn(run_budget(
+            inner.clone(),
+            budget,
+            queues.clone(),
+            weights,
+            work_conserving,
+        ));
+        match worker.await {
+            Err(err) if err.is_panic() => {
+                warn!(budget = ?budget, "budget worker panicked; restarting");
+                metrics::WORKER_RESTARTS
+                    .with_label_values(&[budget_label(budget)])
+                    .inc();
+            }
+            _ => return,
+        }
+    }
+}
+
 async fn run_budget(
     inner: Arc<Inner>,
     budget: Budget,
-    mut rx_critical: mpsc::Receiver<WorkItem>,
-    mut rx_normal: mpsc::Receiver<WorkItem>,
-    mut rx_backfill: mpsc::Receiver<WorkItem>,
+    queues: Arc<Mutex<BudgetQueues>>,
     weights: [u32; 3],
+    work_conserving: bool,
 ) {
+    let mut queues = queues.lock().await;
+    let BudgetQueues {
+        critical: rx_critical,
+        normal: rx_normal,
+        backfill: rx_backfill,
+    } = &mut *queues;
+    // Backfill may take the whole cycle when nothing else is waiting.
+    let backfill_slots = if work_conserving {
+        weights.iter().sum::<u32>().max(1)
+    } else {
+        weights[2]
+    };
     loop {
+        metrics::WORKER_LAST_TICK
+            .with_label_values(&[budget_label(budget)])
+            .set(unix_now_secs());
         let mut processed = false;
 
         for (weight, rx) in [
-            (weights[0], &mut rx_critical),
-            (weights[1], &mut rx_normal),
-            (weights[2], &mut rx_backfill),
+            (weights[0], &mut *rx_critical),
+            (weights[1], &mut *rx_normal),
         ] {
             for _ in 0..weight {
-                match rx.try_recv() {
-                    Ok(work) => {
-                        processed = true;
-                        metrics::QUEUE_LENGTH
-                            .with_label_values(&[
-                                budget_label(budget),
-                                priority_label(work.request.priority),
-                            ])
-                            .dec();
-                        process_work(inner.clone(), budget, work).await;
-                    }
-                    Err(tokio::sync::mpsc::error::TryRecvError::Empty) => break,
-                    Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => return,
+                match take_work(&inner, budget, rx).await {
+                    Take::Processed => processed = true,
+                    Take::Empty => break,
+                    Take::Closed => return,
                 }
             }
         }
+        for slot in 0..backfill_slots {
+            // Slots beyond backfill's own weight are borrowed; give them back
+            // as soon as higher-priority work shows up.
+            if slot >= weights[2] && !(rx_critical.is_empty() && rx_normal.is_empty()) {
+                break;
+            }
+            match take_work(&inner, budget, rx_backfill).await {
+                Take::Processed => processed = true,
+                Take::Empty => break,
+                Take::Closed => return,
+            }
+        }
+        if processed {
+            metrics::SCHEDULER_CYCLES
+                .with_label_values(&[budget_label(budget)])
+                .inc();
+        }
 
         if !processed {
             tokio::select! {
@@ -541,19 +883,98 @@ async fn run_budget(
                         .dec();
                     process_work(inner.clone(), budget, work).await;
                 },
-                else => break,
+                // Closed queues are noticed by `try_recv` on the next pass.
+                _ = sleep(WORKER_IDLE_TICK) => {},
             }
         }
     }
 }
 
+enum Take {
+    Processed,
+    Empty,
+    Closed,
+}
+
+async fn take_work(inner: &Arc<Inner>, budget: Budget, rx: &mut mpsc::Receiver<WorkItem>) -> Take {
+    match rx.try_recv() {
+        Ok(work) => {
+            metrics::QUEUE_LENGTH
+                .with_label_values(&[budget_label(budget), priority_label(work.request.priority)])
+                .dec();
+            process_work(inner.clone(), budget, work).await;
+            Take::Processed
+        }
+        Err(tokio::sync::mpsc::error::TryRecvError::Empty) => Take::Empty,
+        Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => Take::Closed,
+    }
+}
+
+fn unix_now_secs() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64())
+        .unwrap_or_default()
+}
+
 async fn process_work(inner: Arc<Inner>, budget: Budget, work: WorkItem) {
+    let _unwind = UnwindGuard {
+        inner: inner.clone(),
+        key: work.key.clone(),
+        budget,
+        priority: work.request.priority,
+    };
+    let span = info_span!(
+        parent: &work.parent_span,
+        "github_request",
+        budget = budget_label(budget),
+        priority = priority_label(work.request.priority),
+        request = %work.key,
+    );
+    run_work(inner, budget, work).instrument(span).await
+}
+
+/// Fails the request's waiters if its worker panics mid-request, so callers
+/// get an error instead of waiting forever while the worker restarts.
+struct UnwindGuard {
+    inner: Arc<Inner>,
+    key: String,
+    budget: Budget,
+    priority: crate::model::Priority,
+}
+
+impl Drop for UnwindGuard {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            return;
+        }
+        self.inner
+            .fail_waiters(&self.key, "broker worker panicked while handling request");
+        metrics::PENDING
+            .with_label_values(&[budget_label(self.budget), priority_label(self.priority)])
+            .dec();
+    }
+}
+
+async fn run_work(inner: Arc<Inner>, budget: Budget, work: WorkItem) {
     let key = work.key.clone();
     let mut attempt = 0;
     let request = work.request;
     loop {
         attempt += 1;
-        match execute_once(inner.clone(), budget, &work.cached, request.clone()).await {
+        if inner.is_abandoned(&key, &work.abandoned) {
+            record_abandoned(budget, &request, attempt);
+            break;
+        }
+        match execute_once(
+            inner.clone(),
+            budget,
+            &work.cached,
+            &work.abandoned,
+            request.clone(),
+        )
+        .await
+        {
             Ok(response) => {
                 inner.finish(key, Ok(response)).await;
                 // Decrement pending on completion
@@ -563,9 +984,15 @@ async fn process_work(inner: Arc<Inner>, budget: Budget, work: WorkItem) {
                 break;
             }
             Err(err) => {
+                if err.downcast_ref::<RequestAbandoned>().is_some() {
+                    record_abandoned(budget, &request, attempt);
+                    break;
+                }
+
                 // Do not retry on most 4xx client errors (e.g., 404 Not Found),
                 // except for 403/429 which may be rate/permission related.
-                let mut retry_allowed = true;
+                // Retrying would only wait out the same reset the cap refused.
+                let mut retry_allowed = err.downcast_ref::<TokensExhaustedError>().is_none();
                 if let Some(http) = err.downcast_ref::<HttpStatusError>() {
                     let status = http.status;
                     if status.is_client_error()
@@ -587,14 +1014,34 @@ async fn process_work(inner: Arc<Inner>, budget: Budget, work: WorkItem) {
                     break;
                 }
 
-                warn!(
-                    attempt,
-                    budget = ?budget,
-                    priority = %request.priority.as_str(),
-                    request = %request.key(),
-                    error = %err,
-                    "GitHub request attempt failed"
-                );
+                // The token is already parked until its reset, so the next
+                // attempt waits in `pick_token` instead of backing off.
+                if err.downcast_ref::<GraphqlRateLimitedError>().is_some() {
+                    metrics::RETRIES_TOTAL
+                        .with_label_values(&[budget_label(budget), "graphql_rate_limited"])
+                        .inc();
+                    continue;
+                }
+
+                // Throttles were already logged (sampled) where they happened.
+                if err.downcast_ref::<Throttled>().is_some() {
+                    debug!(
+                        attempt,
+                        budget = ?budget,
+                        request = %request.log_key(),
+                        error = %err,
+                        "GitHub request attempt throttled"
+                    );
+                } else {
+                    warn!(
+                        attempt,
+                        budget = ?budget,
+                        priority = %request.priority.as_str(),
+                        request = %request.log_key(),
+                        error = %err,
+                        "GitHub request attempt failed"
+                    );
+                }
                 let backoff = exponential_jitter_backoff(
                     inner.backoff_base,
                     attempt - 1,
@@ -604,16 +1051,60 @@ async fn process_work(inner: Arc<Inner>, budget: Budget, work: WorkItem) {
                 metrics::RETRIES_TOTAL
                     .with_label_values(&[budget_label(budget), "error"])
                     .inc();
-                sleep(backoff).await;
+                // Stop backing off as soon as nobody is waiting; the check at
+                // the top of the loop then drops the work.
+                tokio::select! {
+                    _ = sleep(backoff) => {}
+                    _ = work.abandoned.notified() => {}
+                }
             }
         }
     }
 }
 
+#[derive(Debug)]
+struct RequestAbandoned;
+
+impl std::fmt::Display for RequestAbandoned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("request abandoned by every caller")
+    }
+}
+
+impl std::error::Error for RequestAbandoned {}
+
+/// GitHub asked us to slow down; the advertised wait has already been slept.
+#[derive(Debug)]
+struct Throttled(&'static str);
+
+impl std::fmt::Display for Throttled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for Throttled {}
+
+fn record_abandoned(budget: Budget, request: &GithubRequest, attempt: u32) {
+    debug!(
+        attempt,
+        budget = ?budget,
+        request = %request.log_key(),
+        "dropping GitHub request with no remaining callers"
+    );
+    metrics::ABANDONED_TOTAL
+        .with_label_values(&[budget_label(budget)])
+        .inc();
+    metrics::PENDING
+        .with_label_values(&[budget_label(budget), priority_label(request.priority)])
+        .dec();
+}
+
 async fn execute_once(
     inner: Arc<Inner>,
     budget: Budget,
     cached: &Option<CachedResponse>,
+    abandoned: &Notify,
     mut request: GithubRequest,
 ) -> Result<BrokerResponse> {
     let permit = inner.inflight.clone().acquire_owned().await?;
@@ -630,13 +1121,43 @@ async fn execute_once(
     };
 
     let token = loop {
-        match inner.token_pool.pick_token(budget).await {
-            TokenSelection::Token(token) => break token,
+        match inner.token_pool.pick_token(budget, request.priority).await {
+            TokenSelection::Token(token) => {
+                metrics::ALL_TOKENS_EXHAUSTED
+                    .with_label_values(&[budget_label(budget)])
+                    .set(0);
+                break token;
+            }
             TokenSelection::Wait(wait) => {
+                metrics::ALL_TOKENS_EXHAUSTED
+                    .with_label_values(&[budget_label(budget)])
+                    .set(1);
+                if let Some(max_wait) = inner.max_rate_limit_wait.filter(|max| wait > *max) {
+                    warn!(
+                        budget = ?budget,
+                        priority = %request.priority.as_str(),
+                        request = %request.log_key(),
+                        wait_secs = wait.as_secs(),
+                        max_wait_secs = max_wait.as_secs(),
+                        "every token is rate limited past the maximum wait; failing request"
+                    );
+                    metrics::INFLIGHT
+                        .with_label_values(&[budget_label(budget)])
+                        .dec();
+                    return Err(TokensExhaustedError { budget, wait }.into());
+                }
                 metrics::SLEEP_SECONDS
                     .with_label_values(&[budget_label(budget), "rate_limit"])
                     .inc_by(wait.as_secs());
-                sleep(wait + Duration::from_secs(1)).await;
+                tokio::select! {
+                    _ = sleep(wait + Duration::from_secs(1)) => {}
+                    _ = abandoned.notified() => {
+                        metrics::INFLIGHT
+                            .with_label_values(&[budget_label(budget)])
+                            .dec();
+                        return Err(RequestAbandoned.into());
+                    }
+                }
             }
         }
     };
@@ -645,13 +1166,29 @@ async fn execute_once(
         budget = ?budget,
         priority = %request.priority.as_str(),
         token_id = %token.id,
-        request = %request.key(),
+        request = %request.log_key(),
         "selected GitHub token for request"
     );
 
+    let secret = match inner.token_providers.get(&token.id) {
+        Some(provider) => match provider.secret().await {
+            Ok(secret) => secret,
+            Err(err) => {
+                metrics::INFLIGHT
+                    .with_label_values(&[budget_label(budget)])
+                    .dec();
+                // Flattened so an exchange status is never mistaken for this request's own status.
+                return Err(anyhow::anyhow!(
+                    "refreshing credentials for token {}: {err:#}",
+                    token.id
+                ));
+            }
+        },
+        None => token.secret.clone(),
+    };
     request.headers_mut().insert(
         header::AUTHORIZATION,
-        HeaderValue::from_str(&format!("token {}", token.secret))?,
+        HeaderValue::from_str(&format!("token {secret}"))?,
     );
 
     metrics::SCHEDULED_TOTAL
@@ -706,6 +1243,9 @@ async fn execute_once(
                     metrics::CACHE_HITS
                         .with_label_values(&[budget_label(budget)])
                         .inc();
+                    if request.uri().path().starts_with("/users/") {
+                        metrics::USER_NOT_MODIFIED.inc();
+                    }
                     return Ok(BrokerResponse {
                         status: entry.status,
                         headers: entry.headers.clone(),
@@ -714,6 +1254,22 @@ async fn execute_once(
                 }
             }
 
+            if status.is_success() && budget == Budget::Graphql {
+                if let Some(limited) = serde_json::from_slice(resp.body())
+                    .ok()
+                    .and_then(|body| GraphqlRateLimitedError::from_body(&body))
+                {
+                    park_rate_limited_token(&inner, &token.id, &limited, rate_info.as_ref()).await;
+                    warn!(
+                        request = %request.log_key(),
+                        token_id = %token.id,
+                        reset_at = limited.reset_at.map(|reset| reset.timestamp()),
+                        "GitHub GraphQL responded RATE_LIMITED"
+                    );
+                    return Err(limited.into());
+                }
+            }
+
             if status.is_success() {
                 let cache_key = request.key().to_string();
                 let response = BrokerResponse::from_http(resp);
@@ -734,7 +1290,8 @@ async fn execute_once(
                 }
 
                 let cost = if budget == Budget::Graphql {
-                    extract_graphql_cost(&response).unwrap_or(1)
+                    extract_graphql_cost(&response)
+                        .unwrap_or_else(|| inner.graphql_cost.estimate(request.body()))
                 } else {
                     1
                 };
@@ -767,54 +1324,79 @@ async fn execute_once(
                 return Ok(response);
             }
 
+            // GraphQL secondary limits arrive as a 403 with `retry-after` and a
+            // GraphQL JSON body; they take this branch just like REST calls.
             if let Some(retry) = parse_retry_after(&headers) {
-                warn!(
-                    status = %status,
-                    request = %request.key(),
-                    budget = ?budget,
-                    priority = %request.priority.as_str(),
-                    token_id = %token.id,
-                    github_request_id = headers
-                        .get("x-github-request-id")
-                        .and_then(|v| v.to_str().ok())
-                        .unwrap_or("-"),
-                    retry_after_seconds = retry.wait.as_secs(),
-                    rate_limit_remaining = rate_info.as_ref().map(|data| data.remaining),
-                    rate_limit_reset = rate_info
-                        .as_ref()
-                        .map(|data| data.reset.timestamp()),
-                    "GitHub responded with retryable status"
-                );
+                if let Some(suppressed) = inner.throttle_log.sample(budget) {
+                    warn!(
+                        status = %status,
+                        request = %request.log_key(),
+                        budget = ?budget,
+                        priority = %request.priority.as_str(),
+                        token_id = %token.id,
+                        github_request_id = headers
+                            .get("x-github-request-id")
+                            .and_then(|v| v.to_str().ok())
+                            .unwrap_or("-"),
+                        retry_after_seconds = retry.wait.as_secs(),
+                        rate_limit_remaining = rate_info.as_ref().map(|data| data.remaining),
+                        rate_limit_reset = rate_info
+                            .as_ref()
+                            .map(|data| data.reset.timestamp()),
+                        suppressed,
+                        "GitHub responded with retryable status"
+                    );
+                } else {
+                    debug!(
+                        status = %status,
+                        request = %request.log_key(),
+                        budget = ?budget,
+                        token_id = %token.id,
+                        retry_after_seconds = retry.wait.as_secs(),
+                        "GitHub responded with retryable status"
+                    );
+                }
                 metrics::SLEEP_SECONDS
                     .with_label_values(&[budget_label(budget), retry.reason])
                     .inc_by(retry.wait.as_secs());
                 sleep(retry.wait + Duration::from_secs(1)).await;
-                return Err(anyhow::anyhow!("retry after"));
+                return Err(Throttled("retry after").into());
             }
 
             if status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS {
                 let rate_info = parse_rate_limit(&headers);
-                warn!(
-                    status = %status,
-                    request = %request.key(),
-                    budget = ?budget,
-                    priority = %request.priority.as_str(),
-                    token_id = %token.id,
-                    github_request_id = headers
-                        .get("x-github-request-id")
-                        .and_then(|v| v.to_str().ok())
-                        .unwrap_or("-"),
-                    rate_limit_remaining = rate_info.as_ref().map(|data| data.remaining),
-                    rate_limit_reset = rate_info
-                        .as_ref()
-                        .map(|data| data.reset.timestamp()),
-                    "GitHub returned secondary rate limit response"
-                );
+                if let Some(suppressed) = inner.throttle_log.sample(budget) {
+                    warn!(
+                        status = %status,
+                        request = %request.log_key(),
+                        budget = ?budget,
+                        priority = %request.priority.as_str(),
+                        token_id = %token.id,
+                        github_request_id = headers
+                            .get("x-github-request-id")
+                            .and_then(|v| v.to_str().ok())
+                            .unwrap_or("-"),
+                        rate_limit_remaining = rate_info.as_ref().map(|data| data.remaining),
+                        rate_limit_reset = rate_info
+                            .as_ref()
+                            .map(|data| data.reset.timestamp()),
+                        suppressed,
+                        "GitHub returned secondary rate limit response"
+                    );
+                } else {
+                    debug!(
+                        status = %status,
+                        request = %request.log_key(),
+                        budget = ?budget,
+                        token_id = %token.id,
+                        "GitHub returned secondary rate limit response"
+                    );
+                }
                 metrics::SLEEP_SECONDS
                     .with_label_values(&[budget_label(budget), "secondary_limit"])
                     .inc_by(3);
                 sleep(Duration::from_secs(3)).await;
-                return Err(anyhow::anyhow!("secondary rate limit"));
+                return Err(Throttled("secondary rate limit").into());
             }
 
             let rate_info = parse_rate_limit(&headers);
@@ -826,7 +1408,7 @@ async fn execute_once(
             let body_preview = body_preview(&body_snapshot);
             warn!(
                 status = %status,
-                request = %request.key(),
+                request = %request.log_key(),
                 budget = ?budget,
                 priority = %request.priority.as_str(),
                 token_id = %token.id,
@@ -842,6 +1424,7 @@ async fn execute_once(
                 status,
                 request.uri().path().trim_start_matches('/'),
             )
+            .with_body(body_snapshot)
             .into())
         }
         Err(err) => Err(err),
@@ -853,18 +1436,48 @@ fn body_preview(body: &[u8]) -> String {
         return String::new();
     }
     let text = String::from_utf8_lossy(body);
-    truncate_str(&text, 256)
+    common::text::truncate_chars(&text, 256)
 }
 
-fn truncate_str(value: &str, limit: usize) -> String {
-    if value.is_empty() {
-        return String::new();
-    }
-    let mut truncated: String = value.chars().take(limit).collect();
-    if truncated.len() < value.len() {
-        truncated.push('…');
-    }
-    truncated
+// Marks the token's GraphQL budget as exhausted until the reset reported in the
+// body, falling back to the headers and then to a short fixed pause.
+async fn park_rate_limited_token(
+    inner: &Inner,
+    token_id: &str,
+    limited: &GraphqlRateLimitedError,
+    headers_update: Option<&RateLimitUpdate>,
+) {
+    metrics::GRAPHQL_RATE_LIMITED
+        .with_label_values(&[token_id])
+        .inc();
+    let reset = limited
+        .reset_at
+        .or_else(|| headers_update.map(|update| update.reset))
+        .filter(|reset| *reset > chrono::Utc::now())
+        .unwrap_or_else(|| chrono::Utc::now() + chrono::Duration::seconds(60));
+    let limit = match inner
+        .token_pool
+        .get_numbers(Budget::Graphql, token_id)
+        .await
+    {
+        Some((limit, _)) => limit,
+        None => return,
+    };
+    inner
+        .token_pool
+        .update(
+            Budget::Graphql,
+            token_id,
+            RateLimitUpdate {
+                limit,
+                remaining: 0,
+                reset,
+            },
+        )
+        .await;
+    metrics::RATE_REMAINING
+        .with_label_values(&[token_id, budget_label(Budget::Graphql)])
+        .set(0);
 }
 
 fn extract_graphql_cost(response: &BrokerResponse) -> Option<u64> {
@@ -896,11 +1509,7 @@ fn status_class(status: StatusCode) -> &'static str {
 }
 
 fn budget_label(budget: Budget) -> &'static str {
-    match budget {
-        Budget::Core => "core",
-        Budget::Search => "search",
-        Budget::Graphql => "graphql",
-    }
+    budget.as_str()
 }
 
 fn priority_label(priority: crate::model::Priority) -> &'static str {
@@ -0,0 +1,49 @@
+use serde_json::Value;
+
+/// Estimates what a GraphQL query costs from its page-size variables, for
+/// responses that do not report `rateLimit.cost`.
+///
+/// Follows GitHub's published formula: every connection needs one request per
+/// parent node, assuming each page is full, and 100 requests cost one point.
+/// Rounds up rather than to nearest so estimates err towards scheduling less.
+#[derive(Debug, Clone)]
+pub struct GraphqlCostModel {
+    /// Variables holding connection page sizes, outermost connection first;
+    /// each present one is taken as nested inside the previous.
+    pub page_size_variables: Vec<String>,
+    /// Connection requests charged as one point.
+    pub requests_per_point: u64,
+}
+
+impl Default for GraphqlCostModel {
+    fn default() -> Self {
+        Self {
+            page_size_variables: vec![
+                "perPage".to_string(),
+                "commentsPerPage".to_string(),
+                "first".to_string(),
+            ],
+            requests_per_point: 100,
+        }
+    }
+}
+
+impl GraphqlCostModel {
+    /// Cost of the GraphQL request `body`, at least 1.
+    pub fn estimate(&self, body: &[u8]) -> u64 {
+        let variables = serde_json::from_slice::<Value>(body)
+            .ok()
+            .and_then(|body| body.get("variables").cloned())
+            .unwrap_or(Value::Null);
+        let mut parents = 1u64;
+        let mut requests = 0u64;
+        for name in &self.page_size_variables {
+            let Some(size) = variables.get(name).and_then(Value::as_u64) else {
+                continue;
+            };
+            requests = requests.saturating_add(parents);
+            parents = parents.saturating_mul(size);
+        }
+        requests.div_ceil(self.requests_per_point.max(1)).max(1)
+    }
+}
@@ -1,11 +1,19 @@
 use std::fmt;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use http::StatusCode;
+use serde_json::Value;
 
-#[derive(Debug)]
+use crate::model::Budget;
+
+#[derive(Debug, Clone)]
 pub struct HttpStatusError {
     pub status: StatusCode,
     pub endpoint: String,
+    /// The error response body, e.g. GitHub's `{"message": ..., "errors": [...]}`;
+    /// empty when there was none.
+    pub body: Vec<u8>,
 }
 
 impl HttpStatusError {
@@ -13,6 +21,7 @@ impl HttpStatusError {
         Self {
             status,
             endpoint: String::new(),
+            body: Vec::new(),
         }
     }
 
@@ -20,8 +29,14 @@ impl HttpStatusError {
         Self {
             status,
             endpoint: endpoint.into(),
+            body: Vec::new(),
         }
     }
+
+    pub fn with_body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
 }
 
 impl fmt::Display for HttpStatusError {
@@ -35,3 +50,87 @@ impl fmt::Display for HttpStatusError {
 }
 
 impl std::error::Error for HttpStatusError {}
+
+/// A GraphQL response carrying a `RATE_LIMITED` error. GitHub answers these with
+/// HTTP 200, so they must be recognised from the body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphqlRateLimitedError {
+    /// `data.rateLimit.resetAt`, when the response still included it.
+    pub reset_at: Option<DateTime<Utc>>,
+}
+
+impl GraphqlRateLimitedError {
+    pub fn from_body(body: &Value) -> Option<Self> {
+        let limited = body
+            .get("errors")
+            .and_then(Value::as_array)?
+            .iter()
+            .any(|error| {
+                error
+                    .get("type")
+                    .or_else(|| error.get("extensions").and_then(|ext| ext.get("code")))
+                    .and_then(Value::as_str)
+                    == Some("RATE_LIMITED")
+            });
+        if !limited {
+            return None;
+        }
+        let reset_at = body
+            .get("data")
+            .and_then(|data| data.get("rateLimit"))
+            .and_then(|rl| rl.get("resetAt"))
+            .and_then(Value::as_str)
+            .and_then(|reset| DateTime::parse_from_rfc3339(reset).ok())
+            .map(|reset| reset.with_timezone(&Utc));
+        Some(Self { reset_at })
+    }
+}
+
+impl fmt::Display for GraphqlRateLimitedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reset_at {
+            Some(reset) => write!(f, "graphql rate limited until {}", reset.to_rfc3339()),
+            None => write!(f, "graphql rate limited"),
+        }
+    }
+}
+
+impl std::error::Error for GraphqlRateLimitedError {}
+
+impl From<GraphqlRateLimitedError> for common::AppError {
+    fn from(err: GraphqlRateLimitedError) -> Self {
+        let retry_after = err
+            .reset_at
+            .map(|reset| (reset - Utc::now()).to_std().unwrap_or_default());
+        common::AppError::rate_limited(retry_after)
+    }
+}
+
+/// Every token of a budget is rate-limited for longer than the broker's
+/// configured `max_rate_limit_wait`, so the request was failed instead of
+/// holding an inflight slot until the reset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokensExhaustedError {
+    pub budget: Budget,
+    /// How long the soonest token reset was still away.
+    pub wait: Duration,
+}
+
+impl fmt::Display for TokensExhaustedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "every {:?} token is rate limited for another {}s",
+            self.budget,
+            self.wait.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for TokensExhaustedError {}
+
+impl From<TokensExhaustedError> for common::AppError {
+    fn from(err: TokensExhaustedError) -> Self {
+        common::AppError::rate_limited(Some(err.wait))
+    }
+}
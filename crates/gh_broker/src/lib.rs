@@ -1,12 +1,19 @@
+pub mod app_token;
 pub mod backoff;
 pub mod broker;
 pub mod cache;
+pub mod cost;
 pub mod error;
 pub mod metrics;
 pub mod model;
+pub mod retry;
+pub mod throttle_log;
 pub mod token;
 
+pub use app_token::AppInstallationToken;
 pub use broker::{GithubBroker, GithubBrokerBuilder};
-pub use error::HttpStatusError;
+pub use cost::GraphqlCostModel;
+pub use error::{GraphqlRateLimitedError, HttpStatusError, TokensExhaustedError};
 pub use model::{Budget, GithubRequest, Priority};
-pub use token::{GithubToken, RateLimitState};
+pub use retry::RetryingHttpExec;
+pub use token::{GithubToken, RateLimitState, TokenBudgetSnapshot, TokenProvider};
@@ -1,7 +1,7 @@
 use once_cell::sync::Lazy;
 use prometheus::{
-    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, HistogramVec,
-    IntCounterVec, IntGaugeVec,
+    register_gauge_vec, register_histogram_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge_vec, GaugeVec, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec,
 };
 
 pub static QUEUE_LENGTH: Lazy<IntGaugeVec> = Lazy::new(|| {
@@ -80,6 +80,60 @@ pub static BUDGET_REMAINING_TOTAL: Lazy<IntGaugeVec> = Lazy::new(|| {
     .expect("budget remaining total")
 });
 
+pub static WORKER_LAST_TICK: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "gh_broker_worker_last_tick_timestamp",
+        "Unix time (seconds) of the budget worker's last loop iteration; stalls when the worker is stuck or dead",
+        &["budget"]
+    )
+    .expect("worker last tick gauge")
+});
+
+pub static WORKER_RESTARTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gh_broker_worker_restarts_total",
+        "Budget workers respawned after panicking",
+        &["budget"]
+    )
+    .expect("worker restarts counter")
+});
+
+pub static ABANDONED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gh_broker_abandoned_total",
+        "Requests dropped because every caller cancelled before they completed",
+        &["budget"]
+    )
+    .expect("abandoned counter")
+});
+
+pub static COALESCED_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gh_broker_coalesced_errors_total",
+        "Failures handed to coalesced waiters beyond the caller that issued the request",
+        &["kind"]
+    )
+    .expect("coalesced errors counter")
+});
+
+pub static GRAPHQL_RATE_LIMITED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gh_broker_graphql_rate_limited_total",
+        "GraphQL responses rejected with a RATE_LIMITED error, per token",
+        &["token"]
+    )
+    .expect("graphql rate limited")
+});
+
+pub static ALL_TOKENS_EXHAUSTED: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "gh_broker_all_tokens_exhausted",
+        "1 while every token of the budget is rate-limited and requests are waiting for a reset",
+        &["budget"]
+    )
+    .expect("all tokens exhausted gauge")
+});
+
 pub static SLEEP_SECONDS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "gh_broker_sleep_seconds_total",
@@ -125,6 +179,23 @@ pub static CACHE_MISSES: Lazy<IntCounterVec> = Lazy::new(|| {
     .expect("cache misses")
 });
 
+pub static SCHEDULER_CYCLES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gh_broker_scheduler_cycles_total",
+        "Weighted scheduling passes that processed at least one request, by budget",
+        &["budget"]
+    )
+    .expect("scheduler cycles")
+});
+
+pub static USER_NOT_MODIFIED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "gh_broker_user_not_modified_total",
+        "User lookups revalidated with a 304 and served from the ETag cache"
+    )
+    .expect("user not modified")
+});
+
 pub static LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         "gh_broker_latency_seconds",
@@ -133,3 +204,12 @@ pub static LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .expect("latency")
 });
+
+pub static APP_TOKEN_REFRESHES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gh_broker_app_token_refreshes_total",
+        "GitHub App installation token exchanges by outcome",
+        &["outcome"]
+    )
+    .expect("app token refreshes")
+});
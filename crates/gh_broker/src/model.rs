@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use common::redact::redact_query;
 use http::{header, HeaderMap, HeaderValue, Request};
 use sha2::{Digest, Sha256};
 use std::time::Duration;
@@ -11,6 +12,16 @@ pub enum Budget {
 }
 
 impl Budget {
+    pub const ALL: [Budget; 3] = [Budget::Core, Budget::Search, Budget::Graphql];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Budget::Core => "core",
+            Budget::Search => "search",
+            Budget::Graphql => "graphql",
+        }
+    }
+
     pub fn classify(path: &str, resource_header: Option<&HeaderValue>) -> Self {
         if let Some(resource) = resource_header.and_then(|v| v.to_str().ok()) {
             return match resource {
@@ -20,7 +31,13 @@ impl Budget {
             };
         }
 
-        if path == "/graphql" {
+        // GHES serves REST under `/api/v3` and GraphQL at `/api/graphql`; compare
+        // against the API-relative path so repos named `graphql` or `search` stay Core.
+        let path = path
+            .strip_prefix("/api/v3")
+            .or_else(|| path.strip_prefix("/api"))
+            .unwrap_or(path);
+        if path.trim_end_matches('/') == "/graphql" {
             Budget::Graphql
         } else if path.starts_with("/search/") {
             Budget::Search
@@ -55,6 +72,8 @@ pub struct GithubRequest {
     pub budget: Budget,
     pub priority: Priority,
     pub key: String,
+    // `key` with secrets in the query masked, for logs.
+    log_key: String,
 }
 
 impl Clone for GithubRequest {
@@ -75,10 +94,33 @@ impl Clone for GithubRequest {
             budget: self.budget,
             priority: self.priority,
             key: self.key.clone(),
+            log_key: self.log_key.clone(),
         }
     }
 }
 
+/// Path as used in request keys. GitHub resolves logins case-insensitively,
+/// so `/users/Octocat` and `/users/octocat` share one cache entry and ETag.
+fn key_path(path: &str) -> std::borrow::Cow<'_, str> {
+    match path.strip_prefix("/users/") {
+        Some(rest) if !rest.is_empty() => format!("/users/{}", rest.to_lowercase()).into(),
+        _ => path.into(),
+    }
+}
+
+/// `key` with its query string redacted. Keys carry no spaces before the
+/// query, and a body-hash suffix follows it after a space.
+fn redact_key(key: &str, extra_params: &[String]) -> String {
+    let Some((head, rest)) = key.split_once('?') else {
+        return key.to_string();
+    };
+    let (query, tail) = match rest.split_once(' ') {
+        Some((query, tail)) => (query, format!(" {tail}")),
+        None => (rest, String::new()),
+    };
+    format!("{head}?{}{tail}", redact_query(query, extra_params))
+}
+
 impl GithubRequest {
     pub fn new(inner: Request<Vec<u8>>, priority: Priority) -> anyhow::Result<Self> {
         let resource_hdr = inner.headers().get("x-ratelimit-resource").cloned();
@@ -89,7 +131,7 @@ impl GithubRequest {
         let mut key = format!(
             "{} {}{}",
             inner.method(),
-            inner.uri().path(),
+            key_path(inner.uri().path()),
             inner
                 .uri()
                 .query()
@@ -114,14 +156,22 @@ impl GithubRequest {
             return Err(anyhow::anyhow!("user-agent header required"));
         }
 
+        let log_key = redact_key(&key, &[]);
         Ok(Self {
             inner,
             budget,
             priority,
             key,
+            log_key,
         })
     }
 
+    /// Also masks the values of `params` in [`Self::log_key`], besides
+    /// [`common::redact::REDACTED_QUERY_PARAMS`].
+    pub fn redact_params(&mut self, params: &[String]) {
+        self.log_key = redact_key(&self.key, params);
+    }
+
     pub fn request(&self) -> Request<Vec<u8>> {
         self.clone().inner
     }
@@ -138,9 +188,19 @@ impl GithubRequest {
         self.inner.uri()
     }
 
+    pub fn body(&self) -> &[u8] {
+        self.inner.body()
+    }
+
     pub fn key(&self) -> &str {
         &self.key
     }
+
+    /// The key as it appears in logs: query values that look secret are
+    /// masked, so tokens passed as `?access_token=` never reach a log line.
+    pub fn log_key(&self) -> &str {
+        &self.log_key
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -199,3 +259,79 @@ pub fn parse_retry_after(headers: &HeaderMap) -> Option<RetryAdvice> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_public_api_paths() {
+        assert_eq!(Budget::classify("/graphql", None), Budget::Graphql);
+        assert_eq!(Budget::classify("/search/code", None), Budget::Search);
+        assert_eq!(Budget::classify("/search/issues", None), Budget::Search);
+        assert_eq!(Budget::classify("/repos/o/r/issues", None), Budget::Core);
+    }
+
+    #[test]
+    fn classify_enterprise_api_paths() {
+        assert_eq!(Budget::classify("/api/graphql", None), Budget::Graphql);
+        assert_eq!(
+            Budget::classify("/api/v3/search/code", None),
+            Budget::Search
+        );
+        assert_eq!(
+            Budget::classify("/api/v3/repos/o/r/issues", None),
+            Budget::Core
+        );
+    }
+
+    #[test]
+    fn classify_ignores_repos_named_like_budgets() {
+        assert_eq!(Budget::classify("/repos/o/graphql", None), Budget::Core);
+        assert_eq!(
+            Budget::classify("/repos/search/x/issues", None),
+            Budget::Core
+        );
+        assert_eq!(
+            Budget::classify("/api/v3/repos/o/graphql", None),
+            Budget::Core
+        );
+    }
+
+    #[test]
+    fn classify_prefers_resource_header() {
+        let header = HeaderValue::from_static("search");
+        assert_eq!(Budget::classify("/graphql", Some(&header)), Budget::Search);
+    }
+
+    #[test]
+    fn log_key_masks_secret_query_values() {
+        let request = Request::get(
+            "https://ghes.example/api/v3/repos/o/r/issues?state=all&access_token=s3cr3t&sig=abc",
+        )
+        .header(header::USER_AGENT, "test-agent")
+        .body(Vec::new())
+        .unwrap();
+        let mut request = GithubRequest::new(request, Priority::Normal).unwrap();
+        assert!(request.key().contains("access_token=s3cr3t"));
+        assert_eq!(
+            request.log_key(),
+            "GET /api/v3/repos/o/r/issues?state=all&access_token=REDACTED&sig=abc"
+        );
+
+        request.redact_params(&["SIG".to_string()]);
+        assert_eq!(
+            request.log_key(),
+            "GET /api/v3/repos/o/r/issues?state=all&access_token=REDACTED&sig=REDACTED"
+        );
+
+        let graphql = Request::post("https://api.github.com/graphql?token=s3cr3t")
+            .header(header::USER_AGENT, "test-agent")
+            .body(b"{}".to_vec())
+            .unwrap();
+        let graphql = GithubRequest::new(graphql, Priority::Normal).unwrap();
+        assert!(graphql
+            .log_key()
+            .starts_with("POST /graphql?token=REDACTED body:"));
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::redact::redact_query;
+use http::{HeaderMap, Request, Response, StatusCode, Uri};
+use tokio::time::sleep;
+use tracing::warn;
+
+use crate::backoff::exponential_jitter_backoff;
+use crate::broker::HttpExec;
+use crate::metrics;
+use crate::model::Budget;
+
+/// Wraps any [`HttpExec`] with the broker's exponential jitter backoff so
+/// one-off calls (token verification, `/rate_limit` probes) get the same
+/// retry behaviour as queued requests without going through the queue.
+///
+/// Transport errors, 5xx, 429 and rate-limited 403s are retried; every other
+/// response is returned as-is for the caller to interpret.
+pub struct RetryingHttpExec {
+    inner: Arc<dyn HttpExec>,
+    max_attempts: u32,
+    backoff_base: Duration,
+    backoff_max: Duration,
+    jitter_frac: f32,
+}
+
+impl RetryingHttpExec {
+    pub fn new(inner: Arc<dyn HttpExec>) -> Self {
+        Self {
+            inner,
+            max_attempts: 5,
+            backoff_base: Duration::from_millis(500),
+            backoff_max: Duration::from_millis(60_000),
+            jitter_frac: 0.2,
+        }
+    }
+
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    pub fn backoff(mut self, base: Duration, max: Duration, jitter_frac: f32) -> Self {
+        self.backoff_base = base;
+        self.backoff_max = max;
+        self.jitter_frac = jitter_frac;
+        self
+    }
+}
+
+#[async_trait]
+impl HttpExec for RetryingHttpExec {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let budget = Budget::classify(req.uri().path(), None);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = self.inner.execute(clone_request(&req)).await;
+            let reason = match &result {
+                Ok(resp) if !is_retryable_response(resp.status(), resp.headers()) => return result,
+                Ok(_) => "status",
+                Err(_) => "error",
+            };
+            if attempt >= self.max_attempts {
+                return result;
+            }
+
+            warn!(
+                attempt,
+                budget = budget.as_str(),
+                uri = %redacted_uri(req.uri()),
+                status = result.as_ref().ok().map(|resp| resp.status().as_u16()),
+                error = result.as_ref().err().map(|err| err.to_string()),
+                "retrying GitHub request"
+            );
+            metrics::RETRIES_TOTAL
+                .with_label_values(&[budget.as_str(), reason])
+                .inc();
+            sleep(exponential_jitter_backoff(
+                self.backoff_base,
+                attempt - 1,
+                self.backoff_max,
+                self.jitter_frac,
+            ))
+            .await;
+        }
+    }
+}
+
+/// 403 is only retried when GitHub signals rate limiting; otherwise it is a
+/// permission error that will not go away.
+pub fn is_retryable_response(status: StatusCode, headers: &HeaderMap) -> bool {
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        return true;
+    }
+    status == StatusCode::FORBIDDEN
+        && (headers.contains_key(http::header::RETRY_AFTER)
+            || headers
+                .get("x-ratelimit-remaining")
+                .and_then(|v| v.to_str().ok())
+                == Some("0"))
+}
+
+/// `uri` for logs, with secrets in its query masked.
+fn redacted_uri(uri: &Uri) -> String {
+    let mut logged = uri.to_string();
+    if let Some(query) = uri.query() {
+        logged.truncate(logged.len() - query.len());
+        logged.push_str(&redact_query(query, &[]));
+    }
+    logged
+}
+
+fn clone_request(req: &Request<Vec<u8>>) -> Request<Vec<u8>> {
+    let mut clone = Request::new(req.body().clone());
+    *clone.method_mut() = req.method().clone();
+    *clone.uri_mut() = req.uri().clone();
+    *clone.version_mut() = req.version();
+    *clone.headers_mut() = req.headers().clone();
+    clone
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::model::Budget;
+
+/// Samples the warnings for expected throttling (`retry-after` and secondary
+/// limits) so a busy broker logs at most one line per budget per interval.
+/// `SLEEP_SECONDS` still counts every wait.
+pub struct ThrottleLog {
+    interval: Duration,
+    windows: Mutex<HashMap<Budget, Window>>,
+}
+
+struct Window {
+    logged_at: Instant,
+    suppressed: u64,
+}
+
+impl ThrottleLog {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns how many throttles were suppressed since the last logged one
+    /// when this throttle should be logged, or `None` to skip it.
+    pub fn sample(&self, budget: Budget) -> Option<u64> {
+        self.sample_at(budget, Instant::now())
+    }
+
+    fn sample_at(&self, budget: Budget, now: Instant) -> Option<u64> {
+        let mut windows = self.windows.lock().expect("throttle log lock poisoned");
+        match windows.get_mut(&budget) {
+            Some(window) if now.duration_since(window.logged_at) < self.interval => {
+                window.suppressed += 1;
+                None
+            }
+            Some(window) => {
+                window.logged_at = now;
+                Some(std::mem::take(&mut window.suppressed))
+            }
+            None => {
+                windows.insert(
+                    budget,
+                    Window {
+                        logged_at: now,
+                        suppressed: 0,
+                    },
+                );
+                Some(0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logs_once_per_interval_per_budget_with_suppressed_count() {
+        let log = ThrottleLog::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert_eq!(log.sample_at(Budget::Core, start), Some(0));
+        assert_eq!(
+            log.sample_at(Budget::Core, start + Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(
+            log.sample_at(Budget::Core, start + Duration::from_secs(59)),
+            None
+        );
+        // Budgets are sampled independently.
+        assert_eq!(log.sample_at(Budget::Graphql, start), Some(0));
+
+        assert_eq!(
+            log.sample_at(Budget::Core, start + Duration::from_secs(60)),
+            Some(2)
+        );
+        assert_eq!(
+            log.sample_at(Budget::Core, start + Duration::from_secs(200)),
+            Some(0)
+        );
+    }
+}
@@ -1,15 +1,35 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use tokio::sync::Mutex;
 
-use crate::model::{Budget, RateLimitUpdate};
+use crate::model::{Budget, Priority, RateLimitUpdate};
 
 #[derive(Debug, Clone)]
 pub struct GithubToken {
     pub id: String,
     pub secret: String,
+    /// Budgets this token may be picked for (e.g. a fine-grained token that
+    /// only works for REST); `None` allows all of them.
+    pub budgets: Option<Vec<Budget>>,
+}
+
+impl GithubToken {
+    pub fn serves(&self, budget: Budget) -> bool {
+        self.budgets
+            .as_ref()
+            .is_none_or(|budgets| budgets.contains(&budget))
+    }
+}
+
+/// Supplies the secret for a pool entry whose credential is not static, such
+/// as a GitHub App installation token. Called before every request, so
+/// implementations should cache and only refresh near expiry.
+#[async_trait]
+pub trait TokenProvider: Send + Sync {
+    async fn secret(&self) -> anyhow::Result<String>;
 }
 
 #[derive(Debug, Clone)]
@@ -66,9 +86,21 @@ impl TokenState {
     }
 }
 
+/// Point-in-time view of one token's budget as tracked by the pool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenBudgetSnapshot {
+    pub token_id: String,
+    pub budget: Budget,
+    pub limit: i64,
+    pub remaining: i64,
+    pub reset_at: DateTime<Utc>,
+}
+
 #[derive(Clone)]
 pub struct TokenPool {
     inner: Arc<Mutex<Vec<TokenState>>>,
+    // Remaining calls per token/budget that only Critical requests may spend.
+    critical_reserve: i64,
 }
 
 pub enum TokenSelection {
@@ -81,18 +113,29 @@ impl TokenPool {
         let states = tokens.into_iter().map(TokenState::new).collect();
         Self {
             inner: Arc::new(Mutex::new(states)),
+            critical_reserve: 0,
         }
     }
 
-    pub async fn pick_token(&self, budget: Budget) -> TokenSelection {
+    pub fn with_critical_reserve(mut self, reserve: i64) -> Self {
+        self.critical_reserve = reserve.max(0);
+        self
+    }
+
+    pub async fn pick_token(&self, budget: Budget, priority: Priority) -> TokenSelection {
         let mut guard = self.inner.lock().await;
         let now = Utc::now();
+        let floor = if priority == Priority::Critical {
+            0
+        } else {
+            self.critical_reserve
+        };
         let mut best = None;
         let mut next_reset = None;
 
-        for state in guard.iter_mut() {
+        for state in guard.iter_mut().filter(|state| state.token.serves(budget)) {
             let rl = state.state_for(budget);
-            if rl.remaining > 0 || rl.reset_at <= now {
+            if rl.remaining > floor || rl.reset_at <= now {
                 let score = rl.remaining as f64 / rl.limit.max(1) as f64;
                 match best {
                     None => best = Some((score, state.token.clone())),
@@ -116,7 +159,7 @@ impl TokenPool {
         } else if let Some(wait) = next_reset {
             TokenSelection::Wait(wait)
         } else {
-            // No tokens configured
+            // No tokens configured for this budget
             TokenSelection::Wait(std::time::Duration::from_secs(30))
         }
     }
@@ -156,7 +199,7 @@ impl TokenPool {
         let guard = self.inner.lock().await;
         let mut limit_sum: i64 = 0;
         let mut remaining_sum: i64 = 0;
-        for token in guard.iter() {
+        for token in guard.iter().filter(|token| token.token.serves(budget)) {
             if let Some(state) = token.budgets.get(&budget) {
                 limit_sum += state.limit;
                 remaining_sum += state.remaining;
@@ -165,6 +208,26 @@ impl TokenPool {
         (limit_sum, remaining_sum)
     }
 
+    /// Limit/remaining/reset for every token and budget, in token then budget order.
+    pub async fn snapshot(&self) -> Vec<TokenBudgetSnapshot> {
+        let guard = self.inner.lock().await;
+        let mut out = Vec::with_capacity(guard.len() * Budget::ALL.len());
+        for token in guard.iter() {
+            for budget in Budget::ALL.into_iter().filter(|b| token.token.serves(*b)) {
+                if let Some(state) = token.budgets.get(&budget) {
+                    out.push(TokenBudgetSnapshot {
+                        token_id: token.token.id.clone(),
+                        budget,
+                        limit: state.limit,
+                        remaining: state.remaining,
+                        reset_at: state.reset_at,
+                    });
+                }
+            }
+        }
+        out
+    }
+
     // Returns a snapshot of all token ids currently tracked.
     pub async fn token_ids(&self) -> Vec<String> {
         let guard = self.inner.lock().await;
@@ -0,0 +1,165 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use http::{header, Method, Request, Response, StatusCode};
+use serde_json::{json, Value};
+
+use gh_broker::broker::HttpExec;
+use gh_broker::{
+    AppInstallationToken, GithubBrokerBuilder, HttpStatusError, Priority, TokenProvider,
+};
+
+const KEY: &str = include_str!("fixtures/test_app_key.pem");
+
+/// Mints `ghs_<n>` installation tokens valid for `lifetime`, and records the
+/// `Authorization` header of every other request.
+struct MockGithub {
+    lifetime: Duration,
+    status: StatusCode,
+    exchanges: Mutex<Vec<String>>,
+    api_auth: Mutex<Vec<String>>,
+}
+
+impl MockGithub {
+    fn new(lifetime: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            lifetime,
+            status: StatusCode::CREATED,
+            exchanges: Mutex::new(Vec::new()),
+            api_auth: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn exchange_count(&self) -> usize {
+        self.exchanges.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl HttpExec for MockGithub {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let auth = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .map(|v| v.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        if req.uri().path() == "/app/installations/42/access_tokens" {
+            assert_eq!(req.method(), Method::POST);
+            let mut exchanges = self.exchanges.lock().unwrap();
+            exchanges.push(auth);
+            if !self.status.is_success() {
+                return Ok(Response::builder()
+                    .status(self.status)
+                    .body(br#"{"message":"Bad credentials"}"#.to_vec())?);
+            }
+            let body = json!({
+                "token": format!("ghs_{}", exchanges.len()),
+                "expires_at": (Utc::now() + self.lifetime).to_rfc3339(),
+            });
+            return Ok(Response::builder()
+                .status(self.status)
+                .body(body.to_string().into_bytes())?);
+        }
+        self.api_auth.lock().unwrap().push(auth);
+        Ok(Response::builder().status(200).body(b"{}".to_vec())?)
+    }
+}
+
+fn provider(mock: &Arc<MockGithub>) -> AppInstallationToken {
+    AppInstallationToken::new("1234", 42, KEY, mock.clone()).expect("test key parses")
+}
+
+#[tokio::test]
+async fn reuses_token_until_it_nears_expiry() -> Result<()> {
+    let mock = MockGithub::new(Duration::hours(1));
+    let tokens = provider(&mock);
+
+    assert_eq!(tokens.secret().await?, "ghs_1");
+    assert_eq!(tokens.secret().await?, "ghs_1");
+    assert_eq!(mock.exchange_count(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn refreshes_token_inside_refresh_margin() -> Result<()> {
+    // Every minted token is already within the default five-minute margin.
+    let mock = MockGithub::new(Duration::minutes(2));
+    let tokens = provider(&mock);
+
+    assert_eq!(tokens.secret().await?, "ghs_1");
+    assert_eq!(tokens.secret().await?, "ghs_2");
+    assert_eq!(mock.exchange_count(), 2);
+
+    let relaxed = provider(&mock).refresh_margin(Duration::seconds(30));
+    assert_eq!(relaxed.secret().await?, "ghs_3");
+    assert_eq!(relaxed.secret().await?, "ghs_3");
+    Ok(())
+}
+
+#[tokio::test]
+async fn exchange_is_authenticated_with_app_jwt() -> Result<()> {
+    let mock = MockGithub::new(Duration::hours(1));
+    provider(&mock).secret().await?;
+
+    let auth = mock.exchanges.lock().unwrap()[0].clone();
+    let jwt = auth.strip_prefix("Bearer ").expect("bearer auth");
+    let parts: Vec<&str> = jwt.split('.').collect();
+    assert_eq!(parts.len(), 3);
+    let header: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[0])?)?;
+    assert_eq!(header["alg"], "RS256");
+    let claims: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[1])?)?;
+    assert_eq!(claims["iss"], "1234");
+    let lifetime = claims["exp"].as_i64().unwrap() - claims["iat"].as_i64().unwrap();
+    assert!(lifetime <= 600, "GitHub rejects JWTs over ten minutes");
+    // 2048-bit key.
+    assert_eq!(URL_SAFE_NO_PAD.decode(parts[2])?.len(), 256);
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejected_exchange_surfaces_status() -> Result<()> {
+    let mock = Arc::new(MockGithub {
+        lifetime: Duration::hours(1),
+        status: StatusCode::UNAUTHORIZED,
+        exchanges: Mutex::new(Vec::new()),
+        api_auth: Mutex::new(Vec::new()),
+    });
+    let err = provider(&mock).secret().await.unwrap_err();
+    let status = err.downcast_ref::<HttpStatusError>().expect("status error");
+    assert_eq!(status.status, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[test]
+fn rejects_non_pem_key() {
+    let mock = MockGithub::new(Duration::hours(1));
+    assert!(AppInstallationToken::new("1234", 42, "not a key", mock).is_err());
+}
+
+#[tokio::test]
+async fn broker_sends_installation_token_for_provider_entries() -> Result<()> {
+    let mock = MockGithub::new(Duration::hours(1));
+    let broker = GithubBrokerBuilder::new(Vec::new())
+        .token_provider("app-42", Arc::new(provider(&mock)))
+        .http_exec(mock.clone())
+        .build();
+
+    for path in ["/users/a", "/users/b"] {
+        let request = Request::get(format!("https://api.github.com{path}"))
+            .header(header::USER_AGENT, "test-agent")
+            .body(Vec::new())?;
+        let response = broker.enqueue(request, Priority::Normal).await?;
+        assert_eq!(response.status(), 200);
+    }
+
+    assert_eq!(
+        *mock.api_auth.lock().unwrap(),
+        vec!["token ghs_1".to_string(), "token ghs_1".to_string()]
+    );
+    assert_eq!(mock.exchange_count(), 1);
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use http::{Request, Response};
+use tokio::sync::Notify;
+
+use gh_broker::broker::HttpExec;
+use gh_broker::{GithubBrokerBuilder, GithubToken, Priority};
+
+/// Holds `/users/slow` until released; answers everything else immediately.
+struct SlowExec {
+    release: Notify,
+    seen: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl HttpExec for SlowExec {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let path = req.uri().path().to_string();
+        self.seen.lock().unwrap().push(path.clone());
+        if path == "/users/slow" {
+            self.release.notified().await;
+        }
+        Ok(Response::builder().status(200).body(b"{}".to_vec())?)
+    }
+}
+
+fn get(path: &str) -> Request<Vec<u8>> {
+    Request::get(format!("https://api.github.com{path}"))
+        .header(http::header::USER_AGENT, "test-agent")
+        .body(Vec::new())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn cancelled_enqueue_leaves_no_pending_entry_and_is_never_sent() {
+    let exec = Arc::new(SlowExec {
+        release: Notify::new(),
+        seen: Mutex::new(Vec::new()),
+    });
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec.clone())
+    .build();
+
+    // Occupies the core worker so the next request stays queued.
+    let slow = tokio::time::timeout(
+        Duration::from_millis(50),
+        broker.enqueue(get("/users/slow"), Priority::Normal),
+    )
+    .await;
+    assert!(slow.is_err(), "slow request should still be running");
+
+    let queued = tokio::time::timeout(
+        Duration::from_millis(50),
+        broker.enqueue(get("/users/queued"), Priority::Normal),
+    )
+    .await;
+    assert!(queued.is_err(), "queued request should not have run yet");
+    assert_eq!(broker.pending_requests(), 0, "cancelled waiters leaked");
+
+    exec.release.notify_one();
+    // Same queue, so once this answers the cancelled request has been dequeued.
+    let after = broker
+        .enqueue(get("/users/after"), Priority::Normal)
+        .await
+        .expect("later request succeeds");
+    assert_eq!(after.status(), 200);
+
+    assert_eq!(
+        *exec.seen.lock().unwrap(),
+        vec!["/users/slow".to_string(), "/users/after".to_string()],
+        "abandoned request must not reach GitHub"
+    );
+    assert_eq!(broker.pending_requests(), 0);
+}
+
+#[tokio::test]
+async fn shared_request_survives_one_caller_cancelling() {
+    let exec = Arc::new(SlowExec {
+        release: Notify::new(),
+        seen: Mutex::new(Vec::new()),
+    });
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec.clone())
+    .build();
+
+    let patient = tokio::spawn(broker.enqueue(get("/users/slow"), Priority::Normal));
+    let impatient = tokio::time::timeout(
+        Duration::from_millis(50),
+        broker.enqueue(get("/users/slow"), Priority::Normal),
+    )
+    .await;
+    assert!(impatient.is_err());
+    assert_eq!(
+        broker.pending_requests(),
+        1,
+        "remaining caller keeps the entry"
+    );
+
+    exec.release.notify_one();
+    let response = patient.await.unwrap().expect("remaining caller answered");
+    assert_eq!(response.status(), 200);
+    assert_eq!(broker.pending_requests(), 0);
+    assert_eq!(exec.seen.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,69 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use http::{header, Request, Response, StatusCode};
+
+use gh_broker::broker::HttpExec;
+use gh_broker::metrics::COALESCED_ERRORS;
+use gh_broker::{GithubBrokerBuilder, GithubToken, HttpStatusError, Priority};
+
+/// Answers every request with GitHub's 404 after a short delay, so identical
+/// requests enqueued together are coalesced onto one in-flight call.
+#[derive(Default)]
+struct SlowNotFound {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl HttpExec for SlowNotFound {
+    async fn execute(&self, _req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(br#"{"message":"Not Found"}"#.to_vec())?)
+    }
+}
+
+fn get_repo() -> Request<Vec<u8>> {
+    Request::get("https://api.github.com/repos/octo-org/gone")
+        .header(header::USER_AGENT, "test-agent")
+        .body(Vec::new())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn coalesced_waiters_all_see_typed_status_error() -> Result<()> {
+    let exec = Arc::new(SlowNotFound::default());
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec.clone())
+    .build();
+    let fanned_out_before = COALESCED_ERRORS.with_label_values(&["status"]).get();
+
+    let (first, second) = tokio::join!(
+        broker.enqueue(get_repo(), Priority::Normal),
+        broker.enqueue(get_repo(), Priority::Normal),
+    );
+
+    assert_eq!(exec.calls.load(Ordering::SeqCst), 1, "requests coalesce");
+    for result in [first, second] {
+        let err = result.expect_err("404 is an error");
+        let status = err
+            .downcast_ref::<HttpStatusError>()
+            .expect("typed status error");
+        assert_eq!(status.status, StatusCode::NOT_FOUND);
+        assert_eq!(status.endpoint, "repos/octo-org/gone");
+    }
+    assert_eq!(
+        COALESCED_ERRORS.with_label_values(&["status"]).get() - fanned_out_before,
+        1
+    );
+    Ok(())
+}
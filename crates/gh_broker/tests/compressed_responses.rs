@@ -0,0 +1,98 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use gh_broker::broker::ReqwestExecutor;
+use gh_broker::{GithubBrokerBuilder, GithubToken, Priority};
+
+/// `{"login":"octocat","id":1}`, gzip-compressed.
+const GZIPPED_USER: &[u8] = &[
+    0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0xab, 0x56, 0xca, 0xc9, 0x4f, 0xcf,
+    0xcc, 0x53, 0xb2, 0x52, 0xca, 0x4f, 0x2e, 0xc9, 0x4f, 0x4e, 0x2c, 0x51, 0xd2, 0x51, 0xca, 0x4c,
+    0x51, 0xb2, 0x32, 0xac, 0x05, 0x00, 0x35, 0x84, 0x94, 0x07, 0x1a, 0x00, 0x00, 0x00,
+];
+
+/// Serves the gzipped user with an ETag, then `304 Not Modified` to any
+/// request revalidating it. Records the raw request heads.
+async fn serve_user(listener: TcpListener, seen: Arc<Mutex<Vec<String>>>) {
+    loop {
+        let Ok((mut socket, _)) = listener.accept().await else {
+            return;
+        };
+        let mut buf = vec![0u8; 8192];
+        let mut len = 0;
+        while !buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+            match socket.read(&mut buf[len..]).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => len += n,
+            }
+        }
+        let head = String::from_utf8_lossy(&buf[..len]).to_lowercase();
+        let revalidating = head.contains("if-none-match: \"v1\"");
+        seen.lock().unwrap().push(head);
+
+        let mut response = Vec::new();
+        if revalidating {
+            response.extend_from_slice(
+                b"HTTP/1.1 304 Not Modified\r\netag: \"v1\"\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+            );
+        } else {
+            response.extend_from_slice(
+                format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-encoding: gzip\r\n\
+                     etag: \"v1\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    GZIPPED_USER.len()
+                )
+                .as_bytes(),
+            );
+            response.extend_from_slice(GZIPPED_USER);
+        }
+        let _ = socket.write_all(&response).await;
+        let _ = socket.shutdown().await;
+    }
+}
+
+#[tokio::test]
+async fn gzip_bodies_are_decoded_and_cached_decoded() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    tokio::spawn(serve_user(listener, seen.clone()));
+
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(Arc::new(ReqwestExecutor::new("test-agent")))
+    .build();
+    let request = || {
+        http::Request::get(format!("http://{addr}/users/octocat"))
+            .header(http::header::USER_AGENT, "test-agent")
+            .body(Vec::new())
+            .unwrap()
+    };
+
+    let fresh = broker
+        .enqueue(request(), Priority::Normal)
+        .await
+        .expect("gzipped response");
+    assert_eq!(fresh.status(), 200);
+    assert_eq!(fresh.body().as_slice(), br#"{"login":"octocat","id":1}"#);
+    assert!(fresh.headers().get("content-encoding").is_none());
+
+    let revalidated = broker
+        .enqueue(request(), Priority::Normal)
+        .await
+        .expect("revalidated response");
+    assert_eq!(revalidated.status(), 200);
+    assert_eq!(revalidated.body(), fresh.body());
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 2);
+    assert!(seen
+        .iter()
+        .all(|head| head.contains("accept-encoding: gzip, br")));
+    assert!(seen[1].contains("if-none-match: \"v1\""));
+}
@@ -0,0 +1,23 @@
+use gh_broker::broker::ReqwestExecutor;
+
+/// Self-signed CA standing in for a TLS-intercepting proxy's.
+const PROXY_CA: &str = include_str!("fixtures/test_proxy_ca.pem");
+
+#[test]
+fn executor_trusts_a_pem_ca_bundle() {
+    assert!(ReqwestExecutor::with_ca_bundle("acme-spam-lab/1.0", PROXY_CA.as_bytes()).is_ok());
+
+    // Corporate bundles often hold several certificates.
+    let bundle = format!("{PROXY_CA}\n{PROXY_CA}");
+    assert!(ReqwestExecutor::with_ca_bundle("acme-spam-lab/1.0", bundle.as_bytes()).is_ok());
+}
+
+#[test]
+fn executor_rejects_a_bundle_without_certificates() {
+    for bad in ["", "not a certificate"] {
+        assert!(
+            ReqwestExecutor::with_ca_bundle("acme-spam-lab/1.0", bad.as_bytes()).is_err(),
+            "{bad:?}"
+        );
+    }
+}
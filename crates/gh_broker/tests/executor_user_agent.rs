@@ -0,0 +1,41 @@
+use gh_broker::broker::{HttpExec, ReqwestExecutor};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Answers one request with `204` and returns its lowercased head.
+async fn capture_head(listener: TcpListener) -> String {
+    let (mut socket, _) = listener.accept().await.unwrap();
+    let mut buf = vec![0u8; 8192];
+    let mut len = 0;
+    while !buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+        match socket.read(&mut buf[len..]).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => len += n,
+        }
+    }
+    let _ = socket
+        .write_all(b"HTTP/1.1 204 No Content\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+        .await;
+    String::from_utf8_lossy(&buf[..len]).to_lowercase()
+}
+
+#[tokio::test]
+async fn executor_sends_the_configured_user_agent() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let head = tokio::spawn(capture_head(listener));
+
+    let exec = ReqwestExecutor::new("acme-spam-lab/1.0");
+    let response = exec
+        .execute(
+            http::Request::get(format!("http://{addr}/rate_limit"))
+                .body(Vec::new())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+    assert_eq!(response.status(), 204);
+
+    let head = head.await.unwrap();
+    assert!(head.contains("user-agent: acme-spam-lab/1.0\r\n"), "{head}");
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use http::{header, Request, Response, StatusCode};
+
+use gh_broker::broker::HttpExec;
+use gh_broker::{Budget, GithubBrokerBuilder, GithubToken, Priority};
+
+/// Answers every GraphQL query without a `rateLimit` block or rate headers.
+struct NoCostGraphql;
+
+#[async_trait]
+impl HttpExec for NoCostGraphql {
+    async fn execute(&self, _req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let body = serde_json::json!({"data": {"repository": {"issues": {"nodes": []}}}});
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(serde_json::to_vec(&body)?)?)
+    }
+}
+
+async fn graphql_remaining(variables: serde_json::Value) -> i64 {
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(Arc::new(NoCostGraphql))
+    .build();
+    let body =
+        serde_json::json!({"query": "query RepoIssues { __typename }", "variables": variables});
+    let request = Request::post("https://api.github.com/graphql")
+        .header(header::USER_AGENT, "test-agent")
+        .body(serde_json::to_vec(&body).unwrap())
+        .unwrap();
+    broker
+        .enqueue(request, Priority::Normal)
+        .await
+        .expect("graphql response");
+    broker
+        .rate_limit_snapshot()
+        .await
+        .into_iter()
+        .find(|snapshot| snapshot.budget == Budget::Graphql)
+        .expect("graphql budget")
+        .remaining
+}
+
+#[tokio::test]
+async fn missing_cost_is_estimated_from_page_sizes() {
+    // 1 request for the issues page + 100 for each issue's comments page.
+    let nested = graphql_remaining(serde_json::json!({
+        "owner": "o",
+        "name": "r",
+        "perPage": 100,
+        "commentsPerPage": 100
+    }))
+    .await;
+    assert!(5000 - nested > 1, "consumed {}", 5000 - nested);
+    assert_eq!(nested, 4998);
+
+    let flat = graphql_remaining(serde_json::json!({"owner": "o", "name": "r"})).await;
+    assert_eq!(flat, 4999);
+}
@@ -0,0 +1,86 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use http::{Request, Response};
+
+use gh_broker::broker::HttpExec;
+use gh_broker::metrics::GRAPHQL_RATE_LIMITED;
+use gh_broker::{Budget, GithubBrokerBuilder, GithubToken, Priority};
+
+/// Answers `RATE_LIMITED` (HTTP 200, like GitHub) for token `s1`; succeeds for any other.
+struct RateLimitedExec {
+    reset_at: DateTime<Utc>,
+    seen: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl HttpExec for RateLimitedExec {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let auth = req.headers()[http::header::AUTHORIZATION]
+            .to_str()?
+            .to_string();
+        self.seen.lock().unwrap().push(auth.clone());
+        let body = if auth == "token s1" {
+            serde_json::json!({
+                "data": {"rateLimit": {"limit": 5000, "remaining": 0, "resetAt": self.reset_at.to_rfc3339()}},
+                "errors": [{"type": "RATE_LIMITED", "message": "API rate limit exceeded for user"}]
+            })
+        } else {
+            serde_json::json!({"data": {"rateLimit": {"cost": 1}, "viewer": {"login": "octocat"}}})
+        };
+        Ok(Response::builder()
+            .status(200)
+            .body(serde_json::to_vec(&body)?)?)
+    }
+}
+
+#[tokio::test]
+async fn rate_limited_token_is_parked_until_reset_and_request_moves_on() {
+    // Whole seconds, so the RFC 3339 round trip through the body is exact.
+    let reset_at = DateTime::from_timestamp((Utc::now() + Duration::minutes(20)).timestamp(), 0)
+        .expect("valid timestamp");
+    let exec = Arc::new(RateLimitedExec {
+        reset_at,
+        seen: Mutex::new(Vec::new()),
+    });
+    let broker = GithubBrokerBuilder::new(vec![
+        GithubToken {
+            id: "t1".into(),
+            secret: "s1".into(),
+            budgets: None,
+        },
+        GithubToken {
+            id: "t2".into(),
+            secret: "s2".into(),
+            budgets: None,
+        },
+    ])
+    .http_exec(exec.clone())
+    .build();
+
+    let request = Request::post("https://api.github.com/graphql")
+        .header(http::header::USER_AGENT, "test-agent")
+        .body(br#"{"query":"{ viewer { login } }"}"#.to_vec())
+        .unwrap();
+    let response = broker
+        .enqueue(request, Priority::Normal)
+        .await
+        .expect("second token answers");
+    assert_eq!(response.status(), 200);
+
+    assert_eq!(
+        *exec.seen.lock().unwrap(),
+        vec!["token s1".to_string(), "token s2".to_string()]
+    );
+    assert_eq!(GRAPHQL_RATE_LIMITED.with_label_values(&["t1"]).get(), 1);
+
+    let snapshot = broker.rate_limit_snapshot().await;
+    let t1 = snapshot
+        .iter()
+        .find(|s| s.token_id == "t1" && s.budget == Budget::Graphql)
+        .expect("t1 graphql entry");
+    assert_eq!(t1.remaining, 0);
+    assert_eq!(t1.reset_at, reset_at);
+}
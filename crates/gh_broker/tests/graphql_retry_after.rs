@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use http::{header, Request, Response, StatusCode};
+
+use gh_broker::broker::HttpExec;
+use gh_broker::metrics::SLEEP_SECONDS;
+use gh_broker::{GithubBrokerBuilder, GithubToken, Priority};
+
+/// First call hits GitHub's GraphQL secondary limit (403 + `retry-after: 1`);
+/// later calls succeed.
+#[derive(Default)]
+struct SecondaryLimitedGraphql {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl HttpExec for SecondaryLimitedGraphql {
+    async fn execute(&self, _req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            let body = serde_json::json!({
+                "documentation_url": "https://docs.github.com/graphql/overview/rate-limits-and-node-limits-for-the-graphql-api#secondary-rate-limits",
+                "message": "You have exceeded a secondary rate limit."
+            });
+            return Ok(Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .header(header::RETRY_AFTER, "1")
+                .body(serde_json::to_vec(&body)?)?);
+        }
+        let body =
+            serde_json::json!({"data": {"rateLimit": {"cost": 1}, "viewer": {"login": "octocat"}}});
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(serde_json::to_vec(&body)?)?)
+    }
+}
+
+#[tokio::test]
+async fn graphql_403_with_retry_after_waits_then_retries() {
+    let exec = Arc::new(SecondaryLimitedGraphql::default());
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec.clone())
+    .backoff(Duration::from_millis(1), Duration::from_millis(1), 0.0)
+    .build();
+    let slept_before = SLEEP_SECONDS
+        .with_label_values(&["graphql", "retry_after"])
+        .get();
+
+    let request = Request::post("https://api.github.com/graphql")
+        .header(header::USER_AGENT, "test-agent")
+        .body(br#"{"query":"{ viewer { login } }"}"#.to_vec())
+        .unwrap();
+    let started = Instant::now();
+    let response = broker
+        .enqueue(request, Priority::Normal)
+        .await
+        .expect("retried after the advertised wait");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(exec.calls.load(Ordering::SeqCst), 2);
+    assert!(
+        started.elapsed() >= Duration::from_secs(1),
+        "broker must honour retry-after instead of failing fast"
+    );
+    assert_eq!(
+        SLEEP_SECONDS
+            .with_label_values(&["graphql", "retry_after"])
+            .get()
+            - slept_before,
+        1
+    );
+}
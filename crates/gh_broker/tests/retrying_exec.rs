@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use http::{Request, Response, StatusCode};
+
+use gh_broker::broker::HttpExec;
+use gh_broker::RetryingHttpExec;
+
+/// Replies with `failure` for the first `failures` calls, then 200.
+struct FlakyExec {
+    calls: AtomicUsize,
+    failures: usize,
+    failure: StatusCode,
+}
+
+impl FlakyExec {
+    fn new(failures: usize, failure: StatusCode) -> Arc<Self> {
+        Arc::new(Self {
+            calls: AtomicUsize::new(0),
+            failures,
+            failure,
+        })
+    }
+}
+
+#[async_trait]
+impl HttpExec for FlakyExec {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        assert_eq!(req.headers()[http::header::AUTHORIZATION], "token s1");
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        let status = if call < self.failures {
+            self.failure
+        } else {
+            StatusCode::OK
+        };
+        Ok(Response::builder().status(status).body(b"{}".to_vec())?)
+    }
+}
+
+fn rate_limit_request() -> Request<Vec<u8>> {
+    Request::get("https://api.github.com/rate_limit")
+        .header(http::header::AUTHORIZATION, "token s1")
+        .body(Vec::new())
+        .unwrap()
+}
+
+fn retrying(inner: Arc<FlakyExec>) -> RetryingHttpExec {
+    RetryingHttpExec::new(inner).backoff(Duration::from_millis(1), Duration::from_millis(5), 0.0)
+}
+
+#[tokio::test]
+async fn retries_server_errors_until_success() {
+    let inner = FlakyExec::new(2, StatusCode::BAD_GATEWAY);
+    let response = retrying(inner.clone())
+        .execute(rate_limit_request())
+        .await
+        .expect("response");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn returns_last_response_when_attempts_run_out() {
+    let inner = FlakyExec::new(10, StatusCode::SERVICE_UNAVAILABLE);
+    let response = retrying(inner.clone())
+        .max_attempts(3)
+        .execute(rate_limit_request())
+        .await
+        .expect("response");
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn does_not_retry_auth_failures() {
+    let inner = FlakyExec::new(1, StatusCode::UNAUTHORIZED);
+    let response = retrying(inner.clone())
+        .execute(rate_limit_request())
+        .await
+        .expect("response");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,136 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use http::{header, Request, Response, StatusCode};
+
+use gh_broker::broker::HttpExec;
+use gh_broker::{GithubBrokerBuilder, GithubToken, Priority};
+
+const THROTTLED_CALLS: usize = 3;
+
+/// The first `THROTTLED_CALLS` calls get a `429` with `retry-after: 0`.
+#[derive(Default)]
+struct ThrottledExec {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl HttpExec for ThrottledExec {
+    async fn execute(&self, _req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < THROTTLED_CALLS {
+            return Ok(Response::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .header(header::RETRY_AFTER, "0")
+                .body(Vec::new())?);
+        }
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(b"{}".to_vec())?)
+    }
+}
+
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn repeated_throttles_within_the_interval_log_one_warning() {
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_ansi(false)
+        .with_writer({
+            let captured = captured.clone();
+            move || captured.clone()
+        })
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let exec = Arc::new(ThrottledExec::default());
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec.clone())
+    .backoff(Duration::from_millis(1), Duration::from_millis(1), 0.0)
+    .throttle_log_interval(Duration::from_secs(3600))
+    .build();
+
+    let request = Request::get("https://api.github.com/repos/octocat/hello-world")
+        .header(header::USER_AGENT, "test-agent")
+        .body(Vec::new())
+        .unwrap();
+    let response = broker
+        .enqueue(request, Priority::Critical)
+        .await
+        .expect("succeeds after the throttles");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(exec.calls.load(Ordering::SeqCst), THROTTLED_CALLS + 1);
+
+    let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<_> = logs.lines().collect();
+    assert_eq!(lines.len(), 1, "{logs}");
+    assert!(
+        lines[0].contains("GitHub responded with retryable status"),
+        "{logs}"
+    );
+    assert!(lines[0].contains("suppressed=0"), "{logs}");
+}
+
+#[tokio::test]
+async fn logged_request_keys_mask_query_secrets() {
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_ansi(false)
+        .with_writer({
+            let captured = captured.clone();
+            move || captured.clone()
+        })
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(Arc::new(ThrottledExec::default()))
+    .backoff(Duration::from_millis(1), Duration::from_millis(1), 0.0)
+    .redact_query_params(vec!["sig".into()])
+    .build();
+
+    let request = Request::get(
+        "https://ghes.example/api/v3/repos/octocat/hello-world?access_token=s3cr3t&sig=abc123",
+    )
+    .header(header::USER_AGENT, "test-agent")
+    .body(Vec::new())
+    .unwrap();
+    broker
+        .enqueue(request, Priority::Critical)
+        .await
+        .expect("succeeds after the throttles");
+
+    let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    assert!(
+        logs.contains("access_token=REDACTED&sig=REDACTED"),
+        "{logs}"
+    );
+    assert!(!logs.contains("s3cr3t"), "{logs}");
+    assert!(!logs.contains("abc123"), "{logs}");
+}
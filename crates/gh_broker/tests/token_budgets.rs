@@ -0,0 +1,60 @@
+use chrono::{Duration, Utc};
+
+use gh_broker::model::{Budget, RateLimitUpdate};
+use gh_broker::token::{GithubToken, TokenPool, TokenSelection};
+use gh_broker::Priority;
+
+fn picked(selection: TokenSelection) -> Option<String> {
+    match selection {
+        TokenSelection::Token(token) => Some(token.id),
+        TokenSelection::Wait(_) => None,
+    }
+}
+
+#[tokio::test]
+async fn rest_only_token_is_never_picked_for_graphql() {
+    let pool = TokenPool::new(vec![
+        GithubToken {
+            id: "rest".into(),
+            secret: "s1".into(),
+            budgets: Some(vec![Budget::Core, Budget::Search]),
+        },
+        GithubToken {
+            id: "any".into(),
+            secret: "s2".into(),
+            budgets: None,
+        },
+    ]);
+    // Leave the REST-only token with the most headroom so it would win on score.
+    let reset = Utc::now() + Duration::minutes(30);
+    for budget in Budget::ALL {
+        pool.update(
+            budget,
+            "any",
+            RateLimitUpdate {
+                limit: 5000,
+                remaining: 100,
+                reset,
+            },
+        )
+        .await;
+    }
+
+    for _ in 0..5 {
+        let selection = pool.pick_token(Budget::Graphql, Priority::Normal).await;
+        assert_eq!(picked(selection).as_deref(), Some("any"));
+    }
+    let selection = pool.pick_token(Budget::Core, Priority::Normal).await;
+    assert_eq!(picked(selection).as_deref(), Some("rest"));
+
+    // Once the only GraphQL-capable token is exhausted, GraphQL waits instead
+    // of falling back to the REST-only token.
+    pool.consume(Budget::Graphql, "any", 100).await;
+    let selection = pool.pick_token(Budget::Graphql, Priority::Normal).await;
+    assert_eq!(picked(selection), None);
+
+    let snapshot = pool.snapshot().await;
+    assert!(snapshot
+        .iter()
+        .all(|s| !(s.token_id == "rest" && s.budget == Budget::Graphql)));
+}
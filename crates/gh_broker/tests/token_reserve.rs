@@ -0,0 +1,52 @@
+use chrono::{Duration, Utc};
+
+use gh_broker::model::{Budget, Priority, RateLimitUpdate};
+use gh_broker::token::{GithubToken, TokenPool, TokenSelection};
+
+async fn low_remaining_pool(reserve: i64) -> TokenPool {
+    let pool = TokenPool::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .with_critical_reserve(reserve);
+    pool.update(
+        Budget::Core,
+        "t1",
+        RateLimitUpdate {
+            limit: 5000,
+            remaining: 5,
+            reset: Utc::now() + Duration::minutes(10),
+        },
+    )
+    .await;
+    pool
+}
+
+#[tokio::test]
+async fn backfill_waits_while_critical_uses_reserved_headroom() {
+    let pool = low_remaining_pool(10).await;
+
+    match pool.pick_token(Budget::Core, Priority::Backfill).await {
+        TokenSelection::Wait(wait) => assert!(wait > std::time::Duration::ZERO),
+        TokenSelection::Token(token) => panic!("backfill got reserved token {}", token.id),
+    }
+    match pool.pick_token(Budget::Core, Priority::Normal).await {
+        TokenSelection::Wait(_) => {}
+        TokenSelection::Token(token) => panic!("normal got reserved token {}", token.id),
+    }
+    match pool.pick_token(Budget::Core, Priority::Critical).await {
+        TokenSelection::Token(token) => assert_eq!(token.id, "t1"),
+        TokenSelection::Wait(_) => panic!("critical request should use the reserve"),
+    }
+}
+
+#[tokio::test]
+async fn no_reserve_keeps_tokens_available_to_all_priorities() {
+    let pool = low_remaining_pool(0).await;
+
+    assert!(matches!(
+        pool.pick_token(Budget::Core, Priority::Backfill).await,
+        TokenSelection::Token(_)
+    ));
+}
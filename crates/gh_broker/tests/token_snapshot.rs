@@ -0,0 +1,49 @@
+use chrono::{Duration, Utc};
+
+use gh_broker::model::{Budget, RateLimitUpdate};
+use gh_broker::token::{GithubToken, TokenPool};
+
+#[tokio::test]
+async fn snapshot_reflects_consumed_cost() {
+    let pool = TokenPool::new(vec![
+        GithubToken {
+            id: "t1".into(),
+            secret: "s1".into(),
+            budgets: None,
+        },
+        GithubToken {
+            id: "t2".into(),
+            secret: "s2".into(),
+            budgets: None,
+        },
+    ]);
+    let reset = Utc::now() + Duration::minutes(30);
+    pool.update(
+        Budget::Graphql,
+        "t1",
+        RateLimitUpdate {
+            limit: 5000,
+            remaining: 4000,
+            reset,
+        },
+    )
+    .await;
+    pool.consume(Budget::Graphql, "t1", 25).await;
+
+    let snapshot = pool.snapshot().await;
+    assert_eq!(snapshot.len(), 6);
+
+    let t1_graphql = snapshot
+        .iter()
+        .find(|s| s.token_id == "t1" && s.budget == Budget::Graphql)
+        .expect("t1 graphql entry");
+    assert_eq!(t1_graphql.limit, 5000);
+    assert_eq!(t1_graphql.remaining, 3975);
+    assert_eq!(t1_graphql.reset_at, reset);
+
+    let t2_graphql = snapshot
+        .iter()
+        .find(|s| s.token_id == "t2" && s.budget == Budget::Graphql)
+        .expect("t2 graphql entry");
+    assert_eq!(t2_graphql.remaining, 5000);
+}
@@ -0,0 +1,75 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use http::{Request, Response};
+
+use gh_broker::broker::HttpExec;
+use gh_broker::metrics::ALL_TOKENS_EXHAUSTED;
+use gh_broker::{Budget, GithubBrokerBuilder, GithubToken, Priority, TokensExhaustedError};
+
+/// Succeeds once but reports the token's core budget as spent until an hour from now.
+struct SpendingExec {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl HttpExec for SpendingExec {
+    async fn execute(&self, _req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let reset = (Utc::now() + chrono::Duration::hours(1)).timestamp();
+        Ok(Response::builder()
+            .status(200)
+            .header("x-ratelimit-limit", "5000")
+            .header("x-ratelimit-remaining", "0")
+            .header("x-ratelimit-reset", reset.to_string())
+            .header("x-ratelimit-resource", "core")
+            .body(b"{}".to_vec())?)
+    }
+}
+
+fn request(path: &str) -> Request<Vec<u8>> {
+    Request::get(format!("https://api.github.com/{path}"))
+        .header(http::header::USER_AGENT, "test-agent")
+        .body(Vec::new())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn exhausted_pool_fails_fast_past_the_max_wait() {
+    let exec = Arc::new(SpendingExec {
+        calls: AtomicUsize::new(0),
+    });
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec.clone())
+    .max_rate_limit_wait(Duration::from_secs(60))
+    .build();
+
+    broker
+        .enqueue(request("rate_limit"), Priority::Normal)
+        .await
+        .expect("first request spends the last call");
+    assert_eq!(ALL_TOKENS_EXHAUSTED.with_label_values(&["core"]).get(), 0);
+
+    let err = tokio::time::timeout(
+        Duration::from_secs(5),
+        broker.enqueue(request("users/octocat"), Priority::Normal),
+    )
+    .await
+    .expect("fails fast instead of waiting for the reset")
+    .expect_err("no token has calls left");
+    let exhausted = err
+        .downcast_ref::<TokensExhaustedError>()
+        .expect("typed exhaustion error");
+    assert_eq!(exhausted.budget, Budget::Core);
+    assert!(exhausted.wait > Duration::from_secs(60));
+    assert_eq!(ALL_TOKENS_EXHAUSTED.with_label_values(&["core"]).get(), 1);
+    assert_eq!(exec.calls.load(Ordering::SeqCst), 1, "not retried");
+}
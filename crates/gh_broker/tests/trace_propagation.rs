@@ -0,0 +1,65 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use http::{Request, Response};
+use opentelemetry::trace::{TraceContextExt, TraceId, TracerProvider as _};
+use opentelemetry_sdk::trace::TracerProvider;
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::prelude::*;
+
+use gh_broker::broker::HttpExec;
+use gh_broker::{GithubBrokerBuilder, GithubToken, Priority};
+
+#[derive(Default)]
+struct TraceRecordingExec {
+    seen: Mutex<Vec<TraceId>>,
+}
+
+#[async_trait]
+impl HttpExec for TraceRecordingExec {
+    async fn execute(&self, _req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let trace_id = tracing::Span::current()
+            .context()
+            .span()
+            .span_context()
+            .trace_id();
+        self.seen.lock().unwrap().push(trace_id);
+        Ok(Response::builder().status(200).body(b"{}".to_vec())?)
+    }
+}
+
+#[tokio::test]
+async fn broker_request_joins_callers_trace() {
+    let provider = TracerProvider::builder().build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let exec = Arc::new(TraceRecordingExec::default());
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec.clone())
+    .build();
+
+    let parent = tracing::info_span!("process_repo");
+    let parent_trace = parent.context().span().span_context().trace_id();
+    assert_ne!(parent_trace, TraceId::INVALID);
+
+    let request = Request::get("https://api.github.com/repos/octocat/hello-world")
+        .header(http::header::USER_AGENT, "test-agent")
+        .body(Vec::new())
+        .unwrap();
+    let response = async { broker.enqueue(request, Priority::Critical).await }
+        .instrument(parent)
+        .await
+        .expect("broker response");
+    assert_eq!(response.status(), 200);
+
+    let seen = exec.seen.lock().unwrap().clone();
+    assert_eq!(seen, vec![parent_trace]);
+}
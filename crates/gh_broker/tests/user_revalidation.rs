@@ -0,0 +1,76 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use http::{header, Request, Response, StatusCode};
+
+use gh_broker::broker::HttpExec;
+use gh_broker::metrics::USER_NOT_MODIFIED;
+use gh_broker::{GithubBrokerBuilder, GithubToken, Priority};
+
+const ETAG: &str = "W/\"user-etag\"";
+
+/// Serves a user with an ETag and answers 304 when that ETag is presented.
+/// Records the `If-None-Match` header of every request it sees.
+#[derive(Default)]
+struct ConditionalUsers {
+    if_none_match: Mutex<Vec<Option<String>>>,
+}
+
+#[async_trait]
+impl HttpExec for ConditionalUsers {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let presented = req
+            .headers()
+            .get(header::IF_NONE_MATCH)
+            .map(|v| v.to_str().unwrap().to_string());
+        self.if_none_match.lock().unwrap().push(presented.clone());
+        if presented.as_deref() == Some(ETAG) {
+            return Ok(Response::builder()
+                .status(StatusCode::NOT_MODIFIED)
+                .header(header::ETAG, ETAG)
+                .body(Vec::new())?);
+        }
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(header::ETAG, ETAG)
+            .body(br#"{"login":"octocat","followers":10}"#.to_vec())?)
+    }
+}
+
+fn get_user(login: &str) -> Request<Vec<u8>> {
+    Request::get(format!("https://api.github.com/users/{login}"))
+        .header(header::USER_AGENT, "test-agent")
+        .body(Vec::new())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn repeat_user_fetch_revalidates_against_cached_etag() -> Result<()> {
+    let exec = Arc::new(ConditionalUsers::default());
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec.clone())
+    .build();
+    let not_modified_before = USER_NOT_MODIFIED.get();
+
+    let first = broker
+        .enqueue(get_user("octocat"), Priority::Normal)
+        .await?;
+    // Login case differs between payloads; both must share one cache entry.
+    let second = broker
+        .enqueue(get_user("Octocat"), Priority::Normal)
+        .await?;
+
+    assert_eq!(
+        *exec.if_none_match.lock().unwrap(),
+        vec![None, Some(ETAG.to_string())]
+    );
+    assert_eq!(second.status(), StatusCode::OK);
+    assert_eq!(second.body(), first.body());
+    assert_eq!(USER_NOT_MODIFIED.get(), not_modified_before + 1);
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use http::{Request, Response};
+use tokio::sync::Notify;
+
+use gh_broker::broker::HttpExec;
+use gh_broker::metrics::SCHEDULER_CYCLES;
+use gh_broker::{GithubBroker, GithubBrokerBuilder, GithubToken, Priority};
+
+/// Holds `gate` requests until released and records every request it sees.
+/// Handling `yield` enqueues a normal-priority `urgent` request first.
+#[derive(Default)]
+struct GateExec {
+    release: Notify,
+    seen: Mutex<Vec<String>>,
+    broker: Mutex<Option<Arc<dyn GithubBroker>>>,
+}
+
+impl GateExec {
+    fn seen(&self) -> Vec<String> {
+        self.seen.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl HttpExec for GateExec {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let uri = req.uri();
+        let name = uri
+            .query()
+            .unwrap_or_else(|| uri.path().rsplit('/').next().unwrap())
+            .trim_start_matches("q=")
+            .to_string();
+        self.seen.lock().unwrap().push(name.clone());
+        match name.as_str() {
+            "gate" => self.release.notified().await,
+            "yield" => {
+                let broker = self.broker.lock().unwrap().clone().unwrap();
+                let path = uri.path().to_string();
+                tokio::spawn(async move {
+                    broker.enqueue(get(&path, "urgent"), Priority::Normal).await
+                });
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            _ => {}
+        }
+        Ok(Response::builder().status(200).body(b"{}".to_vec())?)
+    }
+}
+
+fn get(path: &str, name: &str) -> Request<Vec<u8>> {
+    let uri = if path.starts_with("/search/") {
+        format!("https://api.github.com{path}?q={name}")
+    } else {
+        format!("https://api.github.com{path}/{name}")
+    };
+    Request::get(uri)
+        .header(http::header::USER_AGENT, "test-agent")
+        .body(Vec::new())
+        .unwrap()
+}
+
+fn broker(exec: &Arc<GateExec>, work_conserving: bool) -> Arc<dyn GithubBroker> {
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(exec.clone())
+    .work_conserving(work_conserving)
+    .build();
+    *exec.broker.lock().unwrap() = Some(broker.clone());
+    broker
+}
+
+/// Parks the budget worker on `gate`, queues `names` as backfill in order,
+/// then releases the worker and waits for everything to finish.
+async fn run_backfill(
+    broker: &Arc<dyn GithubBroker>,
+    exec: &Arc<GateExec>,
+    path: &str,
+    names: &[&str],
+) {
+    let gate = tokio::spawn(broker.enqueue(get(path, "gate"), Priority::Backfill));
+    while !exec.seen().contains(&"gate".to_string()) {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let mut queued = Vec::new();
+    for name in names {
+        queued.push(tokio::spawn(
+            broker.enqueue(get(path, name), Priority::Backfill),
+        ));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    exec.release.notify_one();
+    gate.await.unwrap().unwrap();
+    for handle in queued {
+        handle.await.unwrap().unwrap();
+    }
+    // The cycle is counted once the pass that served the last request ends.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+}
+
+#[tokio::test]
+async fn idle_priority_slots_go_to_backfill() {
+    let names = ["b1", "b2", "b3", "b4", "b5", "b6"];
+    let cycles = || SCHEDULER_CYCLES.with_label_values(&["core"]).get();
+
+    let exec = Arc::new(GateExec::default());
+    let before = cycles();
+    run_backfill(&broker(&exec, false), &exec, "/users", &names).await;
+    let static_cycles = cycles() - before;
+
+    let exec = Arc::new(GateExec::default());
+    let before = cycles();
+    run_backfill(&broker(&exec, true), &exec, "/users", &names).await;
+    let conserving_cycles = cycles() - before;
+
+    // Core weights are [4, 2, 1]: one backfill request per pass, or all
+    // seven slots when critical and normal are empty. The gate itself adds a
+    // pass unless the idle worker picked it up outside the weighted loop.
+    assert!(
+        static_cycles >= 7,
+        "static weights took {static_cycles} passes"
+    );
+    assert_eq!(conserving_cycles, 1);
+}
+
+#[tokio::test]
+async fn borrowed_backfill_slots_yield_to_new_normal_work() {
+    let exec = Arc::new(GateExec::default());
+    let broker = broker(&exec, true);
+    run_backfill(
+        &broker,
+        &exec,
+        "/search/issues",
+        &["yield", "b2", "b3", "b4"],
+    )
+    .await;
+
+    assert_eq!(
+        exec.seen(),
+        vec!["gate", "yield", "urgent", "b2", "b3", "b4"]
+    );
+}
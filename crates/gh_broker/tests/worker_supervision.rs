@@ -0,0 +1,90 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use http::{Request, Response};
+
+use gh_broker::broker::HttpExec;
+use gh_broker::metrics::{WORKER_LAST_TICK, WORKER_RESTARTS};
+use gh_broker::{GithubBrokerBuilder, GithubToken, Priority};
+
+/// Panics on `/users/boom`, taking the core worker task down with it.
+struct PanickingExec;
+
+#[async_trait]
+impl HttpExec for PanickingExec {
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        if req.uri().path() == "/users/boom" {
+            panic!("exec blew up");
+        }
+        Ok(Response::builder().status(200).body(b"{}".to_vec())?)
+    }
+}
+
+fn get(path: &str) -> Request<Vec<u8>> {
+    Request::get(format!("https://api.github.com{path}"))
+        .header(http::header::USER_AGENT, "test-agent")
+        .body(Vec::new())
+        .unwrap()
+}
+
+fn core_tick() -> f64 {
+    WORKER_LAST_TICK.with_label_values(&["core"]).get()
+}
+
+async fn wait_for_tick_after(previous: f64) -> f64 {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let tick = core_tick();
+            if tick > previous {
+                return tick;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("core worker heartbeat should advance")
+}
+
+#[tokio::test]
+async fn panicked_worker_fails_its_request_and_is_restarted() {
+    let broker = GithubBrokerBuilder::new(vec![GithubToken {
+        id: "t1".into(),
+        secret: "s1".into(),
+        budgets: None,
+    }])
+    .http_exec(Arc::new(PanickingExec))
+    .build();
+
+    // Idle workers keep ticking.
+    let first = wait_for_tick_after(0.0).await;
+    let idle = wait_for_tick_after(first).await;
+    assert!(idle > first);
+
+    let restarts_before = WORKER_RESTARTS.with_label_values(&["core"]).get();
+    let failed = tokio::time::timeout(
+        Duration::from_secs(5),
+        broker.enqueue(get("/users/boom"), Priority::Normal),
+    )
+    .await
+    .expect("waiter must not hang on a dead worker");
+    assert!(failed.is_err());
+    assert_eq!(broker.pending_requests(), 0);
+
+    // The respawned worker drains the same queue and keeps its heartbeat going.
+    let response = tokio::time::timeout(
+        Duration::from_secs(5),
+        broker.enqueue(get("/users/fine"), Priority::Normal),
+    )
+    .await
+    .expect("queue keeps draining after the panic")
+    .expect("request succeeds");
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        WORKER_RESTARTS.with_label_values(&["core"]).get(),
+        restarts_before + 1
+    );
+    let after_restart = core_tick();
+    wait_for_tick_after(after_restart).await;
+}
@@ -0,0 +1,96 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use thiserror::Error;
+
+/// A GitHub payload that did not match the shape the normalizer expects.
+#[derive(Debug, Error)]
+#[error("normalizing {kind} {}: field `{field}`: {message}", id.map_or_else(|| "?".to_string(), |id| id.to_string()))]
+pub struct NormalizationError {
+    /// `repo`, `issue`, `comment` or `user`.
+    pub kind: &'static str,
+    /// The payload's `id`, when it has a numeric one.
+    pub id: Option<i64>,
+    /// Path of the offending field, e.g. `user.login`.
+    pub field: String,
+    pub message: String,
+}
+
+impl NormalizationError {
+    /// Whether the payload lacked `field` altogether, rather than holding a bad value.
+    pub fn is_missing_field(&self) -> bool {
+        missing_field(&self.message).is_some()
+    }
+}
+
+/// Deserializes a `kind` payload, reporting which field broke on failure.
+pub fn parse_payload<T: DeserializeOwned>(
+    kind: &'static str,
+    value: &Value,
+) -> Result<T, NormalizationError> {
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        let path = err.path().to_string();
+        let message = err.into_inner().to_string();
+        // A missing field fails at its parent, so the path stops one level short.
+        let field = match missing_field(&message) {
+            Some(name) if path == "." => name.to_string(),
+            Some(name) => format!("{path}.{name}"),
+            None => path,
+        };
+        NormalizationError {
+            kind,
+            id: value.get("id").and_then(Value::as_i64),
+            field,
+            message,
+        }
+    })
+}
+
+fn missing_field(message: &str) -> Option<&str> {
+    message.strip_prefix("missing field `")?.split('`').next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payloads::IssuePayload;
+    use serde_json::json;
+
+    fn issue() -> Value {
+        json!({
+            "id": 7,
+            "number": 3,
+            "state": "open",
+            "title": "Crash on start",
+            "body": null,
+            "user": {"id": 1, "login": "octocat"},
+            "comments": 0,
+            "created_at": "2024-05-01T10:00:00Z",
+            "updated_at": "2024-05-01T10:00:00Z",
+            "closed_at": null
+        })
+    }
+
+    #[test]
+    fn wrong_type_names_the_field() {
+        let mut value = issue();
+        value["user"]["login"] = json!(42);
+        let err = parse_payload::<IssuePayload>("issue", &value).unwrap_err();
+        assert_eq!(err.kind, "issue");
+        assert_eq!(err.id, Some(7));
+        assert_eq!(err.field, "user.login");
+        assert!(err
+            .to_string()
+            .starts_with("normalizing issue 7: field `user.login`"));
+    }
+
+    #[test]
+    fn missing_field_names_the_field() {
+        let mut value = issue();
+        value.as_object_mut().unwrap().remove("title");
+        let err = parse_payload::<IssuePayload>("issue", &value).unwrap_err();
+        assert_eq!(err.field, "title");
+        assert!(err.is_missing_field());
+
+        assert!(parse_payload::<IssuePayload>("issue", &issue()).is_ok());
+    }
+}
@@ -1,7 +1,15 @@
+pub mod error;
+pub mod metrics;
 pub mod models;
 pub mod payloads;
+pub mod strategy;
 pub mod transform;
 
+pub use error::{parse_payload, NormalizationError};
 pub use models::{NormalizedComment, NormalizedIssue, NormalizedRepository, NormalizedUser};
 pub use payloads::{CommentPayload, IssuePayload, RepoPayload, UserPayload};
-pub use transform::{normalize_comment, normalize_issue, normalize_repo, normalize_user};
+pub use strategy::PayloadStrategy;
+pub use transform::{
+    dedupe_text, normalize_comment, normalize_comment_with, normalize_issue, normalize_issue_with,
+    normalize_repo, normalize_user, normalize_whitespace,
+};
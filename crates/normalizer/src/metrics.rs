@@ -0,0 +1,11 @@
+use once_cell::sync::Lazy;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+
+pub static MISSING_FIELDS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "normalizer_missing_fields_total",
+        "Required fields missing from GitHub payloads, by payload kind and field path",
+        &["kind", "field"]
+    )
+    .expect("normalizer missing fields")
+});
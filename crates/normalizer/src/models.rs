@@ -21,6 +21,8 @@ pub struct NormalizedUser {
     pub followers: Option<i64>,
     pub following: Option<i64>,
     pub public_repos: Option<i64>,
+    pub bio: Option<String>,
+    pub blog: Option<String>,
     pub raw: serde_json::Value,
 }
 
@@ -38,6 +40,12 @@ pub struct NormalizedIssue {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub closed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub state_reason: Option<String>,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub author_association: Option<String>,
     pub dedupe_hash: String,
     pub raw: serde_json::Value,
 }
@@ -50,6 +58,8 @@ pub struct NormalizedComment {
     pub body: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub author_association: Option<String>,
     pub dedupe_hash: String,
     pub raw: serde_json::Value,
 }
@@ -23,6 +23,11 @@ pub struct UserPayload {
     pub followers: Option<i64>,
     pub following: Option<i64>,
     pub public_repos: Option<i64>,
+    #[serde(default)]
+    pub bio: Option<String>,
+    /// The profile's website; GitHub sends `""` when unset.
+    #[serde(default)]
+    pub blog: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -38,6 +43,18 @@ pub struct IssuePayload {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub closed_at: Option<DateTime<Utc>>,
+    /// Why the issue was closed or reopened: `completed`, `not_planned`,
+    /// `reopened`, ... Pull requests and older payloads have none.
+    #[serde(default)]
+    pub state_reason: Option<String>,
+    /// Whether maintainers locked the conversation. Only users with write
+    /// access can lock, so it marks maintainer action.
+    #[serde(default)]
+    pub locked: bool,
+    /// The author's relationship to the repository: `OWNER`, `MEMBER`,
+    /// `COLLABORATOR`, `CONTRIBUTOR`, `FIRST_TIME_CONTRIBUTOR`, `NONE`, ...
+    #[serde(default)]
+    pub author_association: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -47,6 +64,9 @@ pub struct CommentPayload {
     pub body: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
+    /// As on [`IssuePayload::author_association`].
+    #[serde(default)]
+    pub author_association: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use common::config::Leniency;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::error::{parse_payload, NormalizationError};
+use crate::metrics::MISSING_FIELDS_TOTAL;
+
+/// Parses GitHub payloads, counting required fields they lack and, when
+/// lenient, filling the defaults registered for them.
+///
+/// When GitHub starts omitting a field on some object variant, register a
+/// default here with [`PayloadStrategy::with_default`] instead of defaulting
+/// it where the payload is built, so the drift shows up in
+/// `normalizer_missing_fields_total`.
+#[derive(Debug, Clone)]
+pub struct PayloadStrategy {
+    leniency: Leniency,
+    defaults: HashMap<(&'static str, String), Value>,
+}
+
+impl Default for PayloadStrategy {
+    fn default() -> Self {
+        Self::new(Leniency::default())
+    }
+}
+
+impl PayloadStrategy {
+    /// Strategy with defaults for the fields GitHub is known to leave out.
+    pub fn new(leniency: Leniency) -> Self {
+        Self {
+            leniency,
+            defaults: HashMap::new(),
+        }
+        .with_default("issue", "title", json!(""))
+        .with_default("issue", "comments", json!(0))
+        .with_default("comment", "body", json!(""))
+        // Mannequins and some app actors come without a type.
+        .with_default("user", "type", json!("User"))
+    }
+
+    /// Fills `field` (a path such as `user.login`) of `kind` payloads with
+    /// `value` when lenient, replacing any earlier default for it.
+    pub fn with_default(mut self, kind: &'static str, field: &str, value: Value) -> Self {
+        self.defaults.insert((kind, field.to_string()), value);
+        self
+    }
+
+    pub fn leniency(&self) -> Leniency {
+        self.leniency
+    }
+
+    /// Like [`parse_payload`], recording each missing required field and, when
+    /// lenient, retrying with its default filled in.
+    pub fn parse<T: DeserializeOwned>(
+        &self,
+        kind: &'static str,
+        value: &Value,
+    ) -> Result<T, NormalizationError> {
+        let mut patched: Option<Value> = None;
+        loop {
+            let err = match parse_payload(kind, patched.as_ref().unwrap_or(value)) {
+                Ok(parsed) => return Ok(parsed),
+                Err(err) => err,
+            };
+            if !err.is_missing_field() {
+                return Err(err);
+            }
+            MISSING_FIELDS_TOTAL
+                .with_label_values(&[kind, &err.field])
+                .inc();
+            if self.leniency == Leniency::Strict {
+                return Err(err);
+            }
+            let Some(default) = self.defaults.get(&(kind, err.field.clone())) else {
+                return Err(err);
+            };
+            let patched = patched.get_or_insert_with(|| value.clone());
+            // Each pass fills a field that was absent, so this terminates.
+            if !insert_missing(patched, &err.field, default.clone()) {
+                return Err(err);
+            }
+        }
+    }
+}
+
+/// Sets the dotted `path` in `value`; false if a parent is not an object or
+/// the field is already there.
+fn insert_missing(value: &mut Value, path: &str, field_value: Value) -> bool {
+    let (parents, name) = match path.rsplit_once('.') {
+        Some((parents, name)) => (Some(parents), name),
+        None => (None, path),
+    };
+    let mut target = value;
+    for segment in parents.into_iter().flat_map(|parents| parents.split('.')) {
+        match target.get_mut(segment) {
+            Some(next) => target = next,
+            None => return false,
+        }
+    }
+    match target.as_object_mut() {
+        Some(object) if !object.contains_key(name) => {
+            object.insert(name.to_string(), field_value);
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payloads::IssuePayload;
+
+    fn issue() -> Value {
+        json!({
+            "id": 7,
+            "number": 3,
+            "state": "open",
+            "title": "Crash on start",
+            "body": null,
+            "user": {"id": 1, "login": "octocat"},
+            "comments": 0,
+            "created_at": "2024-05-01T10:00:00Z",
+            "updated_at": "2024-05-01T10:00:00Z",
+            "closed_at": null
+        })
+    }
+
+    fn missing(kind: &str, field: &str) -> u64 {
+        MISSING_FIELDS_TOTAL.with_label_values(&[kind, field]).get()
+    }
+
+    #[test]
+    fn strict_fails_and_counts_missing_field() {
+        let mut value = issue();
+        value.as_object_mut().unwrap().remove("comments");
+        let before = missing("issue", "comments");
+
+        let err = PayloadStrategy::new(Leniency::Strict)
+            .parse::<IssuePayload>("issue", &value)
+            .unwrap_err();
+        assert_eq!(err.field, "comments");
+        assert_eq!(missing("issue", "comments"), before + 1);
+    }
+
+    #[test]
+    fn lenient_fills_registered_defaults_and_counts_them() {
+        let mut value = issue();
+        value.as_object_mut().unwrap().remove("title");
+        let before = missing("issue", "title");
+
+        let parsed: IssuePayload = PayloadStrategy::new(Leniency::Lenient)
+            .parse("issue", &value)
+            .unwrap();
+        assert_eq!(parsed.title, "");
+        assert_eq!(missing("issue", "title"), before + 1);
+
+        let before = missing("issue", "user.login");
+        value["title"] = json!("Crash on start");
+        value["user"].as_object_mut().unwrap().remove("login");
+        let parsed: IssuePayload = PayloadStrategy::new(Leniency::Lenient)
+            .with_default("issue", "user.login", json!("ghost"))
+            .parse("issue", &value)
+            .unwrap();
+        assert_eq!(parsed.user.unwrap().login, "ghost");
+        assert_eq!(missing("issue", "user.login"), before + 1);
+    }
+
+    #[test]
+    fn lenient_still_fails_fields_without_default() {
+        let mut value = issue();
+        value.as_object_mut().unwrap().remove("created_at");
+        let before = missing("issue", "created_at");
+
+        let err = PayloadStrategy::new(Leniency::Lenient)
+            .parse::<IssuePayload>("issue", &value)
+            .unwrap_err();
+        assert_eq!(err.field, "created_at");
+        assert_eq!(missing("issue", "created_at"), before + 1);
+    }
+}
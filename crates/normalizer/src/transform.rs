@@ -1,4 +1,7 @@
-use common::text::dedupe_hash;
+use std::borrow::Cow;
+
+use common::config::DedupeMode;
+use common::text::{dedupe_hash, strip_quotes_and_signature};
 use serde_json::Value;
 
 use crate::models::{NormalizedComment, NormalizedIssue, NormalizedRepository, NormalizedUser};
@@ -25,12 +28,76 @@ pub fn normalize_user(payload: &UserPayload, raw: Value) -> NormalizedUser {
         followers: payload.followers,
         following: payload.following,
         public_repos: payload.public_repos,
+        bio: non_blank(payload.bio.as_deref()),
+        blog: non_blank(payload.blog.as_deref()),
         raw,
     }
 }
 
+fn non_blank(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Canonical whitespace for a body, applied before hashing and scoring so the
+/// same text fetched over REST (`\r\n`) and GraphQL (`\n`) is stored alike.
+/// Line endings become `\n`, spaces and tabs inside a line collapse to one
+/// (leading indentation is kept for code), trailing spaces go, at most one
+/// blank line separates paragraphs, and the ends are trimmed.
+pub fn normalize_whitespace(body: &str) -> String {
+    let unified = body.replace("\r\n", "\n").replace('\r', "\n");
+    let mut normalized = String::with_capacity(unified.len());
+    let mut blank_lines = 0usize;
+    for line in unified.trim().split('\n') {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if !normalized.is_empty() {
+            normalized.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        blank_lines = 0;
+
+        let content = line.trim_start();
+        normalized.push_str(&line[..line.len() - content.len()]);
+        let mut in_space = false;
+        for ch in content.chars() {
+            if ch.is_whitespace() {
+                if !in_space {
+                    normalized.push(' ');
+                }
+                in_space = true;
+            } else {
+                normalized.push(ch);
+                in_space = false;
+            }
+        }
+    }
+    normalized
+}
+
+/// The part of `body` hashed under `mode`.
+pub fn dedupe_text(body: &str, mode: DedupeMode) -> Cow<'_, str> {
+    match mode {
+        DedupeMode::FullText => Cow::Borrowed(body),
+        DedupeMode::StripQuotes => Cow::Owned(strip_quotes_and_signature(body)),
+    }
+}
+
 pub fn normalize_issue(payload: &IssuePayload, repo_id: i64, raw: Value) -> NormalizedIssue {
-    let body = payload.body.clone();
+    normalize_issue_with(payload, repo_id, raw, DedupeMode::FullText)
+}
+
+pub fn normalize_issue_with(
+    payload: &IssuePayload,
+    repo_id: i64,
+    raw: Value,
+    dedupe_mode: DedupeMode,
+) -> NormalizedIssue {
+    let body = payload.body.as_deref().map(normalize_whitespace);
     NormalizedIssue {
         id: payload.id,
         repo_id,
@@ -44,13 +111,28 @@ pub fn normalize_issue(payload: &IssuePayload, repo_id: i64, raw: Value) -> Norm
         created_at: payload.created_at,
         updated_at: payload.updated_at,
         closed_at: payload.closed_at,
-        dedupe_hash: dedupe_hash(&payload.title, &body.unwrap_or_default()),
+        state_reason: payload.state_reason.clone(),
+        locked: payload.locked,
+        author_association: payload.author_association.clone(),
+        dedupe_hash: dedupe_hash(
+            &payload.title,
+            &dedupe_text(body.as_deref().unwrap_or_default(), dedupe_mode),
+        ),
         raw,
     }
 }
 
 pub fn normalize_comment(payload: &CommentPayload, issue_id: i64, raw: Value) -> NormalizedComment {
-    let body = payload.body.clone();
+    normalize_comment_with(payload, issue_id, raw, DedupeMode::FullText)
+}
+
+pub fn normalize_comment_with(
+    payload: &CommentPayload,
+    issue_id: i64,
+    raw: Value,
+    dedupe_mode: DedupeMode,
+) -> NormalizedComment {
+    let body = normalize_whitespace(&payload.body);
     NormalizedComment {
         id: payload.id,
         issue_id,
@@ -58,7 +140,8 @@ pub fn normalize_comment(payload: &CommentPayload, issue_id: i64, raw: Value) ->
         body: body.clone(),
         created_at: payload.created_at,
         updated_at: payload.updated_at,
-        dedupe_hash: dedupe_hash("", &body),
+        author_association: payload.author_association.clone(),
+        dedupe_hash: dedupe_hash("", &dedupe_text(&body, dedupe_mode)),
         raw,
     }
 }
@@ -83,6 +166,9 @@ mod tests {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
         };
         let normalized = normalize_issue(&payload, 42, json!({}));
         assert_eq!(normalized.repo_id, 42);
@@ -97,9 +183,95 @@ mod tests {
             body: "Hi there".into(),
             created_at: Utc::now(),
             updated_at: None,
+            author_association: None,
         };
         let normalized = normalize_comment(&payload, 55, json!({}));
         assert_eq!(normalized.issue_id, 55);
         assert!(normalized.dedupe_hash.len() == 64);
     }
+
+    #[test]
+    fn strip_quotes_mode_ignores_quoted_context() {
+        let comment = |body: &str| CommentPayload {
+            id: 1,
+            user: None,
+            body: body.into(),
+            created_at: Utc::now(),
+            updated_at: None,
+            author_association: None,
+        };
+        let first = comment("> Does this work on Windows?\n\nCheap meds at pills.example");
+        let second = comment(
+            "On Tue, Bob wrote:\n> The build fails on arm64\n> with a linker error\n\nCheap meds at pills.example\n-- \nsent from my phone",
+        );
+        let plain = comment("Cheap meds at pills.example");
+
+        let hash = |payload: &CommentPayload, mode| {
+            normalize_comment_with(payload, 1, json!({}), mode).dedupe_hash
+        };
+        assert_ne!(
+            hash(&first, DedupeMode::FullText),
+            hash(&second, DedupeMode::FullText)
+        );
+        assert_eq!(
+            hash(&first, DedupeMode::StripQuotes),
+            hash(&second, DedupeMode::StripQuotes)
+        );
+        assert_eq!(
+            hash(&first, DedupeMode::StripQuotes),
+            hash(&plain, DedupeMode::FullText)
+        );
+    }
+
+    #[test]
+    fn whitespace_is_normalized_before_hashing() {
+        let issue = |body: &str| IssuePayload {
+            id: 1,
+            number: 10,
+            pull_request: None,
+            state: "open".into(),
+            title: "Free gift card".into(),
+            body: Some(body.into()),
+            user: None,
+            comments: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            state_reason: None,
+            locked: false,
+            author_association: None,
+        };
+        let rest = issue(
+            "Claim it now:  \r\n\r\n\r\n\r\n    code\t\there\r\n> quoted\r\n-- \r\nsig\r\n\r\n",
+        );
+        let graphql = issue("\nClaim it now:\n\n    code here\n> quoted\n--\nsig");
+
+        for mode in [DedupeMode::FullText, DedupeMode::StripQuotes] {
+            let rest = normalize_issue_with(&rest, 1, json!({}), mode);
+            let graphql = normalize_issue_with(&graphql, 1, json!({}), mode);
+            assert_eq!(rest.dedupe_hash, graphql.dedupe_hash);
+            assert_eq!(rest.body, graphql.body);
+        }
+        assert_eq!(
+            normalize_issue(&rest, 1, json!({})).body.as_deref(),
+            Some("Claim it now:\n\n    code here\n> quoted\n--\nsig")
+        );
+        assert_eq!(normalize_whitespace(" \r\n\t "), "");
+    }
+
+    #[test]
+    fn user_normalization_drops_blank_profile_fields() {
+        let raw = json!({
+            "id": 9,
+            "login": "fresh",
+            "type": "User",
+            "created_at": "2024-05-01T00:00:00Z",
+            "bio": "  Deals daily: bit.ly/deals ",
+            "blog": ""
+        });
+        let payload: UserPayload = serde_json::from_value(raw.clone()).unwrap();
+        let normalized = normalize_user(&payload, raw);
+        assert_eq!(normalized.bio.as_deref(), Some("Deals daily: bit.ly/deals"));
+        assert_eq!(normalized.blog, None);
+    }
 }
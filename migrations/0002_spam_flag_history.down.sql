@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_spam_flag_history_subject;
+DROP TABLE IF EXISTS spam_flag_history;
@@ -0,0 +1,17 @@
+-- Append-only audit trail of every spam assessment; spam_flags keeps the latest per version.
+CREATE TABLE spam_flag_history (
+    id BIGSERIAL PRIMARY KEY,
+    subject_type TEXT NOT NULL CHECK (subject_type IN ('issue', 'comment')),
+    subject_id BIGINT NOT NULL,
+    score REAL NOT NULL,
+    reasons TEXT[] NOT NULL,
+    version TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_spam_flag_history_subject ON spam_flag_history (subject_type, subject_id, created_at DESC);
+
+-- Seed history with the assessments recorded before this table existed
+INSERT INTO spam_flag_history (subject_type, subject_id, score, reasons, version, created_at)
+SELECT subject_type, subject_id, score, reasons, version, created_at
+FROM spam_flags;
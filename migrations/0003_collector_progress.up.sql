@@ -0,0 +1,8 @@
+-- In-flight pagination state so a restarted collector resumes a repo from its last
+-- completed page. Rows are removed once the repo finishes and its watermark advances.
+CREATE TABLE collector_progress (
+    repo_full_name TEXT PRIMARY KEY,
+    cursor TEXT NOT NULL,
+    newest_ts TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
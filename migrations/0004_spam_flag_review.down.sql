@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_spam_flags_reviewed_status;
+ALTER TABLE spam_flags
+    DROP COLUMN IF EXISTS reviewed_by,
+    DROP COLUMN IF EXISTS reviewed_at,
+    DROP COLUMN IF EXISTS reviewed_status;
+DROP TYPE IF EXISTS review_status;
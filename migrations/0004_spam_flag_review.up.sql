@@ -0,0 +1,9 @@
+-- Reviewer triage of spam flags; confirmed/false_positive verdicts double as training labels.
+CREATE TYPE review_status AS ENUM ('unreviewed', 'confirmed', 'false_positive');
+
+ALTER TABLE spam_flags
+    ADD COLUMN reviewed_status review_status NOT NULL DEFAULT 'unreviewed',
+    ADD COLUMN reviewed_at TIMESTAMPTZ,
+    ADD COLUMN reviewed_by TEXT;
+
+CREATE INDEX idx_spam_flags_reviewed_status ON spam_flags (reviewed_status);
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_spam_flags_created_at;
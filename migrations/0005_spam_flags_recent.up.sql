@@ -0,0 +1,2 @@
+-- Backs the reverse-chronological /flags/recent feed.
+CREATE INDEX idx_spam_flags_created_at ON spam_flags (created_at DESC, id DESC);
@@ -0,0 +1,8 @@
+-- Cached public organization membership, a trust signal for authors.
+CREATE TABLE org_memberships (
+    org TEXT NOT NULL,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    is_member BOOLEAN NOT NULL,
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (org, user_id)
+);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_issues_not_planned_user;
+ALTER TABLE issues DROP COLUMN IF EXISTS state_reason;
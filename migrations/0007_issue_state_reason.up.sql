@@ -0,0 +1,5 @@
+-- Why an issue was closed or reopened (`completed`, `not_planned`, `reopened`, ...).
+ALTER TABLE issues ADD COLUMN state_reason TEXT;
+
+-- Counting an author's `not_planned` closures is a spam signal.
+CREATE INDEX idx_issues_not_planned_user ON issues (user_id) WHERE state_reason = 'not_planned';
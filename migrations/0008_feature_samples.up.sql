@@ -0,0 +1,9 @@
+-- Features of sampled clean (unflagged) issues/comments, as training negatives.
+CREATE TABLE feature_samples (
+    subject_type TEXT NOT NULL,
+    subject_id BIGINT NOT NULL,
+    features JSONB NOT NULL,
+    version TEXT NOT NULL,
+    sampled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (subject_type, subject_id)
+);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_issues_repo_normalized_title;
+ALTER TABLE issues DROP COLUMN IF EXISTS normalized_title;
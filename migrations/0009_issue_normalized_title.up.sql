@@ -0,0 +1,7 @@
+-- Titles folded for cross-issue comparison (see common::text::normalize_title).
+ALTER TABLE issues ADD COLUMN normalized_title TEXT NOT NULL DEFAULT '';
+
+UPDATE issues
+SET normalized_title = btrim(regexp_replace(regexp_replace(lower(title), '[0-9]', '', 'g'), '\s+', ' ', 'g'));
+
+CREATE INDEX idx_issues_repo_normalized_title ON issues (repo_id, normalized_title, created_at);
@@ -0,0 +1,11 @@
+-- Edit history summary of issues/comments that crossed the preliminary score
+-- threshold, from GraphQL `userContentEdits`.
+CREATE TABLE content_edits (
+    subject_type TEXT NOT NULL,
+    subject_id BIGINT NOT NULL,
+    edit_count INTEGER NOT NULL,
+    last_edited_at TIMESTAMPTZ,
+    last_editor_login TEXT,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (subject_type, subject_id)
+);
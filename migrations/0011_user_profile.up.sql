@@ -0,0 +1,7 @@
+-- Free-text profile fields, scored for promotional links on new accounts.
+ALTER TABLE users ADD COLUMN bio TEXT;
+ALTER TABLE users ADD COLUMN blog TEXT;
+
+UPDATE users
+SET bio = NULLIF(btrim(raw->>'bio'), ''),
+    blog = NULLIF(btrim(raw->>'blog'), '');
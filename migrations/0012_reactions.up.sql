@@ -0,0 +1,13 @@
+-- Who reacted to issues/comments that crossed the preliminary score threshold,
+-- from GraphQL `reactions`, to spot rings of new accounts boosting each other.
+CREATE TABLE reactions (
+    subject_type TEXT NOT NULL,
+    subject_id BIGINT NOT NULL,
+    user_login TEXT NOT NULL,
+    content TEXT NOT NULL,
+    user_created_at TIMESTAMPTZ,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (subject_type, subject_id, user_login, content)
+);
+
+CREATE INDEX idx_reactions_user_login ON reactions (user_login);
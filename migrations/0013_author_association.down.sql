@@ -0,0 +1,2 @@
+ALTER TABLE comments DROP COLUMN IF EXISTS author_association;
+ALTER TABLE issues DROP COLUMN IF EXISTS author_association;
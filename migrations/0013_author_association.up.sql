@@ -0,0 +1,4 @@
+-- The author's relationship to the repository as GitHub reports it
+-- (`OWNER`, `MEMBER`, `COLLABORATOR`, `CONTRIBUTOR`, `FIRST_TIME_CONTRIBUTOR`, `NONE`, ...).
+ALTER TABLE issues ADD COLUMN author_association TEXT;
+ALTER TABLE comments ADD COLUMN author_association TEXT;
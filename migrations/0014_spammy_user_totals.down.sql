@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS spammy_user_totals_state;
+DROP TABLE IF EXISTS spammy_user_totals;
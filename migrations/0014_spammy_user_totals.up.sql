@@ -0,0 +1,22 @@
+-- All-time per-author flag aggregate behind the undecayed, unwindowed
+-- top_spammy_users ranking. Kept current incrementally: a refresh recomputes
+-- only authors with spam_flag_history rows past last_history_id.
+CREATE TABLE spammy_user_totals (
+    login TEXT PRIMARY KEY,
+    avg_score DOUBLE PRECISION NOT NULL,
+    total_score REAL NOT NULL,
+    flag_count BIGINT NOT NULL,
+    reasons TEXT[] NOT NULL
+);
+
+CREATE INDEX idx_spammy_user_totals_rank
+    ON spammy_user_totals (total_score DESC, flag_count DESC, login ASC);
+
+CREATE TABLE spammy_user_totals_state (
+    singleton BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (singleton),
+    last_history_id BIGINT NOT NULL,
+    refreshed_at TIMESTAMPTZ
+);
+
+-- Nothing aggregated yet, so the first refresh covers every flag.
+INSERT INTO spammy_user_totals_state (last_history_id) VALUES (0);
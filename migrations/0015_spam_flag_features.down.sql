@@ -0,0 +1 @@
+ALTER TABLE spam_flags DROP COLUMN IF EXISTS features;
@@ -0,0 +1,4 @@
+-- The FeatureSet a flag was scored from, so reviewers see magnitudes
+-- (`url_count = 37`) and not only reason codes. NULL unless
+-- `collector.store_flag_features` is on.
+ALTER TABLE spam_flags ADD COLUMN features JSONB;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS issue_state_transitions;
+ALTER TABLE issues DROP COLUMN IF EXISTS locked;
@@ -0,0 +1,23 @@
+-- Whether maintainers locked the conversation (GraphQL/REST `locked`).
+ALTER TABLE issues ADD COLUMN locked BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- State/lock changes the collector saw between two runs, recorded when
+-- `collector.rapid_closure_window_mins` is set. `rapid_maintainer_closure`
+-- marks open -> closed within that window of creation by someone other than
+-- the author.
+CREATE TABLE issue_state_transitions (
+    id BIGSERIAL PRIMARY KEY,
+    issue_id BIGINT NOT NULL REFERENCES issues (id) ON DELETE CASCADE,
+    user_id BIGINT,
+    from_state TEXT NOT NULL,
+    to_state TEXT NOT NULL,
+    from_locked BOOLEAN NOT NULL,
+    to_locked BOOLEAN NOT NULL,
+    rapid_maintainer_closure BOOLEAN NOT NULL,
+    observed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_issue_state_transitions_issue ON issue_state_transitions (issue_id);
+CREATE INDEX idx_issue_state_transitions_rapid
+    ON issue_state_transitions (user_id)
+    WHERE rapid_maintainer_closure;